@@ -0,0 +1,298 @@
+//! Annotation overlays from a sidecar JSON file (`--annotations FILE`)
+//!
+//! Linters and AI reviewers report findings as line/message pairs. batless
+//! reads them from a JSON file, either a bare array or `{"annotations": [...]}`,
+//! and shows them under the annotated lines in text output and as an
+//! `annotations` array in JSON. Entries may name a `file`; entries without
+//! one apply to every input.
+//!
+//! SARIF 2.1 result files (`--sarif`) are mapped onto the same model, so CI
+//! static analysis output can be viewed without conversion.
+
+use crate::error::{BatlessError, BatlessResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// How serious an annotation is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationSeverity {
+    Error,
+    Warning,
+    #[default]
+    Info,
+    Note,
+}
+
+impl AnnotationSeverity {
+    /// Lowercase name used in text output
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+            Self::Note => "note",
+        }
+    }
+
+    /// ANSI color for the severity label
+    pub const fn ansi(self) -> &'static str {
+        match self {
+            Self::Error => "\x1b[31m",
+            Self::Warning => "\x1b[33m",
+            Self::Info | Self::Note => "\x1b[36m",
+        }
+    }
+}
+
+/// A message attached to a line (or range of lines)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// File the annotation belongs to; `None` applies to every input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    pub message: String,
+    #[serde(default)]
+    pub severity: AnnotationSeverity,
+    /// Tool or rule that produced the annotation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl Annotation {
+    /// Whether this annotation targets `path`; relative names match as path suffixes
+    pub fn applies_to(&self, path: &str) -> bool {
+        let Some(file) = &self.file else {
+            return true;
+        };
+        let file = file.strip_prefix("./").unwrap_or(file);
+        let path = path.strip_prefix("./").unwrap_or(path);
+        file == path || Path::new(path).ends_with(file) || Path::new(file).ends_with(path)
+    }
+
+    /// Gutter note rendered under the annotated line
+    pub fn note(&self, use_color: bool) -> String {
+        let label = self.severity.as_str();
+        let mut note = if use_color {
+            format!(
+                "^-- {}{label}\x1b[0m: {}",
+                self.severity.ansi(),
+                self.message
+            )
+        } else {
+            format!("^-- {label}: {}", self.message)
+        };
+        if let Some(source) = &self.source {
+            let _ = write!(note, " [{source}]");
+        }
+        note
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnnotationFile {
+    List(Vec<Annotation>),
+    Wrapped { annotations: Vec<Annotation> },
+}
+
+/// Read annotations from a JSON file
+pub fn load_annotations(path: &str) -> BatlessResult<Vec<Annotation>> {
+    let content = fs::read_to_string(path).map_err(|e| BatlessError::FileReadError {
+        path: path.to_string(),
+        source: e,
+    })?;
+    let parsed: AnnotationFile = serde_json::from_str(&content).map_err(|e| {
+        BatlessError::config_error_with_help(
+            format!("Invalid annotations file {path}: {e}"),
+            Some(
+                r#"Expected [{"line": 3, "message": "...", "severity": "warning"}] or {"annotations": [...]}"#
+                    .to_string(),
+            ),
+        )
+    })?;
+    let annotations = match parsed {
+        AnnotationFile::List(list) | AnnotationFile::Wrapped { annotations: list } => list,
+    };
+    if let Some(bad) = annotations.iter().find(|a| a.line == 0) {
+        return Err(BatlessError::config_error_with_help(
+            format!("Annotation '{}' in {path} has line 0", bad.message),
+            Some("Annotation lines start at 1".to_string()),
+        ));
+    }
+    Ok(annotations)
+}
+
+/// Read the results of a SARIF 2.1 log as annotations.
+///
+/// Each result's first physical location becomes one annotation; results
+/// without a line are skipped. The source is `tool/ruleId`.
+pub fn load_sarif(path: &str) -> BatlessResult<Vec<Annotation>> {
+    let content = fs::read_to_string(path).map_err(|e| BatlessError::FileReadError {
+        path: path.to_string(),
+        source: e,
+    })?;
+    let log: Value = serde_json::from_str(&content).map_err(|e| {
+        BatlessError::config_error_with_help(
+            format!("Invalid SARIF file {path}: {e}"),
+            Some("Expected a SARIF 2.1 log with a top-level \"runs\" array".to_string()),
+        )
+    })?;
+    let Some(runs) = log["runs"].as_array() else {
+        return Err(BatlessError::config_error_with_help(
+            format!("Invalid SARIF file {path}: missing \"runs\" array"),
+            Some("Expected a SARIF 2.1 log with a top-level \"runs\" array".to_string()),
+        ));
+    };
+
+    let mut annotations = Vec::new();
+    for run in runs {
+        let tool = run["tool"]["driver"]["name"].as_str();
+        for result in run["results"].as_array().into_iter().flatten() {
+            let location = &result["locations"][0]["physicalLocation"];
+            let Some(line) = location["region"]["startLine"].as_u64() else {
+                continue;
+            };
+            let rule = result["ruleId"].as_str();
+            let source = match (tool, rule) {
+                (Some(tool), Some(rule)) => Some(format!("{tool}/{rule}")),
+                (tool, rule) => tool.or(rule).map(String::from),
+            };
+            annotations.push(Annotation {
+                file: location["artifactLocation"]["uri"]
+                    .as_str()
+                    .map(|uri| uri.strip_prefix("file://").unwrap_or(uri).to_string()),
+                line: usize::try_from(line).unwrap_or(usize::MAX).max(1),
+                end_line: location["region"]["endLine"]
+                    .as_u64()
+                    .and_then(|end| usize::try_from(end).ok()),
+                message: result["message"]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                severity: match result["level"].as_str() {
+                    Some("error") => AnnotationSeverity::Error,
+                    Some("note") => AnnotationSeverity::Note,
+                    Some("none") => AnnotationSeverity::Info,
+                    // SARIF's default level is "warning"
+                    _ => AnnotationSeverity::Warning,
+                },
+                source,
+            });
+        }
+    }
+    Ok(annotations)
+}
+
+/// Annotations for `path` whose start line lies within `first..=last`, in line order
+pub fn select(
+    annotations: &[Annotation],
+    path: &str,
+    first: usize,
+    last: usize,
+) -> Vec<Annotation> {
+    let mut selected: Vec<Annotation> = annotations
+        .iter()
+        .filter(|a| a.applies_to(path) && (first..=last).contains(&a.line))
+        .cloned()
+        .collect();
+    selected.sort_by_key(|a| a.line);
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn annotation(file: Option<&str>, line: usize) -> Annotation {
+        Annotation {
+            file: file.map(String::from),
+            line,
+            end_line: None,
+            message: "m".to_string(),
+            severity: AnnotationSeverity::default(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_load_both_shapes() {
+        let mut list = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            list,
+            r#"[{{"line": 2, "message": "unused", "severity": "warning", "source": "clippy"}}]"#
+        )
+        .unwrap();
+        let loaded = load_annotations(list.path().to_str().unwrap()).unwrap();
+        assert_eq!(loaded[0].severity, AnnotationSeverity::Warning);
+        assert_eq!(loaded[0].note(false), "^-- warning: unused [clippy]");
+
+        let mut wrapped = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            wrapped,
+            r#"{{"annotations": [{{"line": 1, "message": "hi"}}]}}"#
+        )
+        .unwrap();
+        let loaded = load_annotations(wrapped.path().to_str().unwrap()).unwrap();
+        assert_eq!(loaded[0].severity, AnnotationSeverity::Info);
+
+        let mut zero = tempfile::NamedTempFile::new().unwrap();
+        write!(zero, r#"[{{"line": 0, "message": "bad"}}]"#).unwrap();
+        assert!(load_annotations(zero.path().to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_load_sarif() {
+        let mut sarif = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            sarif,
+            r#"{{"version": "2.1.0", "runs": [{{
+                "tool": {{"driver": {{"name": "clippy"}}}},
+                "results": [
+                    {{"ruleId": "unused", "level": "error", "message": {{"text": "unused import"}},
+                      "locations": [{{"physicalLocation": {{
+                          "artifactLocation": {{"uri": "file://src/lib.rs"}},
+                          "region": {{"startLine": 4, "endLine": 5}}}}}}]}},
+                    {{"message": {{"text": "no location"}}}}
+                ]
+            }}]}}"#
+        )
+        .unwrap();
+        let loaded = load_sarif(sarif.path().to_str().unwrap()).unwrap();
+        assert_eq!(
+            loaded,
+            [Annotation {
+                file: Some("src/lib.rs".to_string()),
+                line: 4,
+                end_line: Some(5),
+                message: "unused import".to_string(),
+                severity: AnnotationSeverity::Error,
+                source: Some("clippy/unused".to_string()),
+            }]
+        );
+
+        let mut invalid = tempfile::NamedTempFile::new().unwrap();
+        write!(invalid, "{{}}").unwrap();
+        assert!(load_sarif(invalid.path().to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_select_filters_by_file_and_range() {
+        let annotations = vec![
+            annotation(Some("src/main.rs"), 9),
+            annotation(None, 3),
+            annotation(Some("other.rs"), 4),
+            annotation(Some("./src/main.rs"), 40),
+        ];
+        let selected = select(&annotations, "/repo/src/main.rs", 1, 20);
+        let lines: Vec<usize> = selected.iter().map(|a| a.line).collect();
+        assert_eq!(lines, [3, 9]);
+    }
+}
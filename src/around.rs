@@ -0,0 +1,227 @@
+//! Context extraction around a known line (`--around LINE:CONTEXT`)
+//!
+//! Given a line number from a stack trace or compiler error, batless keeps
+//! only the surrounding lines and names the enclosing function or type,
+//! found via the same tree-sitter summary used by `--summary`.
+
+use crate::ast_summarizer::AstSummarizer;
+use crate::summary::SummaryLevel;
+use crate::summary_item::SummaryItem;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+/// Context lines used when `--around` is given a bare line number
+pub const DEFAULT_AROUND_CONTEXT: usize = 10;
+
+/// Preview height assumed when neither `FZF_PREVIEW_LINES` nor `LINES` is set
+pub const DEFAULT_PREVIEW_HEIGHT: usize = 40;
+
+/// Summary kinds that can enclose a line; imports and bindings never do
+const ENCLOSING_KINDS: &[&str] = &[
+    "function",
+    "method",
+    "arrow",
+    "decorator",
+    "impl",
+    "trait",
+    "mod",
+    "class",
+    "struct",
+    "enum",
+    "interface",
+];
+
+/// Requested focus line and number of context lines on each side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AroundSpec {
+    pub line: usize,
+    pub context: usize,
+}
+
+impl AroundSpec {
+    /// Last line that has to be read to fill the window
+    pub const fn last_line(&self) -> usize {
+        self.line.saturating_add(self.context)
+    }
+}
+
+/// Context that centers a line in the preview pane: half the height reported
+/// by fzf (`FZF_PREVIEW_LINES`) or the shell (`LINES`)
+pub fn preview_context() -> usize {
+    let height = ["FZF_PREVIEW_LINES", "LINES"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok()?.trim().parse::<usize>().ok())
+        .filter(|&height| height > 0)
+        .unwrap_or(DEFAULT_PREVIEW_HEIGHT);
+    height.saturating_sub(1) / 2
+}
+
+/// Parse `LINE:CONTEXT` (or a bare `LINE`) into an [`AroundSpec`]
+pub fn parse_around(input: &str) -> Result<AroundSpec, String> {
+    let (line, context) = input.split_once(':').unwrap_or((input, ""));
+    let line: usize = line
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid line '{line}' in '{input}' (expected e.g. 245:20)"))?;
+    if line == 0 {
+        return Err("line numbers start at 1".to_string());
+    }
+    let context = if context.trim().is_empty() {
+        DEFAULT_AROUND_CONTEXT
+    } else {
+        context
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid context '{context}' in '{input}'"))?
+    };
+    Ok(AroundSpec { line, context })
+}
+
+/// The extracted window, with 1-based inclusive line bounds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineWindow {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    /// Innermost function or type containing `line`
+    pub enclosing: Option<SummaryItem>,
+    /// Name given to `--symbol`; the window is then that symbol's range and
+    /// `enclosing` the symbol itself
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+impl LineWindow {
+    /// One-line header for text output, e.g.
+    /// `// lines 225-265 around 245 in function process_file (lines 200-310)`,
+    /// or `// function Config::load (lines 40-62)` for `--symbol`
+    pub fn header(&self) -> String {
+        if let (Some(symbol), Some(item)) = (&self.symbol, &self.enclosing) {
+            let name = item.qualified_name.as_ref().unwrap_or(symbol);
+            return format!(
+                "// {} {name} (lines {}-{})",
+                item.kind, self.start, self.end
+            );
+        }
+        let mut header = format!("// lines {}-{} around {}", self.start, self.end, self.line);
+        if let Some(item) = &self.enclosing {
+            let _ = write!(header, " in {}", item.kind);
+            if let Some(name) = &item.name {
+                let _ = write!(header, " {name}");
+            }
+            let _ = match item.end_line {
+                Some(end) => write!(header, " (lines {}-{end})", item.line_number),
+                None => write!(header, " (line {})", item.line_number),
+            };
+        }
+        header
+    }
+}
+
+/// Cut the window described by `spec` out of `lines`, or `None` when the
+/// focus line is past the end of the input.
+pub fn extract_window(
+    lines: &[String],
+    language: Option<&str>,
+    spec: AroundSpec,
+) -> Option<(Vec<String>, LineWindow)> {
+    if spec.line > lines.len() {
+        return None;
+    }
+    let start = spec.line.saturating_sub(spec.context).max(1);
+    let end = spec.last_line().min(lines.len());
+    let window = LineWindow {
+        line: spec.line,
+        start,
+        end,
+        enclosing: enclosing_item(lines, language, spec.line),
+        symbol: None,
+    };
+    Some((lines[start - 1..end].to_vec(), window))
+}
+
+/// Innermost summary item whose range contains `line`
+fn enclosing_item(lines: &[String], language: Option<&str>, line: usize) -> Option<SummaryItem> {
+    let items = AstSummarizer::extract_summary(&lines.join("\n"), language, SummaryLevel::Standard);
+    innermost_enclosing(&items, line).cloned()
+}
+
+/// Innermost function or type among `items` whose range contains `line`
+pub fn innermost_enclosing<'a>(
+    items: impl IntoIterator<Item = &'a SummaryItem>,
+    line: usize,
+) -> Option<&'a SummaryItem> {
+    items
+        .into_iter()
+        .filter(|item| ENCLOSING_KINDS.contains(&item.kind.as_str()))
+        .filter(|item| item.line_number <= line && item.end_line.is_some_and(|end| end >= line))
+        .max_by_key(|item| item.line_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_around() {
+        assert_eq!(
+            parse_around("245:20"),
+            Ok(AroundSpec {
+                line: 245,
+                context: 20
+            })
+        );
+        assert_eq!(parse_around("7").unwrap().context, DEFAULT_AROUND_CONTEXT);
+        assert!(parse_around("0:5").is_err());
+        assert!(parse_around("x:5").is_err());
+        assert!(parse_around("5:y").is_err());
+    }
+
+    #[test]
+    fn test_window_clamped_and_enclosing_function_found() {
+        let source = lines(
+            "use std::io;\n\nstruct S;\n\nimpl S {\n    fn run(&self) {\n        let a = 1;\n        let b = 2;\n    }\n}\n",
+        );
+        let spec = AroundSpec {
+            line: 7,
+            context: 1,
+        };
+        let (window_lines, window) = extract_window(&source, Some("Rust"), spec).unwrap();
+        assert_eq!(
+            window_lines,
+            [
+                "    fn run(&self) {",
+                "        let a = 1;",
+                "        let b = 2;"
+            ]
+        );
+        assert_eq!((window.start, window.end), (6, 8));
+        let enclosing = window.enclosing.as_ref().unwrap();
+        assert_eq!(enclosing.name.as_deref(), Some("run"));
+        assert_eq!(
+            window.header(),
+            "// lines 6-8 around 7 in function run (lines 6-9)"
+        );
+
+        let spec = AroundSpec {
+            line: 2,
+            context: 50,
+        };
+        let (_, window) = extract_window(&source, Some("Rust"), spec).unwrap();
+        assert_eq!((window.start, window.end), (1, 10));
+        assert!(window.enclosing.is_none());
+    }
+
+    #[test]
+    fn test_line_past_end() {
+        let spec = AroundSpec {
+            line: 5,
+            context: 1,
+        };
+        assert!(extract_window(&lines("a\nb"), None, spec).is_none());
+    }
+}
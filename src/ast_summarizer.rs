@@ -4,28 +4,681 @@
 //! the source code into an Abstract Syntax Tree (AST) and extracting
 //! relevant nodes based on the summary level.
 
+use crate::error::{BatlessError, BatlessResult};
+use crate::formatters::index_formatter::IndexFormatter;
+use crate::grammar_loader;
 use crate::summary::SummaryLevel;
-use crate::summary_item::SummaryItem;
+use crate::summary_item::{SummaryItem, SymbolMetrics};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
 use std::time::{Duration, Instant};
-use tree_sitter::{ParseOptions, Parser, Query, QueryCursor, StreamingIterator};
+use tree_sitter::{Node, ParseOptions, Parser, Query, QueryCursor, StreamingIterator};
 
 /// Maximum time allowed for tree-sitter parsing before aborting.
 const PARSE_TIMEOUT: Duration = Duration::from_millis(500);
 
+/// Grammars with summary queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grammar {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+}
+
+impl Grammar {
+    /// The grammars compiled in; none in WebAssembly builds
+    const ALL: &'static [Self] = if cfg!(target_arch = "wasm32") {
+        &[]
+    } else {
+        &[Self::Rust, Self::Python, Self::JavaScript, Self::TypeScript]
+    };
+
+    /// Compiled-in grammar summarizing `language`
+    fn for_language(language: &str) -> Option<Self> {
+        let grammar = match language {
+            "Rust" => Self::Rust,
+            "Python" => Self::Python,
+            "JavaScript" | "JSX" => Self::JavaScript,
+            "TypeScript" | "TSX" => Self::TypeScript,
+            _ => return None,
+        };
+        Self::ALL.contains(&grammar).then_some(grammar)
+    }
+
+    fn language(self) -> tree_sitter::Language {
+        let name = match self {
+            Self::Rust => "Rust",
+            Self::Python => "Python",
+            Self::JavaScript => "JavaScript",
+            Self::TypeScript => "TypeScript",
+        };
+        grammar_loader::bundled(name)
+            .expect("every grammar in Grammar::ALL is compiled in")
+            .0
+    }
+
+    /// Directory name under `queries/` holding the user's query files
+    const fn dir_name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Python => "python",
+            Self::JavaScript => "javascript",
+            Self::TypeScript => "typescript",
+        }
+    }
+
+    fn query_source(self, level: SummaryLevel) -> Option<&'static str> {
+        Some(match self {
+            Self::Rust => match level {
+                SummaryLevel::Minimal => {
+                    "(function_item name: (identifier) @name) @function
+                         (struct_item name: (type_identifier) @name) @struct
+                         (enum_item name: (type_identifier) @name) @enum
+                         (impl_item) @impl"
+                }
+                SummaryLevel::Standard | SummaryLevel::Signatures => {
+                    "(function_item name: (identifier) @name) @function
+                         (struct_item name: (type_identifier) @name) @struct
+                         (enum_item name: (type_identifier) @name) @enum
+                         (impl_item) @impl
+                         (trait_item name: (type_identifier) @name) @trait
+                         (mod_item name: (identifier) @name) @mod
+                         (use_declaration) @use"
+                }
+                SummaryLevel::Detailed => {
+                    "(function_item name: (identifier) @name) @function
+                         (struct_item name: (type_identifier) @name) @struct
+                         (enum_item name: (type_identifier) @name) @enum
+                         (impl_item) @impl
+                         (trait_item name: (type_identifier) @name) @trait
+                         (mod_item name: (identifier) @name) @mod
+                         (macro_definition name: (identifier) @name) @macro
+                         (use_declaration) @use
+                         (let_declaration) @let
+                         (const_item) @const
+                         (static_item) @static"
+                }
+                SummaryLevel::None => return None,
+            },
+            Self::Python => match level {
+                SummaryLevel::Minimal => {
+                    "(function_definition name: (identifier) @name) @function
+                         (class_definition name: (identifier) @name) @class"
+                }
+                SummaryLevel::Standard | SummaryLevel::Signatures => {
+                    "(function_definition name: (identifier) @name) @function
+                         (class_definition name: (identifier) @name) @class
+                         (import_statement) @import
+                         (import_from_statement) @import_from
+                         (decorated_definition) @decorator"
+                }
+                SummaryLevel::Detailed => {
+                    "(function_definition name: (identifier) @name) @function
+                         (class_definition name: (identifier) @name) @class
+                         (import_statement) @import
+                         (import_from_statement) @import_from
+                         (decorated_definition) @decorator
+                         (assignment left: (identifier) @name) @assignment
+                         (global_statement) @global
+                         (nonlocal_statement) @nonlocal"
+                }
+                SummaryLevel::None => return None,
+            },
+            Self::JavaScript => match level {
+                SummaryLevel::Minimal => {
+                    "(function_declaration name: (identifier) @name) @function
+                         (class_declaration name: (identifier) @name) @class
+                         (arrow_function) @arrow"
+                }
+                SummaryLevel::Standard | SummaryLevel::Signatures => {
+                    "(function_declaration name: (identifier) @name) @function
+                         (class_declaration name: (identifier) @name) @class
+                         (method_definition name: (property_identifier) @name) @method
+                         (arrow_function) @arrow
+                         (export_statement) @export
+                         (import_statement) @import"
+                }
+                SummaryLevel::Detailed => {
+                    "(function_declaration name: (identifier) @name) @function
+                         (class_declaration name: (identifier) @name) @class
+                         (method_definition name: (property_identifier) @name) @method
+                         (arrow_function) @arrow
+                         (export_statement) @export
+                         (import_statement) @import
+                         (variable_declarator name: (identifier) @name) @var
+                         (lexical_declaration) @const"
+                }
+                SummaryLevel::None => return None,
+            },
+            Self::TypeScript => match level {
+                SummaryLevel::Minimal => {
+                    "(function_declaration name: (identifier) @name) @function
+                         (class_declaration name: (type_identifier) @name) @class
+                         (interface_declaration name: (type_identifier) @name) @interface
+                         (arrow_function) @arrow"
+                }
+                SummaryLevel::Standard | SummaryLevel::Signatures => {
+                    "(function_declaration name: (identifier) @name) @function
+                         (class_declaration name: (type_identifier) @name) @class
+                         (interface_declaration name: (type_identifier) @name) @interface
+                         (type_alias_declaration name: (type_identifier) @name) @type
+                         (method_definition name: (property_identifier) @name) @method
+                         (arrow_function) @arrow
+                         (export_statement) @export
+                         (import_statement) @import"
+                }
+                SummaryLevel::Detailed => {
+                    "(function_declaration name: (identifier) @name) @function
+                         (class_declaration name: (type_identifier) @name) @class
+                         (interface_declaration name: (type_identifier) @name) @interface
+                         (type_alias_declaration name: (type_identifier) @name) @type
+                         (enum_declaration name: (identifier) @name) @enum
+                         (method_definition name: (property_identifier) @name) @method
+                         (arrow_function) @arrow
+                         (export_statement) @export
+                         (import_statement) @import
+                         (variable_declarator name: (identifier) @name) @var
+                         (lexical_declaration) @const"
+                }
+                SummaryLevel::None => return None,
+            },
+        })
+    }
+}
+
+/// Compiled summary queries by grammar and level (minimal, standard,
+/// signatures, detailed); compiling one costs more than parsing a typical
+/// file, so they are built once and shared by every thread. A query that
+/// does not compile is kept as its error, so it is not retried per file.
+static SUMMARY_QUERIES: [[OnceLock<Result<Query, String>>; 4]; 4] =
+    [const { [const { OnceLock::new() }; 4] }; 4];
+
+/// Summary query of `grammar` at `level`; `None` when the level has none
+fn summary_query(grammar: Grammar, level: SummaryLevel) -> BatlessResult<Option<&'static Query>> {
+    let Some(source) = grammar.query_source(level) else {
+        return Ok(None);
+    };
+    let slot = match level {
+        SummaryLevel::Minimal => 0,
+        SummaryLevel::Standard => 1,
+        SummaryLevel::Signatures => 2,
+        SummaryLevel::Detailed => 3,
+        SummaryLevel::None => return Ok(None),
+    };
+    let compiled = SUMMARY_QUERIES[grammar as usize][slot].get_or_init(|| {
+        let language = grammar.language();
+        // Broken user files are reported by `check_custom_queries`; fall back here
+        Query::new(
+            &language,
+            &compose_query(custom_queries(), grammar.dir_name(), level, source),
+        )
+        .or_else(|_| Query::new(&language, source))
+        .map_err(|e| e.to_string())
+    });
+    compiled.as_ref().map(Some).map_err(|e| {
+        BatlessError::processing_error(format!(
+            "The {} {} summary query does not compile: {e}",
+            grammar.dir_name(),
+            level.as_str()
+        ))
+    })
+}
+
+/// Compiled queries for runtime-loaded grammars by language and level
+type DynamicQueries = HashMap<(String, SummaryLevel), Option<&'static Query>>;
+
+/// Summary query for a grammar loaded at runtime, made of the user's query
+/// files alone; compiled once per language and level and kept for the
+/// process, like the built-in queries
+fn dynamic_query(
+    language: &str,
+    grammar: &tree_sitter::Language,
+    level: SummaryLevel,
+) -> Option<&'static Query> {
+    static QUERIES: OnceLock<Mutex<DynamicQueries>> = OnceLock::new();
+    let language = language.to_lowercase();
+    let mut queries = QUERIES
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    *queries
+        .entry((language, level))
+        .or_insert_with_key(|(language, level)| {
+            let source = compose_query(custom_queries(), language, *level, "");
+            if source.trim().is_empty() {
+                return None;
+            }
+            Query::new(grammar, &source)
+                .ok()
+                .map(|query| &*Box::leak(Box::new(query)))
+        })
+}
+
+/// A user query file (`queries/<language>/<file>.scm` in the config directory)
+///
+/// `minimal.scm`, `standard.scm`, `signatures.scm` and `detailed.scm` apply
+/// to their level, any other file to every level. Files extend the built-in
+/// query unless a leading comment line reads `; replace`.
+struct CustomQuery {
+    path: PathBuf,
+    /// Lowercase language name of the directory (`rust`, `gleam`)
+    language: String,
+    level: Option<SummaryLevel>,
+    replace: bool,
+    source: String,
+}
+
+/// Roots searched for `<language>/*.scm`: `~/.config/batless/queries` and
+/// the platform config directory
+fn custom_query_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = crate::sys::home_dir()
+        .map(|home| home.join(".config/batless/queries"))
+        .into_iter()
+        .chain(crate::sys::config_dir().map(|dir| dir.join("batless/queries")))
+        .collect();
+    roots.dedup();
+    roots
+}
+
+/// Query files under `root/<language>/`, sorted by path
+fn load_custom_queries(root: &Path) -> Vec<CustomQuery> {
+    let Ok(languages) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = languages
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    let mut queries = Vec::new();
+    for dir in dirs {
+        let Some(language) = dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let language = language.to_lowercase();
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "scm"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Ok(source) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let level =
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| match stem {
+                        "minimal" | "standard" | "signatures" | "detailed" => {
+                            SummaryLevel::parse(stem).ok()
+                        }
+                        _ => None,
+                    });
+            let replace = source
+                .lines()
+                .map(str::trim)
+                .take_while(|line| line.is_empty() || line.starts_with(';'))
+                .any(|line| line.trim_start_matches(';').trim() == "replace");
+            queries.push(CustomQuery {
+                path,
+                language: language.clone(),
+                level,
+                replace,
+                source,
+            });
+        }
+    }
+    queries
+}
+
+/// The user's query files, read once per process
+fn custom_queries() -> &'static [CustomQuery] {
+    static QUERIES: OnceLock<Vec<CustomQuery>> = OnceLock::new();
+    QUERIES.get_or_init(|| {
+        custom_query_roots()
+            .iter()
+            .flat_map(|root| load_custom_queries(root))
+            .collect()
+    })
+}
+
+/// The built-in query for a language and level, extended or replaced by the
+/// user's files in `queries/<language>/`
+fn compose_query(
+    queries: &[CustomQuery],
+    language: &str,
+    level: SummaryLevel,
+    builtin: &str,
+) -> String {
+    let applicable: Vec<&CustomQuery> = queries
+        .iter()
+        .filter(|query| query.language == language && query.level.is_none_or(|only| only == level))
+        .collect();
+    let mut source = if applicable.iter().any(|query| query.replace) {
+        String::new()
+    } else {
+        builtin.to_string()
+    };
+    for query in applicable {
+        source.push('\n');
+        source.push_str(&query.source);
+    }
+    source
+}
+
+/// A summary item before its line text is looked up, keyed by its first row
+struct LineItem {
+    kind: String,
+    end_row: usize,
+    name: Option<String>,
+    /// Full signature at the signatures level
+    signature: Option<String>,
+    /// First sentence of the attached documentation (`--summary-docs`)
+    doc: Option<String>,
+    /// Enclosing containers and the separator joining them to the name
+    container: Option<(String, &'static str)>,
+    /// Function metrics at the detailed level
+    metrics: Option<SymbolMetrics>,
+}
+
+/// Node kinds that add a decision point to a function
+const BRANCH_KINDS: &[&str] = &[
+    // Rust
+    "if_expression",
+    "match_arm",
+    "while_expression",
+    "loop_expression",
+    "for_expression",
+    // Python, JavaScript and TypeScript
+    "if_statement",
+    "elif_clause",
+    "for_statement",
+    "for_in_statement",
+    "while_statement",
+    "do_statement",
+    "except_clause",
+    "catch_clause",
+    "case_clause",
+    "switch_case",
+    "conditional_expression",
+    "ternary_expression",
+];
+
+/// Node kinds that open a nesting level
+const NESTING_KINDS: &[&str] = &[
+    "if_expression",
+    "match_expression",
+    "while_expression",
+    "loop_expression",
+    "for_expression",
+    "if_statement",
+    "for_statement",
+    "for_in_statement",
+    "while_statement",
+    "do_statement",
+    "switch_statement",
+    "match_statement",
+    "try_statement",
+    "with_statement",
+];
+
+/// The function definition behind a capture; `export function f` is captured
+/// as its export statement
+fn function_node<'t>(kind: &str, node: Node<'t>) -> Option<Node<'t>> {
+    if SIGNATURE_KINDS.contains(&kind) {
+        return Some(node);
+    }
+    node.child_by_field_name("declaration")
+        .filter(|declaration| {
+            matches!(
+                declaration.kind(),
+                "function_declaration" | "generator_function_declaration"
+            )
+        })
+}
+
+/// Line count, branch count and nesting depth of a function
+fn symbol_metrics(function: Node) -> SymbolMetrics {
+    let mut metrics = SymbolMetrics {
+        lines: function.end_position().row - function.start_position().row + 1,
+        branches: 0,
+        depth: 0,
+    };
+    let body = function.child_by_field_name("body").unwrap_or(function);
+    walk_metrics(body, 0, &mut metrics);
+    metrics
+}
+
+fn walk_metrics(node: Node, depth: usize, metrics: &mut SymbolMetrics) {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let kind = child.kind();
+        let short_circuit = kind == "boolean_operator"
+            || (kind == "binary_expression"
+                && child
+                    .child_by_field_name("operator")
+                    .is_some_and(|op| matches!(op.kind(), "&&" | "||" | "??")));
+        if short_circuit || BRANCH_KINDS.contains(&kind) {
+            metrics.branches += 1;
+        }
+        // `else if` continues a chain rather than nesting inside it
+        let nests = NESTING_KINDS.contains(&kind) && node.kind() != "else_clause";
+        let child_depth = depth + usize::from(nests);
+        metrics.depth = metrics.depth.max(child_depth);
+        walk_metrics(child, child_depth, metrics);
+    }
+}
+
+/// Names of the classes, impls, traits and modules enclosing `node`,
+/// outermost first, with the separator the language uses between them
+fn container_path(node: Node, content: &str) -> Option<(String, &'static str)> {
+    let text = |n: Node, field| {
+        n.child_by_field_name(field)
+            .and_then(|name| name.utf8_text(content.as_bytes()).ok())
+    };
+    let mut names = Vec::new();
+    let mut separator = ".";
+    let mut ancestor = node.parent();
+    while let Some(current) = ancestor {
+        let name = match current.kind() {
+            // `impl<T> Trait for Repo<T>` belongs to `Repo`
+            "impl_item" => {
+                separator = "::";
+                text(current, "type").and_then(|ty| ty.split('<').next())
+            }
+            "trait_item" | "mod_item" => {
+                separator = "::";
+                text(current, "name")
+            }
+            "class_definition"
+            | "class_declaration"
+            | "class"
+            | "abstract_class_declaration"
+            | "interface_declaration" => text(current, "name"),
+            _ => None,
+        };
+        names.extend(name.map(str::trim));
+        ancestor = current.parent();
+    }
+    if names.is_empty() {
+        return None;
+    }
+    names.reverse();
+    Some((names.join(separator), separator))
+}
+
+/// Captures whose declaration is expanded at the signatures level
+const SIGNATURE_KINDS: &[&str] = &["function", "method", "arrow"];
+
+/// Source from the start of `anchor`'s line up to the body of `node`, when a
+/// wrapped signature spans several lines; `None` keeps the first line
+fn signature_text(kind: &str, node: Node, anchor: Node, content: &str) -> Option<String> {
+    if !SIGNATURE_KINDS.contains(&kind) {
+        return None;
+    }
+    let body = node.child_by_field_name("body")?;
+    let line_start = anchor.start_byte() - anchor.start_position().column;
+    let signature = content.get(line_start..body.start_byte())?.trim_end();
+    signature.contains('\n').then(|| signature.to_string())
+}
+
+/// First sentence of the doc comment or docstring attached to `node`
+fn doc_text(node: Node, content: &str) -> Option<String> {
+    let text = |n: Node| n.utf8_text(content.as_bytes()).ok();
+    match node.kind() {
+        // Python: the first statement of the body, when it is a string
+        "function_definition" | "class_definition" => {
+            let statement = node.child_by_field_name("body")?.named_child(0)?;
+            let string = statement.named_child(0)?;
+            if statement.kind() != "expression_statement" || string.kind() != "string" {
+                return None;
+            }
+            let raw = text(string)?.trim_start_matches(|c: char| "rRuUbBfF".contains(c));
+            let quote = ["\"\"\"", "'''", "\"", "'"]
+                .into_iter()
+                .find(|q| raw.starts_with(q))?;
+            let inner = raw.strip_prefix(quote)?;
+            first_sentence(inner.strip_suffix(quote).unwrap_or(inner).lines())
+        }
+        // Rust: `///` lines or a `/** */` block above the item and its attributes
+        "function_item" | "struct_item" | "enum_item" | "impl_item" | "trait_item" | "mod_item"
+        | "macro_definition" | "const_item" | "static_item" => {
+            let mut lines = Vec::new();
+            let mut sibling = node.prev_sibling();
+            while let Some(prev) = sibling {
+                let comment = text(prev)?;
+                match prev.kind() {
+                    "attribute_item" => {}
+                    "line_comment"
+                        if comment.starts_with("///") && !comment.starts_with("////") =>
+                    {
+                        lines.push(comment[3..].trim_end());
+                    }
+                    "block_comment" if lines.is_empty() && comment.starts_with("/**") => {
+                        return first_sentence(jsdoc_lines(comment));
+                    }
+                    _ => break,
+                }
+                sibling = prev.prev_sibling();
+            }
+            lines.reverse();
+            first_sentence(lines.into_iter())
+        }
+        // JavaScript and TypeScript: a `/** */` block directly above the
+        // declaration, or above the statement that exports or assigns it
+        _ => {
+            let mut outer = node;
+            while let Some(parent) = outer.parent().filter(|p| {
+                matches!(
+                    p.kind(),
+                    "export_statement"
+                        | "variable_declarator"
+                        | "lexical_declaration"
+                        | "variable_declaration"
+                )
+            }) {
+                outer = parent;
+            }
+            let comment = outer.prev_sibling().filter(|c| {
+                c.kind() == "comment" && c.end_position().row + 1 >= outer.start_position().row
+            })?;
+            let comment = text(comment)?;
+            comment
+                .starts_with("/**")
+                .then(|| first_sentence(jsdoc_lines(comment)))
+                .flatten()
+        }
+    }
+}
+
+/// Lines of a `/** ... */` comment without the delimiters and leading `*`
+fn jsdoc_lines(comment: &str) -> impl Iterator<Item = &str> {
+    let inner = comment.trim_start_matches("/**");
+    inner
+        .strip_suffix("*/")
+        .unwrap_or(inner)
+        .lines()
+        .map(|line| line.trim_start().trim_start_matches('*'))
+}
+
+/// First sentence of the first paragraph, with whitespace collapsed; a
+/// paragraph ends at a blank line or a JSDoc `@tag`
+fn first_sentence<'a>(lines: impl Iterator<Item = &'a str>) -> Option<String> {
+    let paragraph = lines
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty() && !line.starts_with('@'))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let sentence = paragraph
+        .find(". ")
+        .map_or(paragraph.as_str(), |end| &paragraph[..=end]);
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+/// Python `__all__ = [...]` and `__all__ += (...)` assignments
+fn python_all_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?m)^__all__\s*\+?=\s*[\[(]([^\])]*)[\])]").expect("__all__ pattern is valid")
+    })
+}
+
+/// Names exported through `__all__`; `None` when the module does not set it
+fn python_all(content: &str) -> Option<HashSet<String>> {
+    let mut names = None;
+    for list in python_all_pattern().captures_iter(content) {
+        let names = names.get_or_insert_with(HashSet::new);
+        names.extend(
+            list[1]
+                .split(',')
+                .map(|name| name.trim().trim_matches(|c| c == '"' || c == '\''))
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+        );
+    }
+    names
+}
+
+/// Whether a Python name is public by convention (dunders such as
+/// `__init__` count as public)
+fn is_public_python_name(name: &str) -> bool {
+    !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__"))
+}
+
 /// AST-based summary extractor
 pub struct AstSummarizer;
 
 impl AstSummarizer {
     /// Parse content with a timeout to prevent hangs on pathological inputs.
     /// Returns `None` if parsing fails or times out.
-    fn parse_with_timeout(parser: &mut Parser, content: &str) -> Option<tree_sitter::Tree> {
-        Self::parse_with_deadline(parser, content, PARSE_TIMEOUT)
+    fn parse_with_timeout(
+        parser: &mut Parser,
+        content: &str,
+        deadline: Option<Instant>,
+    ) -> Option<tree_sitter::Tree> {
+        let timeout = deadline.map_or(PARSE_TIMEOUT, |deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(PARSE_TIMEOUT)
+        });
+        Self::parse_with_deadline(parser, content, timeout)
     }
 
     /// Parse content with a configurable timeout duration.
     /// Returns `None` if parsing fails or the deadline is exceeded.
-    fn parse_with_deadline(
+    pub(crate) fn parse_with_deadline(
         parser: &mut Parser,
         content: &str,
         timeout: Duration,
@@ -54,167 +707,205 @@ impl AstSummarizer {
         )
     }
 
+    /// Check the user's query files (`~/.config/batless/queries/<language>/*.scm`)
+    /// against their grammars, built in or loaded at runtime; returns how many
+    /// were loaded. Directories of unknown languages are skipped.
+    pub fn check_custom_queries() -> BatlessResult<usize> {
+        let queries = custom_queries();
+        for query in queries {
+            let grammar = Grammar::ALL
+                .iter()
+                .copied()
+                .find(|grammar| grammar.dir_name() == query.language)
+                .map(Grammar::language)
+                .or_else(|| grammar_loader::grammar_for(&query.language));
+            let Some(grammar) = grammar else {
+                continue;
+            };
+            Query::new(&grammar, &query.source).map_err(|e| {
+                BatlessError::config_error_with_help(
+                    format!("Invalid summary query {}: {e}", query.path.display()),
+                    Some(
+                        "Fix the tree-sitter query or remove the file; captures become \
+                         summary kinds and @name names the symbol"
+                            .to_string(),
+                    ),
+                )
+            })?;
+        }
+        Ok(queries.len())
+    }
+
+    /// Compile every summary query now instead of on first use
+    pub fn warm_up() {
+        for &grammar in Grammar::ALL {
+            for level in [
+                SummaryLevel::Minimal,
+                SummaryLevel::Standard,
+                SummaryLevel::Signatures,
+                SummaryLevel::Detailed,
+            ] {
+                // Failures surface, and fall back, when a file is summarized
+                let _ = summary_query(grammar, level);
+            }
+        }
+        python_all_pattern();
+    }
+
     /// Extract a summary of important code structures using AST parsing
     pub fn extract_summary(
         content: &str,
         language: Option<&str>,
         level: SummaryLevel,
     ) -> Vec<SummaryItem> {
-        if !level.is_enabled() {
-            return Vec::new();
-        }
-
-        match language {
-            Some("Rust") => Self::summarize_rust(content, level),
-            Some("Python") => Self::summarize_python(content, level),
-            Some("JavaScript" | "JSX") => Self::summarize_javascript(content, level),
-            Some("TypeScript" | "TSX") => Self::summarize_typescript(content, level),
-            // Fallback to empty for unsupported languages (caller should handle fallback to regex)
-            _ => Vec::new(),
-        }
+        Self::extract_summary_with_docs(content, language, level, false)
     }
 
-    fn summarize_rust(content: &str, level: SummaryLevel) -> Vec<SummaryItem> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_rust::LANGUAGE.into())
-            .expect("Error loading Rust grammar");
-
-        let Some(tree) = Self::parse_with_timeout(&mut parser, content) else {
-            return Vec::new();
-        };
-        let root_node = tree.root_node();
-
-        let query_string = match level {
-            SummaryLevel::Minimal => {
-                "(function_item name: (identifier) @name) @function
-                 (struct_item name: (type_identifier) @name) @struct
-                 (enum_item name: (type_identifier) @name) @enum
-                 (impl_item) @impl"
-            }
-            SummaryLevel::Standard => {
-                "(function_item name: (identifier) @name) @function
-                 (struct_item name: (type_identifier) @name) @struct
-                 (enum_item name: (type_identifier) @name) @enum
-                 (impl_item) @impl
-                 (trait_item name: (type_identifier) @name) @trait
-                 (mod_item name: (identifier) @name) @mod
-                 (use_declaration) @use"
-            }
-            SummaryLevel::Detailed => {
-                "(function_item name: (identifier) @name) @function
-                 (struct_item name: (type_identifier) @name) @struct
-                 (enum_item name: (type_identifier) @name) @enum
-                 (impl_item) @impl
-                 (trait_item name: (type_identifier) @name) @trait
-                 (mod_item name: (identifier) @name) @mod
-                 (macro_definition name: (identifier) @name) @macro
-                 (use_declaration) @use
-                 (let_declaration) @let
-                 (const_item) @const
-                 (static_item) @static"
-            }
-            SummaryLevel::None => return Vec::new(),
-        };
-
-        let query = Query::new(&tree_sitter_rust::LANGUAGE.into(), query_string)
-            .expect("Error compiling query");
-
-        let capture_names = query.capture_names().to_vec();
-        let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, content.as_bytes());
+    /// Like [`Self::extract_summary`]; with `docs`, items also carry the
+    /// first sentence of their doc comment or docstring. Empty when the
+    /// grammar or its query cannot be loaded.
+    pub fn extract_summary_with_docs(
+        content: &str,
+        language: Option<&str>,
+        level: SummaryLevel,
+        docs: bool,
+    ) -> Vec<SummaryItem> {
+        Self::try_extract_summary(content, language, level, docs).unwrap_or_default()
+    }
 
-        let lines: Vec<&str> = content.lines().collect();
-        // BTreeMap<start_line, (kind, end_line)> — first write wins per line
-        let mut line_items: std::collections::BTreeMap<usize, (String, usize)> =
-            std::collections::BTreeMap::new();
+    /// Like [`Self::extract_summary_with_docs`], but a grammar that cannot
+    /// be loaded or a query that does not compile is an error, so callers can
+    /// tell it apart from a file with nothing to summarize
+    pub fn try_extract_summary(
+        content: &str,
+        language: Option<&str>,
+        level: SummaryLevel,
+        docs: bool,
+    ) -> BatlessResult<Vec<SummaryItem>> {
+        Self::try_extract_summary_until(content, language, level, docs, None)
+    }
 
-        while let Some(m) = matches.next() {
-            // Use @name capture's row when present — it lands on the declaration line,
-            // not on any preceding decorator whose span is included in the outer node
-            let name_row = m
-                .captures
-                .iter()
-                .find(|c| capture_names[c.index as usize] == "name")
-                .map(|c| c.node.start_position().row);
-            for capture in m.captures {
-                let kind = &capture_names[capture.index as usize];
-                if *kind == "name" {
-                    continue;
-                }
-                let start_line = name_row.unwrap_or_else(|| capture.node.start_position().row);
-                let end_line = capture.node.end_position().row;
-                line_items
-                    .entry(start_line)
-                    .or_insert_with(|| (kind.to_string(), end_line));
-            }
+    /// Like [`Self::try_extract_summary`], but parsing and query matching stop
+    /// once `deadline` passes, leaving the items found so far
+    pub fn try_extract_summary_until(
+        content: &str,
+        language: Option<&str>,
+        level: SummaryLevel,
+        docs: bool,
+        deadline: Option<Instant>,
+    ) -> BatlessResult<Vec<SummaryItem>> {
+        if !level.is_enabled() {
+            return Ok(Vec::new());
         }
 
-        line_items
+        let summarize = |grammar| Self::summarize_builtin(grammar, content, level, docs, deadline);
+        let items = match language {
+            Some(name) => match Grammar::for_language(name) {
+                Some(grammar) => summarize(grammar)?,
+                None => Self::summarize_dynamic(content, name, level, docs, deadline)?,
+            },
+            // Fallback to empty for unsupported languages (caller should handle fallback to regex)
+            None => Vec::new(),
+        };
+        Ok(items
             .into_iter()
-            .filter_map(|(idx, (kind, end_row))| {
-                lines
-                    .get(idx)
-                    .map(|&line| SummaryItem::new(line, idx + 1, Some(end_row + 1), kind))
+            .map(|item| {
+                let visibility = SummaryItem::detect_visibility(&item.line, language);
+                item.with_visibility(visibility)
             })
-            .collect()
+            .collect())
     }
 
-    fn summarize_python(content: &str, level: SummaryLevel) -> Vec<SummaryItem> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_python::LANGUAGE.into())
-            .expect("Error loading Python grammar");
-
-        let Some(tree) = Self::parse_with_timeout(&mut parser, content) else {
-            return Vec::new();
-        };
-        let root_node = tree.root_node();
-
-        let query_string = match level {
-            SummaryLevel::Minimal => {
-                "(function_definition name: (identifier) @name) @function
-                 (class_definition name: (identifier) @name) @class"
-            }
-            SummaryLevel::Standard => {
-                "(function_definition name: (identifier) @name) @function
-                 (class_definition name: (identifier) @name) @class
-                 (import_statement) @import
-                 (import_from_statement) @import_from
-                 (decorated_definition) @decorator"
+    /// Keep only the public API surface: Rust `pub` items; JavaScript and
+    /// TypeScript exports and the non-private members of exported classes;
+    /// Python names listed in `__all__` (or not starting with `_` when it is
+    /// absent) and their public members. Other languages are left as-is.
+    pub fn retain_public(items: &mut Vec<SummaryItem>, content: &str, language: Option<&str>) {
+        match language {
+            Some("Rust") => items.retain(|item| item.visibility.as_deref() == Some("pub")),
+            Some("JavaScript" | "JSX" | "TypeScript" | "TSX") => {
+                // `export class Api {` is captured as the export statement, without a name
+                let exported: HashSet<String> = items
+                    .iter()
+                    .filter(|item| {
+                        item.container.is_none() && item.visibility.as_deref() == Some("export")
+                    })
+                    .filter_map(|item| {
+                        item.name
+                            .clone()
+                            .or_else(|| IndexFormatter::extract_name(&item.line))
+                    })
+                    .collect();
+                items.retain(|item| match &item.container {
+                    None => item.visibility.as_deref() == Some("export"),
+                    Some(container) => {
+                        let member = item.line.trim_start();
+                        container
+                            .split('.')
+                            .next()
+                            .is_some_and(|outer| exported.contains(outer))
+                            && !["private ", "protected ", "#"]
+                                .iter()
+                                .any(|prefix| member.starts_with(prefix))
+                    }
+                });
             }
-            SummaryLevel::Detailed => {
-                "(function_definition name: (identifier) @name) @function
-                 (class_definition name: (identifier) @name) @class
-                 (import_statement) @import
-                 (import_from_statement) @import_from
-                 (decorated_definition) @decorator
-                 (assignment left: (identifier) @name) @assignment
-                 (global_statement) @global
-                 (nonlocal_statement) @nonlocal"
+            Some("Python") => {
+                let all = python_all(content);
+                // Imports and decorator lines have no name and are not API
+                items.retain(|item| {
+                    let Some(name) = &item.name else {
+                        return false;
+                    };
+                    let mut path = item
+                        .container
+                        .iter()
+                        .flat_map(|container| container.split('.'))
+                        .chain([name.as_str()]);
+                    let Some(outer) = path.next() else {
+                        return false;
+                    };
+                    all.as_ref()
+                        .map_or_else(|| !outer.starts_with('_'), |all| all.contains(outer))
+                        && path.all(is_public_python_name)
+                });
             }
-            SummaryLevel::None => return Vec::new(),
-        };
-
-        let query = Query::new(&tree_sitter_python::LANGUAGE.into(), query_string)
-            .expect("Error compiling query");
+            _ => {}
+        }
+    }
 
+    /// Turn the query matches over a parsed file into summary items, one per line
+    fn collect_items(
+        root_node: Node,
+        query: &Query,
+        content: &str,
+        level: SummaryLevel,
+        docs: bool,
+        deadline: Option<Instant>,
+    ) -> Vec<SummaryItem> {
         let capture_names = query.capture_names().to_vec();
         let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, content.as_bytes());
+        let mut matches = cursor.matches(query, root_node, content.as_bytes());
 
         let lines: Vec<&str> = content.lines().collect();
-        let mut line_items: std::collections::BTreeMap<usize, (String, usize)> =
+        // BTreeMap<start_line, LineItem> — first write wins per line
+        let mut line_items: std::collections::BTreeMap<usize, LineItem> =
             std::collections::BTreeMap::new();
 
         while let Some(m) = matches.next() {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
             // Use @name capture's row when present — it lands on the declaration line,
             // not on any preceding decorator whose span is included in the outer node
-            let name_row = m
+            let name_capture = m
                 .captures
                 .iter()
-                .find(|c| capture_names[c.index as usize] == "name")
-                .map(|c| c.node.start_position().row);
+                .find(|c| capture_names[c.index as usize] == "name");
+            let name_row = name_capture.map(|c| c.node.start_position().row);
+            let name = name_capture
+                .and_then(|c| c.node.utf8_text(content.as_bytes()).ok())
+                .map(str::to_string);
             for capture in m.captures {
                 let kind = &capture_names[capture.index as usize];
                 if *kind == "name" {
@@ -222,189 +913,105 @@ impl AstSummarizer {
                 }
                 let start_line = name_row.unwrap_or_else(|| capture.node.start_position().row);
                 let end_line = capture.node.end_position().row;
-                line_items
-                    .entry(start_line)
-                    .or_insert_with(|| (kind.to_string(), end_line));
+                line_items.entry(start_line).or_insert_with(|| {
+                    let signature = (level == SummaryLevel::Signatures)
+                        .then(|| {
+                            let anchor = name_capture.map_or(capture.node, |c| c.node);
+                            signature_text(kind, capture.node, anchor, content)
+                        })
+                        .flatten();
+                    LineItem {
+                        kind: kind.to_string(),
+                        end_row: end_line,
+                        name: name.clone(),
+                        signature,
+                        doc: docs.then(|| doc_text(capture.node, content)).flatten(),
+                        container: container_path(capture.node, content),
+                        metrics: (level == SummaryLevel::Detailed)
+                            .then(|| function_node(kind, capture.node).map(symbol_metrics))
+                            .flatten(),
+                    }
+                });
             }
         }
 
         line_items
             .into_iter()
-            .filter_map(|(idx, (kind, end_row))| {
-                lines
-                    .get(idx)
-                    .map(|&line| SummaryItem::new(line, idx + 1, Some(end_row + 1), kind))
+            .filter_map(|(idx, item)| {
+                lines.get(idx).map(|&line| {
+                    let line = item.signature.as_deref().unwrap_or(line);
+                    let (container, separator) = item.container.unzip();
+                    SummaryItem::new(line, idx + 1, Some(item.end_row + 1), item.kind)
+                        .with_name(item.name)
+                        .with_doc(item.doc)
+                        .with_container(container, separator.unwrap_or("."))
+                        .with_metrics(item.metrics)
+                })
             })
             .collect()
     }
 
-    fn summarize_javascript(content: &str, level: SummaryLevel) -> Vec<SummaryItem> {
+    /// Summarize with one of the compiled-in grammars
+    fn summarize_builtin(
+        grammar: Grammar,
+        content: &str,
+        level: SummaryLevel,
+        docs: bool,
+        deadline: Option<Instant>,
+    ) -> BatlessResult<Vec<SummaryItem>> {
         let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_javascript::LANGUAGE.into())
-            .expect("Error loading JavaScript grammar");
-
-        let Some(tree) = Self::parse_with_timeout(&mut parser, content) else {
-            return Vec::new();
+        parser.set_language(&grammar.language()).map_err(|e| {
+            BatlessError::processing_error(format!(
+                "Cannot load the {} grammar: {e}",
+                grammar.dir_name()
+            ))
+        })?;
+        let Some(query) = summary_query(grammar, level)? else {
+            return Ok(Vec::new());
         };
-        let root_node = tree.root_node();
-
-        let query_string = match level {
-            SummaryLevel::Minimal => {
-                "(function_declaration name: (identifier) @name) @function
-                 (class_declaration name: (identifier) @name) @class
-                 (arrow_function) @arrow"
-            }
-            SummaryLevel::Standard => {
-                "(function_declaration name: (identifier) @name) @function
-                 (class_declaration name: (identifier) @name) @class
-                 (method_definition name: (property_identifier) @name) @method
-                 (arrow_function) @arrow
-                 (export_statement) @export
-                 (import_statement) @import"
-            }
-            SummaryLevel::Detailed => {
-                "(function_declaration name: (identifier) @name) @function
-                 (class_declaration name: (identifier) @name) @class
-                 (method_definition name: (property_identifier) @name) @method
-                 (arrow_function) @arrow
-                 (export_statement) @export
-                 (import_statement) @import
-                 (variable_declarator name: (identifier) @name) @var
-                 (lexical_declaration) @const"
-            }
-            SummaryLevel::None => return Vec::new(),
+        let Some(tree) = Self::parse_with_timeout(&mut parser, content, deadline) else {
+            return Ok(Vec::new());
         };
-
-        let query = Query::new(&tree_sitter_javascript::LANGUAGE.into(), query_string)
-            .expect("Error compiling query");
-
-        let capture_names = query.capture_names().to_vec();
-        let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, content.as_bytes());
-
-        let lines: Vec<&str> = content.lines().collect();
-        let mut line_items: std::collections::BTreeMap<usize, (String, usize)> =
-            std::collections::BTreeMap::new();
-
-        while let Some(m) = matches.next() {
-            // Use @name capture's row when present — it lands on the declaration line,
-            // not on any preceding decorator whose span is included in the outer node
-            let name_row = m
-                .captures
-                .iter()
-                .find(|c| capture_names[c.index as usize] == "name")
-                .map(|c| c.node.start_position().row);
-            for capture in m.captures {
-                let kind = &capture_names[capture.index as usize];
-                if *kind == "name" {
-                    continue;
-                }
-                let start_line = name_row.unwrap_or_else(|| capture.node.start_position().row);
-                let end_line = capture.node.end_position().row;
-                line_items
-                    .entry(start_line)
-                    .or_insert_with(|| (kind.to_string(), end_line));
-            }
-        }
-
-        line_items
-            .into_iter()
-            .filter_map(|(idx, (kind, end_row))| {
-                lines
-                    .get(idx)
-                    .map(|&line| SummaryItem::new(line, idx + 1, Some(end_row + 1), kind))
-            })
-            .collect()
+        Ok(Self::collect_items(
+            tree.root_node(),
+            query,
+            content,
+            level,
+            docs,
+            deadline,
+        ))
     }
 
-    fn summarize_typescript(content: &str, level: SummaryLevel) -> Vec<SummaryItem> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
-            .expect("Error loading TypeScript grammar");
-
-        let Some(tree) = Self::parse_with_timeout(&mut parser, content) else {
-            return Vec::new();
+    /// Summarize with a grammar loaded at runtime; empty when none is loaded
+    /// for `language` or it has no query files
+    fn summarize_dynamic(
+        content: &str,
+        language: &str,
+        level: SummaryLevel,
+        docs: bool,
+        deadline: Option<Instant>,
+    ) -> BatlessResult<Vec<SummaryItem>> {
+        let Some(grammar) = grammar_loader::grammar_for(language) else {
+            return Ok(Vec::new());
         };
-        let root_node = tree.root_node();
-
-        let query_string = match level {
-            SummaryLevel::Minimal => {
-                "(function_declaration name: (identifier) @name) @function
-                 (class_declaration name: (type_identifier) @name) @class
-                 (interface_declaration name: (type_identifier) @name) @interface
-                 (arrow_function) @arrow"
-            }
-            SummaryLevel::Standard => {
-                "(function_declaration name: (identifier) @name) @function
-                 (class_declaration name: (type_identifier) @name) @class
-                 (interface_declaration name: (type_identifier) @name) @interface
-                 (type_alias_declaration name: (type_identifier) @name) @type
-                 (method_definition name: (property_identifier) @name) @method
-                 (arrow_function) @arrow
-                 (export_statement) @export
-                 (import_statement) @import"
-            }
-            SummaryLevel::Detailed => {
-                "(function_declaration name: (identifier) @name) @function
-                 (class_declaration name: (type_identifier) @name) @class
-                 (interface_declaration name: (type_identifier) @name) @interface
-                 (type_alias_declaration name: (type_identifier) @name) @type
-                 (enum_declaration name: (identifier) @name) @enum
-                 (method_definition name: (property_identifier) @name) @method
-                 (arrow_function) @arrow
-                 (export_statement) @export
-                 (import_statement) @import
-                 (variable_declarator name: (identifier) @name) @var
-                 (lexical_declaration) @const"
-            }
-            SummaryLevel::None => return Vec::new(),
+        let Some(query) = dynamic_query(language, &grammar, level) else {
+            return Ok(Vec::new());
         };
-
-        let query = Query::new(
-            &tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-            query_string,
-        )
-        .expect("Error compiling query");
-
-        let capture_names = query.capture_names().to_vec();
-        let mut cursor = QueryCursor::new();
-        let mut matches = cursor.matches(&query, root_node, content.as_bytes());
-
-        let lines: Vec<&str> = content.lines().collect();
-        let mut line_items: std::collections::BTreeMap<usize, (String, usize)> =
-            std::collections::BTreeMap::new();
-
-        while let Some(m) = matches.next() {
-            // Use @name capture's row when present — it lands on the declaration line,
-            // not on any preceding decorator whose span is included in the outer node
-            let name_row = m
-                .captures
-                .iter()
-                .find(|c| capture_names[c.index as usize] == "name")
-                .map(|c| c.node.start_position().row);
-            for capture in m.captures {
-                let kind = &capture_names[capture.index as usize];
-                if *kind == "name" {
-                    continue;
-                }
-                let start_line = name_row.unwrap_or_else(|| capture.node.start_position().row);
-                let end_line = capture.node.end_position().row;
-                line_items
-                    .entry(start_line)
-                    .or_insert_with(|| (kind.to_string(), end_line));
-            }
-        }
-
-        line_items
-            .into_iter()
-            .filter_map(|(idx, (kind, end_row))| {
-                lines
-                    .get(idx)
-                    .map(|&line| SummaryItem::new(line, idx + 1, Some(end_row + 1), kind))
-            })
-            .collect()
+        let mut parser = Parser::new();
+        parser.set_language(&grammar).map_err(|e| {
+            BatlessError::processing_error(format!("Cannot load the {language} grammar: {e}"))
+        })?;
+        let Some(tree) = Self::parse_with_timeout(&mut parser, content, deadline) else {
+            return Ok(Vec::new());
+        };
+        Ok(Self::collect_items(
+            tree.root_node(),
+            query,
+            content,
+            level,
+            docs,
+            deadline,
+        ))
     }
 }
 
@@ -425,6 +1032,186 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_warm_up_compiles_every_query() {
+        AstSummarizer::warm_up();
+        for &grammar in Grammar::ALL {
+            assert!(summary_query(grammar, SummaryLevel::None)
+                .unwrap()
+                .is_none());
+            let query = summary_query(grammar, SummaryLevel::Detailed)
+                .unwrap()
+                .unwrap();
+            assert!(std::ptr::eq(
+                query,
+                summary_query(grammar, SummaryLevel::Detailed)
+                    .unwrap()
+                    .unwrap()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_signatures_level_keeps_wrapped_signatures_whole() {
+        let rust = "pub fn connect(\n    host: &str,\n    port: u16,\n) -> Result<Conn, Error>\nwhere\n    Conn: Send,\n{\n    todo!()\n}\n\nfn short(a: i32) -> i32 {\n    a\n}\n";
+        let items = AstSummarizer::extract_summary(rust, Some("Rust"), SummaryLevel::Signatures);
+        assert_eq!(
+            items[0].line,
+            "pub fn connect(\n    host: &str,\n    port: u16,\n) -> Result<Conn, Error>\nwhere\n    Conn: Send,"
+        );
+        assert_eq!(items[1].line, "fn short(a: i32) -> i32 {");
+        let standard = AstSummarizer::extract_summary(rust, Some("Rust"), SummaryLevel::Standard);
+        assert_eq!(standard[0].line, "pub fn connect(");
+
+        let ts = "class Api {\n  async fetch(\n    url: string,\n    retries = 3,\n  ): Promise<Response> {\n    return get(url);\n  }\n}\n";
+        let items =
+            AstSummarizer::extract_summary(ts, Some("TypeScript"), SummaryLevel::Signatures);
+        let method = items.iter().find(|item| item.kind == "method").unwrap();
+        assert_eq!(
+            method.line,
+            "  async fetch(\n    url: string,\n    retries = 3,\n  ): Promise<Response>"
+        );
+
+        let py = "def load(\n    path,\n    strict=False,\n) -> dict:\n    return {}\n";
+        let items = AstSummarizer::extract_summary(py, Some("Python"), SummaryLevel::Signatures);
+        assert_eq!(
+            items[0].line,
+            "def load(\n    path,\n    strict=False,\n) -> dict:"
+        );
+    }
+
+    #[test]
+    fn test_docs_keep_first_sentence() {
+        let docs = |code: &str, lang| {
+            AstSummarizer::extract_summary_with_docs(code, Some(lang), SummaryLevel::Standard, true)
+                .into_iter()
+                .map(|item| item.doc)
+                .collect::<Vec<_>>()
+        };
+        let rust = "/// Opens the file. Fails when missing.\n#[inline]\npub fn open() {}\n\n// plain\nfn hidden() {}\n";
+        assert_eq!(
+            docs(rust, "Rust"),
+            [Some("Opens the file.".to_string()), None]
+        );
+        let py = "def f():\n    \"\"\"Return one.\n\n    Details.\n    \"\"\"\n    return 1\n";
+        assert_eq!(docs(py, "Python"), [Some("Return one.".to_string())]);
+        let ts = "/**\n * Adds two numbers\n * together.\n * @param a first\n */\nexport function add(a: number) {}\n";
+        assert_eq!(
+            docs(ts, "TypeScript"),
+            [Some("Adds two numbers together.".to_string())]
+        );
+        // Without the flag no docs are collected
+        let items = AstSummarizer::extract_summary(rust, Some("Rust"), SummaryLevel::Standard);
+        assert!(items.iter().all(|item| item.doc.is_none()));
+    }
+
+    #[test]
+    fn test_nested_items_are_qualified() {
+        let qualified = |code: &str, lang| {
+            AstSummarizer::extract_summary(code, Some(lang), SummaryLevel::Standard)
+                .into_iter()
+                .map(|item| item.qualified_name)
+                .collect::<Vec<_>>()
+        };
+        let py = "class UserRepo:\n    def save(self):\n        pass\n\ndef top():\n    pass\n";
+        assert_eq!(
+            qualified(py, "Python"),
+            [None, Some("UserRepo.save".to_string()), None]
+        );
+        let rust = "mod store {\n    impl<T> Repo<T> {\n        fn save(&self) {}\n    }\n}\n";
+        let items = AstSummarizer::extract_summary(rust, Some("Rust"), SummaryLevel::Standard);
+        assert_eq!(items[2].container.as_deref(), Some("store::Repo"));
+        assert_eq!(
+            items[2].qualified_name.as_deref(),
+            Some("store::Repo::save")
+        );
+        // The impl block itself has no name to qualify
+        assert_eq!(items[1].container.as_deref(), Some("store"));
+        assert_eq!(items[1].qualified_name, None);
+        let js = "class Api {\n  get() {}\n}\n";
+        assert_eq!(
+            qualified(js, "JavaScript"),
+            [None, Some("Api.get".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_retain_public() {
+        let public = |code: &str, lang| {
+            let mut items =
+                AstSummarizer::extract_summary(code, Some(lang), SummaryLevel::Standard);
+            AstSummarizer::retain_public(&mut items, code, Some(lang));
+            items
+                .into_iter()
+                .map(|item| item.line.trim().to_string())
+                .collect::<Vec<_>>()
+        };
+        let rust = "use std::fs;\npub struct Repo;\nimpl Repo {\n    pub fn save(&self) {}\n    fn sync(&self) {}\n}\npub(crate) fn helper() {}\n";
+        assert_eq!(
+            public(rust, "Rust"),
+            ["pub struct Repo;", "pub fn save(&self) {}"]
+        );
+
+        let ts = "import { x } from './x';\nexport class Api {\n  get() {}\n  private sync() {}\n}\nclass Hidden {\n  run() {}\n}\n";
+        assert_eq!(public(ts, "TypeScript"), ["export class Api {", "get() {}"]);
+
+        let py = "import os\n\nclass Repo:\n    def __init__(self):\n        pass\n\n    def _sync(self):\n        pass\n\ndef _helper():\n    pass\n";
+        assert_eq!(public(py, "Python"), ["class Repo:", "def __init__(self):"]);
+        let with_all = format!("__all__ = ['_helper']\n{py}");
+        assert_eq!(public(&with_all, "Python"), ["def _helper():"]);
+    }
+
+    #[test]
+    fn test_detailed_level_measures_functions() {
+        let rust = "pub fn risky(x: i32, y: bool) -> i32 {\n    if x > 0 && y {\n        for i in 0..x {\n            match i {\n                0 => return 1,\n                _ => {}\n            }\n        }\n    } else if x < 0 {\n        return -1;\n    }\n    0\n}\nstruct S;\n";
+        let items = AstSummarizer::extract_summary(rust, Some("Rust"), SummaryLevel::Detailed);
+        assert_eq!(
+            items[0].metrics,
+            Some(SymbolMetrics {
+                lines: 13,
+                branches: 6,
+                depth: 3
+            })
+        );
+        assert_eq!(items.last().unwrap().metrics, None);
+        let standard = AstSummarizer::extract_summary(rust, Some("Rust"), SummaryLevel::Standard);
+        assert_eq!(standard[0].metrics, None);
+
+        // Exported functions are captured through their export statement
+        let js = "export function f(a) {\n  return a ? 1 : 2;\n}\n";
+        let items = AstSummarizer::extract_summary(js, Some("JavaScript"), SummaryLevel::Detailed);
+        assert_eq!(items[0].metrics.map(|m| m.branches), Some(1));
+    }
+
+    #[test]
+    fn test_custom_queries_extend_or_replace() {
+        let root = tempfile::tempdir().unwrap();
+        let rust = root.path().join("rust");
+        fs::create_dir_all(&rust).unwrap();
+        fs::write(rust.join("macros.scm"), "(macro_invocation) @macro\n").unwrap();
+        fs::write(
+            rust.join("detailed.scm"),
+            ";; replace\n(line_comment) @comment\n",
+        )
+        .unwrap();
+        fs::write(rust.join("notes.txt"), "ignored").unwrap();
+
+        let queries = load_custom_queries(root.path());
+        assert_eq!(queries.len(), 2);
+        let builtin = Grammar::Rust.query_source(SummaryLevel::Standard).unwrap();
+        let standard = compose_query(&queries, "rust", SummaryLevel::Standard, builtin);
+        assert!(standard.starts_with(builtin) && standard.contains("@macro"));
+        let builtin = Grammar::Rust.query_source(SummaryLevel::Detailed).unwrap();
+        let detailed = compose_query(&queries, "rust", SummaryLevel::Detailed, builtin);
+        assert!(!detailed.contains("function_item") && detailed.contains("@comment"));
+        assert_eq!(
+            compose_query(&queries, "python", SummaryLevel::Standard, "(x)"),
+            "(x)"
+        );
+        let query = Query::new(&Grammar::Rust.language(), &standard);
+        assert!(query.is_ok());
+    }
+
     #[test]
     fn test_unsupported_language_returns_empty() {
         let result =
@@ -489,6 +1276,18 @@ mod tests {
         assert!(result.iter().any(|l| l.line.contains("static Y")));
     }
 
+    #[test]
+    fn test_items_carry_name_and_visibility() {
+        let code = "pub fn main() {}\nstruct S {}\nimpl S {}";
+        let result = AstSummarizer::extract_summary(code, Some("Rust"), SummaryLevel::Standard);
+        assert_eq!(result[0].name.as_deref(), Some("main"));
+        assert_eq!(result[0].visibility.as_deref(), Some("pub"));
+        assert_eq!(result[1].name.as_deref(), Some("S"));
+        assert_eq!(result[1].visibility.as_deref(), Some("private"));
+        assert_eq!(result[2].kind, "impl");
+        assert_eq!(result[2].name, None);
+    }
+
     #[test]
     fn test_python_minimal_level() {
         let code = "import os\ndef foo():\n    pass\nclass Bar:\n    pass";
@@ -552,7 +1351,7 @@ mod tests {
         parser
             .set_language(&tree_sitter_rust::LANGUAGE.into())
             .unwrap();
-        let tree = AstSummarizer::parse_with_timeout(&mut parser, "fn main() {}");
+        let tree = AstSummarizer::parse_with_timeout(&mut parser, "fn main() {}", None);
         assert!(tree.is_some(), "Valid code should parse successfully");
         let tree = tree.unwrap();
         assert_eq!(tree.root_node().kind(), "source_file");
@@ -564,7 +1363,7 @@ mod tests {
         parser
             .set_language(&tree_sitter_rust::LANGUAGE.into())
             .unwrap();
-        let tree = AstSummarizer::parse_with_timeout(&mut parser, "");
+        let tree = AstSummarizer::parse_with_timeout(&mut parser, "", None);
         assert!(tree.is_some(), "Empty content should still produce a tree");
     }
 
@@ -575,7 +1374,7 @@ mod tests {
             .set_language(&tree_sitter_rust::LANGUAGE.into())
             .unwrap();
         // Tree-sitter produces partial trees for invalid syntax (doesn't return None)
-        let tree = AstSummarizer::parse_with_timeout(&mut parser, "{{{{{{");
+        let tree = AstSummarizer::parse_with_timeout(&mut parser, "{{{{{{", None);
         assert!(tree.is_some());
         assert!(tree.unwrap().root_node().has_error());
     }
@@ -600,7 +1399,7 @@ mod tests {
         for ((name, lang), (_, snippet)) in languages.iter().zip(snippets.iter()) {
             let mut parser = Parser::new();
             parser.set_language(lang).unwrap();
-            let tree = AstSummarizer::parse_with_timeout(&mut parser, snippet);
+            let tree = AstSummarizer::parse_with_timeout(&mut parser, snippet, None);
             assert!(
                 tree.is_some(),
                 "parse_with_timeout should succeed for {name}"
@@ -627,4 +1426,29 @@ mod tests {
             "Zero timeout should abort parsing and return None"
         );
     }
+
+    #[test]
+    fn test_passed_deadline_stops_matching() {
+        let code = "fn a() {}\nfn b() {}\n";
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .unwrap();
+        let tree = AstSummarizer::parse_with_timeout(&mut parser, code, None).unwrap();
+        let query = summary_query(Grammar::Rust, SummaryLevel::Standard)
+            .unwrap()
+            .unwrap();
+        let collect = |deadline| {
+            AstSummarizer::collect_items(
+                tree.root_node(),
+                query,
+                code,
+                SummaryLevel::Standard,
+                false,
+                deadline,
+            )
+        };
+        assert_eq!(collect(None).len(), 2);
+        assert!(collect(Some(Instant::now())).is_empty());
+    }
 }
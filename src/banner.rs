@@ -0,0 +1,44 @@
+//! Per-file banners for multi-file output
+//!
+//! When several files are rendered as text, each is preceded by a header
+//! (`==> path <==` by default, like `head`/`tail`) and optionally followed by a
+//! footer, so concatenated output stays self-describing.
+
+use crate::file_info::FileInfo;
+
+/// Header used when no `file_header` template is configured
+pub const DEFAULT_FILE_HEADER: &str = "==> {path} <==";
+
+/// Expand a banner template for a processed file.
+///
+/// Supported placeholders: `{path}`, `{lines}`, `{bytes}` and `{language}`.
+pub fn render(template: &str, path: &str, info: &FileInfo) -> String {
+    template
+        .replace("{path}", path)
+        .replace("{lines}", &info.total_lines.to_string())
+        .replace("{bytes}", &info.total_bytes.to_string())
+        .replace("{language}", info.language.as_deref().unwrap_or("unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_placeholders() {
+        let info = FileInfo::with_metadata(2, 4, Some("Rust".to_string()), "UTF-8".to_string());
+        assert_eq!(
+            render(DEFAULT_FILE_HEADER, "src/a.rs", &info),
+            "==> src/a.rs <=="
+        );
+        assert_eq!(
+            render(
+                "{path}:{lines} lines, {language} ({bytes} bytes)",
+                "a.rs",
+                &info
+            ),
+            "a.rs:2 lines, Rust (4 bytes)"
+        );
+        assert_eq!(render("{language}", "x", &FileInfo::new()), "unknown");
+    }
+}
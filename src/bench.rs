@@ -0,0 +1,217 @@
+//! Built-in self-profiling for batless
+//!
+//! `batless --bench <file>` runs each pipeline stage several times against a
+//! real input and reports per-stage timings, so performance issues can be
+//! filed with actionable numbers and regressions spotted outside criterion CI.
+
+use crate::ast_summarizer::AstSummarizer;
+use crate::config::BatlessConfig;
+use crate::error::BatlessResult;
+use crate::formatter::{OutputFormatter, OutputMode};
+use crate::language::LanguageDetector;
+use crate::processor::FileProcessor;
+use crate::summarizer::SummaryExtractor;
+use crate::summary::SummaryLevel;
+use crate::tokens::{AiModel, TokenCounter, TokenExtractor};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+/// Default number of iterations per stage
+pub const DEFAULT_BENCH_ITERATIONS: usize = 10;
+
+/// Timing statistics for a single pipeline stage, in microseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTiming {
+    pub stage: &'static str,
+    pub min_us: u128,
+    pub mean_us: u128,
+    pub max_us: u128,
+}
+
+/// Result of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub file: String,
+    pub language: Option<String>,
+    pub iterations: usize,
+    pub input_bytes: usize,
+    pub input_lines: usize,
+    pub stages: Vec<StageTiming>,
+    pub total_mean_us: u128,
+    /// Peak resident set size of the process in KiB (Linux only)
+    pub peak_rss_kb: Option<u64>,
+    pub batless_version: &'static str,
+}
+
+impl BenchReport {
+    /// Render a fixed-width text table
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "batless {} benchmark", self.batless_version);
+        let _ = writeln!(
+            out,
+            "file: {} ({} bytes, {} lines, language: {})",
+            self.file,
+            self.input_bytes,
+            self.input_lines,
+            self.language.as_deref().unwrap_or("unknown")
+        );
+        let _ = writeln!(out, "iterations: {}\n", self.iterations);
+        let _ = writeln!(
+            out,
+            "{:<24} {:>12} {:>12} {:>12}",
+            "stage", "min (µs)", "mean (µs)", "max (µs)"
+        );
+        for stage in &self.stages {
+            let _ = writeln!(
+                out,
+                "{:<24} {:>12} {:>12} {:>12}",
+                stage.stage, stage.min_us, stage.mean_us, stage.max_us
+            );
+        }
+        let _ = writeln!(out, "{:<24} {:>25}", "total (mean)", self.total_mean_us);
+        if let Some(rss) = self.peak_rss_kb {
+            let _ = writeln!(out, "peak RSS: {rss} KiB");
+        }
+        out
+    }
+}
+
+/// Run every pipeline stage `iterations` times against `file_path`
+pub fn run_benchmark(
+    file_path: &str,
+    config: &BatlessConfig,
+    iterations: usize,
+) -> BatlessResult<BenchReport> {
+    let iterations = iterations.max(1);
+    // Time the raw read without summary/token work so stages stay separable
+    let read_config = config
+        .clone()
+        .with_summary_level(SummaryLevel::None)
+        .with_summary_mode(false)
+        .with_include_tokens(false);
+
+    let file_info = FileProcessor::process_file(file_path, &read_config)?;
+    let content = file_info.lines.join("\n");
+    let language = file_info.language.clone();
+    let lang = language.as_deref();
+    let counter = TokenCounter::new(AiModel::Generic);
+
+    let mut stages = vec![
+        time_stage("read", iterations, || {
+            FileProcessor::process_file(file_path, &read_config).map(|_| ())
+        })?,
+        time_stage("language_detection", iterations, || {
+            let _ = LanguageDetector::detect_language_with_source(file_path);
+            Ok(())
+        })?,
+        time_stage("ast_summary", iterations, || {
+            let _ = AstSummarizer::extract_summary(&content, lang, SummaryLevel::Standard);
+            Ok(())
+        })?,
+        time_stage("regex_summary", iterations, || {
+            let _ =
+                SummaryExtractor::extract_summary(&file_info.lines, lang, SummaryLevel::Standard);
+            Ok(())
+        })?,
+        time_stage("identifier_extraction", iterations, || {
+            let _ = TokenExtractor::extract(&content, file_path, config.token_options());
+            Ok(())
+        })?,
+        time_stage("token_count", iterations, || {
+            let _ = counter.count_tokens(&content);
+            Ok(())
+        })?,
+    ];
+    stages.push(time_stage("json_format", iterations, || {
+        OutputFormatter::format_output(&file_info, file_path, config, OutputMode::Json).map(|_| ())
+    })?);
+
+    let total_mean_us = stages.iter().map(|s| s.mean_us).sum();
+
+    Ok(BenchReport {
+        file: file_path.to_string(),
+        language,
+        iterations,
+        input_bytes: file_info.total_bytes,
+        input_lines: file_info.total_lines,
+        stages,
+        total_mean_us,
+        peak_rss_kb: peak_rss_kb(),
+        batless_version: env!("CARGO_PKG_VERSION"),
+    })
+}
+
+fn time_stage<F>(stage: &'static str, iterations: usize, mut run: F) -> BatlessResult<StageTiming>
+where
+    F: FnMut() -> BatlessResult<()>,
+{
+    let mut min = Duration::MAX;
+    let mut max = Duration::ZERO;
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        run()?;
+        let elapsed = start.elapsed();
+        min = min.min(elapsed);
+        max = max.max(elapsed);
+        total += elapsed;
+    }
+    Ok(StageTiming {
+        stage,
+        min_us: min.as_micros(),
+        mean_us: total.as_micros() / iterations as u128,
+        max_us: max.as_micros(),
+    })
+}
+
+/// Read the process high-water mark from `/proc/self/status` where available
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_run_benchmark_reports_all_stages() {
+        let mut file = NamedTempFile::with_suffix(".rs").unwrap();
+        writeln!(file, "pub fn answer() -> i32 {{\n    42\n}}").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let report = run_benchmark(path, &BatlessConfig::default(), 2).unwrap();
+        assert_eq!(report.iterations, 2);
+        assert_eq!(report.language.as_deref(), Some("Rust"));
+        let names: Vec<_> = report.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(
+            names,
+            [
+                "read",
+                "language_detection",
+                "ast_summary",
+                "regex_summary",
+                "identifier_extraction",
+                "token_count",
+                "json_format"
+            ]
+        );
+        assert!(report
+            .stages
+            .iter()
+            .all(|s| s.min_us <= s.mean_us && s.mean_us <= s.max_us));
+        assert!(report.render_text().contains("identifier_extraction"));
+    }
+
+    #[test]
+    fn test_run_benchmark_missing_file() {
+        assert!(run_benchmark("does-not-exist.rs", &BatlessConfig::default(), 1).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! Byte-stable JSON output (`--canonical-json`)
+//!
+//! Object keys are emitted in whatever order a `json!` literal or a struct
+//! declares them, which can shift between versions and breaks diff-based
+//! caches downstream. Canonical output re-serializes every document with
+//! keys sorted recursively, so identical inputs always produce identical
+//! bytes. Layout (compact or pretty) is kept as it was.
+
+use crate::error::BatlessResult;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Serialize `value`, sorting object keys when `canonical` is set
+pub fn to_json_string<T: Serialize>(
+    value: &T,
+    pretty: bool,
+    canonical: bool,
+) -> BatlessResult<String> {
+    if !canonical {
+        return Ok(if pretty {
+            serde_json::to_string_pretty(value)?
+        } else {
+            serde_json::to_string(value)?
+        });
+    }
+    let mut value = serde_json::to_value(value)?;
+    value.sort_all_objects();
+    Ok(if pretty {
+        serde_json::to_string_pretty(&value)?
+    } else {
+        serde_json::to_string(&value)?
+    })
+}
+
+/// Canonicalize formatted output: one JSON document, or one per line (JSONL)
+///
+/// Output that is not JSON is returned unchanged.
+pub fn canonicalize_output(output: &str) -> String {
+    if let Ok(value) = serde_json::from_str::<Value>(output) {
+        let pretty = output.trim_end().contains('\n');
+        return to_json_string(&value, pretty, true).unwrap_or_else(|_| output.to_string());
+    }
+    let lines: Option<Vec<String>> = output
+        .lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                return Some(line.to_string());
+            }
+            let value = serde_json::from_str::<Value>(line).ok()?;
+            to_json_string(&value, false, true).ok()
+        })
+        .collect();
+    lines.map_or_else(|| output.to_string(), |lines| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_keys_sorted_recursively() {
+        let value = json!({"zeta": 1, "alpha": {"y": [{"b": 2, "a": 1}], "x": null}});
+        assert_eq!(
+            to_json_string(&value, false, true).unwrap(),
+            r#"{"alpha":{"x":null,"y":[{"a":1,"b":2}]},"zeta":1}"#
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_output_keeps_layout() {
+        assert_eq!(canonicalize_output(r#"{"b":1,"a":2}"#), r#"{"a":2,"b":1}"#);
+        assert_eq!(
+            canonicalize_output("{\n  \"b\": 1,\n  \"a\": 2\n}"),
+            "{\n  \"a\": 2,\n  \"b\": 1\n}"
+        );
+        assert_eq!(
+            canonicalize_output("{\"n\":1,\"a\":0}\n{\"n\":2,\"a\":0}"),
+            "{\"a\":0,\"n\":1}\n{\"a\":0,\"n\":2}"
+        );
+        assert_eq!(canonicalize_output("fn main() {}"), "fn main() {}");
+    }
+}
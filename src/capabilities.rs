@@ -0,0 +1,114 @@
+//! Feature detection for wrapping tools (`--capabilities`)
+//!
+//! Scripts that drive batless can read this document to find out which
+//! modes, schemas, models and grammars the installed build offers instead of
+//! comparing version numbers.
+
+use crate::config::BatlessConfig;
+use crate::config_manager::AiProfile;
+use crate::formatter::OutputMode;
+use crate::formatters::ast_formatter::AstFormatter;
+use crate::json_schema::JsonSchemaValidator;
+use crate::tokens::AiModel;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Version of the capabilities document itself; bumped when fields are
+/// removed or change meaning
+pub const CAPABILITIES_VERSION: u32 = 1;
+
+/// What this build of batless supports
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub capabilities_version: u32,
+    pub version: &'static str,
+    /// `--mode` values
+    pub output_modes: Vec<&'static str>,
+    /// `schema_version` written in JSON output
+    pub schema_version: String,
+    /// Names accepted by `--get-schema`
+    pub schemas: Vec<String>,
+    /// `--ai-model` names, including `[models]` entries from the config
+    pub ai_models: Vec<String>,
+    /// `--profile` names
+    pub ai_profiles: Vec<String>,
+    /// Languages parsed with tree-sitter for summaries, `--symbol` and
+    /// `--uses`, including runtime grammars from the config
+    pub ast_languages: Vec<String>,
+    /// Cargo features, and whether this build was compiled with them
+    pub features: BTreeMap<&'static str, bool>,
+}
+
+impl Capabilities {
+    /// Capabilities of this build under `config`
+    pub fn detect(config: &BatlessConfig) -> Self {
+        let mut schemas = JsonSchemaValidator::new().schema_names();
+        schemas.sort();
+
+        let ai_models = AiModel::all()
+            .iter()
+            .map(|model| model.as_str().to_string())
+            .chain(config.models.keys().cloned())
+            .collect();
+        let ai_profiles = AiProfile::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .map(|value| value.get_name().to_string())
+            .collect();
+        let ast_languages = AstFormatter::LANGUAGES
+            .iter()
+            .map(ToString::to_string)
+            .chain(config.grammars.keys().cloned())
+            .collect();
+
+        let features = BTreeMap::from([
+            ("media", cfg!(feature = "media")),
+            ("compression", cfg!(feature = "compression")),
+            ("sqlite", cfg!(feature = "sqlite")),
+            ("parquet", cfg!(feature = "parquet")),
+            ("wasm", cfg!(feature = "wasm")),
+            ("serve", cfg!(feature = "serve")),
+            ("clipboard", cfg!(feature = "clipboard")),
+            ("dynamic-grammars", cfg!(feature = "dynamic-grammars")),
+            ("exact-tokenizer", cfg!(feature = "exact-tokenizer")),
+            ("otel", cfg!(feature = "otel")),
+        ]);
+
+        Self {
+            capabilities_version: CAPABILITIES_VERSION,
+            version: env!("CARGO_PKG_VERSION"),
+            output_modes: OutputMode::all().iter().map(OutputMode::as_str).collect(),
+            schema_version: BatlessConfig::default().schema_version,
+            schemas,
+            ai_models,
+            ai_profiles,
+            ast_languages,
+            features,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_list_builtins_and_config() {
+        let mut grammars = BTreeMap::new();
+        grammars.insert("Gleam".to_string(), "gleam".to_string());
+        let config = BatlessConfig::default().with_grammars(grammars);
+        let capabilities = Capabilities::detect(&config);
+
+        assert!(capabilities.output_modes.contains(&"json"));
+        assert!(capabilities.schemas.contains(&"json_output".to_string()));
+        assert!(capabilities.ai_models.contains(&"claude".to_string()));
+        assert!(capabilities.ai_profiles.contains(&"claude-max".to_string()));
+        assert_eq!(capabilities.ast_languages.first().unwrap(), "Rust");
+        assert_eq!(capabilities.ast_languages.last().unwrap(), "Gleam");
+        assert_eq!(
+            capabilities.features["compression"],
+            cfg!(feature = "compression")
+        );
+    }
+}
@@ -1,9 +1,20 @@
-//! Semantic boundary finding for streaming chunk splitting
+//! Semantic boundary finding for streaming chunk splitting, and retrieval
+//! chunks (`--chunk-for-rag`)
 //!
 //! Uses tree-sitter to locate the start lines of top-level declarations
 //! (functions, classes, structs, impls, etc.) so that streaming chunks
 //! can end at natural code boundaries rather than mid-function.
+//!
+//! [`RagChunker`] cuts a whole file into overlapping chunks of a token
+//! budget for embedding, preferring to cut before top-level declarations,
+//! then before nested definitions, then after blank lines.
 
+use crate::ast_summarizer::AstSummarizer;
+use crate::summary::SummaryLevel;
+use crate::summary_item::SummaryItem;
+use crate::symbol::{display_name, leading_start};
+use crate::tokens::TokenCounter;
+use serde::Serialize;
 use std::ops::ControlFlow;
 use std::time::{Duration, Instant};
 use tree_sitter::{ParseOptions, Parser};
@@ -21,12 +32,14 @@ impl SemanticBoundaryFinder {
     /// Supports Rust, Python, JavaScript/JSX, and TypeScript/TSX.
     /// Returns an empty `Vec` for unsupported languages or when parsing fails.
     pub fn find_boundaries(content: &str, language: Option<&str>) -> Vec<usize> {
-        let ts_language: tree_sitter::Language = match language {
-            Some("Rust") => tree_sitter_rust::LANGUAGE.into(),
-            Some("Python") => tree_sitter_python::LANGUAGE.into(),
-            Some("JavaScript" | "JSX") => tree_sitter_javascript::LANGUAGE.into(),
-            Some("TypeScript" | "TSX") => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
-            _ => return Vec::new(),
+        // TSX boundaries come from the TypeScript grammar
+        let name = match language {
+            Some("TSX") => "TypeScript",
+            Some(name) => name,
+            None => return Vec::new(),
+        };
+        let Some((ts_language, _)) = crate::grammar_loader::bundled(name) else {
+            return Vec::new();
         };
 
         let mut parser = Parser::new();
@@ -73,6 +86,202 @@ impl SemanticBoundaryFinder {
     }
 }
 
+/// Default `--chunk-overlap`
+pub const DEFAULT_CHUNK_OVERLAP: usize = 64;
+
+/// One retrieval chunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RagChunk {
+    pub file: String,
+    pub chunk_index: usize,
+    /// 1-based, inclusive
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Byte range in the emitted text (the output lines joined with LF, after
+    /// BOM stripping and redaction), end exclusive; not offsets into the
+    /// file on disk
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// Estimated tokens for `model`
+    pub tokens: usize,
+    pub model: String,
+    /// Innermost definition the chunk starts inside, when it starts
+    /// mid-definition (`Config::load`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Definitions that overlap the chunk, in source order
+    pub symbols: Vec<String>,
+    pub text: String,
+}
+
+/// Splits files into overlapping chunks of about `max_tokens` tokens
+pub struct RagChunker {
+    counter: TokenCounter,
+    max_tokens: usize,
+    overlap: usize,
+}
+
+impl RagChunker {
+    /// Chunks of at most `max_tokens` estimated tokens, each repeating up
+    /// to `overlap` tokens of whole lines from the end of the previous one.
+    /// A single line over the budget becomes a chunk of its own.
+    pub const fn new(counter: TokenCounter, max_tokens: usize, overlap: usize) -> Self {
+        Self {
+            counter,
+            max_tokens,
+            overlap,
+        }
+    }
+
+    /// Chunks of `lines`, labelled with `file` and the definitions they hold
+    pub fn chunk(&self, lines: &[String], language: Option<&str>, file: &str) -> Vec<RagChunk> {
+        let content = lines.join("\n");
+        let items = AstSummarizer::extract_summary(&content, language, SummaryLevel::Detailed);
+        let line_tokens: Vec<usize> = lines
+            .iter()
+            .map(|line| self.counter.count_tokens(line).tokens)
+            .collect();
+        let cuts = Self::cut_points(lines, language, &items, &content);
+
+        let mut line_starts = Vec::with_capacity(lines.len() + 1);
+        let mut offset = 0;
+        for line in lines {
+            line_starts.push(offset);
+            offset += line.len() + 1;
+        }
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < lines.len() {
+            let end = self.chunk_end(start, &line_tokens, &cuts);
+            let start_line = start + 1;
+            let end_line = end;
+            chunks.push(RagChunk {
+                file: file.to_string(),
+                chunk_index: chunks.len(),
+                start_line,
+                end_line,
+                start_byte: line_starts[start],
+                end_byte: line_starts[end - 1] + lines[end - 1].len(),
+                tokens: line_tokens[start..end].iter().sum(),
+                model: self.counter.model().as_str().to_string(),
+                context: Self::context(&items, start_line),
+                symbols: Self::symbols(&items, start_line, end_line),
+                text: lines[start..end].join("\n"),
+            });
+            if end == lines.len() {
+                break;
+            }
+            let next = self.next_start(start, end, &line_tokens);
+            // An overlap that leaves no room for new lines is dropped
+            start = if self.chunk_end(next, &line_tokens, &cuts) > end {
+                next
+            } else {
+                end
+            };
+        }
+        chunks
+    }
+
+    /// Preferred places to cut, as 0-based first lines of the next chunk:
+    /// top-level declarations, then every definition, then lines after a
+    /// blank line. Doc comments and attributes stay with their definition.
+    fn cut_points(
+        lines: &[String],
+        language: Option<&str>,
+        items: &[SummaryItem],
+        content: &str,
+    ) -> [Vec<usize>; 3] {
+        let with_leading = |line: usize| leading_start(lines, line + 1) - 1;
+        let top_level = SemanticBoundaryFinder::find_boundaries(content, language)
+            .into_iter()
+            .map(with_leading)
+            .collect();
+        let definitions = items
+            .iter()
+            .filter(|item| item.kind != "import")
+            .map(|item| with_leading(item.line_number - 1))
+            .collect();
+        let paragraphs = (1..lines.len())
+            .filter(|&line| lines[line - 1].trim().is_empty() && !lines[line].trim().is_empty())
+            .collect();
+        [top_level, definitions, paragraphs]
+    }
+
+    /// Exclusive end of the chunk starting at `start`: the last preferred
+    /// cut that keeps the chunk at least half full, else as many lines as fit
+    fn chunk_end(&self, start: usize, line_tokens: &[usize], cuts: &[Vec<usize>; 3]) -> usize {
+        let mut total = 0;
+        let mut fits = start;
+        while fits < line_tokens.len() && total + line_tokens[fits] <= self.max_tokens {
+            total += line_tokens[fits];
+            fits += 1;
+        }
+        if fits == line_tokens.len() {
+            return fits;
+        }
+        if fits == start {
+            return start + 1;
+        }
+        let half = self.max_tokens / 2;
+        let filled = |end: usize| line_tokens[start..end].iter().sum::<usize>() >= half;
+        cuts.iter()
+            .find_map(|points| {
+                points
+                    .iter()
+                    .copied()
+                    .filter(|&cut| cut > start && cut <= fits && filled(cut))
+                    .max()
+            })
+            .unwrap_or(fits)
+    }
+
+    /// First line of the chunk after `start..end`, repeating the trailing
+    /// lines that fit in the overlap while always moving forward
+    fn next_start(&self, start: usize, end: usize, line_tokens: &[usize]) -> usize {
+        let mut next = end;
+        if self.overlap == 0 {
+            return next;
+        }
+        let mut total = 0;
+        while next > start + 1 && total + line_tokens[next - 1] <= self.overlap {
+            total += line_tokens[next - 1];
+            next -= 1;
+        }
+        next
+    }
+
+    /// Innermost named definition that began before `start_line` and is
+    /// still open on it
+    fn context(items: &[SummaryItem], start_line: usize) -> Option<String> {
+        items
+            .iter()
+            .filter(|item| {
+                item.name.is_some()
+                    && item.line_number < start_line
+                    && item.end_line.is_some_and(|end| end >= start_line)
+            })
+            .max_by_key(|item| item.line_number)
+            .map(display_name)
+    }
+
+    /// Named definitions overlapping `start_line..=end_line`
+    fn symbols(items: &[SummaryItem], start_line: usize, end_line: usize) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for item in items {
+            let item_end = item.end_line.unwrap_or(item.line_number);
+            if item.name.is_none() || item.line_number > end_line || item_end < start_line {
+                continue;
+            }
+            let name = display_name(item);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,6 +299,65 @@ mod tests {
         assert!(boundaries.is_empty());
     }
 
+    fn rag_lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn rag_chunker(max_tokens: usize, overlap: usize) -> RagChunker {
+        RagChunker::new(
+            TokenCounter::new(crate::tokens::AiModel::Gpt4),
+            max_tokens,
+            overlap,
+        )
+    }
+
+    #[test]
+    fn test_rag_chunks_cut_before_declarations() {
+        let source = "/// First\nfn first() {\n    let a = 1;\n    let b = 2;\n}\n\n/// Second\nfn second() {\n    let c = 3;\n}\n";
+        let lines = rag_lines(source);
+        let chunks = rag_chunker(25, 0).chunk(&lines, Some("Rust"), "lib.rs");
+        assert_eq!(chunks.len(), 2, "{chunks:#?}");
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 6));
+        assert_eq!(chunks[0].symbols, ["first"]);
+        // The doc comment stays with its function
+        assert!(chunks[1].text.starts_with("/// Second\nfn second"));
+        assert_eq!(chunks[1].symbols, ["second"]);
+        assert_eq!(
+            &source[chunks[1].start_byte..chunks[1].end_byte],
+            chunks[1].text
+        );
+        assert!(chunks.iter().all(|chunk| chunk.tokens <= 25));
+    }
+
+    #[test]
+    fn test_rag_chunks_overlap_and_context() {
+        let body = (0..30)
+            .map(|n| format!("    let v{n} = {n};\n"))
+            .collect::<Vec<_>>()
+            .concat();
+        let source = format!("fn long() {{\n{body}}}\n");
+        let lines = rag_lines(&source);
+        let chunks = rag_chunker(60, 12).chunk(&lines, Some("Rust"), "lib.rs");
+        assert!(chunks.len() > 2);
+        for pair in chunks.windows(2) {
+            assert!(pair[1].start_line <= pair[0].end_line, "{pair:#?}");
+            assert!(pair[1].end_line > pair[0].end_line);
+        }
+        assert_eq!(chunks[0].context, None);
+        assert_eq!(chunks[1].context.as_deref(), Some("long"));
+        assert_eq!(chunks.last().unwrap().end_line, lines.len());
+    }
+
+    #[test]
+    fn test_rag_chunks_without_grammar() {
+        let lines = rag_lines(&"word ".repeat(400));
+        let chunks = rag_chunker(50, 10).chunk(&lines, None, "notes.txt");
+        // One line over the budget is kept whole
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].tokens > 50);
+        assert!(chunks[0].symbols.is_empty());
+    }
+
     #[test]
     fn test_none_language_returns_empty() {
         let boundaries = SemanticBoundaryFinder::find_boundaries("fn foo() {}", None);
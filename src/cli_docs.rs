@@ -0,0 +1,382 @@
+//! Man page, Markdown reference and completion value lists built from the
+//! clap definition (`--generate-man`, `--help-markdown`,
+//! `--generate-completions`)
+//!
+//! All are rendered at runtime from the same `Command` that parses the
+//! arguments, so packagers and docs sites never carry a stale copy.
+
+use crate::config::BatlessConfig;
+use crate::json_schema::JsonSchemaValidator;
+use crate::language::LanguageDetector;
+use crate::tokens::AiModel;
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{Arg, Command};
+use std::fmt::Write as _;
+
+/// `cmd` with the real names offered for options that take free-form
+/// strings, so generated completions can complete them: languages
+/// (including runtime grammars), AI models (including `[models]` entries)
+/// and schema names. Only for generating completions, since parsing with
+/// these lists would reject aliases such as `js`.
+pub fn with_value_hints(cmd: Command, config: &BatlessConfig) -> Command {
+    let languages: Vec<String> = LanguageDetector::list_languages()
+        .into_iter()
+        .chain(config.grammars.keys().cloned())
+        .collect();
+    let models: Vec<String> = AiModel::all()
+        .iter()
+        .map(|model| model.as_str().to_string())
+        .chain(config.models.keys().cloned())
+        .collect();
+    let mut schemas = JsonSchemaValidator::new().schema_names();
+    schemas.sort();
+
+    cmd.mut_arg("language", |arg| {
+        arg.value_parser(possible_values(&languages))
+    })
+    .mut_arg("stdin_language", |arg| {
+        arg.value_parser(possible_values(&languages))
+    })
+    .mut_arg("ai_model", |arg| arg.value_parser(possible_values(&models)))
+    .mut_arg("get_schema", |arg| {
+        arg.value_parser(possible_values(&schemas))
+    })
+}
+
+fn possible_values(names: &[String]) -> PossibleValuesParser {
+    // Leaked once per completion script; clap stores `&'static str` names
+    PossibleValuesParser::new(
+        names
+            .iter()
+            .map(|name| PossibleValue::new(&*Box::leak(name.clone().into_boxed_str()))),
+    )
+}
+
+/// One visible argument, ready for rendering
+struct ArgDoc {
+    /// `-n, --max-lines <N>` or `[FILE]`
+    syntax: String,
+    help: String,
+    default: Option<String>,
+    /// Possible values with their help
+    values: Vec<(String, Option<String>)>,
+}
+
+impl ArgDoc {
+    fn new(arg: &Arg) -> Self {
+        let value_names: Vec<String> = arg.get_value_names().map_or_else(
+            || vec![arg.get_id().as_str().to_uppercase()],
+            |names| names.iter().map(ToString::to_string).collect(),
+        );
+        let takes_values = arg.get_action().takes_values();
+        let optional_value = arg
+            .get_num_args()
+            .is_some_and(|range| range.min_values() == 0);
+
+        let syntax = if arg.is_positional() {
+            let names = value_names.join(" ");
+            let repeated = if arg
+                .get_num_args()
+                .is_some_and(|range| range.max_values() > 1)
+            {
+                "..."
+            } else {
+                ""
+            };
+            if arg.is_required_set() {
+                format!("<{names}>{repeated}")
+            } else {
+                format!("[{names}]{repeated}")
+            }
+        } else {
+            let mut flags: Vec<String> = arg
+                .get_short()
+                .map(|s| format!("-{s}"))
+                .into_iter()
+                .collect();
+            flags.extend(arg.get_long().map(|long| format!("--{long}")));
+            let mut syntax = flags.join(", ");
+            if takes_values {
+                let values = value_names
+                    .iter()
+                    .map(|name| format!("<{name}>"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if optional_value {
+                    let _ = write!(syntax, " [{values}]");
+                } else {
+                    let _ = write!(syntax, " {values}");
+                }
+            }
+            syntax
+        };
+
+        let help = arg
+            .get_long_help()
+            .or_else(|| arg.get_help())
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let default = (takes_values && !arg.get_default_values().is_empty()).then(|| {
+            arg.get_default_values()
+                .iter()
+                .map(|value| value.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        let values = if arg.is_hide_possible_values_set() {
+            Vec::new()
+        } else {
+            arg.get_possible_values()
+                .iter()
+                .filter(|value| !value.is_hide_set())
+                .map(|value| {
+                    (
+                        value.get_name().to_string(),
+                        value.get_help().map(ToString::to_string),
+                    )
+                })
+                .collect()
+        };
+
+        Self {
+            syntax,
+            help,
+            default,
+            values,
+        }
+    }
+}
+
+/// Visible positionals and options of `cmd`, positionals first
+fn arg_docs(cmd: &Command) -> (Vec<ArgDoc>, Vec<ArgDoc>) {
+    let visible = cmd.get_arguments().filter(|arg| !arg.is_hide_set());
+    let (positionals, options): (Vec<&Arg>, Vec<&Arg>) =
+        visible.partition(|arg| arg.is_positional());
+    (
+        positionals.into_iter().map(ArgDoc::new).collect(),
+        options.into_iter().map(ArgDoc::new).collect(),
+    )
+}
+
+/// Built command and its usage line without the `Usage: ` prefix
+fn prepare(cmd: &Command) -> (Command, String) {
+    let mut cmd = cmd.clone();
+    cmd.build();
+    let usage = cmd.render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage:").trim().to_string();
+    (cmd, usage)
+}
+
+fn description(cmd: &Command) -> String {
+    cmd.get_long_about()
+        .or_else(|| cmd.get_about())
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+/// Section 1 man page in roff
+pub fn man_page(cmd: &Command) -> String {
+    let (cmd, usage) = prepare(cmd);
+    let name = cmd.get_name();
+    let version = cmd.get_version().unwrap_or_default();
+    let mut out = format!(
+        ".TH {} 1 \"\" \"{name} {version}\" \"User Commands\"\n",
+        name.to_uppercase()
+    );
+    let _ = writeln!(
+        out,
+        ".SH NAME\n{name} \\- {}",
+        roff_escape(&cmd.get_about().map(ToString::to_string).unwrap_or_default())
+    );
+    let _ = writeln!(out, ".SH SYNOPSIS\n{}", roff_escape(&usage));
+    let _ = writeln!(out, ".SH DESCRIPTION\n{}", roff_escape(&description(&cmd)));
+
+    let (positionals, options) = arg_docs(&cmd);
+    for (title, docs) in [("ARGUMENTS", positionals), ("OPTIONS", options)] {
+        if docs.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, ".SH {title}");
+        for doc in docs {
+            let _ = writeln!(
+                out,
+                ".TP\n\\fB{}\\fR\n{}",
+                roff_escape(&doc.syntax),
+                roff_escape(&doc.help)
+            );
+            if let Some(default) = &doc.default {
+                let _ = writeln!(out, ".br\nDefault: {}", roff_escape(default));
+            }
+            if !doc.values.is_empty() {
+                out.push_str(".br\nPossible values:\n.RS\n");
+                for (value, help) in &doc.values {
+                    let line = match help {
+                        Some(help) => {
+                            format!("\\fB{}\\fR \\- {}", roff_escape(value), roff_escape(help))
+                        }
+                        None => format!("\\fB{}\\fR", roff_escape(value)),
+                    };
+                    let _ = writeln!(out, ".IP \\(bu 2\n{line}");
+                }
+                out.push_str(".RE\n");
+            }
+        }
+    }
+
+    let _ = writeln!(out, ".SH VERSION\nv{}", roff_escape(version));
+    if let Some(author) = cmd.get_author().filter(|author| !author.is_empty()) {
+        let _ = writeln!(out, ".SH AUTHORS\n{}", roff_escape(author));
+    }
+    out
+}
+
+/// Markdown reference for docs sites
+pub fn markdown(cmd: &Command) -> String {
+    let (cmd, usage) = prepare(cmd);
+    let mut out = format!(
+        "# {}\n\n{}\n\n## Usage\n\n```text\n{usage}\n```\n",
+        cmd.get_name(),
+        description(&cmd)
+    );
+
+    let (positionals, options) = arg_docs(&cmd);
+    for (title, docs) in [("Arguments", positionals), ("Options", options)] {
+        if docs.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "\n## {title}\n");
+        for doc in docs {
+            let _ = write!(out, "- `{}`", doc.syntax);
+            if !doc.help.is_empty() {
+                let _ = write!(out, ": {}", doc.help.replace('\n', " "));
+            }
+            if let Some(default) = &doc.default {
+                let _ = write!(out, " (default: `{default}`)");
+            }
+            out.push('\n');
+            for (value, help) in &doc.values {
+                let _ = match help {
+                    Some(help) => writeln!(out, "  - `{value}`: {help}"),
+                    None => writeln!(out, "  - `{value}`"),
+                };
+            }
+        }
+    }
+    out
+}
+
+/// Escape text for roff: backslashes and hyphens, and control characters at
+/// the start of a line
+fn roff_escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let escaped = line.replace('\\', "\\e").replace('-', "\\-");
+            if escaped.starts_with(['.', '\'']) {
+                format!("\\&{escaped}")
+            } else {
+                escaped
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{value_parser, ArgAction};
+
+    fn command() -> Command {
+        Command::new("demo")
+            .version("1.2.3")
+            .about("Show files")
+            .arg(Arg::new("file").help("File to view"))
+            .arg(
+                Arg::new("max-lines")
+                    .short('n')
+                    .long("max-lines")
+                    .value_name("N")
+                    .default_value("10000")
+                    .value_parser(value_parser!(usize))
+                    .help("Stop after N lines"),
+            )
+            .arg(
+                Arg::new("mode")
+                    .long("mode")
+                    .value_parser(["plain", "json"])
+                    .help("Output mode"),
+            )
+            .arg(
+                Arg::new("secret")
+                    .long("secret")
+                    .hide(true)
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    #[test]
+    fn test_man_page() {
+        let man = man_page(&command());
+        assert!(man.starts_with(".TH DEMO 1 \"\" \"demo 1.2.3\""), "{man}");
+        assert!(man.contains(".SH NAME\ndemo \\- Show files\n"));
+        assert!(man.contains(
+            "\\fB\\-n, \\-\\-max\\-lines <N>\\fR\nStop after N lines\n.br\nDefault: 10000"
+        ));
+        assert!(man.contains(".IP \\(bu 2\n\\fBjson\\fR"));
+        assert!(man.contains("\\fB[FILE]\\fR\nFile to view"));
+        assert!(!man.contains("secret"));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = markdown(&command());
+        assert!(md.starts_with("# demo\n\nShow files\n\n## Usage\n"));
+        assert!(md.contains("- `-n, --max-lines <N>`: Stop after N lines (default: `10000`)\n"));
+        assert!(md.contains("- `--mode <MODE>`: Output mode\n  - `plain`\n  - `json`\n"));
+        assert!(md.contains("- `-h, --help`"));
+        assert!(!md.contains("secret"));
+    }
+
+    #[test]
+    fn test_value_hints_use_real_lists() {
+        let mut models = std::collections::BTreeMap::new();
+        models.insert(
+            "house-llm".to_string(),
+            crate::tokens::CustomModelSpec {
+                context_window: 32_000,
+                tokens_per_word: 1.3,
+            },
+        );
+        let config = BatlessConfig {
+            models,
+            ..BatlessConfig::default()
+        };
+        let cmd = with_value_hints(
+            <crate::config_manager::Args as clap::CommandFactory>::command(),
+            &config,
+        );
+        let names = |id: &str| -> Vec<String> {
+            cmd.get_arguments()
+                .find(|arg| arg.get_id() == id)
+                .unwrap()
+                .get_possible_values()
+                .iter()
+                .map(|value| value.get_name().to_string())
+                .collect()
+        };
+        assert!(names("language").contains(&"Rust".to_string()));
+        assert!(names("stdin_language").contains(&"Python".to_string()));
+        let models = names("ai_model");
+        assert!(models.contains(&"claude-sonnet".to_string()));
+        assert!(models.contains(&"house-llm".to_string()));
+        assert!(names("get_schema").contains(&"json_output".to_string()));
+    }
+
+    #[test]
+    fn test_roff_escape() {
+        assert_eq!(
+            roff_escape(".hidden\nC:\\path -x"),
+            "\\&.hidden\nC:\\epath \\-x"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! System clipboard output for `--copy` (`clipboard` feature)
+//!
+//! Formatted output can be placed on the clipboard as well as, or instead
+//! of, being written to stdout, which helps when prompts are assembled by
+//! hand. macOS, Windows, X11 and Wayland are supported; on X11 the contents
+//! outlive batless only when a clipboard manager takes them over.
+
+use crate::error::{BatlessError, BatlessResult};
+
+/// Replace the clipboard contents with `text`
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> BatlessResult<()> {
+    let error = |e: arboard::Error| {
+        BatlessError::config_error_with_help(
+            format!("Cannot access the clipboard: {e}"),
+            Some("Write to stdout and pipe into pbcopy, xclip or clip.exe instead".to_string()),
+        )
+    };
+    arboard::Clipboard::new()
+        .map_err(error)?
+        .set_text(text)
+        .map_err(error)
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> BatlessResult<()> {
+    ensure_available()
+}
+
+/// Fail before any work is done when this build has no clipboard support
+pub fn ensure_available() -> BatlessResult<()> {
+    if cfg!(feature = "clipboard") {
+        return Ok(());
+    }
+    Err(BatlessError::config_error_with_help(
+        "--copy needs clipboard support, which this build does not include".to_string(),
+        Some(
+            "Rebuild with --features clipboard, or pipe stdout into pbcopy, xclip or clip.exe"
+                .to_string(),
+        ),
+    ))
+}
@@ -0,0 +1,390 @@
+//! Golden output corpus for the JSON contract (`--emit-goldens`,
+//! `--verify-goldens`)
+//!
+//! A fixed set of small sources is run through a fixed set of configurations
+//! and each JSON document is stored as `<fixture>.<variant>.json`, pretty
+//! printed with sorted keys. Verifying compares the stored documents with
+//! what this build produces, value by value: added fields are compatible,
+//! while removed or changed fields break consumers that parse the output.
+//! Integrators keep a corpus next to their parser and re-verify it on
+//! upgrade; the repository verifies its own under `tests/goldens`.
+
+use crate::canonical_json::to_json_string;
+use crate::config::BatlessConfig;
+use crate::error::{BatlessError, BatlessResult};
+use crate::formatter::{OutputFormatter, OutputMode};
+use crate::processor::FileProcessor;
+use crate::summary::SummaryLevel;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// A source file in the corpus
+#[derive(Debug, Clone, Copy)]
+pub struct Fixture {
+    pub name: &'static str,
+    /// Logical filename, used for language detection
+    pub filename: &'static str,
+    pub content: &'static str,
+}
+
+/// A configuration every fixture is rendered with
+#[derive(Debug, Clone, Copy)]
+pub struct Variant {
+    pub name: &'static str,
+    pub config: fn() -> BatlessConfig,
+}
+
+pub const FIXTURES: &[Fixture] = &[
+    Fixture {
+        name: "rust",
+        filename: "lib.rs",
+        content: "use std::collections::HashMap;\n\n\
+            /// Key-value store\n\
+            pub struct Store {\n    items: HashMap<String, u32>,\n}\n\n\
+            impl Store {\n    /// Look up a key\n    \
+            pub fn get(&self, key: &str) -> Option<u32> {\n        \
+            self.items.get(key).copied()\n    }\n}\n\n\
+            fn helper(x: u32) -> u32 {\n    x * 2\n}\n",
+    },
+    Fixture {
+        name: "python",
+        filename: "app.py",
+        content: "import os\n\n\
+            class Loader:\n    \"\"\"Reads files.\"\"\"\n\n    \
+            def load(self, path):\n        return open(path).read()\n\n\n\
+            def main():\n    print(os.getcwd())\n",
+    },
+    Fixture {
+        name: "javascript",
+        filename: "index.js",
+        content: "import { readFile } from 'fs';\n\n\
+            export function parse(text) {\n  return JSON.parse(text);\n}\n\n\
+            class Cache {\n  get(key) {\n    return this.map[key];\n  }\n}\n",
+    },
+    Fixture {
+        name: "text",
+        filename: "notes.txt",
+        content: "First line\r\nSecond line\r\n\r\nLast line\r\n",
+    },
+];
+
+pub const VARIANTS: &[Variant] = &[
+    Variant {
+        name: "default",
+        config: BatlessConfig::default,
+    },
+    Variant {
+        name: "summary",
+        config: || BatlessConfig::default().with_summary_level(SummaryLevel::Standard),
+    },
+    Variant {
+        name: "tokens",
+        config: || BatlessConfig::default().with_include_tokens(true),
+    },
+    Variant {
+        name: "truncated",
+        config: || BatlessConfig::default().with_max_lines(3),
+    },
+];
+
+/// One rendered document of the corpus
+#[derive(Debug, Clone, PartialEq)]
+pub struct Golden {
+    /// File name inside the corpus directory
+    pub name: String,
+    pub value: Value,
+}
+
+/// Every document of the corpus as this build renders it
+pub fn goldens() -> BatlessResult<Vec<Golden>> {
+    let mut goldens = Vec::with_capacity(FIXTURES.len() * VARIANTS.len());
+    for fixture in FIXTURES {
+        for variant in VARIANTS {
+            let config = (variant.config)();
+            let file_info =
+                FileProcessor::process_content(fixture.content, fixture.filename, &config)?;
+            let output = OutputFormatter::format_output(
+                &file_info,
+                fixture.filename,
+                &config,
+                OutputMode::Json,
+            )?;
+            goldens.push(Golden {
+                name: format!("{}.{}.json", fixture.name, variant.name),
+                value: serde_json::from_str(&output)?,
+            });
+        }
+    }
+    Ok(goldens)
+}
+
+/// Write the corpus to `dir`, creating it if needed; returns the file count
+pub fn emit(dir: &Path) -> BatlessResult<usize> {
+    fs::create_dir_all(dir)?;
+    let goldens = goldens()?;
+    for golden in &goldens {
+        let mut text = to_json_string(&golden.value, true, true)?;
+        text.push('\n');
+        fs::write(dir.join(&golden.name), text)?;
+    }
+    Ok(goldens.len())
+}
+
+/// How a field differs from its golden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Present now, absent from the golden; compatible
+    Added,
+    /// In the golden, absent now
+    Removed,
+    /// Present in both with a different value or type
+    Changed,
+}
+
+impl ChangeKind {
+    /// Whether consumers written against the golden may break
+    pub const fn is_breaking(self) -> bool {
+        !matches!(self, Self::Added)
+    }
+}
+
+/// One difference between a golden and the current output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GoldenChange {
+    pub golden: String,
+    /// Dotted path to the field, e.g. `summary_items.0.kind`; empty for
+    /// the whole document
+    pub path: String,
+    pub kind: ChangeKind,
+    pub detail: String,
+}
+
+/// Result of comparing a corpus directory with the current output
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub checked: usize,
+    pub changes: Vec<GoldenChange>,
+    /// Corpus documents this build renders that the directory lacks
+    pub missing: Vec<String>,
+}
+
+impl VerifyReport {
+    /// True when nothing a consumer relies on was removed or changed
+    pub fn is_compatible(&self) -> bool {
+        !self.changes.iter().any(|change| change.kind.is_breaking())
+    }
+
+    /// Render a human-readable report
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for change in &self.changes {
+            let location = if change.path.is_empty() {
+                change.golden.clone()
+            } else {
+                format!("{}: {}", change.golden, change.path)
+            };
+            let kind = match change.kind {
+                ChangeKind::Added => "added",
+                ChangeKind::Removed => "removed",
+                ChangeKind::Changed => "changed",
+            };
+            let _ = writeln!(out, "{kind:<8} {location}: {}", change.detail);
+        }
+        for name in &self.missing {
+            let _ = writeln!(out, "new      {name}: not in the corpus");
+        }
+        let breaking = self
+            .changes
+            .iter()
+            .filter(|change| change.kind.is_breaking())
+            .count();
+        let _ = writeln!(
+            out,
+            "{} golden(s) checked, {breaking} breaking change(s), {} compatible addition(s)",
+            self.checked,
+            self.changes.len() - breaking
+        );
+        out
+    }
+}
+
+/// Compare the corpus in `dir` with what this build renders
+pub fn verify(dir: &Path) -> BatlessResult<VerifyReport> {
+    if !dir.is_dir() {
+        return Err(BatlessError::config_error_with_help(
+            format!("Golden directory not found: {}", dir.display()),
+            Some("Create one with --emit-goldens <DIR>".to_string()),
+        ));
+    }
+    let current = goldens()?;
+    let mut report = VerifyReport {
+        checked: 0,
+        changes: Vec::new(),
+        missing: Vec::new(),
+    };
+
+    let mut stored: Vec<String> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+        .collect();
+    stored.sort();
+    for name in stored {
+        let text = fs::read_to_string(dir.join(&name))?;
+        let expected: Value = serde_json::from_str(&text).map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Golden {name} is not valid JSON: {e}"),
+                Some("Regenerate the corpus with --emit-goldens <DIR>".to_string()),
+            )
+        })?;
+        report.checked += 1;
+        match current.iter().find(|golden| golden.name == name) {
+            Some(golden) => diff(&name, "", &expected, &golden.value, &mut report.changes),
+            None => report.changes.push(GoldenChange {
+                golden: name,
+                path: String::new(),
+                kind: ChangeKind::Removed,
+                detail: "no longer rendered by this build".to_string(),
+            }),
+        }
+    }
+    report.missing = current
+        .into_iter()
+        .map(|golden| golden.name)
+        .filter(|name| !dir.join(name).exists())
+        .collect();
+    Ok(report)
+}
+
+/// Record how `actual` differs from `expected` at `path`
+fn diff(golden: &str, path: &str, expected: &Value, actual: &Value, out: &mut Vec<GoldenChange>) {
+    let change = |kind, path: String, detail| GoldenChange {
+        golden: golden.to_string(),
+        path,
+        kind,
+        detail,
+    };
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                match actual.get(key) {
+                    Some(now) => diff(golden, &child(key), value, now, out),
+                    None => out.push(change(
+                        ChangeKind::Removed,
+                        child(key),
+                        format!("was {}", brief(value)),
+                    )),
+                }
+            }
+            for (key, value) in actual {
+                if !expected.contains_key(key) {
+                    out.push(change(
+                        ChangeKind::Added,
+                        child(key),
+                        format!("now {}", brief(value)),
+                    ));
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (index, (was, now)) in expected.iter().zip(actual).enumerate() {
+                diff(golden, &child(&index.to_string()), was, now, out);
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => out.push(change(
+            ChangeKind::Changed,
+            path.to_string(),
+            format!("{} item(s), was {}", actual.len(), expected.len()),
+        )),
+        _ if expected != actual => out.push(change(
+            ChangeKind::Changed,
+            path.to_string(),
+            format!("{} -> {}", brief(expected), brief(actual)),
+        )),
+        _ => {}
+    }
+}
+
+/// Short rendering of a value for reports
+fn brief(value: &Value) -> String {
+    const MAX_CHARS: usize = 60;
+    let text = value.to_string();
+    if text.chars().count() <= MAX_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(MAX_CHARS).collect();
+    format!("{cut}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_corpus_covers_every_fixture_and_variant() {
+        let goldens = goldens().unwrap();
+        assert_eq!(goldens.len(), FIXTURES.len() * VARIANTS.len());
+        let summary = goldens
+            .iter()
+            .find(|golden| golden.name == "rust.summary.json")
+            .unwrap();
+        assert_eq!(summary.value["language"], "Rust");
+        assert!(summary.value["summary_items"].is_array());
+    }
+
+    #[test]
+    fn test_diff_classifies_changes() {
+        let expected = json!({"a": 1, "b": {"c": "x"}, "d": [1, 2], "gone": true});
+        let actual = json!({"a": 2, "b": {"c": "x", "new": 0}, "d": [1]});
+        let mut changes = Vec::new();
+        diff("g.json", "", &expected, &actual, &mut changes);
+        let summary: Vec<(&str, ChangeKind)> = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("a", ChangeKind::Changed),
+                ("b.new", ChangeKind::Added),
+                ("d", ChangeKind::Changed),
+                ("gone", ChangeKind::Removed),
+            ]
+        );
+        assert_eq!(changes[0].detail, "1 -> 2");
+    }
+
+    #[test]
+    fn test_emit_then_verify_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let count = emit(dir.path()).unwrap();
+        let report = verify(dir.path()).unwrap();
+        assert_eq!(report.checked, count);
+        assert!(report.changes.is_empty(), "{}", report.render_text());
+        assert!(report.missing.is_empty());
+
+        // A field only the golden has is a breaking change
+        let path = dir.path().join("text.default.json");
+        let mut value: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        value["legacy"] = json!(1);
+        fs::write(&path, value.to_string()).unwrap();
+        fs::remove_file(dir.path().join("rust.tokens.json")).unwrap();
+        let report = verify(dir.path()).unwrap();
+        assert!(!report.is_compatible());
+        assert_eq!(report.changes[0].path, "legacy");
+        assert_eq!(report.missing, ["rust.tokens.json"]);
+    }
+}
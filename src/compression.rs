@@ -0,0 +1,131 @@
+//! Transparent decompression of `.gz`, `.bz2`, `.xz` and `.zst` inputs
+//! (`compression` feature)
+//!
+//! Compressed files are decoded on the fly while lines are read, so the
+//! usual `--max-lines` / `--max-bytes` limits stop decompression early and
+//! the whole file is never inflated in memory. Language detection uses the
+//! inner filename: `main.rs.gz` is Rust, `app.log.gz` is read as `app.log`.
+
+use crate::error::{BatlessError, BatlessResult};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// Compression format, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+impl Codec {
+    /// Codec for a compressed file name, `None` for anything else
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "gz" => Some(Self::Gzip),
+            "bz2" => Some(Self::Bzip2),
+            "xz" => Some(Self::Xz),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Bzip2 => "bzip2",
+            Self::Xz => "xz",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Wrap `reader` in a streaming decoder for this format
+    pub fn decoder<'a, R: Read + 'a>(self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            // The multi-member decoders also handle concatenated archives
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            Self::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+            Self::Xz => Box::new(lzma_rust2::XzReader::new(reader, true)),
+            Self::Zstd => Box::new(
+                ruzstd::decoding::StreamingDecoder::new(reader)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+            ),
+        })
+    }
+}
+
+/// File name without the compression extension (`src/main.rs.gz` → `src/main.rs`)
+pub fn inner_name(path: &str) -> &str {
+    match Codec::from_path(path) {
+        Some(_) => path.rsplit_once('.').map_or(path, |(inner, _)| inner),
+        None => path,
+    }
+}
+
+/// Open a compressed file for reading its decompressed lines
+pub fn open(path: &str, codec: Codec) -> BatlessResult<Box<dyn BufRead>> {
+    let read_error = |e| BatlessError::FileReadError {
+        path: path.to_string(),
+        source: e,
+    };
+    let file = File::open(path).map_err(read_error)?;
+    let decoder = codec.decoder(BufReader::new(file)).map_err(read_error)?;
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TEXT: &str = "def main():\n    pass\n";
+
+    /// `printf 'def main():\n    pass\n' | xz -c`
+    const XZ_FIXTURE: &[u8] = &[
+        0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00, 0x00, 0x04, 0xe6, 0xd6, 0xb4, 0x46, 0x04, 0xc0, 0x19,
+        0x15, 0x21, 0x01, 0x16, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0xe3,
+        0x90, 0xb5, 0x01, 0x00, 0x14, 0x64, 0x65, 0x66, 0x20, 0x6d, 0x61, 0x69, 0x6e, 0x28, 0x29,
+        0x3a, 0x0a, 0x20, 0x20, 0x20, 0x20, 0x70, 0x61, 0x73, 0x73, 0x0a, 0x00, 0x00, 0x00, 0x00,
+        0xee, 0xd9, 0x30, 0x6b, 0x95, 0x4c, 0xac, 0x08, 0x00, 0x01, 0x35, 0x15, 0x76, 0x93, 0x6a,
+        0xef, 0x1f, 0xb6, 0xf3, 0x7d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x59, 0x5a,
+    ];
+
+    fn decode(codec: Codec, bytes: &[u8]) -> String {
+        let mut out = String::new();
+        codec
+            .decoder(bytes)
+            .unwrap()
+            .read_to_string(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_codec_and_inner_name() {
+        assert_eq!(Codec::from_path("app.log.gz"), Some(Codec::Gzip));
+        assert_eq!(Codec::from_path("dump.SQL.ZST"), Some(Codec::Zstd));
+        assert_eq!(Codec::from_path("main.rs"), None);
+        assert_eq!(inner_name("src/main.rs.bz2"), "src/main.rs");
+        assert_eq!(inner_name("notes.txt"), "notes.txt");
+    }
+
+    #[test]
+    fn test_round_trips_every_codec() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(TEXT.as_bytes()).unwrap();
+        assert_eq!(decode(Codec::Gzip, &gz.finish().unwrap()), TEXT);
+
+        let mut bz = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bz.write_all(TEXT.as_bytes()).unwrap();
+        assert_eq!(decode(Codec::Bzip2, &bz.finish().unwrap()), TEXT);
+
+        let zst = ruzstd::encoding::compress_to_vec(
+            TEXT.as_bytes(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        assert_eq!(decode(Codec::Zstd, &zst), TEXT);
+        assert_eq!(decode(Codec::Xz, XZ_FIXTURE), TEXT);
+    }
+}
@@ -3,9 +3,15 @@
 //! This module handles all configuration-related functionality including
 //! default values, validation, and configuration parsing.
 
+use crate::around::AroundSpec;
+use crate::config_check::{self, ConfigFileKind};
 use crate::config_validation::validate_config;
-use crate::error::{BatlessError, BatlessResult};
+use crate::env_policy::TermEnv;
+use crate::error::{BatlessError, BatlessResult, ConfigurationWarning};
 use crate::summary::SummaryLevel;
+use crate::tokens::{
+    AiModel, CustomModelSpec, TokenCounter, TokenExtractor, TokenOptions, TokenOrder,
+};
 use serde::{Deserialize, Serialize};
 
 /// Strategy for splitting streaming chunks
@@ -19,9 +25,129 @@ pub enum ChunkStrategy {
     /// unsupported languages)
     Semantic,
 }
+
+/// ANSI style applied to emphasized lines in colored text output
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightStyle {
+    /// Swap foreground and background (default)
+    #[default]
+    Inverse,
+    Bold,
+    Underline,
+}
+
+impl HighlightStyle {
+    /// SGR escape sequence that switches the style on
+    pub const fn ansi(self) -> &'static str {
+        match self {
+            Self::Inverse => "\x1b[7m",
+            Self::Bold => "\x1b[1m",
+            Self::Underline => "\x1b[4m",
+        }
+    }
+}
+
+/// Severity of a log record, ordered from least to most severe
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    /// Parse a level name as written by common loggers (`WARNING`, `err`, `crit`, ...)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trace" | "verbose" => Some(Self::Trace),
+            "debug" | "dbg" => Some(Self::Debug),
+            "info" | "information" | "notice" | "note" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warn),
+            "error" | "err" => Some(Self::Error),
+            "fatal" | "critical" | "crit" | "panic" | "emerg" | "alert" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name used in JSON output
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Trace => "trace",
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+            Self::Fatal => "fatal",
+        }
+    }
+
+    /// SGR escape sequence used to color records of this level
+    pub const fn ansi(self) -> &'static str {
+        match self {
+            Self::Trace | Self::Debug => "\x1b[2m",
+            Self::Info => "\x1b[32m",
+            Self::Warn => "\x1b[33m",
+            Self::Error => "\x1b[31m",
+            Self::Fatal => "\x1b[1;31m",
+        }
+    }
+}
+
+/// Inclusive 1-based line range, written `N` or `START:END`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl LineRange {
+    /// Parse `N` or `START:END`
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let (start, end) = input.split_once(':').unwrap_or((input, input));
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("invalid line '{value}' in '{input}' (lines start at 1)"))
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err(format!("range '{input}' ends before it starts"));
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Whether `line` falls inside the range
+    pub const fn contains(&self, line: usize) -> bool {
+        self.start <= line && line <= self.end
+    }
+}
+
+/// Forge URL scheme used for line permalinks
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkFormat {
+    /// `https://github.com/org/repo/blob/<sha>/path#L10-L20`
+    Github,
+    /// `https://gitlab.com/org/repo/-/blob/<sha>/path#L10-20`
+    Gitlab,
+    /// `https://git.sr.ht/~user/repo/tree/<sha>/item/path#L10-20`
+    Sourcehut,
+}
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Repo-committed project directory holding `config.toml` and profile files
+pub const PROJECT_CONFIG_DIR: &str = ".batless";
+
 /// Configuration structure for batless operations
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BatlessConfig {
@@ -34,6 +160,12 @@ pub struct BatlessConfig {
     /// Override language detection with specific language
     #[serde(default)]
     pub language: Option<String>,
+    /// Logical filename for stdin input, used for language detection and output
+    #[serde(default)]
+    pub stdin_filename: Option<String>,
+    /// Language of stdin input, taking precedence over `stdin_filename` detection
+    #[serde(default)]
+    pub stdin_language: Option<String>,
     /// Whether to strip ANSI escape sequences
     #[serde(default)]
     pub strip_ansi: bool,
@@ -43,12 +175,36 @@ pub struct BatlessConfig {
     /// Whether to include tokens in JSON output
     #[serde(default)]
     pub include_tokens: bool,
+    /// Keep only the first occurrence of each token in the JSON sample
+    #[serde(default)]
+    pub unique_tokens: bool,
+    /// Tokens listed in JSON output; `identifier_count` still counts all
+    #[serde(default = "default_max_token_sample")]
+    pub max_token_sample: usize,
+    /// Order of the listed tokens
+    #[serde(default)]
+    pub token_order: TokenOrder,
+    /// Report the N most frequent identifiers with their counts
+    #[serde(default)]
+    pub token_frequencies: Option<usize>,
     /// Summary extraction level
     #[serde(default)]
     pub summary_level: SummaryLevel,
     /// Whether to enable summary mode (deprecated, use summary_level)
     #[serde(default)]
     pub summary_mode: bool,
+    /// Attach the first sentence of each item's doc comment to summaries
+    #[serde(default)]
+    pub summary_docs: bool,
+    /// Leave test modules, functions and files out of summaries and context packs
+    #[serde(default)]
+    pub no_tests: bool,
+    /// Keep only public or exported symbols in summaries
+    #[serde(default)]
+    pub public_only: bool,
+    /// Parse with tree-sitter to fill in syntax errors even without a summary
+    #[serde(default)]
+    pub check_syntax: bool,
     /// Enable streaming JSON output for large files
     #[serde(default)]
     pub streaming_json: bool,
@@ -58,6 +214,28 @@ pub struct BatlessConfig {
     /// Enable resume capability with checkpoint support
     #[serde(default)]
     pub enable_resume: bool,
+    /// Start a stream over when its file changes mid-stream instead of failing
+    #[serde(default)]
+    pub restart_on_change: bool,
+    /// Delay between streaming chunks in milliseconds
+    #[serde(default)]
+    pub stream_delay_ms: Option<u64>,
+    /// Upper bound on streaming chunks emitted per second
+    #[serde(default)]
+    pub max_chunks_per_second: Option<u32>,
+    /// Stop streaming after this many chunks
+    #[serde(default)]
+    pub max_chunks: Option<usize>,
+    /// Stop streaming once this many estimated tokens have been emitted
+    #[serde(default)]
+    pub stream_token_budget: Option<usize>,
+    /// Stream plain output of files over `auto_stream_threshold` bytes
+    /// instead of reading them whole
+    #[serde(default = "default_auto_stream")]
+    pub auto_stream: bool,
+    /// File size in bytes above which `auto_stream` applies
+    #[serde(default = "default_auto_stream_threshold")]
+    pub auto_stream_threshold: u64,
     /// Schema version for JSON output compatibility
     #[serde(default = "default_schema_version")]
     pub schema_version: String,
@@ -73,6 +251,19 @@ pub struct BatlessConfig {
     /// Pretty print JSON output (non-streaming JSON mode)
     #[serde(default)]
     pub pretty_json: bool,
+    /// Sort JSON object keys so identical inputs give byte-identical output
+    #[serde(default)]
+    pub canonical_json: bool,
+    /// Byte-identical output across runs: fixed timestamps and pseudonyms,
+    /// input order and canonical JSON
+    #[serde(default)]
+    pub deterministic: bool,
+    /// Samples used to estimate tokens of files over 100KB
+    #[serde(default = "default_sample_count")]
+    pub sample_count: usize,
+    /// Seed for placing the token estimation samples
+    #[serde(default)]
+    pub sample_seed: u64,
     /// Include 1-based line numbers in JSON output lines array
     #[serde(default)]
     pub json_line_numbers: bool,
@@ -85,11 +276,107 @@ pub struct BatlessConfig {
     /// Strip blank lines from output
     #[serde(default)]
     pub strip_blank_lines: bool,
+    /// Replace function bodies with `{ ... }` / `...` (tree-sitter languages)
+    #[serde(default)]
+    pub signatures_only: bool,
+    /// Split lines at lone `\r` (classic Mac endings) so every terminator becomes `\n`
+    #[serde(default)]
+    pub normalize_line_endings: bool,
     /// Strategy for splitting streaming chunks
     #[serde(default)]
     pub chunk_strategy: ChunkStrategy,
+    /// Forge format for summary/index permalinks (requires `repo_url`)
+    #[serde(default)]
+    pub link_format: Option<LinkFormat>,
+    /// Base repository URL used when building permalinks
+    #[serde(default)]
+    pub repo_url: Option<String>,
+    /// Memory ceiling in bytes for buffered input (accepts `"256MB"` in TOML)
+    #[serde(default, deserialize_with = "deserialize_memory_size")]
+    pub max_memory: Option<usize>,
+    /// Wall-clock processing timeout per file in milliseconds (accepts `"2s"` in TOML)
+    #[serde(default, deserialize_with = "deserialize_duration_ms")]
+    pub timeout_ms: Option<u64>,
+    /// Most bytes accepted on stdin before failing (accepts `"64MB"` in TOML)
+    #[serde(default, deserialize_with = "deserialize_memory_size")]
+    pub stdin_max_bytes: Option<usize>,
+    /// Time stdin has to finish in milliseconds (accepts `"10s"` in TOML)
+    #[serde(default, deserialize_with = "deserialize_duration_ms")]
+    pub stdin_timeout_ms: Option<u64>,
+    /// Keep only the lines around a focus line (`--around LINE:CONTEXT`)
+    #[serde(default)]
+    pub around: Option<AroundSpec>,
+    /// Keep only the definition of a named symbol (`--symbol NAME`)
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// List the occurrences of an identifier instead of the content (`--uses NAME`)
+    #[serde(default)]
+    pub uses: Option<String>,
+    /// Line to mark in text output (`--highlight-line N`)
+    #[serde(default)]
+    pub highlight_line: Option<usize>,
+    /// Lines to emphasize (`--highlight-lines 3,10:15`)
+    #[serde(default)]
+    pub highlight_lines: Vec<LineRange>,
+    /// Style for emphasized lines in colored text output
+    #[serde(default)]
+    pub highlight_style: HighlightStyle,
+    /// Sidecar JSON file with line annotations to overlay (`--annotations`)
+    #[serde(default)]
+    pub annotations: Option<String>,
+    /// SARIF 2.1 results to overlay as annotations (`--sarif`)
+    #[serde(default)]
+    pub sarif: Option<String>,
+    /// Fast fuzzy-finder preview presentation (`--preview-window`)
+    #[serde(default)]
+    pub preview_window: bool,
+    /// Fold JSON/YAML containers nested deeper than this (`--fold-depth N`)
+    #[serde(default)]
+    pub fold_depth: Option<usize>,
+    /// Drop log records below this level in log output (`--min-level`)
+    #[serde(default)]
+    pub min_log_level: Option<LogLevel>,
+    /// Emit semantic highlight spans in JSON/JSONL output instead of ANSI colors
+    #[serde(default)]
+    pub highlight_spans: bool,
+    /// Mask secrets with the built-in redaction rules
+    #[serde(default)]
+    pub redact: bool,
+    /// Pseudonymize emails, IPs and UUIDs (enables redaction)
+    #[serde(default)]
+    pub anonymize: bool,
+    /// Additional regexes to redact (enables redaction)
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+    /// Header template printed before each file in multi-file text output
+    #[serde(default)]
+    pub file_header: Option<String>,
+    /// Footer template printed after each file in multi-file text output
+    #[serde(default)]
+    pub file_footer: Option<String>,
+    /// Template that replaces plain and summary output (`--template`)
+    #[serde(default)]
+    pub template: Option<String>,
+    /// User-defined AI models (`[models.<name>]` tables) usable with `--ai-model`
+    #[serde(default)]
+    pub models: BTreeMap<String, CustomModelSpec>,
+    /// File name or path globs mapped to languages (`[language_map]`),
+    /// checked before extension detection
+    #[serde(default)]
+    pub language_map: BTreeMap<String, String>,
+    /// Directory holding runtime grammar libraries (default
+    /// `~/.config/batless/grammars`)
+    #[serde(default)]
+    pub grammar_dir: Option<PathBuf>,
+    /// Languages parsed with runtime-loaded grammars (`[grammars]`), mapped
+    /// to the grammar name (`Gleam = "gleam"`)
+    #[serde(default)]
+    pub grammars: BTreeMap<String, String>,
 }
 
+/// Config keys whose values are paths (or select files) that batless reads
+const FILE_READING_KEYS: [&str; 4] = ["annotations", "sarif", "grammar_dir", "grammars"];
+
 const fn default_max_lines() -> usize {
     10000
 }
@@ -102,34 +389,195 @@ const fn default_streaming_chunk_size() -> usize {
     1000
 }
 
+const fn default_auto_stream() -> bool {
+    true
+}
+
+const fn default_auto_stream_threshold() -> u64 {
+    64 * 1024 * 1024
+}
+
+const fn default_max_token_sample() -> usize {
+    TokenExtractor::MAX_SAMPLE_SIZE
+}
+
+const fn default_sample_count() -> usize {
+    crate::tokens::TokenCounter::DEFAULT_SAMPLE_COUNT
+}
+
 fn default_schema_version() -> String {
     "2.1".to_string()
 }
 
+/// Parse a human-readable memory size such as `256MB`, `1.5GiB`, `512k` or `1048576`.
+///
+/// Decimal (`KB`, `MB`, `GB`) and binary (`KiB`, `MiB`, `GiB`) suffixes are both
+/// accepted; single-letter suffixes (`k`, `m`, `g`) are treated as binary.
+pub fn parse_memory_size(input: &str) -> Result<usize, String> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid memory size '{input}' (expected e.g. 256MB)"))?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "k" | "kib" => 1024.0,
+        "m" | "mib" => 1024.0 * 1024.0,
+        "g" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        other => return Err(format!("unknown memory size unit '{other}' in '{input}'")),
+    };
+    let bytes = value * multiplier;
+    if !bytes.is_finite() || bytes >= usize::MAX as f64 {
+        return Err(format!("memory size '{input}' is too large"));
+    }
+    Ok(bytes as usize)
+}
+
+fn deserialize_memory_size<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MemorySize {
+        Bytes(usize),
+        Text(String),
+    }
+
+    match Option::<MemorySize>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(MemorySize::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(MemorySize::Text(text)) => parse_memory_size(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Parse a human-readable duration such as `500ms`, `2s`, `1.5m` or `30` (seconds)
+/// into milliseconds.
+pub fn parse_duration_ms(input: &str) -> Result<u64, String> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{input}' (expected e.g. 500ms or 2s)"))?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "ms" => 1.0,
+        "" | "s" => 1e3,
+        "m" | "min" => 60e3,
+        other => return Err(format!("unknown duration unit '{other}' in '{input}'")),
+    };
+    let millis = value * multiplier;
+    if !millis.is_finite() || millis >= u64::MAX as f64 {
+        return Err(format!("duration '{input}' is too large"));
+    }
+    Ok(millis as u64)
+}
+
+fn deserialize_duration_ms<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Duration {
+        Millis(u64),
+        Text(String),
+    }
+
+    match Option::<Duration>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Duration::Millis(millis)) => Ok(Some(millis)),
+        Some(Duration::Text(text)) => parse_duration_ms(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
 impl Default for BatlessConfig {
     fn default() -> Self {
         Self {
             max_lines: 10000,
             max_bytes: None,
             language: None,
+            stdin_filename: None,
+            stdin_language: None,
             strip_ansi: false,
             use_color: true,
             include_tokens: false,
+            unique_tokens: false,
+            max_token_sample: default_max_token_sample(),
+            token_order: TokenOrder::Source,
+            token_frequencies: None,
             summary_level: SummaryLevel::None,
             summary_mode: false,
+            summary_docs: false,
+            no_tests: false,
+            public_only: false,
+            check_syntax: false,
             streaming_json: false,
             streaming_chunk_size: default_streaming_chunk_size(),
             enable_resume: false,
+            restart_on_change: false,
+            stream_delay_ms: None,
+            max_chunks_per_second: None,
+            max_chunks: None,
+            stream_token_budget: None,
+            auto_stream: default_auto_stream(),
+            auto_stream_threshold: default_auto_stream_threshold(),
             schema_version: default_schema_version(),
             debug: false,
             show_line_numbers: false,
             show_line_numbers_nonblank: false,
             pretty_json: false,
+            canonical_json: false,
+            deterministic: false,
+            sample_count: default_sample_count(),
+            sample_seed: 0,
             json_line_numbers: false,
             hash: false,
             strip_comments: false,
             strip_blank_lines: false,
+            signatures_only: false,
+            normalize_line_endings: false,
             chunk_strategy: ChunkStrategy::Line,
+            link_format: None,
+            repo_url: None,
+            max_memory: None,
+            timeout_ms: None,
+            stdin_max_bytes: None,
+            stdin_timeout_ms: None,
+            around: None,
+            symbol: None,
+            uses: None,
+            highlight_line: None,
+            highlight_lines: Vec::new(),
+            highlight_style: HighlightStyle::Inverse,
+            annotations: None,
+            sarif: None,
+            preview_window: false,
+            fold_depth: None,
+            min_log_level: None,
+            highlight_spans: false,
+            redact: false,
+            anonymize: false,
+            redact_patterns: Vec::new(),
+            file_header: None,
+            template: None,
+            file_footer: None,
+            models: BTreeMap::new(),
+            language_map: BTreeMap::new(),
+            grammar_dir: None,
+            grammars: BTreeMap::new(),
         }
     }
 }
@@ -158,6 +606,36 @@ impl BatlessConfig {
         self
     }
 
+    /// Set the logical filename reported for stdin input
+    pub fn with_stdin_filename(mut self, stdin_filename: Option<String>) -> Self {
+        self.stdin_filename = stdin_filename;
+        self
+    }
+
+    /// Set the language of stdin input
+    pub fn with_stdin_language(mut self, stdin_language: Option<String>) -> Self {
+        self.stdin_language = stdin_language;
+        self
+    }
+
+    /// Time to stamp output with: the Unix epoch when `deterministic`
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        if self.deterministic {
+            chrono::DateTime::UNIX_EPOCH
+        } else {
+            chrono::Utc::now()
+        }
+    }
+
+    /// Path to report for `file_path`: the `stdin_filename` for `-`, otherwise
+    /// the path itself
+    pub fn display_path<'a>(&'a self, file_path: &'a str) -> &'a str {
+        match self.stdin_filename.as_deref() {
+            Some(name) if file_path == "-" => name,
+            _ => file_path,
+        }
+    }
+
     /// Set ANSI stripping
     pub const fn with_strip_ansi(mut self, strip_ansi: bool) -> Self {
         self.strip_ansi = strip_ansi;
@@ -176,6 +654,30 @@ impl BatlessConfig {
         self
     }
 
+    /// Sample each token once, at its first occurrence
+    pub const fn with_unique_tokens(mut self, unique_tokens: bool) -> Self {
+        self.unique_tokens = unique_tokens;
+        self
+    }
+
+    /// Set how many tokens are listed in JSON output
+    pub const fn with_max_token_sample(mut self, max_token_sample: usize) -> Self {
+        self.max_token_sample = max_token_sample;
+        self
+    }
+
+    /// Set the order of listed tokens
+    pub const fn with_token_order(mut self, token_order: TokenOrder) -> Self {
+        self.token_order = token_order;
+        self
+    }
+
+    /// Rank the N most frequent identifiers
+    pub const fn with_token_frequencies(mut self, top_n: Option<usize>) -> Self {
+        self.token_frequencies = top_n;
+        self
+    }
+
     /// Set summary mode
     pub const fn with_summary_mode(mut self, summary_mode: bool) -> Self {
         self.summary_mode = summary_mode;
@@ -196,6 +698,30 @@ impl BatlessConfig {
         self
     }
 
+    /// Include doc comment first sentences in summaries
+    pub const fn with_summary_docs(mut self, summary_docs: bool) -> Self {
+        self.summary_docs = summary_docs;
+        self
+    }
+
+    /// Report tree-sitter syntax errors for every file
+    pub const fn with_check_syntax(mut self, check_syntax: bool) -> Self {
+        self.check_syntax = check_syntax;
+        self
+    }
+
+    /// Exclude test code from summaries and context packs
+    pub const fn with_no_tests(mut self, no_tests: bool) -> Self {
+        self.no_tests = no_tests;
+        self
+    }
+
+    /// Restrict summaries to the public API
+    pub const fn with_public_only(mut self, public_only: bool) -> Self {
+        self.public_only = public_only;
+        self
+    }
+
     /// Enable streaming JSON output
     pub const fn with_streaming_json(mut self, streaming_json: bool) -> Self {
         self.streaming_json = streaming_json;
@@ -214,6 +740,57 @@ impl BatlessConfig {
         self
     }
 
+    /// Restart streams whose file changes while they are read
+    pub const fn with_restart_on_change(mut self, restart_on_change: bool) -> Self {
+        self.restart_on_change = restart_on_change;
+        self
+    }
+
+    /// Set the delay between streaming chunks
+    pub const fn with_stream_delay_ms(mut self, delay_ms: Option<u64>) -> Self {
+        self.stream_delay_ms = delay_ms;
+        self
+    }
+
+    /// Limit the streaming chunk rate
+    pub const fn with_max_chunks_per_second(mut self, rate: Option<u32>) -> Self {
+        self.max_chunks_per_second = rate;
+        self
+    }
+
+    /// Cap the number of streaming chunks
+    pub const fn with_max_chunks(mut self, max_chunks: Option<usize>) -> Self {
+        self.max_chunks = max_chunks;
+        self
+    }
+
+    /// Cap the estimated tokens a stream may emit
+    pub const fn with_stream_token_budget(mut self, budget: Option<usize>) -> Self {
+        self.stream_token_budget = budget;
+        self
+    }
+
+    /// Enable or disable streaming of files over the size threshold
+    pub const fn with_auto_stream(mut self, auto_stream: bool) -> Self {
+        self.auto_stream = auto_stream;
+        self
+    }
+
+    /// Set the file size above which plain output is streamed
+    pub const fn with_auto_stream_threshold(mut self, threshold: u64) -> Self {
+        self.auto_stream_threshold = threshold;
+        self
+    }
+
+    /// Minimum interval between streaming chunks implied by the delay and rate limits
+    pub fn stream_interval(&self) -> Option<std::time::Duration> {
+        let delay = self.stream_delay_ms.map(std::time::Duration::from_millis);
+        let rate = self
+            .max_chunks_per_second
+            .map(|rate| std::time::Duration::from_secs(1) / rate.max(1));
+        delay.max(rate)
+    }
+
     /// Set schema version
     pub fn with_schema_version(mut self, version: String) -> Self {
         self.schema_version = version;
@@ -247,6 +824,43 @@ impl BatlessConfig {
         self
     }
 
+    /// Sort JSON object keys for byte-stable output
+    pub const fn with_canonical_json(mut self, canonical: bool) -> Self {
+        self.canonical_json = canonical;
+        self
+    }
+
+    /// Reproducible output; implies canonical JSON
+    pub const fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        if deterministic {
+            self.canonical_json = true;
+        }
+        self
+    }
+
+    /// Sample count and seed for large-file token estimation
+    pub const fn with_token_sampling(mut self, sample_count: usize, sample_seed: u64) -> Self {
+        self.sample_count = sample_count;
+        self.sample_seed = sample_seed;
+        self
+    }
+
+    /// Token counter for `model` using the configured sampling
+    pub const fn token_counter(&self, model: AiModel) -> TokenCounter {
+        TokenCounter::new(model).with_sampling(self.sample_count, self.sample_seed)
+    }
+
+    /// Post-processing for extracted identifiers
+    pub const fn token_options(&self) -> TokenOptions {
+        TokenOptions {
+            max_sample: self.max_token_sample,
+            unique: self.unique_tokens,
+            order: self.token_order,
+            frequencies: self.token_frequencies,
+        }
+    }
+
     /// Include 1-based line numbers in JSON output lines array
     pub const fn with_json_line_numbers(mut self, enabled: bool) -> Self {
         self.json_line_numbers = enabled;
@@ -271,18 +885,239 @@ impl BatlessConfig {
         self
     }
 
+    /// Elide function bodies, keeping only signatures
+    pub const fn with_signatures_only(mut self, enabled: bool) -> Self {
+        self.signatures_only = enabled;
+        self
+    }
+
+    /// Normalize CR and CRLF line endings to LF
+    pub const fn with_normalize_line_endings(mut self, enabled: bool) -> Self {
+        self.normalize_line_endings = enabled;
+        self
+    }
+
     /// Set streaming chunk strategy
     pub const fn with_chunk_strategy(mut self, strategy: ChunkStrategy) -> Self {
         self.chunk_strategy = strategy;
         self
     }
 
+    /// Set forge link format for permalinks
+    pub const fn with_link_format(mut self, format: Option<LinkFormat>) -> Self {
+        self.link_format = format;
+        self
+    }
+
+    /// Set repository URL for permalinks
+    pub fn with_repo_url(mut self, url: Option<String>) -> Self {
+        self.repo_url = url;
+        self
+    }
+
+    /// Set memory ceiling in bytes
+    pub const fn with_max_memory(mut self, max_memory: Option<usize>) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
+    /// Set per-file processing timeout in milliseconds
+    pub const fn with_timeout_ms(mut self, timeout_ms: Option<u64>) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set the most bytes accepted on stdin
+    pub const fn with_stdin_max_bytes(mut self, stdin_max_bytes: Option<usize>) -> Self {
+        self.stdin_max_bytes = stdin_max_bytes;
+        self
+    }
+
+    /// Set the time stdin has to finish in milliseconds
+    pub const fn with_stdin_timeout_ms(mut self, stdin_timeout_ms: Option<u64>) -> Self {
+        self.stdin_timeout_ms = stdin_timeout_ms;
+        self
+    }
+
+    /// Restrict output to the lines around a focus line
+    pub const fn with_around(mut self, around: Option<AroundSpec>) -> Self {
+        self.around = around;
+        self
+    }
+
+    /// Restrict output to the definition of a named function, method or type
+    pub fn with_symbol(mut self, symbol: Option<String>) -> Self {
+        self.symbol = symbol;
+        self
+    }
+
+    /// List where an identifier occurs instead of the content
+    pub fn with_uses(mut self, uses: Option<String>) -> Self {
+        self.uses = uses;
+        self
+    }
+
+    /// Mark a line in text output
+    pub const fn with_highlight_line(mut self, line: Option<usize>) -> Self {
+        self.highlight_line = line;
+        self
+    }
+
+    /// Emphasize the given line ranges
+    pub fn with_highlight_lines(mut self, ranges: Vec<LineRange>) -> Self {
+        self.highlight_lines = ranges;
+        self
+    }
+
+    /// Set the style for emphasized lines
+    pub const fn with_highlight_style(mut self, style: HighlightStyle) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// Overlay annotations from a sidecar JSON file
+    pub fn with_annotations(mut self, path: Option<String>) -> Self {
+        self.annotations = path;
+        self
+    }
+
+    /// Overlay results from a SARIF file
+    pub fn with_sarif(mut self, path: Option<String>) -> Self {
+        self.sarif = path;
+        self
+    }
+
+    /// Whether an annotation source (`--annotations` or `--sarif`) is set
+    pub const fn has_annotations(&self) -> bool {
+        self.annotations.is_some() || self.sarif.is_some()
+    }
+
+    /// Whether source line `line` is emphasized by `--highlight-line(s)`
+    pub fn is_emphasized(&self, line: usize) -> bool {
+        self.highlight_line == Some(line) || self.highlight_lines.iter().any(|r| r.contains(line))
+    }
+
+    /// Whether any line is emphasized
+    pub fn has_emphasis(&self) -> bool {
+        self.highlight_line.is_some() || !self.highlight_lines.is_empty()
+    }
+
+    /// Enable the fuzzy-finder preview presentation
+    pub const fn with_preview_window(mut self, preview_window: bool) -> Self {
+        self.preview_window = preview_window;
+        self
+    }
+
+    /// Fold data files below the given nesting depth
+    pub const fn with_fold_depth(mut self, depth: Option<usize>) -> Self {
+        self.fold_depth = depth;
+        self
+    }
+
+    /// Hide log records below `level` in log output
+    pub const fn with_min_log_level(mut self, level: Option<LogLevel>) -> Self {
+        self.min_log_level = level;
+        self
+    }
+
+    /// Lines to read from the input. An `--around` window may reach past
+    /// `max_lines`; the rest of the limit is kept so the enclosing function
+    /// can still be parsed to its end. Previews never name the enclosing
+    /// function, so they stop as soon as the window is filled. `--symbol`
+    /// reads the whole input, since the symbol can be anywhere in it.
+    pub fn read_line_limit(&self) -> usize {
+        if self.symbol.is_some() {
+            return usize::MAX;
+        }
+        self.around.map_or(self.max_lines, |around| {
+            if self.preview_window {
+                around.last_line()
+            } else {
+                around.last_line().max(self.max_lines)
+            }
+        })
+    }
+
+    /// Enable built-in secret redaction
+    pub const fn with_redact(mut self, redact: bool) -> Self {
+        self.redact = redact;
+        self
+    }
+
+    /// Enable PII pseudonymization
+    pub const fn with_anonymize(mut self, anonymize: bool) -> Self {
+        self.anonymize = anonymize;
+        self
+    }
+
+    /// Set additional redaction patterns
+    pub fn with_redact_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.redact_patterns = patterns;
+        self
+    }
+
+    /// Set the multi-file header template
+    pub fn with_file_header(mut self, template: Option<String>) -> Self {
+        self.file_header = template;
+        self
+    }
+
+    /// Set the multi-file footer template
+    pub fn with_file_footer(mut self, template: Option<String>) -> Self {
+        self.file_footer = template;
+        self
+    }
+
+    /// Set the file name and path globs mapped to languages
+    pub fn with_language_map(mut self, language_map: BTreeMap<String, String>) -> Self {
+        self.language_map = language_map;
+        self
+    }
+
+    /// Set the directory runtime grammars are loaded from
+    pub fn with_grammar_dir(mut self, grammar_dir: Option<PathBuf>) -> Self {
+        self.grammar_dir = grammar_dir;
+        self
+    }
+
+    /// Set the languages parsed with runtime-loaded grammars
+    pub fn with_grammars(mut self, grammars: BTreeMap<String, String>) -> Self {
+        self.grammars = grammars;
+        self
+    }
+
+    /// Set the output template for text modes
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Set highlight span export
+    pub const fn with_highlight_spans(mut self, highlight_spans: bool) -> Self {
+        self.highlight_spans = highlight_spans;
+        self
+    }
+
+    /// Whether `language` is parsed with a runtime-loaded grammar
+    pub fn has_grammar(&self, language: &str) -> bool {
+        self.grammars
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case(language))
+    }
+
     /// Get effective summary level (considering both new and deprecated fields)
     pub fn effective_summary_level(&self) -> SummaryLevel {
         // Priority: summary_level takes precedence over deprecated summary_mode
         if self.summary_level != SummaryLevel::None {
             self.summary_level
-        } else if self.summary_mode {
+        } else if self.summary_mode
+            || self.summary_docs
+            || self.public_only
+            || self
+                .template
+                .as_deref()
+                .is_some_and(|template| template.contains("{summary}"))
+        {
             SummaryLevel::Standard
         } else {
             SummaryLevel::None
@@ -297,8 +1132,28 @@ impl BatlessConfig {
     }
 
     /// Check if color output should be used based on configuration and environment
-    pub const fn should_use_color(&self, is_terminal: bool) -> bool {
-        self.use_color && is_terminal
+    ///
+    /// Follows the `NO_COLOR`, `CLICOLOR`, `CLICOLOR_FORCE`, `TERM=dumb` and
+    /// `CI` conventions (see [`TermEnv`]). Disabled color in config always wins.
+    pub fn should_use_color(&self, is_terminal: bool) -> bool {
+        self.use_color && TermEnv::from_env().color(is_terminal)
+    }
+
+    /// Color decision with the environment passed in explicitly
+    pub const fn resolve_color(
+        &self,
+        is_terminal: bool,
+        no_color: bool,
+        clicolor_force: bool,
+    ) -> bool {
+        let env = TermEnv {
+            no_color,
+            clicolor_off: false,
+            clicolor_force,
+            dumb_term: false,
+            ci: false,
+        };
+        self.use_color && env.color(is_terminal)
     }
 
     /// Get the effective maximum lines (considering both line and byte limits)
@@ -316,8 +1171,35 @@ impl BatlessConfig {
         self.max_bytes
     }
 
+    /// Refuse config keys that name files for batless to read (annotations,
+    /// SARIF reports, runtime grammars) in a config sent by a client, so a
+    /// request can never read the host's files
+    pub fn reject_file_keys(overrides: &serde_json::Value) -> BatlessResult<()> {
+        let Some(key) = FILE_READING_KEYS
+            .iter()
+            .find(|key| overrides.get(key).is_some())
+        else {
+            return Ok(());
+        };
+        Err(BatlessError::config_error_with_help(
+            format!("Config key '{key}' is not accepted from a request: it names a file to read"),
+            Some(format!(
+                "Set {} in the host's own configuration",
+                FILE_READING_KEYS.join(", ")
+            )),
+        ))
+    }
+
     /// Load configuration from a TOML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> BatlessResult<Self> {
+        Self::from_file_with_warnings(path).map(|(config, _)| config)
+    }
+
+    /// Load configuration from a TOML file, also returning warnings for
+    /// unknown or deprecated keys
+    pub fn from_file_with_warnings<P: AsRef<Path>>(
+        path: P,
+    ) -> BatlessResult<(Self, Vec<ConfigurationWarning>)> {
         let content = fs::read_to_string(path.as_ref()).map_err(|e| {
             BatlessError::config_error_with_help(
                 format!(
@@ -341,11 +1223,23 @@ impl BatlessConfig {
         })?;
 
         config.validate()?;
-        Ok(config)
+        let keys = toml::from_str::<toml::Table>(&content)
+            .map(|table| table.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let warnings = config_check::key_warnings(path.as_ref(), &keys, ConfigFileKind::Config);
+        Ok((config, warnings))
     }
 
     /// Load configuration from JSON file (.batlessrc format)
     pub fn from_json_file<P: AsRef<Path>>(path: P) -> BatlessResult<Self> {
+        Self::from_json_file_with_warnings(path).map(|(config, _)| config)
+    }
+
+    /// Load configuration from a JSON file, also returning warnings for
+    /// unknown or deprecated keys
+    pub fn from_json_file_with_warnings<P: AsRef<Path>>(
+        path: P,
+    ) -> BatlessResult<(Self, Vec<ConfigurationWarning>)> {
         let content = fs::read_to_string(path.as_ref()).map_err(|e| {
             BatlessError::config_error_with_help(
                 format!(
@@ -369,7 +1263,22 @@ impl BatlessConfig {
         })?;
 
         config.validate()?;
-        Ok(config)
+        let keys = serde_json::from_str::<serde_json::Value>(&content)
+            .map(|value| config_check::top_level_keys(&value))
+            .unwrap_or_default();
+        let warnings = config_check::key_warnings(path.as_ref(), &keys, ConfigFileKind::Config);
+        Ok((config, warnings))
+    }
+
+    /// Load a TOML or JSON config file (chosen by extension) with key warnings
+    pub fn load_file_with_warnings(
+        path: &Path,
+    ) -> BatlessResult<(Self, Vec<ConfigurationWarning>)> {
+        if path.extension() == Some(std::ffi::OsStr::new("toml")) {
+            Self::from_file_with_warnings(path)
+        } else {
+            Self::from_json_file_with_warnings(path)
+        }
     }
 
     /// Save configuration to a TOML file
@@ -403,37 +1312,73 @@ impl BatlessConfig {
         paths.push(PathBuf::from("batless.toml"));
 
         // 2. User home directory config files
-        if let Some(home_dir) = dirs::home_dir() {
+        if let Some(home_dir) = crate::sys::home_dir() {
             paths.push(home_dir.join(".batlessrc"));
             paths.push(home_dir.join(".config/batless/config.toml"));
             paths.push(home_dir.join(".config/batless.toml"));
         }
 
         // 3. System config directories (lowest precedence)
-        if let Some(config_dir) = dirs::config_dir() {
+        if let Some(config_dir) = crate::sys::config_dir() {
             paths.push(config_dir.join("batless/config.toml"));
         }
 
         paths
     }
 
+    /// Find the nearest `.batless/` project directory in `start` or its ancestors
+    pub fn find_project_dir(start: &Path) -> Option<PathBuf> {
+        let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+        start
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_DIR))
+            .find(|dir| dir.is_dir())
+    }
+
+    /// Config files for processing a file under `start`: the nearest
+    /// `.batless/config.toml` takes precedence over the standard locations
+    pub fn find_config_files_from(start: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Self::find_project_dir(start)
+            .map(|dir| dir.join("config.toml"))
+            .into_iter()
+            .collect();
+        paths.extend(Self::find_config_files());
+        paths
+    }
+
     /// Load configuration with precedence: CLI args > project config > user config > defaults
     pub fn load_with_precedence() -> BatlessResult<Self> {
+        Self::load_from_paths(Self::find_config_files()).map(|(config, _)| config)
+    }
+
+    /// Like [`Self::load_with_precedence`], also honouring the nearest
+    /// `.batless/config.toml` above `start`
+    pub fn load_with_precedence_from(start: &Path) -> BatlessResult<Self> {
+        Self::load_with_warnings_from(start).map(|(config, _)| config)
+    }
+
+    /// Like [`Self::load_with_precedence_from`], also returning warnings for
+    /// unknown or deprecated keys in any loaded file
+    pub fn load_with_warnings_from(
+        start: &Path,
+    ) -> BatlessResult<(Self, Vec<ConfigurationWarning>)> {
+        Self::load_from_paths(Self::find_config_files_from(start))
+    }
+
+    fn load_from_paths(paths: Vec<PathBuf>) -> BatlessResult<(Self, Vec<ConfigurationWarning>)> {
         let mut config = Self::default();
+        let mut warnings = Vec::new();
 
         // Try to load from config files in reverse precedence order
-        for config_path in Self::find_config_files().into_iter().rev() {
+        for config_path in paths.into_iter().rev() {
             if config_path.exists() {
-                let file_config = if config_path.extension() == Some(std::ffi::OsStr::new("toml")) {
-                    Self::from_file(&config_path)?
-                } else {
-                    Self::from_json_file(&config_path)?
-                };
+                let (file_config, file_warnings) = Self::load_file_with_warnings(&config_path)?;
                 config = config.merge_with(file_config);
+                warnings.extend(file_warnings);
             }
         }
 
-        Ok(config)
+        Ok((config, warnings))
     }
 
     /// Merge this configuration with another, taking non-default values from the other
@@ -450,6 +1395,12 @@ impl BatlessConfig {
         if other.language != default.language {
             self.language = other.language;
         }
+        if other.stdin_filename != default.stdin_filename {
+            self.stdin_filename = other.stdin_filename;
+        }
+        if other.stdin_language != default.stdin_language {
+            self.stdin_language = other.stdin_language;
+        }
         if other.strip_ansi != default.strip_ansi {
             self.strip_ansi = other.strip_ansi;
         }
@@ -459,12 +1410,36 @@ impl BatlessConfig {
         if other.include_tokens != default.include_tokens {
             self.include_tokens = other.include_tokens;
         }
+        if other.unique_tokens != default.unique_tokens {
+            self.unique_tokens = other.unique_tokens;
+        }
+        if other.max_token_sample != default.max_token_sample {
+            self.max_token_sample = other.max_token_sample;
+        }
+        if other.token_order != default.token_order {
+            self.token_order = other.token_order;
+        }
+        if other.token_frequencies.is_some() {
+            self.token_frequencies = other.token_frequencies;
+        }
         if other.summary_mode != default.summary_mode {
             self.summary_mode = other.summary_mode;
         }
         if other.summary_level != default.summary_level {
             self.summary_level = other.summary_level;
         }
+        if other.summary_docs != default.summary_docs {
+            self.summary_docs = other.summary_docs;
+        }
+        if other.check_syntax != default.check_syntax {
+            self.check_syntax = other.check_syntax;
+        }
+        if other.no_tests != default.no_tests {
+            self.no_tests = other.no_tests;
+        }
+        if other.public_only != default.public_only {
+            self.public_only = other.public_only;
+        }
         if other.streaming_json != default.streaming_json {
             self.streaming_json = other.streaming_json;
         }
@@ -474,6 +1449,27 @@ impl BatlessConfig {
         if other.enable_resume != default.enable_resume {
             self.enable_resume = other.enable_resume;
         }
+        if other.restart_on_change != default.restart_on_change {
+            self.restart_on_change = other.restart_on_change;
+        }
+        if other.stream_delay_ms != default.stream_delay_ms {
+            self.stream_delay_ms = other.stream_delay_ms;
+        }
+        if other.max_chunks_per_second != default.max_chunks_per_second {
+            self.max_chunks_per_second = other.max_chunks_per_second;
+        }
+        if other.max_chunks != default.max_chunks {
+            self.max_chunks = other.max_chunks;
+        }
+        if other.stream_token_budget != default.stream_token_budget {
+            self.stream_token_budget = other.stream_token_budget;
+        }
+        if other.auto_stream != default.auto_stream {
+            self.auto_stream = other.auto_stream;
+        }
+        if other.auto_stream_threshold != default.auto_stream_threshold {
+            self.auto_stream_threshold = other.auto_stream_threshold;
+        }
         if other.schema_version != default.schema_version {
             self.schema_version = other.schema_version;
         }
@@ -489,6 +1485,18 @@ impl BatlessConfig {
         if other.pretty_json != default.pretty_json {
             self.pretty_json = other.pretty_json;
         }
+        if other.canonical_json != default.canonical_json {
+            self.canonical_json = other.canonical_json;
+        }
+        if other.deterministic != default.deterministic {
+            self = self.with_deterministic(other.deterministic);
+        }
+        if other.sample_count != default.sample_count {
+            self.sample_count = other.sample_count;
+        }
+        if other.sample_seed != default.sample_seed {
+            self.sample_seed = other.sample_seed;
+        }
         if other.json_line_numbers != default.json_line_numbers {
             self.json_line_numbers = other.json_line_numbers;
         }
@@ -501,9 +1509,91 @@ impl BatlessConfig {
         if other.strip_blank_lines != default.strip_blank_lines {
             self.strip_blank_lines = other.strip_blank_lines;
         }
+        if other.signatures_only != default.signatures_only {
+            self.signatures_only = other.signatures_only;
+        }
+        if other.normalize_line_endings != default.normalize_line_endings {
+            self.normalize_line_endings = other.normalize_line_endings;
+        }
         if other.chunk_strategy != default.chunk_strategy {
             self.chunk_strategy = other.chunk_strategy;
         }
+        if other.link_format != default.link_format {
+            self.link_format = other.link_format;
+        }
+        if other.repo_url != default.repo_url {
+            self.repo_url = other.repo_url;
+        }
+        if other.timeout_ms != default.timeout_ms {
+            self.timeout_ms = other.timeout_ms;
+        }
+        if other.stdin_max_bytes != default.stdin_max_bytes {
+            self.stdin_max_bytes = other.stdin_max_bytes;
+        }
+        if other.stdin_timeout_ms != default.stdin_timeout_ms {
+            self.stdin_timeout_ms = other.stdin_timeout_ms;
+        }
+        if other.around != default.around {
+            self.around = other.around;
+        }
+        if other.symbol != default.symbol {
+            self.symbol = other.symbol;
+        }
+        if other.uses != default.uses {
+            self.uses = other.uses;
+        }
+        if other.highlight_line != default.highlight_line {
+            self.highlight_line = other.highlight_line;
+        }
+        if other.highlight_lines != default.highlight_lines {
+            self.highlight_lines = other.highlight_lines;
+        }
+        if other.highlight_style != default.highlight_style {
+            self.highlight_style = other.highlight_style;
+        }
+        if other.annotations != default.annotations {
+            self.annotations = other.annotations;
+        }
+        if other.sarif != default.sarif {
+            self.sarif = other.sarif;
+        }
+        if other.preview_window != default.preview_window {
+            self.preview_window = other.preview_window;
+        }
+        if other.fold_depth != default.fold_depth {
+            self.fold_depth = other.fold_depth;
+        }
+        if other.min_log_level != default.min_log_level {
+            self.min_log_level = other.min_log_level;
+        }
+        if other.max_memory != default.max_memory {
+            self.max_memory = other.max_memory;
+        }
+        if other.highlight_spans != default.highlight_spans {
+            self.highlight_spans = other.highlight_spans;
+        }
+        if other.redact != default.redact {
+            self.redact = other.redact;
+        }
+        if other.anonymize != default.anonymize {
+            self.anonymize = other.anonymize;
+        }
+        self.redact_patterns.extend(other.redact_patterns);
+        if other.file_header != default.file_header {
+            self.file_header = other.file_header;
+        }
+        if other.file_footer != default.file_footer {
+            self.file_footer = other.file_footer;
+        }
+        if other.template != default.template {
+            self.template = other.template;
+        }
+        self.models.extend(other.models);
+        self.language_map.extend(other.language_map);
+        if other.grammar_dir != default.grammar_dir {
+            self.grammar_dir = other.grammar_dir;
+        }
+        self.grammars.extend(other.grammars);
 
         self
     }
@@ -558,14 +1648,28 @@ mod tests {
     #[test]
     fn test_should_use_color() {
         let config = BatlessConfig::default();
-        assert!(config.should_use_color(true));
-        assert!(!config.should_use_color(false));
+        // CI runners set CI=true, which turns color off
+        let env = TermEnv::from_env();
+        assert_eq!(config.should_use_color(true), env.color(true));
+        assert_eq!(config.should_use_color(false), env.color(false));
 
         let config_no_color = config.with_use_color(false);
         assert!(!config_no_color.should_use_color(true));
         assert!(!config_no_color.should_use_color(false));
     }
 
+    #[test]
+    fn test_resolve_color_env_conventions() {
+        let config = BatlessConfig::default();
+        assert!(!config.resolve_color(true, true, false));
+        assert!(config.resolve_color(false, false, true));
+        // NO_COLOR beats CLICOLOR_FORCE, and disabled config beats both
+        assert!(!config.resolve_color(false, true, true));
+        assert!(!config
+            .with_use_color(false)
+            .resolve_color(true, false, true));
+    }
+
     #[test]
     fn test_byte_limit_helpers() {
         let config = BatlessConfig::default();
@@ -631,6 +1735,118 @@ mod tests {
         assert!(merged.use_color);
     }
 
+    #[test]
+    fn test_parse_memory_size() {
+        assert_eq!(parse_memory_size("1048576"), Ok(1_048_576));
+        assert_eq!(parse_memory_size("256MB"), Ok(256_000_000));
+        assert_eq!(parse_memory_size("256MiB"), Ok(256 * 1024 * 1024));
+        assert_eq!(parse_memory_size("512k"), Ok(512 * 1024));
+        assert_eq!(parse_memory_size("1.5 GB"), Ok(1_500_000_000));
+        assert!(parse_memory_size("lots").is_err());
+        assert!(parse_memory_size("10TB").is_err());
+    }
+
+    #[test]
+    fn test_max_memory_from_toml() {
+        let config: BatlessConfig = toml::from_str("max_memory = \"64MB\"").unwrap();
+        assert_eq!(config.max_memory, Some(64_000_000));
+        let config: BatlessConfig = toml::from_str("max_memory = 4096").unwrap();
+        assert_eq!(config.max_memory, Some(4096));
+        assert!(toml::from_str::<BatlessConfig>("max_memory = \"big\"").is_err());
+    }
+
+    #[test]
+    fn test_display_path() {
+        let config = BatlessConfig::default();
+        assert_eq!(config.display_path("-"), "-");
+        let config = config.with_stdin_filename(Some("src/main.rs".to_string()));
+        assert_eq!(config.display_path("-"), "src/main.rs");
+        assert_eq!(config.display_path("lib.rs"), "lib.rs");
+    }
+
+    #[test]
+    fn test_stream_interval() {
+        use std::time::Duration;
+        let config = BatlessConfig::default();
+        assert_eq!(config.stream_interval(), None);
+        let config = config.with_max_chunks_per_second(Some(4));
+        assert_eq!(config.stream_interval(), Some(Duration::from_millis(250)));
+        let config = config.with_stream_delay_ms(Some(100));
+        assert_eq!(config.stream_interval(), Some(Duration::from_millis(250)));
+        let config = config.with_stream_delay_ms(Some(400));
+        assert_eq!(config.stream_interval(), Some(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn test_parse_duration_ms() {
+        assert_eq!(parse_duration_ms("500ms"), Ok(500));
+        assert_eq!(parse_duration_ms("2s"), Ok(2000));
+        assert_eq!(parse_duration_ms("1.5m"), Ok(90_000));
+        assert_eq!(parse_duration_ms("3"), Ok(3000));
+        assert!(parse_duration_ms("soon").is_err());
+        assert!(parse_duration_ms("2h").is_err());
+        let config: BatlessConfig = toml::from_str("timeout_ms = \"250ms\"").unwrap();
+        assert_eq!(config.timeout_ms, Some(250));
+    }
+
+    #[test]
+    fn test_models_from_toml() {
+        let toml_str = r"
+[models.my-llm]
+context_window = 32000
+tokens_per_word = 1.4
+
+[models.tiny]
+context_window = 4096
+";
+        let config: BatlessConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.models["my-llm"].context_window, 32_000);
+        assert!((config.models["tiny"].tokens_per_word - 1.3).abs() < f64::EPSILON);
+
+        let merged = BatlessConfig::default().merge_with(config);
+        assert_eq!(merged.models.len(), 2);
+    }
+
+    #[test]
+    fn test_language_map_from_toml() {
+        let toml_str = r#"
+[language_map]
+"*.star" = "Python"
+"Justfile" = "Makefile"
+"#;
+        let config: BatlessConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.language_map["*.star"], "Python");
+        assert!(config.validate().is_ok());
+
+        let merged = BatlessConfig::default().merge_with(config);
+        assert_eq!(merged.language_map.len(), 2);
+        let typo = BatlessConfig::default()
+            .with_language_map([("*.star".to_string(), "Pyhton".to_string())].into());
+        assert!(typo.validate().unwrap_err().to_string().contains("Python"));
+    }
+
+    #[test]
+    fn test_grammars_from_toml() {
+        let toml_str = r#"
+grammar_dir = "/opt/grammars"
+[grammars]
+Gleam = "gleam"
+[language_map]
+"*.gleam" = "Gleam"
+"#;
+        let config: BatlessConfig = toml::from_str(toml_str).unwrap();
+        assert!(config.has_grammar("gleam"));
+        // Languages with a runtime grammar are valid language_map targets
+        assert!(config.validate().is_ok());
+
+        let merged = BatlessConfig::default().merge_with(config);
+        assert_eq!(merged.grammar_dir, Some(PathBuf::from("/opt/grammars")));
+        assert_eq!(merged.grammars["Gleam"], "gleam");
+        let bad = BatlessConfig::default()
+            .with_grammars([("Gleam".to_string(), "../gleam".to_string())].into());
+        assert!(bad.validate().is_err());
+    }
+
     #[test]
     fn test_config_file_discovery() {
         let paths = BatlessConfig::find_config_files();
@@ -643,6 +1859,41 @@ mod tests {
             .any(|p| p.file_name() == Some(std::ffi::OsStr::new("batless.toml"))));
     }
 
+    #[test]
+    fn test_project_dir_discovery_from_nested_file() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("crates/app/src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir(root.path().join(".batless")).unwrap();
+        fs::write(root.path().join(".batless/config.toml"), "max_lines = 42\n").unwrap();
+
+        let project = BatlessConfig::find_project_dir(&nested).unwrap();
+        assert!(project.ends_with(".batless"));
+        let paths = BatlessConfig::find_config_files_from(&nested);
+        assert_eq!(paths[0], project.join("config.toml"));
+        assert_eq!(
+            BatlessConfig::load_with_precedence_from(&nested)
+                .unwrap()
+                .max_lines,
+            42
+        );
+    }
+
+    #[test]
+    fn test_unknown_key_warnings_do_not_abort() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batless.toml");
+        fs::write(&path, "max_line = 100\nmax_bytes = 2048\n").unwrap();
+
+        let (config, warnings) = BatlessConfig::from_file_with_warnings(&path).unwrap();
+        assert_eq!(config.max_bytes, Some(2048));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].key(), "max_line");
+        assert!(warnings[0]
+            .to_string()
+            .contains("did you mean 'max_lines'?"));
+    }
+
     #[test]
     fn test_load_from_toml_file() {
         use std::io::Write;
@@ -869,4 +2120,24 @@ max_lines = "not_a_number"
         // Should return a Vec (even if empty, which is fine for testing)
         assert!(profiles.is_empty() || !profiles.is_empty());
     }
+
+    #[test]
+    fn test_line_ranges_and_emphasis() {
+        assert_eq!(LineRange::parse("3"), Ok(LineRange { start: 3, end: 3 }));
+        assert_eq!(
+            LineRange::parse("10:15"),
+            Ok(LineRange { start: 10, end: 15 })
+        );
+        assert!(LineRange::parse("0").is_err());
+        assert!(LineRange::parse("15:10").is_err());
+        assert!(LineRange::parse("a:b").is_err());
+
+        let config = BatlessConfig::default()
+            .with_highlight_line(Some(1))
+            .with_highlight_lines(vec![LineRange { start: 10, end: 15 }]);
+        assert!(config.has_emphasis());
+        assert!(config.is_emphasized(1) && config.is_emphasized(12));
+        assert!(!config.is_emphasized(16));
+        assert!(!BatlessConfig::default().has_emphasis());
+    }
 }
@@ -0,0 +1,352 @@
+//! Config file linting for `--check-config`
+//!
+//! Loads config files and custom profiles, reporting parse and validation
+//! errors plus keys that serde would otherwise silently ignore (unknown or
+//! deprecated fields), so broken settings are caught before they are relied on.
+
+use crate::config::{BatlessConfig, PROJECT_CONFIG_DIR};
+use crate::error::{BatlessError, ConfigurationWarning};
+use crate::profile::CustomProfile;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Deprecated config keys and their replacements
+const DEPRECATED_KEYS: &[(&str, &str)] = &[("summary_mode", "summary_level")];
+
+/// Whether a file holds base configuration or a custom profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFileKind {
+    Config,
+    Profile,
+}
+
+impl ConfigFileKind {
+    /// Infer the kind from the location: files under a `profiles/` directory
+    /// or named profiles inside `.batless/` are profiles.
+    pub fn from_path(path: &Path) -> Self {
+        let parent = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str());
+        let is_project_config = path.file_name().and_then(|n| n.to_str()) == Some("config.toml");
+        match parent {
+            Some("profiles") => Self::Profile,
+            Some(PROJECT_CONFIG_DIR) if !is_project_config => Self::Profile,
+            _ => Self::Config,
+        }
+    }
+}
+
+/// Severity of a reported issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub message: String,
+}
+
+/// Check result for one file
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckedFile {
+    pub path: String,
+    pub kind: ConfigFileKind,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Result of checking a set of files
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigCheckReport {
+    pub files: Vec<CheckedFile>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl ConfigCheckReport {
+    /// True when no file has errors (warnings are allowed)
+    pub const fn is_ok(&self) -> bool {
+        self.errors == 0
+    }
+
+    /// Render a human-readable report
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        if self.files.is_empty() {
+            out.push_str("No config files or profiles found\n");
+        }
+        for file in &self.files {
+            let status = if file.issues.is_empty() { ": ok" } else { "" };
+            let kind = match file.kind {
+                ConfigFileKind::Config => "config",
+                ConfigFileKind::Profile => "profile",
+            };
+            let _ = writeln!(out, "{} ({kind}){status}", file.path);
+            for issue in &file.issues {
+                let severity = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                let _ = writeln!(out, "  {severity}: {}", issue.message);
+            }
+        }
+        let _ = writeln!(
+            out,
+            "{} file(s) checked, {} error(s), {} warning(s)",
+            self.files.len(),
+            self.errors,
+            self.warnings
+        );
+        out
+    }
+}
+
+/// All existing config files and profiles visible from `start`
+pub fn discover_files(start: &Path) -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = BatlessConfig::find_config_files_from(start);
+    if let Some(project_dir) = BatlessConfig::find_project_dir(start) {
+        for dir in [project_dir.clone(), project_dir.join("profiles")] {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut profiles: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path.file_name().and_then(|n| n.to_str()) != Some("config.toml")
+                        && matches!(
+                            path.extension().and_then(|e| e.to_str()),
+                            Some("json" | "toml")
+                        )
+                })
+                .collect();
+            profiles.sort();
+            candidates.extend(profiles);
+        }
+    }
+    candidates.extend(CustomProfile::discover_profiles());
+
+    let mut seen = BTreeSet::new();
+    candidates
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter(|path| seen.insert(fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .collect()
+}
+
+/// Check each file in `paths`
+pub fn check_files(paths: &[PathBuf]) -> ConfigCheckReport {
+    let files: Vec<CheckedFile> = paths.iter().map(|path| check_file(path)).collect();
+    let count = |severity| {
+        files
+            .iter()
+            .flat_map(|f| &f.issues)
+            .filter(|i| i.severity == severity)
+            .count()
+    };
+    ConfigCheckReport {
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        files,
+    }
+}
+
+/// Check a single config file or profile
+pub fn check_file(path: &Path) -> CheckedFile {
+    let kind = ConfigFileKind::from_path(path);
+    let mut issues = Vec::new();
+
+    match read_keys(path) {
+        Ok(keys) => {
+            issues.extend(
+                key_warnings(path, &keys, kind)
+                    .into_iter()
+                    .map(|warning| ConfigIssue {
+                        severity: Severity::Warning,
+                        key: Some(warning.key().to_string()),
+                        message: warning.message(),
+                    }),
+            );
+            // Full load surfaces type errors and conflicting combinations
+            if let Err(e) = load(path, kind) {
+                issues.push(ConfigIssue {
+                    severity: Severity::Error,
+                    key: None,
+                    message: e,
+                });
+            }
+        }
+        Err(message) => issues.push(ConfigIssue {
+            severity: Severity::Error,
+            key: None,
+            message,
+        }),
+    }
+
+    CheckedFile {
+        path: path.display().to_string(),
+        kind,
+        issues,
+    }
+}
+
+/// Warnings for unknown and deprecated top-level `keys` of the file at `path`
+pub fn key_warnings(
+    path: &Path,
+    keys: &[String],
+    kind: ConfigFileKind,
+) -> Vec<ConfigurationWarning> {
+    let known: Vec<String> = known_keys(kind).into_iter().collect();
+    let path = path.display().to_string();
+    keys.iter()
+        .filter_map(|key| {
+            if let Some((_, replacement)) = DEPRECATED_KEYS.iter().find(|(old, _)| old == key) {
+                Some(ConfigurationWarning::DeprecatedKey {
+                    path: path.clone(),
+                    key: key.clone(),
+                    replacement,
+                })
+            } else if !known.contains(key) {
+                Some(ConfigurationWarning::UnknownKey {
+                    path: path.clone(),
+                    key: key.clone(),
+                    suggestion: BatlessError::suggest_similar_strings(key, &known)
+                        .into_iter()
+                        .next(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Top-level keys of a parsed TOML or JSON document
+pub fn top_level_keys(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_object()
+        .map(|o| o.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Top-level keys accepted by the config or profile schema
+pub fn known_keys(kind: ConfigFileKind) -> BTreeSet<String> {
+    let value = match kind {
+        ConfigFileKind::Config => serde_json::to_value(BatlessConfig::default()),
+        ConfigFileKind::Profile => serde_json::to_value(CustomProfile::new(String::new(), None)),
+    };
+    value
+        .ok()
+        .and_then(|v| v.as_object().map(|o| o.keys().cloned().collect()))
+        .unwrap_or_default()
+}
+
+/// Parse `path` as TOML or JSON and return its top-level keys
+fn read_keys(path: &Path) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read file: {e}"))?;
+    let value: serde_json::Value = if is_toml(path) {
+        let table: toml::Table =
+            toml::from_str(&content).map_err(|e| format!("invalid TOML: {e}"))?;
+        serde_json::to_value(table).map_err(|e| format!("invalid TOML: {e}"))?
+    } else {
+        serde_json::from_str(&content).map_err(|e| format!("invalid JSON: {e}"))?
+    };
+    if !value.is_object() {
+        return Err("top level must be a table/object".to_string());
+    }
+    Ok(top_level_keys(&value))
+}
+
+fn load(path: &Path, kind: ConfigFileKind) -> Result<(), String> {
+    let result = match kind {
+        ConfigFileKind::Profile => CustomProfile::load_from_file(path).map(|_| ()),
+        ConfigFileKind::Config if is_toml(path) => BatlessConfig::from_file(path).map(|_| ()),
+        ConfigFileKind::Config => BatlessConfig::from_json_file(path).map(|_| ()),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_check_reports_unknown_deprecated_and_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = write(dir.path(), "batless.toml", "max_lines = 50\n");
+        let warn = write(
+            dir.path(),
+            "warn.toml",
+            "summary_mode = true\nmax_line = 5\n",
+        );
+        let conflict = write(dir.path(), "bad.toml", "max_lines = 0\n");
+        let broken = write(dir.path(), "broken.json", "{ not json");
+
+        let report = check_files(&[good, warn, conflict, broken]);
+        assert!(report.files[0].issues.is_empty());
+        let keys: Vec<_> = report.files[1]
+            .issues
+            .iter()
+            .filter_map(|i| i.key.as_deref())
+            .collect();
+        assert_eq!(keys, ["max_line", "summary_mode"]);
+        assert_eq!(report.files[2].issues[0].severity, Severity::Error);
+        assert!(report.files[3].issues[0].message.contains("invalid JSON"));
+        assert_eq!((report.errors, report.warnings), (2, 2));
+        assert!(!report.is_ok());
+        assert!(report
+            .render_text()
+            .contains("unknown key 'max_line' is ignored (did you mean 'max_lines'?)"));
+    }
+
+    #[test]
+    fn test_profiles_checked_against_profile_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let profile = write(
+            dir.path(),
+            ".batless/profiles/ci.json",
+            r#"{"name": "ci", "max_lines": 10, "colour": true}"#,
+        );
+        assert_eq!(ConfigFileKind::from_path(&profile), ConfigFileKind::Profile);
+
+        let checked = check_file(&profile);
+        assert_eq!(checked.issues.len(), 1);
+        assert_eq!(checked.issues[0].key.as_deref(), Some("colour"));
+    }
+
+    #[test]
+    fn test_discover_project_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), ".batless/config.toml", "max_lines = 5\n");
+        write(dir.path(), ".batless/review.json", r#"{"name": "review"}"#);
+        let nested = dir.path().join("src");
+        fs::create_dir(&nested).unwrap();
+
+        let files = discover_files(&nested);
+        assert!(files.iter().any(|p| p.ends_with(".batless/config.toml")));
+        assert!(files.iter().any(|p| p.ends_with(".batless/review.json")));
+    }
+}
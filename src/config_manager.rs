@@ -1,26 +1,44 @@
 //! Manages application configuration by merging settings from files,
 //! command-line arguments, and profiles.
 
+use crate::around::AroundSpec;
 use crate::config::BatlessConfig;
-use crate::error::{BatlessError, BatlessResult};
+use crate::env_policy::StatusStyle;
+use crate::error::{BatlessError, BatlessResult, ConfigurationWarning};
 use crate::formatter::OutputMode;
+use crate::language::LanguageMatch;
+use crate::output_policy::{ErrorFormat, OutputPolicy, Verbosity};
 use crate::profile::CustomProfile;
 use crate::summary::SummaryLevel;
 use crate::tokens::AiModel;
+use crate::workspace::Workspace;
 use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
-use is_terminal::IsTerminal;
+use std::borrow::Cow;
+use std::io::IsTerminal;
 use std::str::FromStr;
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     /// File to view
     pub file: Option<String>,
 
-    /// Language for syntax highlighting (auto-detect if not specified)
+    /// Additional files; each is preceded by a header banner in text output
+    #[arg(value_name = "FILE")]
+    pub more_files: Vec<String>,
+
+    /// Language for syntax highlighting (auto-detect if not specified); aliases like js or golang work
     #[arg(long)]
     pub language: Option<String>,
 
+    /// Logical filename for stdin input (drives language detection and the reported path)
+    #[arg(long, value_name = "PATH")]
+    pub stdin_filename: Option<String>,
+
+    /// Language of stdin input
+    #[arg(long, value_name = "LANG")]
+    pub stdin_language: Option<String>,
+
     /// Limit lines shown
     #[arg(long)]
     pub max_lines: Option<usize>,
@@ -49,6 +67,26 @@ pub struct Args {
     #[arg(long)]
     pub list_languages: bool,
 
+    /// Print supported modes, schemas, models, AST languages and features as JSON
+    #[arg(long)]
+    pub capabilities: bool,
+
+    /// Lint config files and profiles (all discoverable ones, or PATH) and exit
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub check_config: Option<Option<String>>,
+
+    /// Validate options, configuration and input files, then exit without output
+    #[arg(long, conflicts_with = "check_config")]
+    pub check: bool,
+
+    /// Write the golden JSON corpus (built-in fixtures and configurations) to DIR and exit
+    #[arg(long, value_name = "DIR")]
+    pub emit_goldens: Option<String>,
+
+    /// Compare the golden corpus in DIR with this build's output; exit 15 on breaking changes
+    #[arg(long, value_name = "DIR", conflicts_with = "emit_goldens")]
+    pub verify_goldens: Option<String>,
+
     /// Include extracted code identifiers in JSON output (preferred flag)
     #[arg(long)]
     pub include_identifiers: bool,
@@ -57,6 +95,22 @@ pub struct Args {
     #[arg(long, hide = true)]
     pub include_tokens: bool,
 
+    /// List each identifier once, at its first occurrence; the total still counts repeats
+    #[arg(long)]
+    pub unique_identifiers: bool,
+
+    /// List at most N identifiers in JSON output (default 2048); identifier_count still counts all
+    #[arg(long, value_name = "N")]
+    pub max_token_sample: Option<usize>,
+
+    /// Order of listed identifiers: as in the file, most frequent first, or alphabetical
+    #[arg(long, value_name = "ORDER")]
+    pub token_order: Option<CliTokenOrder>,
+
+    /// Add the N most frequent identifiers (default 20) with their counts as token_frequencies in JSON
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "20")]
+    pub token_frequencies: Option<usize>,
+
     /// Summary mode: show only important code structures (deprecated, use --summary-level)
     #[arg(long)]
     pub summary: bool,
@@ -65,13 +119,71 @@ pub struct Args {
     #[arg(long, value_enum)]
     pub summary_level: Option<CliSummaryLevel>,
 
+    /// Include the first sentence of each item's doc comment or docstring in summaries
+    #[arg(long)]
+    pub summary_docs: bool,
+
+    /// Leave test code (`#[cfg(test)]`, `def test_*`, `describe`/`it` blocks, test files) out of summaries and --from-trace packs
+    #[arg(long)]
+    pub no_tests: bool,
+
+    /// Summarize only the public API: Rust `pub` items, JS/TS exports, Python `__all__` or non-underscore names
+    #[arg(long)]
+    pub public_only: bool,
+
     /// Count tokens for AI model context estimation
     #[arg(long)]
     pub count_tokens: bool,
 
-    /// AI model for token counting
-    #[arg(long, value_enum, default_value = "generic")]
-    pub ai_model: CliAiModel,
+    /// Print token statistics (top identifiers, length histogram, per-strategy totals); JSON with --mode=json
+    #[arg(long)]
+    pub token_stats: bool,
+
+    /// Also copy the formatted output to the system clipboard (`clipboard` feature)
+    #[arg(long)]
+    pub copy: bool,
+
+    /// Copy the formatted output to the clipboard instead of writing it to stdout
+    #[arg(long, conflicts_with = "copy")]
+    pub copy_only: bool,
+
+    /// Number of identifiers listed by --token-stats
+    #[arg(long, value_name = "N", default_value_t = crate::tokens::TokenExtractor::DEFAULT_TOP_TOKENS)]
+    pub top_tokens: usize,
+
+    /// Print the exact subword tokens and ids of the --ai-model as JSON lines, one per chunk (needs the exact-tokenizer feature)
+    #[arg(long, value_name = "MODE")]
+    pub tokenize: Option<CliTokenize>,
+
+    /// Print overlapping retrieval chunks cut at declaration boundaries as JSON lines, with line and byte ranges and the symbols each holds
+    #[arg(long, conflicts_with = "tokenize")]
+    pub chunk_for_rag: bool,
+
+    /// Print each file, or each --chunk-for-rag chunk, as a JSON document with repository, path, commit, language, symbol and hash metadata
+    #[arg(long, value_name = "FORMAT", conflicts_with = "tokenize")]
+    pub envelope: Option<CliEnvelope>,
+
+    /// Tokens per --tokenize or --chunk-for-rag chunk
+    #[arg(long, value_name = "N", default_value_t = crate::tokens::subword::DEFAULT_CHUNK_TOKENS)]
+    pub chunk_tokens: usize,
+
+    /// Tokens of whole lines each --chunk-for-rag chunk repeats from the previous one
+    #[arg(long, value_name = "M", default_value_t = crate::chunker::DEFAULT_CHUNK_OVERLAP)]
+    pub chunk_overlap: usize,
+
+    /// Samples used to estimate tokens of files over 100KB (default 5)
+    #[arg(long, value_name = "N")]
+    pub sample_count: Option<usize>,
+
+    /// Seed for placing token estimation samples; the same seed gives the same estimate
+    #[arg(long, value_name = "SEED")]
+    pub sample_seed: Option<u64>,
+
+    /// AI model for token counting: gpt4, gpt4-turbo, gpt35, gpt4o, o3, o4-mini, claude,
+    /// claude-sonnet, claude37-sonnet, gemini, gemini-flash, gemini25-pro, llama3, llama31,
+    /// generic, or a name from the `[models]` config table
+    #[arg(long, value_name = "MODEL", default_value = "generic")]
+    pub ai_model: String,
 
     /// Fit content within AI model context window (truncate if needed)
     #[arg(long)]
@@ -85,6 +197,43 @@ pub struct Args {
     #[arg(long)]
     pub validate_json: bool,
 
+    /// Exit with code 10 when output was truncated by a line, byte or context limit
+    #[arg(long)]
+    pub fail_on_truncation: bool,
+
+    /// Exit with code 11 without printing when the input looks binary
+    #[arg(long)]
+    pub fail_on_binary: bool,
+
+    /// Plain ASCII status messages on stderr: no emoji or other glyphs
+    #[arg(long)]
+    pub no_emoji: bool,
+
+    /// Print only the formatted content: no truncation notices, context
+    /// fitting banners or token analysis (warnings and errors still show)
+    #[arg(short = 'q', long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Write warnings and errors on stderr as text or as one JSON object per line
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = CliErrorFormat::Human)]
+    pub error_format: CliErrorFormat,
+
+    /// More diagnostics on stderr; repeat (-vv) for timings, like --debug
+    #[arg(short = 'v', long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Exit with code 12 when JSON output fails schema validation (implies --validate-json)
+    #[arg(long)]
+    pub fail_on_validation_error: bool,
+
+    /// Fail with an error, printing nothing, when JSON output does not match its schema (implies --validate-json)
+    #[arg(long)]
+    pub strict_validation: bool,
+
+    /// Exit with code 14 when tree-sitter finds syntax errors in the input
+    #[arg(long)]
+    pub fail_on_syntax_errors: bool,
+
     /// Get JSON schema for specified output format
     #[arg(long)]
     pub get_schema: Option<String>,
@@ -93,6 +242,14 @@ pub struct Args {
     #[arg(long, value_enum)]
     pub generate_completions: Option<Shell>,
 
+    /// Print a roff man page generated from these options
+    #[arg(long)]
+    pub generate_man: bool,
+
+    /// Print a Markdown reference of these options for docs sites
+    #[arg(long)]
+    pub help_markdown: bool,
+
     /// Use predefined AI tool profile (overrides other settings)
     #[arg(long, value_enum)]
     pub profile: Option<AiProfile>,
@@ -113,6 +270,30 @@ pub struct Args {
     #[arg(long)]
     pub streaming_chunk_size: Option<usize>,
 
+    /// Delay in milliseconds between streaming chunks
+    #[arg(long, value_name = "MS")]
+    pub stream_delay_ms: Option<u64>,
+
+    /// Limit streaming output to N chunks per second
+    #[arg(long, value_name = "N")]
+    pub max_chunks_per_second: Option<u32>,
+
+    /// Stop streaming after N chunks (preview the head of large files)
+    #[arg(long, value_name = "N")]
+    pub max_chunks: Option<usize>,
+
+    /// Stop streaming before the estimated tokens for --ai-model exceed N
+    #[arg(long, value_name = "N")]
+    pub stream_until_tokens: Option<usize>,
+
+    /// Always read files whole, even above the auto-stream threshold
+    #[arg(long)]
+    pub no_auto_stream: bool,
+
+    /// Stream plain output of files larger than SIZE (default 64MiB)
+    #[arg(long, value_name = "SIZE", value_parser = crate::config::parse_memory_size)]
+    pub auto_stream_threshold: Option<usize>,
+
     /// Streaming chunk strategy: line (fixed line count) or semantic (top-level declaration boundaries)
     #[arg(long, value_name = "STRATEGY")]
     pub chunk_strategy: Option<CliChunkStrategy>,
@@ -125,6 +306,11 @@ pub struct Args {
     #[arg(long)]
     pub checkpoint: Option<String>,
 
+    /// Start a --streaming-json stream over when the file changes mid-stream
+    /// (or no longer matches the checkpoint) instead of failing with E105
+    #[arg(long)]
+    pub restart_on_change: bool,
+
     /// Enable debug mode with detailed processing information
     #[arg(long)]
     pub debug: bool,
@@ -153,10 +339,148 @@ pub struct Args {
     #[arg(long)]
     pub version_json: bool,
 
+    /// Time each pipeline stage against the input file and print a report (JSON with --mode=json)
+    #[arg(long)]
+    pub bench: bool,
+
+    /// Serve a local HTTP API on ADDR (e.g. 127.0.0.1:7878); requires the `serve` feature
+    #[arg(long, value_name = "ADDR")]
+    pub serve: Option<String>,
+
+    /// Print surrounding code for every frame of a stack trace (Rust, Python, Node, Java)
+    #[arg(long, value_name = "FILE")]
+    pub from_trace: Option<String>,
+
+    /// Merge the symbols of all inputs (files or directories) into one index keyed by name (JSON with --mode=json)
+    #[arg(long)]
+    pub summary_index: bool,
+
+    /// Write one artifact per input to DIR, indexed by DIR/manifest.json; inputs may be
+    /// directories or quoted globs ('src/**/*.rs'), --jobs renders in parallel, and later
+    /// runs skip files whose content is unchanged
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["summary_index", "json_envelope", "streaming_json"]
+    )]
+    pub export: Option<String>,
+
+    /// Artifact format for --export (default: json)
+    #[arg(long, value_name = "FORMAT", requires = "export")]
+    pub export_format: Option<CliExportFormat>,
+
+    /// Context lines on each side of a frame for --from-trace
+    #[arg(long, value_name = "N", default_value_t = crate::around::DEFAULT_AROUND_CONTEXT)]
+    pub trace_context: usize,
+
+    /// Print wall-clock time per stage of each file to stderr
+    #[arg(long)]
+    pub time: bool,
+
+    /// Format of the --time report
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value = "kv",
+        requires = "time"
+    )]
+    pub time_format: CliTimeFormat,
+
+    /// Export per-file stage spans over OTLP/HTTP to OTEL_EXPORTER_OTLP_ENDPOINT (needs the otel feature)
+    #[arg(long)]
+    pub otel: bool,
+
+    /// Write files processed, bytes and lines read, truncations, durations and errors of this run to PATH as JSON
+    #[arg(long, value_name = "PATH")]
+    pub metrics_out: Option<String>,
+
+    /// Run at lower CPU priority: niceness N from -20 to 19 (default 10 when no N is given)
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "10",
+        allow_negative_numbers = true,
+        value_parser = clap::value_parser!(i32).range(-20..=19)
+    )]
+    pub nice: Option<i32>,
+
+    /// Only use the disk when nothing else needs it (idle I/O class on Linux, throttled on macOS)
+    #[arg(long)]
+    pub io_low_priority: bool,
+
+    /// Process several input files on N threads (0: one per CPU); output keeps input order
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub jobs: usize,
+
+    /// With --jobs, write each file as soon as it is done instead of in input order
+    #[arg(long)]
+    pub unordered: bool,
+
+    /// Iterations per stage for --bench
+    #[arg(long, value_name = "N", default_value_t = crate::bench::DEFAULT_BENCH_ITERATIONS)]
+    pub bench_iterations: usize,
+
+    /// Create or update a custom profile (requires --non-interactive)
+    #[arg(long)]
+    pub configure: bool,
+
+    /// Run --configure from --set/--from-json without prompting
+    #[arg(long, requires = "configure")]
+    pub non_interactive: bool,
+
+    /// Profile field to set with --configure (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE", requires = "configure")]
+    pub set: Vec<String>,
+
+    /// Start --configure from an existing JSON or TOML profile
+    #[arg(long, value_name = "FILE", requires = "configure")]
+    pub from_json: Option<String>,
+
+    /// Where --configure writes the profile (default: config dir)
+    #[arg(long, value_name = "FILE", requires = "configure")]
+    pub profile_out: Option<String>,
+
+    /// Print a custom profile (name or file) as JSON for sharing and exit
+    #[arg(long, value_name = "NAME")]
+    pub export_profile: Option<String>,
+
+    /// Install a shared profile from a JSON/TOML file or http(s) URL and exit
+    #[arg(long, value_name = "FILE|URL", conflicts_with = "export_profile")]
+    pub import_profile: Option<String>,
+
+    /// Install the --import-profile profile under another name
+    #[arg(long, value_name = "NAME", requires = "import_profile")]
+    pub import_as: Option<String>,
+
+    /// Let --import-profile overwrite a different profile of the same name
+    #[arg(long, requires = "import_profile")]
+    pub replace_profile: bool,
+
+    /// List discovered custom profiles and exit
+    #[arg(long)]
+    pub list_profiles: bool,
+
     /// Pretty-print JSON output (when --mode=json); does not affect streaming
     #[arg(long)]
     pub json_pretty: bool,
 
+    /// Sort JSON object keys recursively for byte-stable output across versions
+    #[arg(long)]
+    pub canonical_json: bool,
+
+    /// Byte-identical output across runs: epoch timestamps, stable pseudonyms, input order and canonical JSON
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Wrap the JSON output of all files in one document with schema and tool versions and aggregates
+    #[arg(
+        long,
+        conflicts_with_all = ["tokenize", "chunk_for_rag", "envelope", "token_stats", "bench"]
+    )]
+    pub json_envelope: bool,
+
     /// Include 1-based line numbers in JSON output lines array (e.g. {"n":1,"text":"..."})
     #[arg(long)]
     pub with_line_numbers: bool,
@@ -165,24 +489,229 @@ pub struct Args {
     #[arg(long)]
     pub hash: bool,
 
-    /// Strip comment-only lines from output
+    /// Strip comments from output (tree-sitter aware where available)
     #[arg(long)]
     pub strip_comments: bool,
 
     /// Strip blank lines from output
     #[arg(long)]
     pub strip_blank_lines: bool,
+
+    /// Keep declarations and signatures, replacing function bodies with { ... }
+    #[arg(long)]
+    pub signatures_only: bool,
+
+    /// Treat lone carriage returns as line breaks so CR, CRLF and LF files read alike
+    #[arg(long)]
+    pub normalize_line_endings: bool,
+
+    /// Add forge permalinks to summary and index entries (requires --repo-url)
+    #[arg(long, value_enum, value_name = "FORGE")]
+    pub link_format: Option<CliLinkFormat>,
+
+    /// Repository base URL for permalinks (e.g. https://github.com/org/repo)
+    #[arg(long, value_name = "URL")]
+    pub repo_url: Option<String>,
+
+    /// Mask secrets (AWS keys, private keys, JWTs, URL passwords) before output
+    #[arg(long)]
+    pub redact: bool,
+
+    /// Replace emails, IPs and UUIDs with stable per-run pseudonyms (implies --redact)
+    #[arg(long)]
+    pub anonymize: bool,
+
+    /// Additional regex to redact (repeatable; implies --redact)
+    #[arg(long, value_name = "REGEX")]
+    pub redact_pattern: Vec<String>,
+
+    /// Header printed before each file when several are given ({path}, {lines}, {bytes}, {language}; empty disables)
+    #[arg(long, value_name = "TEMPLATE")]
+    pub file_header: Option<String>,
+
+    /// Footer printed after each file when several are given (same placeholders)
+    #[arg(long, value_name = "TEMPLATE")]
+    pub file_footer: Option<String>,
+
+    /// Shape text output with a template ({path}, {name}, {language}, {lines}, {bytes}, {encoding}, {content}, {summary}; \n for newlines)
+    #[arg(long, value_name = "TEMPLATE")]
+    pub template: Option<String>,
+
+    /// Memory ceiling for buffered input (e.g. 256MB); fails fast instead of exhausting memory
+    #[arg(long, value_name = "SIZE", value_parser = crate::config::parse_memory_size)]
+    pub max_memory: Option<usize>,
+
+    /// Wall-clock limit per file (e.g. 500ms, 5s); partial results are marked timed_out
+    #[arg(long, value_name = "DURATION", value_parser = crate::config::parse_duration_ms)]
+    pub timeout: Option<u64>,
+
+    /// Fail with E304 once stdin sends more than SIZE (e.g. 64MB)
+    #[arg(long, value_name = "SIZE", value_parser = crate::config::parse_memory_size)]
+    pub stdin_max_bytes: Option<usize>,
+
+    /// Fail with E305 when stdin is not closed within DURATION (e.g. 10s)
+    #[arg(long, value_name = "DURATION", value_parser = crate::config::parse_duration_ms)]
+    pub stdin_timeout: Option<u64>,
+
+    /// Show CONTEXT lines either side of LINE (e.g. 245:20), naming the enclosing function
+    #[arg(long, value_name = "LINE:CONTEXT", value_parser = crate::around::parse_around)]
+    pub around: Option<AroundSpec>,
+
+    /// Show only the definition of NAME (a function, method or type; e.g. parse_config or Config::load)
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["around", "highlight_line"])]
+    pub symbol: Option<String>,
+
+    /// List every occurrence of the identifier NAME with its position and enclosing function or type
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["summary", "summary_level", "signatures_only", "fold_depth"])]
+    pub uses: Option<String>,
+
+    /// Mark line N and center the output on it (sized for the preview pane unless --around is given)
+    #[arg(long, value_name = "N")]
+    pub highlight_line: Option<usize>,
+
+    /// Emphasize lines or ranges (e.g. 3,10:15); marked in text output and flagged in JSON
+    #[arg(long, value_name = "RANGES", value_delimiter = ',', value_parser = crate::config::LineRange::parse)]
+    pub highlight_lines: Vec<crate::config::LineRange>,
+
+    /// Style for emphasized lines when color is on
+    #[arg(long, value_name = "STYLE")]
+    pub highlight_style: Option<CliHighlightStyle>,
+
+    /// Overlay line annotations (linter or review findings) from a JSON file
+    #[arg(long, value_name = "FILE")]
+    pub annotations: Option<String>,
+
+    /// Overlay results from a SARIF 2.1 file (CI static analysis output) as annotations
+    #[arg(long, value_name = "FILE")]
+    pub sarif: Option<String>,
+
+    /// Fuzzy-finder preview mode: numbered plain output, no banners, minimal parsing (e.g. fzf --preview 'batless --preview-window --highlight-line {2} {1}')
+    #[arg(long)]
+    pub preview_window: bool,
+
+    /// Fold JSON/YAML below depth N, previewing deeper values as {... 3 keys} / [... 42 items]
+    #[arg(long, value_name = "N")]
+    pub fold_depth: Option<usize>,
+
+    /// Hide log records below LEVEL in --mode=log and --mode=log-json
+    #[arg(long, value_name = "LEVEL")]
+    pub min_level: Option<CliLogLevel>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum CliOutputMode {
     Plain,
     Json,
+    /// One JSON object per source line
+    Jsonl,
     Summary,
     /// Machine-readable symbol index with line ranges
     Index,
     /// Raw tree-sitter parse tree as JSON
     Ast,
+    /// Import statements and dependency edges as JSON
+    Imports,
+    /// Log records colored by level (filter with --min-level)
+    Log,
+    /// One JSON object per log record
+    #[clap(name = "log-json")]
+    LogJson,
+    /// Offset, hex bytes and ASCII gutter (respects --max-bytes)
+    Hex,
+    /// Hex dump rows as a JSON byte range
+    #[clap(name = "hex-json")]
+    HexJson,
+    /// Exuberant-ctags tags file from the AST outline
+    Ctags,
+    /// Emacs TAGS file from the AST outline
+    Etags,
+    /// Definitions as a SCIP index in JSON
+    Scip,
+    /// Size, line count, language and encoding without reading content (one JSON line per file)
+    Metadata,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliExportFormat {
+    /// The --mode=json document of each file
+    Json,
+    /// A heading and a fenced code block per file
+    Markdown,
+}
+
+impl From<CliExportFormat> for crate::export::ExportFormat {
+    fn from(format: CliExportFormat) -> Self {
+        match format {
+            CliExportFormat::Json => Self::Json,
+            CliExportFormat::Markdown => Self::Markdown,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliTimeFormat {
+    /// `batless-time file=PATH read_us=N ... total_us=N`
+    Kv,
+    /// One JSON object per file
+    Json,
+}
+
+impl From<CliTimeFormat> for crate::timing::TimeFormat {
+    fn from(format: CliTimeFormat) -> Self {
+        match format {
+            CliTimeFormat::Kv => Self::KeyValue,
+            CliTimeFormat::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliTokenOrder {
+    /// As they appear in the file
+    Source,
+    /// Each identifier once, most frequent first
+    Frequency,
+    /// Sorted alphabetically
+    Alpha,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliTokenize {
+    /// The model's own BPE tokens
+    Model,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliErrorFormat {
+    /// `batless: warning: [W202] ...` lines
+    Human,
+    /// One JSON object per warning or error
+    Json,
+}
+
+impl From<CliErrorFormat> for ErrorFormat {
+    fn from(format: CliErrorFormat) -> Self {
+        match format {
+            CliErrorFormat::Human => Self::Human,
+            CliErrorFormat::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliEnvelope {
+    /// `{"id", "text", "metadata"}` documents for vector store loaders
+    Rag,
+}
+
+impl From<CliTokenOrder> for crate::tokens::TokenOrder {
+    fn from(order: CliTokenOrder) -> Self {
+        match order {
+            CliTokenOrder::Source => Self::Source,
+            CliTokenOrder::Frequency => Self::Frequency,
+            CliTokenOrder::Alpha => Self::Alpha,
+        }
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -191,6 +720,31 @@ pub enum CliChunkStrategy {
     Semantic,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliHighlightStyle {
+    Inverse,
+    Bold,
+    Underline,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliLogLevel {
+    Trace,
+    Debug,
+    Info,
+    #[value(alias = "warning")]
+    Warn,
+    Error,
+    Fatal,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CliLinkFormat {
+    Github,
+    Gitlab,
+    Sourcehut,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum Shell {
     Bash,
@@ -280,9 +834,19 @@ impl From<CliOutputMode> for OutputMode {
         match mode {
             CliOutputMode::Plain => Self::Plain,
             CliOutputMode::Json => Self::Json,
+            CliOutputMode::Jsonl => Self::Jsonl,
             CliOutputMode::Summary => Self::Summary,
             CliOutputMode::Index => Self::Index,
             CliOutputMode::Ast => Self::Ast,
+            CliOutputMode::Imports => Self::Imports,
+            CliOutputMode::Log => Self::Log,
+            CliOutputMode::LogJson => Self::LogJson,
+            CliOutputMode::Hex => Self::Hex,
+            CliOutputMode::HexJson => Self::HexJson,
+            CliOutputMode::Ctags => Self::Ctags,
+            CliOutputMode::Etags => Self::Etags,
+            CliOutputMode::Scip => Self::Scip,
+            CliOutputMode::Metadata => Self::Metadata,
         }
     }
 }
@@ -294,12 +858,25 @@ impl FromStr for OutputMode {
         match s {
             "plain" => Ok(Self::Plain),
             "json" => Ok(Self::Json),
+            "jsonl" => Ok(Self::Jsonl),
             "summary" => Ok(Self::Summary),
             "index" => Ok(Self::Index),
             "ast" => Ok(Self::Ast),
+            "imports" => Ok(Self::Imports),
+            "log" => Ok(Self::Log),
+            "log-json" => Ok(Self::LogJson),
+            "hex" => Ok(Self::Hex),
+            "hex-json" => Ok(Self::HexJson),
+            "ctags" => Ok(Self::Ctags),
+            "etags" => Ok(Self::Etags),
+            "scip" => Ok(Self::Scip),
+            "metadata" => Ok(Self::Metadata),
             _ => Err(BatlessError::ConfigurationError {
                 message: format!("Invalid output mode: {s}"),
-                help: Some("Valid modes are: plain, json, summary, index, ast".to_string()),
+                help: Some(
+                    "Valid modes are: plain, json, jsonl, summary, index, ast, imports, log, log-json, hex, hex-json, ctags, etags, scip, metadata"
+                        .to_string(),
+                ),
             }),
         }
     }
@@ -310,6 +887,8 @@ pub enum ColorMode {
     Auto,
     Always,
     Never,
+    /// Emit semantic highlight spans in JSON/JSONL output instead of ANSI codes
+    JsonSpans,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -320,6 +899,8 @@ pub enum CliSummaryLevel {
     Minimal,
     /// Standard summary with most important code
     Standard,
+    /// Standard summary with wrapped function signatures shown in full
+    Signatures,
     /// Detailed summary with comprehensive information
     Detailed,
 }
@@ -330,6 +911,7 @@ impl From<CliSummaryLevel> for SummaryLevel {
             CliSummaryLevel::None => Self::None,
             CliSummaryLevel::Minimal => Self::Minimal,
             CliSummaryLevel::Standard => Self::Standard,
+            CliSummaryLevel::Signatures => Self::Signatures,
             CliSummaryLevel::Detailed => Self::Detailed,
         }
     }
@@ -343,14 +925,28 @@ pub enum CliAiModel {
     Gpt4Turbo,
     /// OpenAI GPT-3.5 family
     Gpt35,
+    /// OpenAI GPT-4o
+    Gpt4o,
+    /// OpenAI o3
+    O3,
+    /// OpenAI o4-mini
+    O4Mini,
     /// Anthropic Claude family (Claude 4.x series)
     Claude,
     /// Anthropic Claude Sonnet (Claude 4.x Sonnet)
     ClaudeSonnet,
-    /// Google Gemini 1.5 Pro (1M context)
+    /// Anthropic Claude 3.7 Sonnet
+    Claude37Sonnet,
+    /// Google Gemini 1.5 Pro (2M context)
     Gemini,
     /// Google Gemini 2.0 Flash (1M context)
     GeminiFlash,
+    /// Google Gemini 2.5 Pro (1M context)
+    Gemini25Pro,
+    /// Meta Llama 3 (8K context)
+    Llama3,
+    /// Meta Llama 3.1+ (128K context)
+    Llama31,
     /// Generic model estimation
     Generic,
 }
@@ -361,6 +957,13 @@ impl From<CliAiModel> for AiModel {
             CliAiModel::Gpt4 => Self::Gpt4,
             CliAiModel::Gpt4Turbo => Self::Gpt4Turbo,
             CliAiModel::Gpt35 => Self::Gpt35,
+            CliAiModel::Gpt4o => Self::Gpt4o,
+            CliAiModel::O3 => Self::O3,
+            CliAiModel::O4Mini => Self::O4Mini,
+            CliAiModel::Claude37Sonnet => Self::Claude37Sonnet,
+            CliAiModel::Gemini25Pro => Self::Gemini25Pro,
+            CliAiModel::Llama3 => Self::Llama3,
+            CliAiModel::Llama31 => Self::Llama31,
             CliAiModel::Claude => Self::Claude,
             CliAiModel::ClaudeSonnet => Self::ClaudeSonnet,
             CliAiModel::Gemini => Self::Gemini,
@@ -372,10 +975,16 @@ impl From<CliAiModel> for AiModel {
 
 /// Manages the application's configuration, merging settings from various
 /// sources like files, command-line arguments, and profiles.
+#[derive(Clone)]
 pub struct ConfigManager {
     args: Args,
     config: BatlessConfig,
     output_mode: OutputMode,
+    ai_model: AiModel,
+    warnings: Vec<ConfigurationWarning>,
+    notices: Vec<String>,
+    /// Input the configuration is resolved for, when not the primary file
+    input: Option<String>,
 }
 
 impl ConfigManager {
@@ -396,6 +1005,10 @@ impl ConfigManager {
             args,
             config: BatlessConfig::default(),
             output_mode: OutputMode::Plain,
+            ai_model: AiModel::Generic,
+            warnings: Vec::new(),
+            notices: Vec::new(),
+            input: None,
         };
         manager.load_and_apply_config()?;
         Ok(manager)
@@ -427,6 +1040,10 @@ impl ConfigManager {
             args: parsed_args,
             config: BatlessConfig::default(),
             output_mode: OutputMode::Plain,
+            ai_model: AiModel::Generic,
+            warnings: Vec::new(),
+            notices: Vec::new(),
+            input: None,
         };
         manager.load_and_apply_config()?;
         Ok(manager)
@@ -437,6 +1054,17 @@ impl ConfigManager {
         &self.args
     }
 
+    /// Non-fatal problems found while loading config files
+    pub fn warnings(&self) -> &[ConfigurationWarning] {
+        &self.warnings
+    }
+
+    /// Informational messages from building the configuration, such as a
+    /// corrected `--language`
+    pub fn notices(&self) -> &[String] {
+        &self.notices
+    }
+
     /// Returns a reference to the final, merged `BatlessConfig`.
     pub const fn config(&self) -> &BatlessConfig {
         &self.config
@@ -447,6 +1075,41 @@ impl ConfigManager {
         self.output_mode
     }
 
+    /// Returns the AI model selected with `--ai-model`.
+    pub const fn ai_model(&self) -> AiModel {
+        self.ai_model
+    }
+
+    /// Which informational messages to print, from `-q`, `-v` and `--debug`
+    pub fn output_policy(&self) -> OutputPolicy {
+        OutputPolicy::new(
+            Verbosity::from_flags(self.args.quiet, self.args.verbose, self.config.debug),
+            StatusStyle::detect(self.args.no_emoji),
+        )
+        .with_error_format(self.args.error_format.into())
+    }
+
+    /// Manager for `file_path`, one of several inputs, with the workspace
+    /// and `.batless/` project config found from its directory and workspace
+    /// rules matched against its path instead of the primary input's;
+    /// borrows `self` for the primary input and stdin
+    pub fn for_input(&self, file_path: &str) -> BatlessResult<Cow<'_, Self>> {
+        if file_path == "-" || Some(file_path) == self.input_file() {
+            return Ok(Cow::Borrowed(self));
+        }
+        let mut manager = Self {
+            args: self.args.clone(),
+            config: BatlessConfig::default(),
+            output_mode: OutputMode::Plain,
+            ai_model: AiModel::Generic,
+            warnings: Vec::new(),
+            notices: Vec::new(),
+            input: Some(file_path.to_string()),
+        };
+        manager.load_and_apply_config()?;
+        Ok(Cow::Owned(manager))
+    }
+
     /// Determines the file path to process, handling stdin as well.
     pub fn file_path(&self) -> BatlessResult<String> {
         self.args.file.as_ref().map_or_else(
@@ -467,19 +1130,41 @@ impl ConfigManager {
         )
     }
 
+    /// All input paths: the primary file (or stdin) followed by any extra files
+    pub fn file_paths(&self) -> BatlessResult<Vec<String>> {
+        let mut paths = vec![self.file_path()?];
+        paths.extend(self.args.more_files.iter().cloned());
+        Ok(paths)
+    }
+
     /// Loads configuration from files, applies command-line arguments,
     /// and resolves profiles to create the final configuration.
     fn load_and_apply_config(&mut self) -> BatlessResult<()> {
+        // 0. The team's workspace file is the lowest layer
+        let workspace = if self.args.check_config.is_some() {
+            None
+        } else {
+            self.load_workspace()?
+        };
+        let workspace_config = match &workspace {
+            Some(workspace) => workspace.config_for(self.primary_input().as_deref())?,
+            None => BatlessConfig::default(),
+        };
+
         // 1. Load base configuration from files
         self.config = if let Some(config_path) = &self.args.config {
-            let path = std::path::Path::new(config_path);
-            if path.extension() == Some(std::ffi::OsStr::new("toml")) {
-                BatlessConfig::from_file(path)?
-            } else {
-                BatlessConfig::from_json_file(path)?
-            }
+            let (config, warnings) =
+                BatlessConfig::load_file_with_warnings(std::path::Path::new(config_path))?;
+            self.warnings.extend(warnings);
+            workspace_config.clone().merge_with(config)
+        } else if self.args.check_config.is_some() {
+            // Broken files are reported by the check instead of aborting startup
+            BatlessConfig::default()
         } else {
-            BatlessConfig::load_with_precedence()?
+            let (config, warnings) =
+                BatlessConfig::load_with_warnings_from(&self.discovery_start())?;
+            self.warnings.extend(warnings);
+            workspace_config.clone().merge_with(config)
         };
 
         // 2. Apply command-line arguments
@@ -491,9 +1176,26 @@ impl ConfigManager {
         // 4. Handle compatibility flags
         self.apply_compatibility_flags();
 
+        // Locked workspace settings win over everything above
+        if let Some(workspace) = &workspace {
+            let (config, changed) =
+                workspace.enforce_locks(std::mem::take(&mut self.config), &workspace_config)?;
+            self.config = config;
+            let path = workspace.path.display().to_string();
+            self.warnings.extend(
+                changed
+                    .into_iter()
+                    .map(|key| ConfigurationWarning::LockedKey {
+                        path: path.clone(),
+                        key,
+                    }),
+            );
+        }
+
         // 5. Final validation
         self.config.validate()?;
-        self.validate_language()?;
+        self.resolve_language()?;
+        self.ai_model = self.resolve_ai_model()?;
 
         Ok(())
     }
@@ -509,35 +1211,127 @@ impl ConfigManager {
         if self.args.max_bytes.is_some() {
             new_config = new_config.with_max_bytes(self.args.max_bytes);
         }
+        if self.args.timeout.is_some() {
+            new_config = new_config.with_timeout_ms(self.args.timeout);
+        }
+        if self.args.stdin_max_bytes.is_some() {
+            new_config = new_config.with_stdin_max_bytes(self.args.stdin_max_bytes);
+        }
+        if self.args.stdin_timeout.is_some() {
+            new_config = new_config.with_stdin_timeout_ms(self.args.stdin_timeout);
+        }
+        if self.args.around.is_some() {
+            new_config = new_config.with_around(self.args.around);
+        }
+        if self.args.symbol.is_some() {
+            new_config = new_config.with_symbol(self.args.symbol.clone());
+        }
+        if self.args.uses.is_some() {
+            new_config = new_config.with_uses(self.args.uses.clone());
+        }
+        if let Some(line) = self.args.highlight_line {
+            new_config = new_config.with_highlight_line(Some(line));
+            if self.args.around.is_none() {
+                new_config = new_config.with_around(Some(AroundSpec {
+                    line,
+                    context: crate::around::preview_context(),
+                }));
+            }
+        }
+        if let Some(level) = self.args.min_level {
+            use crate::config::LogLevel;
+            new_config = new_config.with_min_log_level(Some(match level {
+                CliLogLevel::Trace => LogLevel::Trace,
+                CliLogLevel::Debug => LogLevel::Debug,
+                CliLogLevel::Info => LogLevel::Info,
+                CliLogLevel::Warn => LogLevel::Warn,
+                CliLogLevel::Error => LogLevel::Error,
+                CliLogLevel::Fatal => LogLevel::Fatal,
+            }));
+        }
+        if self.args.fold_depth.is_some() {
+            new_config = new_config.with_fold_depth(self.args.fold_depth);
+        }
+        if !self.args.highlight_lines.is_empty() {
+            new_config = new_config.with_highlight_lines(self.args.highlight_lines.clone());
+        }
+        if let Some(style) = self.args.highlight_style {
+            use crate::config::HighlightStyle;
+            new_config = new_config.with_highlight_style(match style {
+                CliHighlightStyle::Inverse => HighlightStyle::Inverse,
+                CliHighlightStyle::Bold => HighlightStyle::Bold,
+                CliHighlightStyle::Underline => HighlightStyle::Underline,
+            });
+        }
+        if self.args.annotations.is_some() {
+            new_config = new_config.with_annotations(self.args.annotations.clone());
+        }
+        if self.args.sarif.is_some() {
+            new_config = new_config.with_sarif(self.args.sarif.clone());
+        }
+        if self.args.preview_window {
+            new_config = new_config.with_preview_window(true);
+        }
+        if self.args.max_memory.is_some() {
+            new_config = new_config.with_max_memory(self.args.max_memory);
+        }
         if let Some(ref language) = self.args.language {
             new_config = new_config.with_language(Some(language.clone()));
         }
+        if self.args.stdin_filename.is_some() {
+            new_config = new_config.with_stdin_filename(self.args.stdin_filename.clone());
+        }
+        if self.args.stdin_language.is_some() {
+            new_config = new_config.with_stdin_language(self.args.stdin_language.clone());
+        }
         if self.args.strip_ansi {
             new_config = new_config.with_strip_ansi(self.args.strip_ansi);
         }
 
-        let use_color = if self.args.color_specified {
-            match self.args.color {
-                ColorMode::Always => true,
-                ColorMode::Never => false,
-                ColorMode::Auto => std::io::stdout().is_terminal(),
-            }
-        } else if new_config.use_color {
-            std::io::stdout().is_terminal()
-        } else {
-            false
+        // An explicit --color=always/never overrides NO_COLOR / CLICOLOR_FORCE;
+        // auto defers to the environment and terminal detection
+        let use_color = match self.args.color {
+            ColorMode::Always if self.args.color_specified => true,
+            ColorMode::Never | ColorMode::JsonSpans if self.args.color_specified => false,
+            _ => new_config.should_use_color(std::io::stdout().is_terminal()),
         };
         new_config = new_config.with_use_color(use_color);
+        if self.args.color == ColorMode::JsonSpans {
+            new_config = new_config.with_highlight_spans(true);
+        }
 
         if self.args.include_identifiers || self.args.include_tokens {
             new_config = new_config.with_include_tokens(true);
         }
+        if self.args.unique_identifiers {
+            new_config = new_config.with_unique_tokens(true);
+        }
+        if let Some(max_token_sample) = self.args.max_token_sample {
+            new_config = new_config.with_max_token_sample(max_token_sample);
+        }
+        if let Some(order) = self.args.token_order {
+            new_config = new_config.with_token_order(order.into());
+        }
+        if self.args.token_frequencies.is_some() {
+            new_config = new_config.with_token_frequencies(self.args.token_frequencies);
+        }
         if self.args.streaming_json {
             new_config = new_config.with_streaming_json(self.args.streaming_json);
         }
         if self.args.json_pretty {
             new_config = new_config.with_pretty_json(true);
         }
+        if self.args.canonical_json {
+            new_config = new_config.with_canonical_json(true);
+        }
+        if self.args.deterministic || new_config.deterministic {
+            new_config = new_config.with_deterministic(true);
+        }
+        if self.args.sample_count.is_some() || self.args.sample_seed.is_some() {
+            let sample_count = self.args.sample_count.unwrap_or(new_config.sample_count);
+            let sample_seed = self.args.sample_seed.unwrap_or(new_config.sample_seed);
+            new_config = new_config.with_token_sampling(sample_count, sample_seed);
+        }
         if self.args.with_line_numbers {
             new_config = new_config.with_json_line_numbers(true);
         }
@@ -550,9 +1344,33 @@ impl ConfigManager {
         if self.args.strip_blank_lines {
             new_config = new_config.with_strip_blank_lines(true);
         }
+        if self.args.signatures_only {
+            new_config = new_config.with_signatures_only(true);
+        }
+        if self.args.normalize_line_endings {
+            new_config = new_config.with_normalize_line_endings(true);
+        }
         if let Some(chunk_size) = self.args.streaming_chunk_size {
             new_config = new_config.with_streaming_chunk_size(chunk_size);
         }
+        if self.args.stream_delay_ms.is_some() {
+            new_config = new_config.with_stream_delay_ms(self.args.stream_delay_ms);
+        }
+        if self.args.max_chunks_per_second.is_some() {
+            new_config = new_config.with_max_chunks_per_second(self.args.max_chunks_per_second);
+        }
+        if self.args.max_chunks.is_some() {
+            new_config = new_config.with_max_chunks(self.args.max_chunks);
+        }
+        if self.args.stream_until_tokens.is_some() {
+            new_config = new_config.with_stream_token_budget(self.args.stream_until_tokens);
+        }
+        if self.args.no_auto_stream {
+            new_config = new_config.with_auto_stream(false);
+        }
+        if let Some(threshold) = self.args.auto_stream_threshold {
+            new_config = new_config.with_auto_stream_threshold(threshold as u64);
+        }
         if let Some(strategy) = self.args.chunk_strategy {
             use crate::config::ChunkStrategy;
             new_config = new_config.with_chunk_strategy(match strategy {
@@ -560,9 +1378,43 @@ impl ConfigManager {
                 CliChunkStrategy::Semantic => ChunkStrategy::Semantic,
             });
         }
+        if let Some(format) = self.args.link_format {
+            use crate::config::LinkFormat;
+            new_config = new_config.with_link_format(Some(match format {
+                CliLinkFormat::Github => LinkFormat::Github,
+                CliLinkFormat::Gitlab => LinkFormat::Gitlab,
+                CliLinkFormat::Sourcehut => LinkFormat::Sourcehut,
+            }));
+        }
+        if self.args.repo_url.is_some() {
+            new_config = new_config.with_repo_url(self.args.repo_url.clone());
+        }
+        if self.args.redact {
+            new_config = new_config.with_redact(true);
+        }
+        if self.args.anonymize {
+            new_config = new_config.with_anonymize(true);
+        }
+        if !self.args.redact_pattern.is_empty() {
+            let mut patterns = new_config.redact_patterns.clone();
+            patterns.extend(self.args.redact_pattern.iter().cloned());
+            new_config = new_config.with_redact_patterns(patterns);
+        }
+        if self.args.file_header.is_some() {
+            new_config = new_config.with_file_header(self.args.file_header.clone());
+        }
+        if self.args.file_footer.is_some() {
+            new_config = new_config.with_file_footer(self.args.file_footer.clone());
+        }
+        if self.args.template.is_some() {
+            new_config = new_config.with_template(self.args.template.clone());
+        }
         if self.args.enable_resume {
             new_config = new_config.with_enable_resume(self.args.enable_resume);
         }
+        if self.args.restart_on_change {
+            new_config = new_config.with_restart_on_change(true);
+        }
         if self.args.debug {
             new_config = new_config.with_debug(self.args.debug);
         }
@@ -571,13 +1423,75 @@ impl ConfigManager {
         } else if self.args.summary || self.args.mode == Some(CliOutputMode::Summary) {
             new_config = new_config.with_summary_mode(true);
         }
+        if self.args.summary_docs {
+            new_config = new_config.with_summary_docs(true);
+        }
+        if self.args.no_tests {
+            new_config = new_config.with_no_tests(true);
+        }
+        if self.args.public_only {
+            new_config = new_config.with_public_only(true);
+        }
+        if self.args.fail_on_syntax_errors {
+            new_config = new_config.with_check_syntax(true);
+        }
 
         self.config = new_config;
     }
 
+    /// Directory where `.batless/` discovery starts: the input's directory,
+    /// or the working directory for stdin.
+    fn discovery_start(&self) -> std::path::PathBuf {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        match self.input_file() {
+            Some(file) if file != "-" => {
+                let path = cwd.join(file);
+                if path.is_dir() {
+                    path
+                } else {
+                    path.parent().map_or(cwd, std::path::Path::to_path_buf)
+                }
+            }
+            _ => cwd,
+        }
+    }
+
+    /// The nearest `batless.workspace.toml`, with warnings for unknown keys
+    fn load_workspace(&mut self) -> BatlessResult<Option<Workspace>> {
+        let Some(path) = Workspace::find(&self.discovery_start()) else {
+            return Ok(None);
+        };
+        let (workspace, warnings) = Workspace::load(&path)?;
+        self.warnings.extend(warnings);
+        Ok(Some(workspace))
+    }
+
+    /// The input this configuration is for: the primary file unless built
+    /// by [`Self::for_input`]
+    fn input_file(&self) -> Option<&str> {
+        self.input.as_deref().or(self.args.file.as_deref())
+    }
+
+    /// Path workspace rules are matched against: the input file, or the
+    /// name given to stdin with `--stdin-filename`
+    fn primary_input(&self) -> Option<std::path::PathBuf> {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        match self.input_file() {
+            Some(file) if file != "-" => Some(cwd.join(file)),
+            _ => self
+                .args
+                .stdin_filename
+                .as_deref()
+                .map(|name| cwd.join(name)),
+        }
+    }
+
     /// Applies AI profiles to the configuration.
     fn apply_profiles(&mut self) -> BatlessResult<()> {
         self.output_mode = if let Some(custom_profile_path) = &self.args.custom_profile {
+            let project_dir = BatlessConfig::find_project_dir(&self.discovery_start());
+            let custom_profile_path =
+                CustomProfile::resolve_path(custom_profile_path, project_dir.as_deref());
             let custom_profile = CustomProfile::load_from_file(custom_profile_path)?;
             self.config = custom_profile.apply_to_config(std::mem::take(&mut self.config));
             custom_profile
@@ -604,15 +1518,46 @@ impl ConfigManager {
         if self.args.number {
             self.config = std::mem::take(&mut self.config).with_show_line_numbers(true);
         }
+        if self.args.preview_window {
+            self.output_mode = OutputMode::Plain;
+            self.config = std::mem::take(&mut self.config).with_show_line_numbers(true);
+        }
         if self.args.number_nonblank {
             self.config = std::mem::take(&mut self.config).with_show_line_numbers_nonblank(true);
         }
     }
 
+    /// Resolves `--ai-model` against built-in models and the `[models]` registry.
+    fn resolve_ai_model(&self) -> BatlessResult<AiModel> {
+        AiModel::parse_with_registry(&self.args.ai_model, &self.config.models).map_err(|e| {
+            let mut names: Vec<&str> = AiModel::all().iter().map(AiModel::as_str).collect();
+            names.extend(self.config.models.keys().map(String::as_str));
+            BatlessError::config_error_with_help(
+                e,
+                Some(format!(
+                    "Available models: {}. Define custom models under [models.<name>] in your config",
+                    names.join(", ")
+                )),
+            )
+        })
+    }
+
     /// Validates the language setting.
-    fn validate_language(&self) -> BatlessResult<()> {
-        if let Some(ref lang) = self.config.language {
-            crate::LanguageDetector::validate_language(lang)?;
+    fn resolve_language(&mut self) -> BatlessResult<()> {
+        let Some(given) = self.config.language.as_deref() else {
+            return Ok(());
+        };
+        if self.config.has_grammar(given) {
+            return Ok(());
+        }
+        match crate::LanguageDetector::resolve_language(given)? {
+            LanguageMatch::Exact => {}
+            LanguageMatch::Alias(language) => self.config.language = Some(language),
+            LanguageMatch::Corrected(language) => {
+                self.notices
+                    .push(format!("unknown language '{given}', using '{language}'"));
+                self.config.language = Some(language);
+            }
         }
         Ok(())
     }
@@ -621,6 +1566,7 @@ impl ConfigManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::LinkFormat;
 
     fn make_manager(args: &[&str]) -> ConfigManager {
         let mut full_args = vec!["batless"];
@@ -701,6 +1647,17 @@ mod tests {
         assert!(mgr.config().debug);
     }
 
+    #[test]
+    fn test_quiet_and_verbose_flags() {
+        use crate::output_policy::Verbosity;
+        let verbosity = |args: &[&str]| make_manager(args).output_policy().verbosity;
+        assert_eq!(verbosity(&["Cargo.toml"]), Verbosity::Normal);
+        assert_eq!(verbosity(&["-q", "Cargo.toml"]), Verbosity::Quiet);
+        assert_eq!(verbosity(&["-v", "Cargo.toml"]), Verbosity::Verbose);
+        assert_eq!(verbosity(&["-vv", "Cargo.toml"]), Verbosity::Debug);
+        assert!(ConfigManager::from_args_vec(["batless", "-q", "-v", "Cargo.toml"]).is_err());
+    }
+
     #[test]
     fn test_summary_flag_enables_summary() {
         let mgr = make_manager(&["--summary", "Cargo.toml"]);
@@ -758,24 +1715,221 @@ mod tests {
         assert_eq!(mgr.config().max_lines, 5000);
     }
 
+    #[test]
+    fn test_color_always_overrides_environment() {
+        let mgr = make_manager(&["--color=always", "Cargo.toml"]);
+        assert!(mgr.config().use_color);
+        // Tests run with captured (non-terminal) stdout
+        let mgr = make_manager(&["--color=auto", "Cargo.toml"]);
+        assert_eq!(
+            mgr.config().use_color,
+            BatlessConfig::default().should_use_color(false)
+        );
+    }
+
     #[test]
     fn test_color_never() {
         let mgr = make_manager(&["--color=never", "Cargo.toml"]);
         assert!(!mgr.config().use_color);
     }
 
+    #[test]
+    fn test_color_json_spans() {
+        let mgr = make_manager(&["--color=json-spans", "Cargo.toml"]);
+        assert!(!mgr.config().use_color);
+        assert!(mgr.config().highlight_spans);
+    }
+
     #[test]
     fn test_json_pretty() {
         let mgr = make_manager(&["--json-pretty", "--mode=json", "Cargo.toml"]);
         assert!(mgr.config().pretty_json);
     }
 
+    #[test]
+    fn test_sample_flags() {
+        let mgr = make_manager(&["--sample-count", "9", "--sample-seed", "7", "Cargo.toml"]);
+        assert_eq!(mgr.config().sample_count, 9);
+        assert_eq!(mgr.config().sample_seed, 7);
+        let mgr = make_manager(&["--sample-seed", "7", "Cargo.toml"]);
+        assert_eq!(mgr.config().sample_count, 5);
+        assert!(
+            ConfigManager::from_args_vec(["batless", "--sample-count", "0", "Cargo.toml"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_canonical_json_flag() {
+        let mgr = make_manager(&["--canonical-json", "--mode=json", "Cargo.toml"]);
+        assert!(mgr.config().canonical_json);
+        assert!(!make_manager(&["Cargo.toml"]).config().canonical_json);
+    }
+
+    #[test]
+    fn test_deterministic_implies_canonical_json() {
+        let mgr = make_manager(&["--deterministic", "Cargo.toml"]);
+        assert!(mgr.config().deterministic);
+        assert!(mgr.config().canonical_json);
+        assert_eq!(mgr.config().now(), chrono::DateTime::UNIX_EPOCH);
+    }
+
     #[test]
     fn test_streaming_json() {
         let mgr = make_manager(&["--streaming-json", "Cargo.toml"]);
         assert!(mgr.config().streaming_json);
     }
 
+    #[test]
+    fn test_config_file_warnings_collected() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("custom.toml");
+        std::fs::write(&config, "summary_mode = true\n").unwrap();
+
+        let mgr = make_manager(&["--config", config.to_str().unwrap(), "Cargo.toml"]);
+        assert_eq!(mgr.warnings().len(), 1);
+        assert_eq!(mgr.warnings()[0].key(), "summary_mode");
+    }
+
+    #[test]
+    fn test_around_arg() {
+        let mgr = make_manager(&["--around=245:20", "Cargo.toml"]);
+        assert_eq!(
+            mgr.config().around,
+            Some(AroundSpec {
+                line: 245,
+                context: 20
+            })
+        );
+        assert!(ConfigManager::from_args_vec(["batless", "--around=0:3", "Cargo.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_symbol_arg() {
+        let mgr = make_manager(&["--symbol", "Config::load", "Cargo.toml"]);
+        assert_eq!(mgr.config().symbol.as_deref(), Some("Config::load"));
+        assert_eq!(mgr.config().read_line_limit(), usize::MAX);
+        assert!(ConfigManager::from_args_vec([
+            "batless",
+            "--symbol=load",
+            "--around=3",
+            "Cargo.toml"
+        ])
+        .is_err());
+        assert!(ConfigManager::from_args_vec(["batless", "--symbol=", "Cargo.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_uses_arg() {
+        let mgr = make_manager(&["--uses", "parse_config", "Cargo.toml"]);
+        assert_eq!(mgr.config().uses.as_deref(), Some("parse_config"));
+        assert!(ConfigManager::from_args_vec(["batless", "--uses=a.b", "Cargo.toml"]).is_err());
+        assert!(
+            ConfigManager::from_args_vec(["batless", "--uses=a", "--summary", "Cargo.toml"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_highlight_line_and_preview_window() {
+        let mgr = make_manager(&["--highlight-line=50", "--around=50:3", "Cargo.toml"]);
+        assert_eq!(mgr.config().highlight_line, Some(50));
+        assert_eq!(mgr.config().around.map(|a| a.context), Some(3));
+
+        let mgr = make_manager(&["--preview-window", "--highlight-line=50", "Cargo.toml"]);
+        assert_eq!(mgr.config().around.map(|a| a.line), Some(50));
+        assert!(mgr.config().show_line_numbers);
+        assert_eq!(mgr.output_mode(), OutputMode::Plain);
+        // Previews read only as far as the window reaches
+        assert_eq!(
+            mgr.config().read_line_limit(),
+            mgr.config().around.unwrap().last_line()
+        );
+
+        assert!(
+            ConfigManager::from_args_vec(["batless", "--highlight-line=0", "Cargo.toml"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_fold_depth_arg() {
+        let mgr = make_manager(&["--fold-depth=2", "Cargo.toml"]);
+        assert_eq!(mgr.config().fold_depth, Some(2));
+        assert_eq!(make_manager(&["Cargo.toml"]).config().fold_depth, None);
+    }
+
+    #[test]
+    fn test_check_config_args() {
+        let mgr = make_manager(&["--check-config"]);
+        assert_eq!(mgr.args().check_config, Some(None));
+        let mgr = make_manager(&["--check-config", "batless.toml"]);
+        assert_eq!(
+            mgr.args().check_config,
+            Some(Some("batless.toml".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_project_config_discovered_from_file_ancestors() {
+        let root = tempfile::tempdir().unwrap();
+        let src = root.path().join("pkg/src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::create_dir(root.path().join(".batless")).unwrap();
+        std::fs::write(root.path().join(".batless/config.toml"), "max_lines = 12\n").unwrap();
+        std::fs::write(
+            root.path().join(".batless/terse.json"),
+            r#"{"name": "terse", "max_bytes": 4096}"#,
+        )
+        .unwrap();
+        let file = src.join("lib.rs");
+        std::fs::write(&file, "fn main() {}\n").unwrap();
+        let file = file.to_str().unwrap();
+
+        let mgr = make_manager(&[file]);
+        assert_eq!(mgr.config().max_lines, 12);
+
+        let mgr = make_manager(&["--custom-profile=terse", file]);
+        assert_eq!(mgr.config().max_bytes, Some(4096));
+    }
+
+    #[test]
+    fn test_stdin_metadata_args() {
+        let mgr = make_manager(&["--stdin-filename=src/main.rs", "--stdin-language=Rust", "-"]);
+        assert_eq!(mgr.config().stdin_filename.as_deref(), Some("src/main.rs"));
+        assert_eq!(mgr.config().stdin_language.as_deref(), Some("Rust"));
+        assert!(ConfigManager::from_args_vec(["batless", "--stdin-filename=", "-"]).is_err());
+    }
+
+    #[test]
+    fn test_stream_pacing_args() {
+        let mgr = make_manager(&[
+            "--stream-delay-ms=50",
+            "--max-chunks-per-second=10",
+            "--max-chunks=3",
+            "Cargo.toml",
+        ]);
+        assert_eq!(mgr.config().stream_delay_ms, Some(50));
+        assert_eq!(mgr.config().max_chunks_per_second, Some(10));
+        assert_eq!(mgr.config().max_chunks, Some(3));
+        assert!(ConfigManager::from_args_vec(["batless", "--max-chunks=0", "Cargo.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_stream_until_tokens_arg() {
+        let mgr = make_manager(&["--stream-until-tokens=500", "Cargo.toml"]);
+        assert_eq!(mgr.config().stream_token_budget, Some(500));
+        assert!(
+            ConfigManager::from_args_vec(["batless", "--stream-until-tokens=0", "Cargo.toml"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_restart_on_change() {
+        assert!(!make_manager(&["Cargo.toml"]).config().restart_on_change);
+        let mgr = make_manager(&["--streaming-json", "--restart-on-change", "Cargo.toml"]);
+        assert!(mgr.config().restart_on_change);
+    }
+
     #[test]
     fn test_streaming_chunk_size() {
         let mgr = make_manager(&["--streaming-chunk-size=500", "Cargo.toml"]);
@@ -788,6 +1942,240 @@ mod tests {
         assert!(mgr.config().enable_resume);
     }
 
+    #[test]
+    fn test_link_format_and_repo_url() {
+        let mgr = make_manager(&[
+            "--link-format=gitlab",
+            "--repo-url=https://gitlab.com/org/repo",
+            "Cargo.toml",
+        ]);
+        assert_eq!(mgr.config().link_format, Some(LinkFormat::Gitlab));
+        assert_eq!(
+            mgr.config().repo_url.as_deref(),
+            Some("https://gitlab.com/org/repo")
+        );
+    }
+
+    #[test]
+    fn test_multiple_files_and_banners() {
+        let mgr = make_manager(&[
+            "Cargo.toml",
+            "README.md",
+            "--file-header={path} ({lines} lines)",
+        ]);
+        assert_eq!(mgr.file_paths().unwrap(), ["Cargo.toml", "README.md"]);
+        assert_eq!(
+            mgr.config().file_header.as_deref(),
+            Some("{path} ({lines} lines)")
+        );
+        assert_eq!(mgr.config().file_footer, None);
+    }
+
+    #[test]
+    fn test_redaction_args() {
+        let mgr = make_manager(&["--redact-pattern=sk_[a-z]+", "Cargo.toml"]);
+        assert!(!mgr.config().redact);
+        assert_eq!(mgr.config().redact_patterns, ["sk_[a-z]+"]);
+        assert!(!mgr.config().anonymize);
+        let mgr = make_manager(&["--anonymize", "Cargo.toml"]);
+        assert!(mgr.config().anonymize);
+        assert!(
+            ConfigManager::from_args_vec(vec!["batless", "--redact-pattern=(", "Cargo.toml"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_max_memory_parsed() {
+        let mgr = make_manager(&["--max-memory=256MB", "Cargo.toml"]);
+        assert_eq!(mgr.config().max_memory, Some(256_000_000));
+        assert!(
+            ConfigManager::from_args_vec(["batless", "--max-memory=lots", "Cargo.toml"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_timeout_parsed() {
+        let mgr = make_manager(&["--timeout=750ms", "Cargo.toml"]);
+        assert_eq!(mgr.config().timeout_ms, Some(750));
+        assert!(ConfigManager::from_args_vec(["batless", "--timeout=0s", "Cargo.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_language_aliases_and_corrections() {
+        let mgr = make_manager(&["--language=golang", "Cargo.toml"]);
+        assert_eq!(mgr.config().language.as_deref(), Some("Go"));
+        assert!(mgr.notices().is_empty());
+        let mgr = make_manager(&["--language=pyton", "Cargo.toml"]);
+        assert_eq!(mgr.config().language.as_deref(), Some("Python"));
+        assert_eq!(mgr.notices(), ["unknown language 'pyton', using 'Python'"]);
+    }
+
+    #[test]
+    fn test_stdin_limits_parsed() {
+        let mgr = make_manager(&["--stdin-max-bytes=1KB", "--stdin-timeout=2s", "-"]);
+        assert_eq!(mgr.config().stdin_max_bytes, Some(1000));
+        assert_eq!(mgr.config().stdin_timeout_ms, Some(2000));
+        assert!(ConfigManager::from_args_vec(["batless", "--stdin-max-bytes=0", "-"]).is_err());
+        assert!(ConfigManager::from_args_vec(["batless", "--stdin-timeout=0s", "-"]).is_err());
+    }
+
+    #[test]
+    fn test_token_stats_flags() {
+        let mgr = make_manager(&["Cargo.toml"]);
+        assert!(!mgr.args().token_stats);
+        assert_eq!(mgr.args().top_tokens, 10);
+        let mgr = make_manager(&["--token-stats", "--top-tokens=3", "Cargo.toml"]);
+        assert!(mgr.args().token_stats);
+        assert_eq!(mgr.args().top_tokens, 3);
+    }
+
+    #[test]
+    fn test_template_enables_summary_when_used() {
+        let mgr = make_manager(&["--template", "{path}: {content}", "Cargo.toml"]);
+        assert_eq!(mgr.config().template.as_deref(), Some("{path}: {content}"));
+        assert!(!mgr.config().effective_summary_level().is_enabled());
+        let mgr = make_manager(&["--template", "{summary}", "Cargo.toml"]);
+        assert!(mgr.config().effective_summary_level().is_enabled());
+    }
+
+    #[test]
+    fn test_copy_flags() {
+        let mgr = make_manager(&["--copy", "Cargo.toml"]);
+        assert!(mgr.args().copy && !mgr.args().copy_only);
+        assert!(Args::try_parse_from(["batless", "--copy", "--copy-only", "Cargo.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_auto_stream_flags() {
+        let mgr = make_manager(&["Cargo.toml"]);
+        assert!(mgr.config().auto_stream);
+        assert_eq!(mgr.config().auto_stream_threshold, 64 * 1024 * 1024);
+        let mgr = make_manager(&[
+            "--no-auto-stream",
+            "--auto-stream-threshold=1MB",
+            "Cargo.toml",
+        ]);
+        assert!(!mgr.config().auto_stream);
+        assert_eq!(mgr.config().auto_stream_threshold, 1_000_000);
+    }
+
+    #[test]
+    fn test_time_flags() {
+        let mgr = make_manager(&["--time", "Cargo.toml"]);
+        assert!(mgr.args().time);
+        assert!(mgr.args().time_format == CliTimeFormat::Kv);
+        let mgr = make_manager(&["--time", "--time-format=json", "Cargo.toml"]);
+        assert!(mgr.args().time_format == CliTimeFormat::Json);
+        assert!(Args::try_parse_from(["batless", "--time-format=json", "Cargo.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_bench_flags() {
+        let mgr = make_manager(&["Cargo.toml"]);
+        assert!(!mgr.args().bench);
+        assert_eq!(mgr.args().bench_iterations, 10);
+        let mgr = make_manager(&["--bench", "--bench-iterations=3", "Cargo.toml"]);
+        assert!(mgr.args().bench);
+        assert_eq!(mgr.args().bench_iterations, 3);
+    }
+
+    #[test]
+    fn test_configure_args() {
+        let mgr = make_manager(&[
+            "--configure",
+            "--non-interactive",
+            "--set",
+            "name=ci",
+            "--set=max_lines=50",
+            "--profile-out=ci.toml",
+        ]);
+        assert!(mgr.args().configure && mgr.args().non_interactive);
+        assert_eq!(mgr.args().set, ["name=ci", "max_lines=50"]);
+        assert_eq!(mgr.args().profile_out.as_deref(), Some("ci.toml"));
+        assert!(ConfigManager::from_args_vec(vec!["batless", "--set", "name=ci"]).is_err());
+    }
+
+    #[test]
+    fn test_docs_generation_args() {
+        assert!(make_manager(&["--generate-man"]).args().generate_man);
+        assert!(make_manager(&["--help-markdown"]).args().help_markdown);
+    }
+
+    #[test]
+    fn test_capabilities_arg() {
+        assert!(make_manager(&["--capabilities"]).args().capabilities);
+    }
+
+    #[test]
+    fn test_jobs_args() {
+        let mgr = make_manager(&["a.rs", "b.rs"]);
+        assert_eq!(mgr.args().jobs, 1);
+        assert!(!mgr.args().unordered);
+        let mgr = make_manager(&["--jobs=4", "--unordered", "a.rs", "b.rs"]);
+        assert_eq!(mgr.args().jobs, 4);
+        assert!(mgr.args().unordered);
+    }
+
+    #[test]
+    fn test_check_arg() {
+        assert!(make_manager(&["--check", "src/lib.rs"]).args().check);
+        assert!(
+            ConfigManager::from_args_vec(vec!["batless", "--check", "--check-config"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_profile_sharing_args() {
+        let mgr = make_manager(&[
+            "--import-profile",
+            "https://example.com/review.json",
+            "--import-as=team-review",
+            "--replace-profile",
+        ]);
+        assert_eq!(
+            mgr.args().import_profile.as_deref(),
+            Some("https://example.com/review.json")
+        );
+        assert_eq!(mgr.args().import_as.as_deref(), Some("team-review"));
+        assert!(mgr.args().replace_profile);
+        assert_eq!(
+            make_manager(&["--export-profile", "ci"])
+                .args()
+                .export_profile
+                .as_deref(),
+            Some("ci")
+        );
+        assert!(ConfigManager::from_args_vec(vec!["batless", "--import-as", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_fail_on_flags() {
+        let mgr = make_manager(&[
+            "--fail-on-truncation",
+            "--fail-on-binary",
+            "--fail-on-validation-error",
+            "Cargo.toml",
+        ]);
+        assert!(mgr.args().fail_on_truncation);
+        assert!(mgr.args().fail_on_binary);
+        assert!(mgr.args().fail_on_validation_error);
+    }
+
+    #[test]
+    fn test_ai_model_resolution() {
+        let mgr = make_manager(&["--ai-model=claude", "Cargo.toml"]);
+        assert_eq!(mgr.ai_model(), AiModel::Claude);
+        let mgr = make_manager(&["Cargo.toml"]);
+        assert_eq!(mgr.ai_model(), AiModel::Generic);
+        assert!(ConfigManager::from_args_vec([
+            "batless",
+            "--ai-model=no-such-model",
+            "Cargo.toml"
+        ])
+        .is_err());
+    }
+
     #[test]
     fn test_file_path_from_arg() {
         let mgr = make_manager(&["Cargo.toml"]);
@@ -824,6 +2212,11 @@ mod tests {
             OutputMode::Summary
         );
         assert_eq!(OutputMode::from_str("index").unwrap(), OutputMode::Index);
+        assert_eq!(
+            OutputMode::from_str("imports").unwrap(),
+            OutputMode::Imports
+        );
+        assert_eq!(OutputMode::from_str("jsonl").unwrap(), OutputMode::Jsonl);
         assert!(OutputMode::from_str("highlight").is_err());
         assert!(OutputMode::from_str("invalid").is_err());
     }
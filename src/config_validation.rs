@@ -5,16 +5,33 @@
 
 use crate::config::BatlessConfig;
 use crate::error::{BatlessError, BatlessResult};
+use crate::language::{LanguageDetector, LanguageMatch};
+
+/// Largest `max_lines` accepted
+pub const MAX_LINES_LIMIT: usize = 1_000_000;
 
 /// Validate a `BatlessConfig`, returning an error if any values are invalid.
 pub fn validate_config(config: &BatlessConfig) -> BatlessResult<()> {
     validate_max_lines(config)?;
     validate_max_bytes(config)?;
+    validate_max_memory(config)?;
+    validate_timeout(config)?;
+    validate_stdin_limits(config)?;
+    validate_sample_count(config)?;
+    validate_max_token_sample(config)?;
+    validate_highlight_line(config)?;
+    validate_symbol(config)?;
+    validate_uses(config)?;
     validate_language(config)?;
+    validate_language_map(config)?;
+    validate_grammars(config)?;
     validate_limits_combination(config)?;
     validate_streaming(config)?;
     validate_schema_version(config)?;
     validate_logical_combinations(config)?;
+    validate_permalinks(config)?;
+    validate_models(config)?;
+    validate_redact_patterns(config)?;
     Ok(())
 }
 
@@ -28,7 +45,7 @@ fn validate_max_lines(config: &BatlessConfig) -> BatlessResult<()> {
         ));
     }
 
-    if config.max_lines > 1_000_000 {
+    if config.max_lines > MAX_LINES_LIMIT {
         return Err(BatlessError::config_error_with_help(
             format!(
                 "max_lines is unusually large ({}). This may cause performance issues",
@@ -67,8 +84,142 @@ fn validate_max_bytes(config: &BatlessConfig) -> BatlessResult<()> {
     Ok(())
 }
 
+fn validate_max_memory(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.max_memory == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: max_memory must be greater than 0".to_string(),
+            Some("Try using --max-memory with a size (e.g., --max-memory 256MB)".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_timeout(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.timeout_ms == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: timeout must be greater than 0".to_string(),
+            Some("Try using --timeout with a duration (e.g., --timeout 5s)".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_stdin_limits(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.stdin_max_bytes == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: stdin_max_bytes must be greater than 0".to_string(),
+            Some(
+                "Try using --stdin-max-bytes with a size (e.g., --stdin-max-bytes 64MB)"
+                    .to_string(),
+            ),
+        ));
+    }
+    if config.stdin_timeout_ms == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: stdin_timeout must be greater than 0".to_string(),
+            Some(
+                "Try using --stdin-timeout with a duration (e.g., --stdin-timeout 10s)".to_string(),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_sample_count(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.sample_count == 0 {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: sample_count must be greater than 0".to_string(),
+            Some(
+                "Try using --sample-count with a small number (e.g., --sample-count 8)".to_string(),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_max_token_sample(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.max_token_sample == 0 {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: max_token_sample must be greater than 0".to_string(),
+            Some("Use --max-token-sample with a positive number (default 2048)".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_highlight_line(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.highlight_line == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: highlight_line must be greater than 0".to_string(),
+            Some("Line numbers start at 1 (e.g., --highlight-line 42)".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_symbol(config: &BatlessConfig) -> BatlessResult<()> {
+    let Some(symbol) = &config.symbol else {
+        return Ok(());
+    };
+    if symbol.trim().is_empty() {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: symbol must not be empty".to_string(),
+            Some("Name a function, method or type (e.g., --symbol parse_config)".to_string()),
+        ));
+    }
+    if config.around.is_some() {
+        return Err(BatlessError::config_error_with_help(
+            "validation failed: symbol and around cannot be combined".to_string(),
+            Some("Use --symbol to show a definition, or --around for a line window".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_uses(config: &BatlessConfig) -> BatlessResult<()> {
+    match &config.uses {
+        Some(name) if !crate::uses::is_identifier(name) => {
+            Err(BatlessError::config_error_with_help(
+                format!("validation failed: uses must be a single identifier, not '{name}'"),
+                Some("Give the bare name (e.g., --uses load rather than Config::load)".to_string()),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_redact_patterns(config: &BatlessConfig) -> BatlessResult<()> {
+    for pattern in &config.redact_patterns {
+        crate::redaction::compile_pattern(pattern)?;
+    }
+    Ok(())
+}
+
+fn validate_models(config: &BatlessConfig) -> BatlessResult<()> {
+    for (name, spec) in &config.models {
+        if spec.context_window == 0 {
+            return Err(BatlessError::config_error_with_help(
+                format!("validation failed: models.{name}.context_window must be greater than 0"),
+                Some("Set context_window to the model's token limit (e.g., 32000)".to_string()),
+            ));
+        }
+        if !spec.tokens_per_word.is_finite() || spec.tokens_per_word <= 0.0 {
+            return Err(BatlessError::config_error_with_help(
+                format!(
+                    "validation failed: models.{name}.tokens_per_word must be a positive number"
+                ),
+                Some("Typical values are between 1.2 and 1.6".to_string()),
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn validate_language(config: &BatlessConfig) -> BatlessResult<()> {
-    if let Some(ref language) = config.language {
+    for language in [&config.language, &config.stdin_language]
+        .into_iter()
+        .flatten()
+    {
         if language.is_empty() {
             return Err(BatlessError::config_error_with_help(
                 "language cannot be empty when specified".to_string(),
@@ -95,6 +246,58 @@ fn validate_language(config: &BatlessConfig) -> BatlessResult<()> {
         }
     }
 
+    if config.stdin_filename.as_deref() == Some("") {
+        return Err(BatlessError::config_error_with_help(
+            "stdin filename cannot be empty".to_string(),
+            Some(
+                "Pass the logical path of the piped input, e.g. --stdin-filename src/main.rs"
+                    .to_string(),
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_language_map(config: &BatlessConfig) -> BatlessResult<()> {
+    for (pattern, language) in &config.language_map {
+        if pattern.is_empty() {
+            return Err(BatlessError::config_error_with_help(
+                "validation failed: language_map patterns cannot be empty".to_string(),
+                Some("Use a file name or glob such as \"Justfile\" or \"*.star\"".to_string()),
+            ));
+        }
+        if config.has_grammar(language) {
+            continue;
+        }
+        let help = match LanguageDetector::resolve_language(language) {
+            Ok(LanguageMatch::Exact | LanguageMatch::Alias(_)) => continue,
+            Ok(LanguageMatch::Corrected(suggestion)) => format!("Did you mean '{suggestion}'?"),
+            Err(_) => "Use --list-languages to see options".to_string(),
+        };
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "validation failed: language_map maps '{pattern}' to unknown language '{language}'"
+            ),
+            Some(help),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_grammars(config: &BatlessConfig) -> BatlessResult<()> {
+    for (language, grammar) in &config.grammars {
+        let valid_grammar = !grammar.is_empty()
+            && grammar
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if language.is_empty() || !valid_grammar {
+            return Err(BatlessError::config_error_with_help(
+                format!("validation failed: invalid grammars entry '{language}' = '{grammar}'"),
+                Some("Map a language name to a grammar name, e.g. Gleam = \"gleam\"".to_string()),
+            ));
+        }
+    }
     Ok(())
 }
 
@@ -140,6 +343,43 @@ fn validate_streaming(config: &BatlessConfig) -> BatlessResult<()> {
         ));
     }
 
+    if config.max_chunks_per_second == Some(0) || config.max_chunks == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "max_chunks_per_second and max_chunks must be greater than 0".to_string(),
+            Some("Omit the option to stream without a limit".to_string()),
+        ));
+    }
+
+    if config.stream_token_budget == Some(0) {
+        return Err(BatlessError::config_error_with_help(
+            "stream token budget must be greater than 0".to_string(),
+            Some("Omit --stream-until-tokens to stream without a budget".to_string()),
+        ));
+    }
+
+    if let Some(template) = &config.template {
+        crate::template::validate(template).map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Invalid output template: {e}"),
+                Some(format!(
+                    "Available placeholders: {}",
+                    crate::template::PLACEHOLDERS
+                        .iter()
+                        .map(|name| format!("{{{name}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            )
+        })?;
+    }
+
+    if config.auto_stream_threshold == 0 {
+        return Err(BatlessError::config_error_with_help(
+            "auto_stream_threshold must be greater than 0".to_string(),
+            Some("Use --no-auto-stream to turn automatic streaming off".to_string()),
+        ));
+    }
+
     // Validate streaming options combination
     if config.streaming_json
         && config.enable_resume
@@ -184,6 +424,29 @@ fn validate_logical_combinations(config: &BatlessConfig) -> BatlessResult<()> {
     Ok(())
 }
 
+fn validate_permalinks(config: &BatlessConfig) -> BatlessResult<()> {
+    if config.link_format.is_some() && config.repo_url.is_none() {
+        return Err(BatlessError::config_error_with_help(
+            "link_format requires repo_url to be set".to_string(),
+            Some(
+                "Pass --repo-url with the repository base URL (e.g., --repo-url https://github.com/org/repo)"
+                    .to_string(),
+            ),
+        ));
+    }
+
+    if let Some(ref url) = config.repo_url {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(BatlessError::config_error_with_help(
+                format!("repo_url must be an http(s) URL: '{url}'"),
+                Some("Use the repository's web URL, e.g. https://gitlab.com/org/repo".to_string()),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +469,12 @@ mod tests {
         assert!(validate_config(&config).is_err());
     }
 
+    #[test]
+    fn test_validation_zero_max_token_sample() {
+        let config = BatlessConfig::default().with_max_token_sample(0);
+        assert!(validate_config(&config).is_err());
+    }
+
     #[test]
     fn test_validation_large_max_lines() {
         let config = BatlessConfig::default().with_max_lines(2_000_000);
@@ -337,4 +606,42 @@ mod tests {
             .to_string()
             .contains("max_lines should be larger than chunk_size"));
     }
+
+    #[test]
+    fn test_validation_link_format_requires_repo_url() {
+        use crate::config::LinkFormat;
+        let config = BatlessConfig::default().with_link_format(Some(LinkFormat::Github));
+        let result = validate_config(&config);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("requires repo_url"));
+
+        let config = config.with_repo_url(Some("git@github.com:org/repo".to_string()));
+        assert!(validate_config(&config).is_err());
+
+        let config = config.with_repo_url(Some("https://github.com/org/repo".to_string()));
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validation_custom_models() {
+        use crate::tokens::CustomModelSpec;
+        let mut config = BatlessConfig::default();
+        config.models.insert(
+            "my-llm".to_string(),
+            CustomModelSpec {
+                context_window: 0,
+                tokens_per_word: 1.4,
+            },
+        );
+        assert!(validate_config(&config).is_err());
+
+        config.models.get_mut("my-llm").unwrap().context_window = 32_000;
+        assert!(validate_config(&config).is_ok());
+
+        config.models.get_mut("my-llm").unwrap().tokens_per_word = 0.0;
+        assert!(validate_config(&config).is_err());
+    }
 }
@@ -0,0 +1,328 @@
+//! Schema summaries for SQLite databases and Parquet files
+//! (`sqlite` and `parquet` features)
+//!
+//! Datasets are binary, so reading them as text only produces noise. When a
+//! file starts with the SQLite or Parquet signature, batless reports its
+//! tables, columns, types and row counts instead. Without the matching
+//! feature the format is still recognised and a size-only stub is printed.
+
+use crate::error::{BatlessError, BatlessResult};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+/// Schema of a dataset file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetSchema {
+    /// `sqlite` or `parquet`
+    pub format: String,
+    pub size_bytes: u64,
+    /// Whether tables were read; false when the feature is not compiled in
+    pub schema_available: bool,
+    pub tables: Vec<TableSchema>,
+}
+
+/// A table (SQLite) or the file's single record schema (Parquet)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub name: String,
+    /// `table` or `view` for SQLite, `file` for Parquet
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_count: Option<u64>,
+    pub columns: Vec<ColumnSchema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub primary_key: bool,
+}
+
+impl DatasetSchema {
+    fn stub(format: &str, size_bytes: u64) -> Self {
+        Self {
+            format: format.to_string(),
+            size_bytes,
+            schema_available: false,
+            tables: Vec::new(),
+        }
+    }
+
+    /// Text rendering: a header line, then each table and its columns
+    pub fn render_lines(&self) -> Vec<String> {
+        let name = if self.format == "sqlite" {
+            "SQLite database"
+        } else {
+            "Parquet file"
+        };
+        if !self.schema_available {
+            return vec![format!(
+                "[{name}, {} bytes; build with --features {} for its schema]",
+                self.size_bytes, self.format
+            )];
+        }
+        let mut lines = vec![format!(
+            "[{name}, {} table(s), {} bytes]",
+            self.tables.len(),
+            self.size_bytes
+        )];
+        for table in &self.tables {
+            lines.push(match table.row_count {
+                Some(rows) => format!("{} {} ({rows} rows)", table.kind, table.name),
+                None => format!("{} {}", table.kind, table.name),
+            });
+            for column in &table.columns {
+                let mut line = format!("  {} {}", column.name, column.data_type);
+                if column.primary_key {
+                    line.push_str(" PRIMARY KEY");
+                } else if !column.nullable {
+                    line.push_str(" NOT NULL");
+                }
+                lines.push(line);
+            }
+        }
+        lines
+    }
+}
+
+/// Read the schema of `path` if it is a SQLite database or Parquet file
+pub fn probe(path: &str) -> BatlessResult<Option<DatasetSchema>> {
+    let read_error = |e| BatlessError::FileReadError {
+        path: path.to_string(),
+        source: e,
+    };
+    let mut file = File::open(path).map_err(read_error)?;
+    let size = file.metadata().map_err(read_error)?.len();
+    let mut header = Vec::new();
+    (&mut file)
+        .take(SQLITE_MAGIC.len() as u64)
+        .read_to_end(&mut header)
+        .map_err(read_error)?;
+    if header.starts_with(SQLITE_MAGIC) {
+        return sqlite_schema(path, size).map(Some);
+    }
+    if header.starts_with(PARQUET_MAGIC) {
+        return parquet_schema(path, file, size).map(Some);
+    }
+    Ok(None)
+}
+
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+fn dataset_error(path: &str, format: &str, error: &impl std::fmt::Display) -> BatlessError {
+    BatlessError::processing_error_for_path(path, format!("cannot read {format} schema: {error}"))
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_schema(path: &str, size: u64) -> BatlessResult<DatasetSchema> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let error = |e: rusqlite::Error| dataset_error(path, "SQLite", &e);
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(error)?;
+    let mut statement = conn
+        .prepare(
+            "SELECT name, type FROM sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .map_err(error)?;
+    let objects = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(error)?;
+
+    let mut tables = Vec::new();
+    for (name, kind) in objects {
+        let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+        let mut info = conn
+            .prepare(&format!("PRAGMA table_info({quoted})"))
+            .map_err(error)?;
+        let columns = info
+            .query_map([], |row| {
+                Ok(ColumnSchema {
+                    name: row.get(1)?,
+                    data_type: row.get(2)?,
+                    nullable: row.get::<_, i64>(3)? == 0,
+                    primary_key: row.get::<_, i64>(5)? > 0,
+                })
+            })
+            .map_err(error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        let row_count = if kind == "table" {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {quoted}"), [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .ok()
+            .and_then(|count| u64::try_from(count).ok())
+        } else {
+            None
+        };
+        tables.push(TableSchema {
+            name,
+            kind,
+            row_count,
+            columns,
+        });
+    }
+    Ok(DatasetSchema {
+        schema_available: true,
+        tables,
+        ..DatasetSchema::stub("sqlite", size)
+    })
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_schema(_path: &str, size: u64) -> BatlessResult<DatasetSchema> {
+    Ok(DatasetSchema::stub("sqlite", size))
+}
+
+#[cfg(feature = "parquet")]
+fn parquet_schema(path: &str, file: File, size: u64) -> BatlessResult<DatasetSchema> {
+    use parquet::basic::ConvertedType;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let reader = SerializedFileReader::new(file).map_err(|e| dataset_error(path, "Parquet", &e))?;
+    let metadata = reader.metadata().file_metadata();
+    let columns = metadata
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| {
+            let physical = column.physical_type().to_string();
+            let data_type = match column.converted_type() {
+                ConvertedType::NONE => physical,
+                converted => format!("{physical} ({converted})"),
+            };
+            ColumnSchema {
+                name: column.path().string(),
+                data_type,
+                nullable: column.self_type().is_optional(),
+                primary_key: false,
+            }
+        })
+        .collect();
+    let name = std::path::Path::new(path).file_stem().map_or_else(
+        || path.to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    Ok(DatasetSchema {
+        schema_available: true,
+        tables: vec![TableSchema {
+            name,
+            kind: "file".to_string(),
+            row_count: u64::try_from(metadata.num_rows()).ok(),
+            columns,
+        }],
+        ..DatasetSchema::stub("parquet", size)
+    })
+}
+
+#[cfg(not(feature = "parquet"))]
+fn parquet_schema(_path: &str, _file: File, size: u64) -> BatlessResult<DatasetSchema> {
+    Ok(DatasetSchema::stub("parquet", size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_plain_files_are_not_datasets() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"PAR is not enough").unwrap();
+        assert!(probe(file.path().to_str().unwrap()).unwrap().is_none());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_tables_columns_and_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL, bio TEXT);
+             INSERT INTO users (email) VALUES ('a@x'), ('b@x');
+             CREATE VIEW emails AS SELECT email FROM users;",
+        )
+        .unwrap();
+        drop(conn);
+
+        let schema = probe(path.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(schema.format, "sqlite");
+        let names: Vec<&str> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["emails", "users"]);
+        assert_eq!(schema.tables[1].row_count, Some(2));
+        assert_eq!(schema.tables[0].row_count, None);
+        let lines = schema.render_lines();
+        assert!(lines[0].starts_with("[SQLite database, 2 table(s), "));
+        assert!(lines.contains(&"table users (2 rows)".to_string()));
+        assert!(lines.contains(&"  id INTEGER PRIMARY KEY".to_string()));
+        assert!(lines.contains(&"  email TEXT NOT NULL".to_string()));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_columns_and_rows() {
+        use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use std::sync::Arc;
+
+        let schema = Arc::new(
+            parquet::schema::parser::parse_message_type(
+                "message trips { REQUIRED INT64 id; OPTIONAL BYTE_ARRAY city (UTF8); }",
+            )
+            .unwrap(),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trips.parquet");
+        let mut writer = SerializedFileWriter::new(
+            File::create(&path).unwrap(),
+            schema,
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[1, 2, 3], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("Oslo")], Some(&[1, 0, 0]), None)
+            .unwrap();
+        column.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let schema = probe(path.to_str().unwrap()).unwrap().unwrap();
+        let table = &schema.tables[0];
+        assert_eq!(table.name, "trips");
+        assert_eq!(table.row_count, Some(3));
+        assert_eq!(
+            schema.render_lines()[1..],
+            [
+                "file trips (3 rows)",
+                "  id INT64 NOT NULL",
+                "  city BYTE_ARRAY (UTF8)"
+            ]
+        );
+    }
+}
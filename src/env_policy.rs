@@ -0,0 +1,144 @@
+//! Terminal conventions read from the environment
+//!
+//! Honours `NO_COLOR`, `CLICOLOR` / `CLICOLOR_FORCE`, `TERM=dumb` and `CI`,
+//! plus whether the stream is a terminal, so logs captured by CI systems and
+//! dumb terminals get plain ASCII instead of escape codes and emoji glyphs.
+
+use std::io::IsTerminal;
+
+/// The environment variables that affect color and glyphs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermEnv {
+    /// `NO_COLOR` is set to a non-empty value
+    pub no_color: bool,
+    /// `CLICOLOR=0`
+    pub clicolor_off: bool,
+    /// `CLICOLOR_FORCE` is set to anything but `0`
+    pub clicolor_force: bool,
+    /// `TERM=dumb`
+    pub dumb_term: bool,
+    /// `CI` is set to anything but `false` or `0`
+    pub ci: bool,
+}
+
+impl TermEnv {
+    /// Snapshot of the current process environment
+    pub fn from_env() -> Self {
+        let var = |key: &str| std::env::var(key).ok().filter(|value| !value.is_empty());
+        Self {
+            no_color: var("NO_COLOR").is_some(),
+            clicolor_off: var("CLICOLOR").is_some_and(|value| value == "0"),
+            clicolor_force: var("CLICOLOR_FORCE").is_some_and(|value| value != "0"),
+            dumb_term: var("TERM").is_some_and(|value| value == "dumb"),
+            ci: var("CI").is_some_and(|value| !matches!(value.as_str(), "false" | "0")),
+        }
+    }
+
+    /// Whether a stream may be colored; `NO_COLOR` beats `CLICOLOR_FORCE`,
+    /// which beats every other signal
+    pub const fn color(self, is_terminal: bool) -> bool {
+        if self.no_color {
+            return false;
+        }
+        if self.clicolor_force {
+            return true;
+        }
+        is_terminal && !self.clicolor_off && !self.dumb_term && !self.ci
+    }
+
+    /// Whether status messages may use emoji and other non-ASCII glyphs
+    pub const fn emoji(self, is_terminal: bool) -> bool {
+        is_terminal && !self.dumb_term && !self.ci
+    }
+}
+
+/// Decoration of status messages written to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusStyle {
+    pub color: bool,
+    pub emoji: bool,
+}
+
+impl StatusStyle {
+    /// Style for stderr under the current environment; `no_emoji` is `--no-emoji`
+    pub fn detect(no_emoji: bool) -> Self {
+        let env = TermEnv::from_env();
+        let is_terminal = std::io::stderr().is_terminal();
+        Self {
+            color: env.color(is_terminal),
+            emoji: !no_emoji && env.emoji(is_terminal),
+        }
+    }
+
+    /// `emoji` when glyphs are allowed, otherwise the ASCII fallback
+    pub const fn glyph(self, emoji: &'static str, ascii: &'static str) -> &'static str {
+        if self.emoji {
+            emoji
+        } else {
+            ascii
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_conventions() {
+        let env = TermEnv::default();
+        assert!(env.color(true));
+        assert!(!env.color(false));
+        for env in [
+            TermEnv {
+                dumb_term: true,
+                ..env
+            },
+            TermEnv { ci: true, ..env },
+            TermEnv {
+                clicolor_off: true,
+                ..env
+            },
+        ] {
+            assert!(!env.color(true));
+        }
+        let forced = TermEnv {
+            clicolor_force: true,
+            ci: true,
+            ..env
+        };
+        assert!(forced.color(false));
+        assert!(!TermEnv {
+            no_color: true,
+            ..forced
+        }
+        .color(true));
+    }
+
+    #[test]
+    fn test_emoji_falls_back_to_ascii() {
+        let env = TermEnv::default();
+        assert!(env.emoji(true));
+        assert!(!env.emoji(false));
+        assert!(!TermEnv { ci: true, ..env }.emoji(true));
+        assert!(!TermEnv {
+            dumb_term: true,
+            ..env
+        }
+        .emoji(true));
+
+        let style = StatusStyle {
+            color: false,
+            emoji: false,
+        };
+        assert_eq!(style.glyph("✓", "yes"), "yes");
+        assert_eq!(
+            StatusStyle {
+                emoji: true,
+                ..style
+            }
+            .glyph("✓", "yes"),
+            "✓"
+        );
+    }
+}
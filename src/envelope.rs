@@ -0,0 +1,197 @@
+//! Metadata envelopes for vector databases (`--envelope rag`)
+//!
+//! Each file, or each `--chunk-for-rag` chunk, becomes one JSON line of
+//! `{"id", "text", "metadata"}`, the document shape LangChain, LlamaIndex
+//! and most vector store loaders read as-is. Absent metadata is left out
+//! rather than written as `null`, which several stores reject.
+
+use crate::chunker::RagChunk;
+use crate::permalink::Permalinker;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+
+/// One document for a vector store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RagEnvelope {
+    /// Stable id: the path, plus the line range for chunks
+    pub id: String,
+    pub text: String,
+    pub metadata: RagMetadata,
+}
+
+/// Where a document came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RagMetadata {
+    /// `--repo-url`, else the `origin` remote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Repository-relative inside a git checkout, else as given
+    pub path: String,
+    /// Commit checked out when the file was read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Definition the text belongs to (`Config::load`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_path: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// `sha256:` digest of `text`, for skipping unchanged documents
+    pub hash: String,
+}
+
+/// Repository facts shared by every envelope of one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RagSource {
+    pub repo: Option<String>,
+    pub path: String,
+    pub commit: Option<String>,
+    pub language: Option<String>,
+}
+
+impl RagSource {
+    /// Look up the repository, commit and relative path of `file_path`
+    /// with git; standard input and files outside a checkout keep only
+    /// what was given
+    pub fn detect(file_path: &str, language: Option<String>, repo_url: Option<&str>) -> Self {
+        let in_git = file_path != "-";
+        let dir = Permalinker::containing_dir(file_path);
+        let git = |args: &[&str]| in_git.then(|| Permalinker::git_output(dir, args)).flatten();
+        let path = in_git
+            .then(|| Permalinker::repo_relative_path(file_path))
+            .flatten()
+            .unwrap_or_else(|| file_path.replace('\\', "/"));
+        Self {
+            repo: repo_url
+                .map(str::to_string)
+                .or_else(|| git(&["remote", "get-url", "origin"])),
+            commit: git(&["rev-parse", "HEAD"]),
+            path,
+            language,
+        }
+    }
+
+    /// Envelope of a whole file
+    pub fn file(&self, lines: &[String]) -> RagEnvelope {
+        let text = lines.join("\n");
+        RagEnvelope {
+            id: self.path.clone(),
+            metadata: self.metadata(&text, None, Vec::new(), None, 1, lines.len()),
+            text,
+        }
+    }
+
+    /// Envelope of one `--chunk-for-rag` chunk
+    pub fn chunk(&self, chunk: RagChunk) -> RagEnvelope {
+        let symbol_path = chunk.context.or_else(|| chunk.symbols.first().cloned());
+        RagEnvelope {
+            id: format!("{}#L{}-L{}", self.path, chunk.start_line, chunk.end_line),
+            metadata: self.metadata(
+                &chunk.text,
+                symbol_path,
+                chunk.symbols,
+                Some(chunk.chunk_index),
+                chunk.start_line,
+                chunk.end_line,
+            ),
+            text: chunk.text,
+        }
+    }
+
+    fn metadata(
+        &self,
+        text: &str,
+        symbol_path: Option<String>,
+        symbols: Vec<String>,
+        chunk_index: Option<usize>,
+        start_line: usize,
+        end_line: usize,
+    ) -> RagMetadata {
+        RagMetadata {
+            repo: self.repo.clone(),
+            path: self.path.clone(),
+            commit: self.commit.clone(),
+            language: self.language.clone(),
+            symbol_path,
+            symbols,
+            chunk_index,
+            start_line,
+            end_line,
+            hash: Sha256::digest(text.as_bytes()).iter().fold(
+                String::from("sha256:"),
+                |mut hash, byte| {
+                    let _ = write!(hash, "{byte:02x}");
+                    hash
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> RagSource {
+        RagSource {
+            repo: Some("https://github.com/org/repo".to_string()),
+            path: "src/lib.rs".to_string(),
+            commit: None,
+            language: Some("Rust".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_file_envelope() {
+        let lines = vec!["fn a() {}".to_string(), "fn b() {}".to_string()];
+        let envelope = source().file(&lines);
+        assert_eq!(envelope.id, "src/lib.rs");
+        assert_eq!(envelope.text, "fn a() {}\nfn b() {}");
+        assert_eq!(envelope.metadata.end_line, 2);
+        assert_eq!(envelope.metadata.hash.len(), "sha256:".len() + 64);
+
+        let json = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(json["metadata"]["repo"], "https://github.com/org/repo");
+        // Missing values are omitted, not null
+        assert!(json["metadata"].get("commit").is_none());
+        assert!(json["metadata"].get("chunk_index").is_none());
+    }
+
+    #[test]
+    fn test_chunk_envelope_uses_symbol_context() {
+        let chunk = RagChunk {
+            file: "src/lib.rs".to_string(),
+            chunk_index: 3,
+            start_line: 10,
+            end_line: 20,
+            start_byte: 100,
+            end_byte: 300,
+            tokens: 50,
+            model: "generic".to_string(),
+            context: None,
+            symbols: vec!["Config::load".to_string(), "Config::save".to_string()],
+            text: "body".to_string(),
+        };
+        let envelope = source().chunk(chunk);
+        assert_eq!(envelope.id, "src/lib.rs#L10-L20");
+        assert_eq!(
+            envelope.metadata.symbol_path.as_deref(),
+            Some("Config::load")
+        );
+        assert_eq!(envelope.metadata.chunk_index, Some(3));
+    }
+
+    #[test]
+    fn test_detect_outside_git() {
+        let source = RagSource::detect("-", None, None);
+        assert_eq!(source.path, "-");
+        assert_eq!(source.repo, None);
+        assert_eq!(source.commit, None);
+    }
+}
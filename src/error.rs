@@ -3,6 +3,7 @@
 //! This module provides specific error types to improve error handling
 //! and provide better error messages to users.
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::path::Path;
 
@@ -14,6 +15,7 @@ pub enum ErrorCode {
     FileReadError = 102,
     PermissionDenied = 103,
     EncodingError = 104,
+    FileChangedDuringProcessing = 105,
 
     /// Language errors (200-299)
     LanguageNotFound = 203,
@@ -22,6 +24,9 @@ pub enum ErrorCode {
     /// Processing errors (300-399)
     ProcessingError = 301,
     ConfigurationError = 302,
+    MemoryLimitExceeded = 303,
+    StdinLimitExceeded = 304,
+    StdinTimeout = 305,
 
     /// Output errors (400-499)
     JsonSerializationError = 401,
@@ -39,10 +44,14 @@ impl ErrorCode {
             Self::FileReadError => "E102",
             Self::PermissionDenied => "E103",
             Self::EncodingError => "E104",
+            Self::FileChangedDuringProcessing => "E105",
             Self::LanguageNotFound => "E203",
             Self::LanguageDetectionError => "E204",
             Self::ProcessingError => "E301",
             Self::ConfigurationError => "E302",
+            Self::MemoryLimitExceeded => "E303",
+            Self::StdinLimitExceeded => "E304",
+            Self::StdinTimeout => "E305",
             Self::JsonSerializationError => "E401",
             Self::OutputError => "E402",
             Self::IoError => "E501",
@@ -66,6 +75,11 @@ pub enum BatlessError {
         path: String,
         help: String,
     },
+    /// The file changed while it was streamed, or since its checkpoint
+    FileChangedDuringProcessing {
+        path: String,
+        change: String,
+    },
 
     /// Language errors
     LanguageNotFound {
@@ -91,6 +105,19 @@ pub enum BatlessError {
         message: String,
         help: Option<String>,
     },
+    MemoryLimitExceeded {
+        path: String,
+        limit: usize,
+        required: usize,
+    },
+    /// Stdin sent more than `--stdin-max-bytes`
+    StdinLimitExceeded {
+        limit: usize,
+    },
+    /// Stdin was not closed within `--stdin-timeout`
+    StdinTimeout {
+        timeout_ms: u64,
+    },
 
     /// Output formatting errors
     JsonSerializationError(serde_json::Error),
@@ -128,6 +155,17 @@ impl fmt::Display for BatlessError {
                     error_code.as_str()
                 )
             }
+            Self::FileChangedDuringProcessing { path, change } => {
+                write!(
+                    f,
+                    "[{}] File '{path}' changed during processing: {change}",
+                    error_code.as_str()
+                )?;
+                write!(
+                    f,
+                    "\n\nHelp: Re-run once the file stops changing, or pass --restart-on-change to start the stream over"
+                )
+            }
             Self::LanguageNotFound {
                 language,
                 suggestions,
@@ -189,6 +227,43 @@ impl fmt::Display for BatlessError {
                 }
                 Ok(())
             }
+            Self::MemoryLimitExceeded {
+                path,
+                limit,
+                required,
+            } => {
+                write!(
+                    f,
+                    "[{}] Memory limit exceeded for '{path}': needs at least {required} bytes, limit is {limit} bytes",
+                    error_code.as_str()
+                )?;
+                write!(
+                    f,
+                    "\n\nHelp: Use --streaming-json to process the file in bounded chunks, lower --max-lines/--max-bytes, or raise --max-memory"
+                )
+            }
+            Self::StdinLimitExceeded { limit } => {
+                write!(
+                    f,
+                    "[{}] Stdin exceeded the limit of {limit} bytes",
+                    error_code.as_str()
+                )?;
+                write!(
+                    f,
+                    "\n\nHelp: Raise --stdin-max-bytes, or write the input to a file and pass its path"
+                )
+            }
+            Self::StdinTimeout { timeout_ms } => {
+                write!(
+                    f,
+                    "[{}] Stdin was not closed within {timeout_ms}ms",
+                    error_code.as_str()
+                )?;
+                write!(
+                    f,
+                    "\n\nHelp: Check that the producer finishes, or raise --stdin-timeout"
+                )
+            }
             Self::JsonSerializationError(err) => {
                 write!(
                     f,
@@ -224,11 +299,15 @@ impl BatlessError {
             Self::FileNotFound { .. } => ErrorCode::FileNotFound,
             Self::FileReadError { .. } => ErrorCode::FileReadError,
             Self::PermissionDenied { .. } => ErrorCode::PermissionDenied,
+            Self::FileChangedDuringProcessing { .. } => ErrorCode::FileChangedDuringProcessing,
             Self::LanguageNotFound { .. } => ErrorCode::LanguageNotFound,
             Self::LanguageDetectionError { .. } => ErrorCode::LanguageDetectionError,
             Self::EncodingError { .. } => ErrorCode::EncodingError,
             Self::ProcessingError { .. } => ErrorCode::ProcessingError,
             Self::ConfigurationError { .. } => ErrorCode::ConfigurationError,
+            Self::MemoryLimitExceeded { .. } => ErrorCode::MemoryLimitExceeded,
+            Self::StdinLimitExceeded { .. } => ErrorCode::StdinLimitExceeded,
+            Self::StdinTimeout { .. } => ErrorCode::StdinTimeout,
             Self::JsonSerializationError(_) => ErrorCode::JsonSerializationError,
             Self::OutputError(_) => ErrorCode::OutputError,
             Self::IoError(_) => ErrorCode::IoError,
@@ -246,7 +325,23 @@ impl BatlessError {
         language: String,
         available_languages: &[String],
     ) -> Self {
-        let suggestions = Self::suggest_similar_strings(&language, available_languages);
+        // Always offer the closest names, even when none is a near miss
+        let lowered = language.to_lowercase();
+        let mut scored: Vec<_> = available_languages
+            .iter()
+            .map(|name| {
+                (
+                    Self::levenshtein_distance(&lowered, &name.to_lowercase()),
+                    name,
+                )
+            })
+            .collect();
+        scored.sort_by_key(|(distance, _)| *distance);
+        let suggestions = scored
+            .into_iter()
+            .take(3)
+            .map(|(_, name)| name.clone())
+            .collect();
         Self::LanguageNotFound {
             language,
             suggestions,
@@ -307,6 +402,15 @@ impl BatlessError {
         }
     }
 
+    /// Create a MemoryLimitExceeded error for a file
+    pub fn memory_limit_exceeded(path: impl Into<String>, limit: usize, required: usize) -> Self {
+        Self::MemoryLimitExceeded {
+            path: path.into(),
+            limit,
+            required,
+        }
+    }
+
     /// Create an EncodingError for a file
     pub fn encoding_error(path: impl Into<String>, details: impl Into<String>) -> Self {
         Self::EncodingError {
@@ -384,7 +488,7 @@ impl BatlessError {
     }
 
     /// Suggest similar strings from a list
-    fn suggest_similar_strings(target: &str, candidates: &[String]) -> Vec<String> {
+    pub(crate) fn suggest_similar_strings(target: &str, candidates: &[String]) -> Vec<String> {
         let mut scored: Vec<_> = candidates
             .iter()
             .filter_map(|candidate| {
@@ -417,7 +521,7 @@ impl BatlessError {
     }
 
     /// Calculate Levenshtein distance between two strings
-    fn levenshtein_distance(a: &str, b: &str) -> usize {
+    pub(crate) fn levenshtein_distance(a: &str, b: &str) -> usize {
         let a_chars: Vec<char> = a.chars().collect();
         let b_chars: Vec<char> = b.chars().collect();
         let a_len = a_chars.len();
@@ -461,6 +565,171 @@ impl From<serde_json::Error> for BatlessError {
 /// Result type alias for batless operations
 pub type BatlessResult<T> = Result<T, BatlessError>;
 
+/// Non-fatal configuration problem, reported on stderr without aborting processing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigurationWarning {
+    /// A key the config schema does not know; serde would silently ignore it
+    UnknownKey {
+        path: String,
+        key: String,
+        suggestion: Option<String>,
+    },
+    /// A key that still works but has a replacement
+    DeprecatedKey {
+        path: String,
+        key: String,
+        replacement: &'static str,
+    },
+    /// A setting locked by the workspace file was changed by a user config
+    /// file or option; the workspace value is kept
+    LockedKey { path: String, key: String },
+}
+
+impl ConfigurationWarning {
+    /// The offending key
+    pub fn key(&self) -> &str {
+        match self {
+            Self::UnknownKey { key, .. }
+            | Self::DeprecatedKey { key, .. }
+            | Self::LockedKey { key, .. } => key,
+        }
+    }
+
+    /// Description without the file path
+    pub fn message(&self) -> String {
+        match self {
+            Self::UnknownKey {
+                key,
+                suggestion: Some(suggestion),
+                ..
+            } => format!("unknown key '{key}' is ignored (did you mean '{suggestion}'?)"),
+            Self::UnknownKey { key, .. } => format!("unknown key '{key}' is ignored"),
+            Self::DeprecatedKey {
+                key, replacement, ..
+            } => format!("'{key}' is deprecated; use '{replacement}' instead"),
+            Self::LockedKey { key, .. } => {
+                format!("'{key}' is locked by the workspace; your value is ignored")
+            }
+        }
+    }
+
+    /// Stable code of this warning
+    pub const fn code(&self) -> WarningCode {
+        match self {
+            Self::UnknownKey { .. } => WarningCode::UnknownConfigKey,
+            Self::DeprecatedKey { .. } => WarningCode::DeprecatedConfigKey,
+            Self::LockedKey { .. } => WarningCode::LockedConfigKey,
+        }
+    }
+
+    /// As a coded warning, keeping the file path in the message
+    pub fn to_warning(&self) -> Warning {
+        Warning::new(self.code(), self.to_string())
+    }
+}
+
+impl fmt::Display for ConfigurationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Self::UnknownKey { path, .. }
+        | Self::DeprecatedKey { path, .. }
+        | Self::LockedKey { path, .. }) = self;
+        write!(f, "{path}: {}", self.message())
+    }
+}
+
+/// Warning codes for programmatic handling; a code keeps its meaning once
+/// released
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarningCode {
+    /// Configuration (W100-W199)
+    #[serde(rename = "W101")]
+    UnknownConfigKey,
+    #[serde(rename = "W102")]
+    DeprecatedConfigKey,
+    #[serde(rename = "W103")]
+    LockedConfigKey,
+    /// A saved profile could not be loaded and was skipped
+    #[serde(rename = "W104")]
+    ProfileSkipped,
+
+    /// Processing (W200-W299)
+    /// The tree-sitter summary failed, so regex patterns were used
+    #[serde(rename = "W201")]
+    AstFallback,
+    /// `--timeout` expired; the output is partial
+    #[serde(rename = "W202")]
+    TimedOut,
+    /// Limits cut the input, so `--chunk-for-rag` chunks miss its end
+    #[serde(rename = "W203")]
+    ChunksTruncated,
+
+    /// Output (W400-W499)
+    /// JSON output does not match its schema
+    #[serde(rename = "W401")]
+    SchemaMismatch,
+}
+
+impl WarningCode {
+    /// Get the warning code as a string for display
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::UnknownConfigKey => "W101",
+            Self::DeprecatedConfigKey => "W102",
+            Self::LockedConfigKey => "W103",
+            Self::ProfileSkipped => "W104",
+            Self::AstFallback => "W201",
+            Self::TimedOut => "W202",
+            Self::ChunksTruncated => "W203",
+            Self::SchemaMismatch => "W401",
+        }
+    }
+}
+
+/// Non-fatal problem with a stable code, printed as
+/// `batless: warning: [W202] ...` (or a JSON line with `--error-format json`)
+/// and listed in the `warnings` of JSON output when it concerns one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning: [{}] {}", self.code.as_str(), self.message)
+    }
+}
+
+/// Process exit codes for the `--fail-on-*` outcome checks.
+///
+/// Output is still written before exiting with one of these codes. They are
+/// distinct from the codes batless exits with when an error is raised.
+pub mod exit_codes {
+    /// Normal completion
+    pub const SUCCESS: i32 = 0;
+    /// `--fail-on-truncation`: output was cut short by a line, byte or context limit
+    pub const TRUNCATED: i32 = 10;
+    /// `--fail-on-binary`: input looks like a binary file
+    pub const BINARY: i32 = 11;
+    /// `--fail-on-validation-error`: JSON output failed schema validation
+    pub const VALIDATION_FAILED: i32 = 12;
+    /// `--check-config`: a config file or profile has errors
+    pub const CONFIG_INVALID: i32 = 13;
+    /// `--fail-on-syntax-errors`: tree-sitter found parse errors in the input
+    pub const SYNTAX_ERRORS: i32 = 14;
+    /// `--verify-goldens`: output removed or changed a field of the golden corpus
+    pub const GOLDENS_CHANGED: i32 = 15;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -608,6 +877,38 @@ mod tests {
         assert!(display.contains("invalid UTF-8 sequence"));
     }
 
+    #[test]
+    fn test_memory_limit_exceeded_helper() {
+        let error = BatlessError::memory_limit_exceeded("huge.log", 1024, 4096);
+        assert_eq!(error.error_code(), ErrorCode::MemoryLimitExceeded);
+        let display = error.to_string();
+        assert!(display.contains("[E303]"));
+        assert!(display.contains("huge.log"));
+        assert!(display.contains("--streaming-json"));
+    }
+
+    #[test]
+    fn test_stdin_limit_errors() {
+        let error = BatlessError::StdinLimitExceeded { limit: 1024 };
+        assert_eq!(error.error_code(), ErrorCode::StdinLimitExceeded);
+        let display = error.to_string();
+        assert!(display.contains("[E304]") && display.contains("1024 bytes"));
+        let display = BatlessError::StdinTimeout { timeout_ms: 500 }.to_string();
+        assert!(display.contains("[E305]") && display.contains("500ms"));
+    }
+
+    #[test]
+    fn test_file_changed_error() {
+        let error = BatlessError::FileChangedDuringProcessing {
+            path: "app.log".to_string(),
+            change: "size changed from 10 to 20 bytes".to_string(),
+        };
+        assert_eq!(error.error_code(), ErrorCode::FileChangedDuringProcessing);
+        let display = error.to_string();
+        assert!(display.contains("[E105]") && display.contains("app.log"));
+        assert!(display.contains("--restart-on-change"));
+    }
+
     #[test]
     fn test_from_io_error_helper() {
         // NotFound should produce FileNotFound
@@ -0,0 +1,614 @@
+//! Batch export of many files to a directory (`--export`)
+//!
+//! Ingestion pipelines want one artifact per source file rather than one
+//! stream on stdout. Every input is rendered as JSON (or Markdown) under the
+//! output directory, mirroring its path, and `manifest.json` indexes the
+//! artifacts with the SHA-256 of each source. A later run skips sources whose
+//! hash and settings are unchanged. The manifest is saved as files finish, so
+//! an interrupted export picks up where it stopped.
+
+use crate::canonical_json::to_json_string;
+use crate::config::BatlessConfig;
+use crate::error::{BatlessError, BatlessResult};
+use crate::file_info::FileInfo;
+use crate::formatter::{OutputFormatter, OutputMode};
+use crate::language::LanguageDetector;
+use crate::processor::FileProcessor;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+/// Index of the artifacts, written in the output directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Finished files between two manifest checkpoints
+const CHECKPOINT_EVERY: usize = 32;
+
+/// Artifact written for each source file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// The `--mode=json` document of the file
+    #[default]
+    Json,
+    /// A heading and a fenced code block
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Extension appended to the source file name
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// One exported source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source: String,
+    /// Relative to the output directory, with `/` separators
+    pub artifact: String,
+    /// `sha256:` digest of the source bytes
+    pub hash: String,
+    pub bytes: u64,
+    pub lines: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+/// A source file that could not be exported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportError {
+    pub source: String,
+    pub error: String,
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub tool_version: String,
+    pub schema_version: String,
+    pub format: ExportFormat,
+    /// Digest of the version, format and settings the artifacts were
+    /// rendered with; when it changes every file is exported again
+    pub config_hash: String,
+    /// Sorted by source
+    pub files: Vec<ManifestEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ExportError>,
+}
+
+impl Manifest {
+    /// Read the manifest in `dir`; a missing or unreadable one is `None`
+    pub fn load(dir: &Path) -> Option<Self> {
+        let text = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+}
+
+/// Outcome of an export run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExportReport {
+    /// Files rendered in this run
+    pub exported: usize,
+    /// Files skipped because their artifact is up to date
+    pub unchanged: usize,
+    pub failed: usize,
+}
+
+/// What a worker did with one source
+enum Outcome {
+    Exported(ManifestEntry),
+    Unchanged(ManifestEntry),
+    Failed(ExportError),
+}
+
+/// Renders sources into an output directory
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    dir: PathBuf,
+    config: BatlessConfig,
+    /// Sources whose workspace rules or project config differ from `config`
+    source_configs: BTreeMap<String, BatlessConfig>,
+    format: ExportFormat,
+    jobs: usize,
+}
+
+impl Exporter {
+    /// Exporter writing JSON into `dir` on one thread
+    pub fn new(dir: impl Into<PathBuf>, config: BatlessConfig) -> Self {
+        Self {
+            dir: dir.into(),
+            config,
+            source_configs: BTreeMap::new(),
+            format: ExportFormat::Json,
+            jobs: 1,
+        }
+    }
+
+    pub const fn with_format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Render `source` with `config` instead of the shared one
+    pub fn with_source_config(mut self, source: &Path, config: BatlessConfig) -> Self {
+        let differs = serde_json::to_value(&config).ok() != serde_json::to_value(&self.config).ok();
+        if differs {
+            self.source_configs.insert(display(source), config);
+        }
+        self
+    }
+
+    fn config_for(&self, source: &str) -> &BatlessConfig {
+        self.source_configs.get(source).unwrap_or(&self.config)
+    }
+
+    /// Render on `jobs` threads (at least one)
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Export `sources`, skipping the ones the existing manifest shows as
+    /// unchanged, and write the new manifest
+    pub fn run(&self, sources: &[PathBuf]) -> BatlessResult<ExportReport> {
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Cannot create export directory {}: {e}", self.dir.display()),
+                Some("Check that the --export path is writable".to_string()),
+            )
+        })?;
+        let config_hash = self.config_hash()?;
+        let previous: BTreeMap<String, ManifestEntry> = Manifest::load(&self.dir)
+            .filter(|manifest| manifest.config_hash == config_hash)
+            .map(|manifest| {
+                manifest
+                    .files
+                    .into_iter()
+                    .map(|entry| (entry.source.clone(), entry))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let sources: Vec<(String, &Path)> = sources
+            .iter()
+            .map(|path| (display(path), path.as_path()))
+            .collect();
+
+        // Until a source is done, its old entry stands in a checkpoint
+        let mut files: BTreeMap<String, ManifestEntry> = sources
+            .iter()
+            .filter_map(|(source, _)| previous.get_key_value(source))
+            .map(|(source, entry)| (source.clone(), entry.clone()))
+            .collect();
+        let mut errors = Vec::new();
+        let mut report = ExportReport::default();
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.jobs.min(sources.len()) {
+                let sender = sender.clone();
+                let (next, sources, previous) = (&next, &sources, &previous);
+                scope.spawn(move || {
+                    while let Some((source, path)) =
+                        sources.get(next.fetch_add(1, Ordering::Relaxed))
+                    {
+                        let outcome = self.export_one(source, path, previous.get(source));
+                        if sender.send(outcome).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (done, outcome) in receiver.iter().enumerate() {
+                match outcome {
+                    Outcome::Exported(entry) => {
+                        report.exported += 1;
+                        files.insert(entry.source.clone(), entry);
+                    }
+                    Outcome::Unchanged(entry) => {
+                        report.unchanged += 1;
+                        files.insert(entry.source.clone(), entry);
+                    }
+                    Outcome::Failed(error) => {
+                        report.failed += 1;
+                        files.remove(&error.source);
+                        errors.push(error);
+                    }
+                }
+                if (done + 1) % CHECKPOINT_EVERY == 0 {
+                    self.save(&config_hash, &files, &errors)?;
+                }
+            }
+            Ok::<_, BatlessError>(())
+        })?;
+
+        errors.sort_by(|a, b| a.source.cmp(&b.source));
+        self.save(&config_hash, &files, &errors)?;
+        Ok(report)
+    }
+
+    fn export_one(&self, source: &str, path: &Path, previous: Option<&ManifestEntry>) -> Outcome {
+        let failed = |error: String| {
+            Outcome::Failed(ExportError {
+                source: source.to_string(),
+                error,
+            })
+        };
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => return failed(e.to_string()),
+        };
+        let hash = Sha256::digest(&bytes)
+            .iter()
+            .fold(String::from("sha256:"), |mut hash, byte| {
+                let _ = write!(hash, "{byte:02x}");
+                hash
+            });
+        if let Some(entry) = previous {
+            if entry.hash == hash && self.dir.join(&entry.artifact).is_file() {
+                return Outcome::Unchanged(entry.clone());
+            }
+        }
+        match self.render(source) {
+            Ok((artifact, file_info)) => Outcome::Exported(ManifestEntry {
+                source: source.to_string(),
+                artifact,
+                hash,
+                bytes: bytes.len() as u64,
+                lines: file_info.total_lines,
+                language: file_info.language,
+            }),
+            Err(e) => failed(e.to_string()),
+        }
+    }
+
+    /// Write the artifact of `source`; returns its relative path
+    fn render(&self, source: &str) -> BatlessResult<(String, FileInfo)> {
+        let config = self.config_for(source);
+        let file_info = FileProcessor::process_file(source, config)?;
+        let text = match self.format {
+            ExportFormat::Json => {
+                OutputFormatter::format_output(&file_info, source, config, OutputMode::Json)? + "\n"
+            }
+            ExportFormat::Markdown => markdown(source, &file_info),
+        };
+        let artifact = artifact_path(Path::new(source), self.format);
+        let target = self.dir.join(&artifact);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, text).map_err(|e| BatlessError::FileReadError {
+            path: target.display().to_string(),
+            source: e,
+        })?;
+        Ok((display(&artifact), file_info))
+    }
+
+    fn config_hash(&self) -> BatlessResult<String> {
+        let settings = to_json_string(&self.config, false, true)?;
+        let mut digest = Sha256::new()
+            .chain_update(env!("CARGO_PKG_VERSION"))
+            .chain_update(self.format.extension())
+            .chain_update(settings);
+        for (source, config) in &self.source_configs {
+            digest.update(source);
+            digest.update(to_json_string(config, false, true)?);
+        }
+        let digest = digest.finalize();
+        Ok(digest
+            .iter()
+            .fold(String::from("sha256:"), |mut hash, byte| {
+                let _ = write!(hash, "{byte:02x}");
+                hash
+            }))
+    }
+
+    /// Replace the manifest, through a temporary file so a crash never
+    /// leaves a truncated one
+    fn save(
+        &self,
+        config_hash: &str,
+        files: &BTreeMap<String, ManifestEntry>,
+        errors: &[ExportError],
+    ) -> BatlessResult<()> {
+        let manifest = Manifest {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: self.config.schema_version.clone(),
+            format: self.format,
+            config_hash: config_hash.to_string(),
+            files: files.values().cloned().collect(),
+            errors: errors.to_vec(),
+        };
+        let path = self.dir.join(MANIFEST_FILE);
+        let partial = self.dir.join(format!("{MANIFEST_FILE}.tmp"));
+        fs::write(&partial, serde_json::to_string_pretty(&manifest)? + "\n")?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+}
+
+/// Files named by `inputs`: directories are walked and patterns with `*`,
+/// `?` or `{` are matched below their literal leading directories; hidden
+/// directories, symlinks and everything under `output_dir` are skipped, so
+/// a rerun never exports its own artifacts, and the result is sorted
+pub fn sources(inputs: &[String], output_dir: &Path) -> Vec<PathBuf> {
+    let output_dir = fs::canonicalize(output_dir).ok();
+    let skip = output_dir.as_deref();
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if !is_pattern(input) || path.exists() {
+            if path.is_dir() {
+                walk(path, skip, &mut files);
+            } else if !skip.is_some_and(|skip| is_under(path, skip)) {
+                files.push(path.to_path_buf());
+            }
+            continue;
+        }
+        let base: PathBuf = path
+            .components()
+            .take_while(|component| !is_pattern(&component.as_os_str().to_string_lossy()))
+            .collect();
+        let mut candidates = Vec::new();
+        walk(
+            if base.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                &base
+            },
+            skip,
+            &mut candidates,
+        );
+        // `**/` may also stand for no directory at all
+        let shallow = input.replace("**/", "");
+        files.extend(candidates.into_iter().filter(|file| {
+            let file = display(file.strip_prefix(".").unwrap_or(file));
+            LanguageDetector::glob_match(input, &file)
+                || LanguageDetector::glob_match(&shallow, &file)
+        }));
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn is_pattern(text: &str) -> bool {
+    text.contains(['*', '?', '{'])
+}
+
+fn is_under(path: &Path, dir: &Path) -> bool {
+    fs::canonicalize(path).is_ok_and(|path| path.starts_with(dir))
+}
+
+fn walk(dir: &Path, skip: Option<&Path>, out: &mut Vec<PathBuf>) {
+    if skip.is_some_and(|skip| is_under(dir, skip)) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                walk(&path, skip, out);
+            }
+        } else if meta.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+/// `source` below the output directory, so artifacts never land outside it
+///
+/// `..` becomes `@up`, a root `@root` and a drive prefix `@` and its letter;
+/// names that start with `@` get one more, so no two sources share an
+/// artifact.
+fn artifact_path(source: &Path, format: ExportFormat) -> PathBuf {
+    let mut path: PathBuf = source
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => {
+                let part = part.to_os_string();
+                Some(if part.to_string_lossy().starts_with('@') {
+                    let mut escaped = std::ffi::OsString::from("@");
+                    escaped.push(part);
+                    escaped
+                } else {
+                    part
+                })
+            }
+            Component::ParentDir => Some("@up".into()),
+            Component::RootDir => Some("@root".into()),
+            Component::Prefix(prefix) => Some(
+                format!(
+                    "@{}",
+                    prefix
+                        .as_os_str()
+                        .to_string_lossy()
+                        .replace(|c: char| !c.is_ascii_alphanumeric(), "")
+                )
+                .into(),
+            ),
+            Component::CurDir => None,
+        })
+        .collect();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(format.extension());
+    path.set_file_name(name);
+    path
+}
+
+/// `path` with `/` separators
+fn display(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Heading and fenced code block; the fence is longer than any backtick run
+/// in the content
+fn markdown(source: &str, file_info: &FileInfo) -> String {
+    let longest_run = file_info
+        .lines
+        .iter()
+        .flat_map(|line| line.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let language = file_info
+        .language
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase();
+    let mut out = format!("# {source}\n\n{fence}{language}\n");
+    for line in &file_info.lines {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&fence);
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_sources_expand_globs_and_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = display(dir.path());
+        write(dir.path(), "src/lib.rs", "fn a() {}\n");
+        write(dir.path(), "src/deep/mod.rs", "fn b() {}\n");
+        write(dir.path(), "src/notes.txt", "x\n");
+        write(dir.path(), ".git/config", "x\n");
+
+        let found = sources(&[format!("{root}/src/**/*.rs")], Path::new("unused"));
+        assert_eq!(
+            found,
+            [
+                dir.path().join("src/deep/mod.rs"),
+                dir.path().join("src/lib.rs")
+            ]
+        );
+        assert_eq!(sources(&[root], Path::new("unused")).len(), 3);
+    }
+
+    #[test]
+    fn test_artifact_paths_stay_inside() {
+        assert_eq!(
+            artifact_path(Path::new("src/lib.rs"), ExportFormat::Json),
+            Path::new("src/lib.rs.json")
+        );
+        assert_eq!(
+            artifact_path(Path::new("/abs/../x.py"), ExportFormat::Markdown),
+            Path::new("@root/abs/@up/x.py.md")
+        );
+        // Parent directories, roots and look-alike names stay apart
+        let artifacts: std::collections::BTreeSet<_> = [
+            "../a.rs",
+            "@up/a.rs",
+            "@@up/a.rs",
+            "__/a.rs",
+            "/a.rs",
+            "@root/a.rs",
+            "./a.rs",
+        ]
+        .iter()
+        .map(|source| artifact_path(Path::new(source), ExportFormat::Json))
+        .collect();
+        assert_eq!(artifacts.len(), 7, "{artifacts:?}");
+    }
+
+    #[test]
+    fn test_markdown_fence_outgrows_content() {
+        let info = FileInfo::with_metadata(1, 10, Some("Rust".to_string()), "UTF-8".to_string())
+            .with_lines(vec!["let s = \"```\";".to_string()]);
+        assert_eq!(
+            markdown("a.rs", &info),
+            "# a.rs\n\n````rust\nlet s = \"```\";\n````\n"
+        );
+    }
+
+    #[test]
+    fn test_rerun_skips_its_own_output() {
+        let tree = tempfile::tempdir().unwrap();
+        write(tree.path(), "a.rs", "fn a() {}\n");
+        write(tree.path(), "src/b.rs", "fn b() {}\n");
+        let out = tree.path().join("exp");
+        let inputs = [display(tree.path())];
+        let exporter = Exporter::new(&out, BatlessConfig::default());
+
+        let first = exporter.run(&sources(&inputs, &out)).unwrap();
+        assert_eq!(first.exported, 2);
+        let again = sources(&inputs, &out);
+        assert_eq!(again.len(), 2, "{again:?}");
+        // Files given by name inside the output directory are left out too
+        let manifest = display(&out.join(MANIFEST_FILE));
+        assert!(sources(&[manifest], &out).is_empty());
+
+        let second = exporter.run(&again).unwrap();
+        assert_eq!((second.exported, second.unchanged), (0, 2));
+        assert_eq!(Manifest::load(&out).unwrap().files.len(), 2);
+        assert!(!out.join("exp").exists());
+    }
+
+    #[test]
+    fn test_export_is_incremental() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let a = write(src.path(), "a.rs", "fn a() {}\n");
+        let b = write(src.path(), "b.py", "def b(): pass\n");
+        let exporter = Exporter::new(out.path(), BatlessConfig::default()).with_jobs(2);
+
+        let report = exporter.run(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(
+            (report.exported, report.unchanged, report.failed),
+            (2, 0, 0)
+        );
+        let manifest = Manifest::load(out.path()).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        let artifact = out.path().join(&manifest.files[0].artifact);
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(artifact).unwrap()).unwrap();
+        assert_eq!(json["language"], "Rust");
+
+        fs::write(&b, "def b(): return 1\n").unwrap();
+        let missing = src.path().join("missing.rs");
+        let report = exporter.run(&[a.clone(), b.clone(), missing]).unwrap();
+        assert_eq!(
+            (report.exported, report.unchanged, report.failed),
+            (1, 1, 1)
+        );
+        let manifest = Manifest::load(out.path()).unwrap();
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(manifest.errors.len(), 1);
+
+        // Other settings render differently, so everything is redone
+        let exporter = Exporter::new(out.path(), BatlessConfig::default().with_max_lines(1));
+        assert_eq!(exporter.run(&[a, b]).unwrap().exported, 2);
+    }
+}
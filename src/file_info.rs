@@ -3,13 +3,24 @@
 //! This module defines the FileInfo structure that holds all information
 //! about a processed file, including content, metadata, and processing results.
 
+use crate::annotations::Annotation;
+use crate::around::LineWindow;
+use crate::dataset::DatasetSchema;
+use crate::error::{Warning, WarningCode};
+use crate::language::{LanguageCandidate, LanguageSource};
+use crate::media::MediaInfo;
+use crate::summary::SummaryEngine;
 use crate::summary_item::SummaryItem;
+use crate::tokens::TokenFrequency;
+use crate::uses::SymbolUse;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
 
 /// Information about a processed file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
-    /// The processed lines of the file
+    /// The processed lines of the file, one owned allocation per line
     pub lines: Vec<String>,
     /// Original lines before any summary transformations
     pub original_lines: Option<Vec<String>>,
@@ -29,6 +40,11 @@ pub struct FileInfo {
     pub truncated_by_context: bool,
     /// Detected or specified language
     pub language: Option<String>,
+    /// How the language was determined (extension, modeline, editorconfig, ...)
+    pub language_detection_source: Option<LanguageSource>,
+    /// Possible languages with scores, best first (several only for ambiguous extensions)
+    #[serde(default)]
+    pub language_candidates: Vec<LanguageCandidate>,
     /// Detected encoding of the file
     pub encoding: String,
     /// Syntax errors encountered during processing
@@ -47,6 +63,77 @@ pub struct FileInfo {
     pub token_model: Option<String>,
     /// Ratio of original line count to stripped line count (set when strip_comments or strip_blank_lines is active)
     pub compression_ratio: Option<f64>,
+    /// Secrets masked per redaction rule (set when redaction is enabled)
+    pub redactions: Option<BTreeMap<String, usize>>,
+    /// Whether `--timeout` expired; remaining processing stages were skipped
+    pub timed_out: bool,
+    /// When `--timeout` expires, for output modes that parse the file again
+    #[serde(skip)]
+    pub deadline: Option<Instant>,
+    /// Line terminators seen while reading
+    #[serde(default)]
+    pub line_endings: LineEndings,
+    /// Whether the input started with a UTF-8 byte order mark (stripped from line 1)
+    #[serde(default)]
+    pub had_bom: bool,
+    /// Whether the file went through the chunked streaming path (auto-stream)
+    /// rather than being read whole
+    #[serde(default)]
+    pub streamed: bool,
+    /// Annotations from `--annotations` that fall within the output
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// Window kept by `--around`; `lines` then starts at `window.start`
+    #[serde(default)]
+    pub window: Option<LineWindow>,
+    /// Whether `--fold-depth` replaced `lines` with a folded view
+    #[serde(default)]
+    pub folded: bool,
+    /// Image/PDF metadata reported instead of content (`media` feature)
+    #[serde(default)]
+    pub media: Option<MediaInfo>,
+    /// Tables and columns reported for SQLite/Parquet files
+    #[serde(default)]
+    pub dataset: Option<DatasetSchema>,
+    /// Format the input was decompressed from (`gzip`, `bzip2`, `xz`, `zstd`)
+    #[serde(default)]
+    pub compression: Option<String>,
+    /// Occurrences found by `--uses`; `lines` then holds their text form
+    #[serde(default)]
+    pub uses: Option<Vec<SymbolUse>>,
+    /// Extractor that produced `summary_lines`
+    #[serde(default)]
+    pub summary_engine: Option<SummaryEngine>,
+    /// Most frequent identifiers with their counts (`--token-frequencies`)
+    #[serde(default)]
+    pub token_frequencies: Option<Vec<TokenFrequency>>,
+    /// Problems met while processing that did not stop it
+    #[serde(default)]
+    pub warnings: Vec<Warning>,
+}
+
+/// Counts of each line terminator style in the input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineEndings {
+    /// Unix `\n`
+    pub lf: usize,
+    /// Windows `\r\n`
+    pub crlf: usize,
+    /// Classic Mac `\r` (lone carriage returns)
+    pub cr: usize,
+}
+
+impl LineEndings {
+    /// Dominant style: `lf`, `crlf`, `cr`, `mixed`, or `none` for single-line input
+    pub const fn style(&self) -> &'static str {
+        match (self.lf > 0, self.crlf > 0, self.cr > 0) {
+            (false, false, false) => "none",
+            (true, false, false) => "lf",
+            (false, true, false) => "crlf",
+            (false, false, true) => "cr",
+            _ => "mixed",
+        }
+    }
 }
 
 impl FileInfo {
@@ -63,6 +150,8 @@ impl FileInfo {
             truncated_by_bytes: false,
             truncated_by_context: false,
             language: None,
+            language_detection_source: None,
+            language_candidates: Vec::new(),
             encoding: "UTF-8".to_string(),
             syntax_errors: Vec::new(),
             tokens: None,
@@ -72,6 +161,26 @@ impl FileInfo {
             estimated_llm_tokens: None,
             token_model: None,
             compression_ratio: None,
+            redactions: None,
+            timed_out: false,
+            deadline: None,
+            line_endings: LineEndings {
+                lf: 0,
+                crlf: 0,
+                cr: 0,
+            },
+            had_bom: false,
+            streamed: false,
+            annotations: Vec::new(),
+            window: None,
+            folded: false,
+            media: None,
+            dataset: None,
+            compression: None,
+            uses: None,
+            summary_engine: None,
+            token_frequencies: None,
+            warnings: Vec::new(),
         }
     }
 
@@ -93,6 +202,8 @@ impl FileInfo {
             truncated_by_bytes: false,
             truncated_by_context: false,
             language,
+            language_detection_source: None,
+            language_candidates: Vec::new(),
             encoding,
             syntax_errors: Vec::new(),
             tokens: None,
@@ -102,9 +213,50 @@ impl FileInfo {
             estimated_llm_tokens: None,
             token_model: None,
             compression_ratio: None,
+            redactions: None,
+            timed_out: false,
+            deadline: None,
+            line_endings: LineEndings {
+                lf: 0,
+                crlf: 0,
+                cr: 0,
+            },
+            had_bom: false,
+            streamed: false,
+            annotations: Vec::new(),
+            window: None,
+            folded: false,
+            media: None,
+            dataset: None,
+            compression: None,
+            uses: None,
+            summary_engine: None,
+            token_frequencies: None,
+            warnings: Vec::new(),
         }
     }
 
+    /// Record how the language was determined
+    pub const fn with_language_source(mut self, source: Option<LanguageSource>) -> Self {
+        self.language_detection_source = source;
+        self
+    }
+
+    /// Record the scored language candidates
+    pub fn with_language_candidates(mut self, candidates: Vec<LanguageCandidate>) -> Self {
+        self.language_candidates = candidates;
+        self
+    }
+
+    /// Score of the reported language among the candidates
+    pub fn language_confidence(&self) -> Option<f64> {
+        let language = self.language.as_deref()?;
+        self.language_candidates
+            .iter()
+            .find(|candidate| candidate.name == language)
+            .map(|candidate| candidate.score)
+    }
+
     /// Set the processed lines
     pub fn with_lines(mut self, lines: Vec<String>) -> Self {
         self.lines = lines;
@@ -136,6 +288,24 @@ impl FileInfo {
         self.syntax_errors.push(error);
     }
 
+    /// Set identifier frequencies
+    pub fn with_token_frequencies(mut self, frequencies: Option<Vec<TokenFrequency>>) -> Self {
+        self.token_frequencies = frequencies;
+        self
+    }
+
+    /// Record which extractor produced the summary
+    pub const fn with_summary_engine(mut self, engine: Option<SummaryEngine>) -> Self {
+        self.summary_engine = engine;
+        self
+    }
+
+    /// Set syntax errors
+    pub fn with_syntax_errors(mut self, syntax_errors: Vec<String>) -> Self {
+        self.syntax_errors = syntax_errors;
+        self
+    }
+
     /// Set tokens
     pub fn with_tokens(mut self, tokens: Option<Vec<String>>) -> Self {
         self.tokens = tokens;
@@ -167,12 +337,105 @@ impl FileInfo {
         self
     }
 
+    /// Mark that processing stopped at the timeout
+    pub fn with_timed_out(mut self, timed_out: bool) -> Self {
+        if timed_out && !self.timed_out {
+            self.add_warning(Warning::new(
+                WarningCode::TimedOut,
+                "processing timed out; output is partial",
+            ));
+        }
+        self.timed_out = timed_out;
+        self
+    }
+
+    /// Whether the `--timeout` deadline has passed
+    pub fn deadline_passed(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Record a warning for `warnings`
+    pub fn add_warning(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+
+    /// Record the line terminators seen while reading
+    pub const fn with_line_endings(mut self, line_endings: LineEndings) -> Self {
+        self.line_endings = line_endings;
+        self
+    }
+
+    /// Attach annotations to overlay on the output
+    pub fn with_annotations(mut self, annotations: Vec<Annotation>) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    /// Attach image/PDF metadata
+    pub fn with_media(mut self, media: Option<MediaInfo>) -> Self {
+        self.media = media;
+        self
+    }
+
+    /// Attach the schema of a dataset file
+    pub fn with_dataset(mut self, dataset: Option<DatasetSchema>) -> Self {
+        self.dataset = dataset;
+        self
+    }
+
+    /// Record whether a UTF-8 byte order mark was stripped
+    pub const fn with_bom(mut self, had_bom: bool) -> Self {
+        self.had_bom = had_bom;
+        self
+    }
+
+    /// Record that the file was streamed in chunks
+    pub const fn with_streamed(mut self, streamed: bool) -> Self {
+        self.streamed = streamed;
+        self
+    }
+
+    /// Record the format a compressed input was decoded from
+    pub fn with_compression(mut self, compression: Option<String>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Record the `--around` window the lines were cut from
+    pub fn with_window(mut self, window: Option<LineWindow>) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// 1-based source line number of the first entry in `lines`
+    pub fn first_line_number(&self) -> usize {
+        self.window.as_ref().map_or(1, |window| window.start)
+    }
+
+    /// Set per-rule redaction counts
+    pub fn with_redactions(mut self, redactions: Option<BTreeMap<String, usize>>) -> Self {
+        self.redactions = redactions;
+        self
+    }
+
+    /// Total number of redacted secrets
+    pub fn redaction_count(&self) -> usize {
+        self.redactions.as_ref().map_or(0, |r| r.values().sum())
+    }
+
     /// Set summary items
     pub fn with_summary_lines(mut self, summary_lines: Option<Vec<SummaryItem>>) -> Self {
         self.summary_lines = summary_lines;
         self
     }
 
+    /// Set the occurrences found by `--uses`
+    pub fn with_uses(mut self, uses: Option<Vec<SymbolUse>>) -> Self {
+        self.uses = uses;
+        self
+    }
+
     /// Preserve original lines before summary transformations
     pub fn with_original_lines(mut self, original: Option<Vec<String>>) -> Self {
         self.original_lines = original;
@@ -420,6 +683,16 @@ mod tests {
         assert_eq!(info.summary_line_count(), 1);
     }
 
+    #[test]
+    fn test_line_endings_style() {
+        let mut endings = LineEndings::default();
+        assert_eq!(endings.style(), "none");
+        endings.crlf = 3;
+        assert_eq!(endings.style(), "crlf");
+        endings.lf = 1;
+        assert_eq!(endings.style(), "mixed");
+    }
+
     #[test]
     fn test_stats_summary() {
         let mut info =
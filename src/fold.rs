@@ -0,0 +1,280 @@
+//! Structural folding for data files (`--fold-depth N`)
+//!
+//! Giant JSON and YAML configs are mostly nesting. Folding keeps every key
+//! down to depth N and replaces deeper containers with a preview such as
+//! `{... 3 keys}` or `[... 42 items]`, so the shape of the document survives
+//! at a fraction of the size. JSON is parsed (keeping key order); YAML is
+//! folded by indentation, since batless has no YAML parser.
+
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+/// Fold `lines` of a JSON or YAML document; `None` for other languages or
+/// when the content cannot be parsed (e.g. it was truncated).
+pub fn fold_lines(lines: &[String], language: Option<&str>, depth: usize) -> Option<Vec<String>> {
+    match language {
+        Some("JSON") => fold_json(&lines.join("\n"), depth),
+        Some("YAML") => Some(fold_yaml(lines, depth)),
+        _ => None,
+    }
+}
+
+/// JSON value that keeps object keys in document order
+enum Node {
+    Scalar(serde_json::Value),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Node, E> {
+        Ok(Node::Scalar(v.into()))
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::Scalar(serde_json::Value::Null))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Node::Object(entries))
+    }
+}
+
+fn fold_json(content: &str, depth: usize) -> Option<Vec<String>> {
+    let root: Node = serde_json::from_str(content).ok()?;
+    let mut out = Vec::new();
+    render(&root, 0, depth, "", "", &mut out);
+    Some(out)
+}
+
+/// Render `node` at nesting `level`; `prefix` is the `"key": ` part and
+/// `suffix` the trailing comma, both placed on the node's first line.
+fn render(
+    node: &Node,
+    level: usize,
+    depth: usize,
+    prefix: &str,
+    suffix: &str,
+    out: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(level);
+    match node {
+        Node::Scalar(value) => out.push(format!("{indent}{prefix}{value}{suffix}")),
+        Node::Array(items) if items.is_empty() => out.push(format!("{indent}{prefix}[]{suffix}")),
+        Node::Object(entries) if entries.is_empty() => {
+            out.push(format!("{indent}{prefix}{{}}{suffix}"));
+        }
+        Node::Array(items) if level >= depth => {
+            out.push(format!(
+                "{indent}{prefix}[... {}]{suffix}",
+                plural(items.len(), "item")
+            ));
+        }
+        Node::Object(entries) if level >= depth => {
+            out.push(format!(
+                "{indent}{prefix}{{... {}}}{suffix}",
+                plural(entries.len(), "key")
+            ));
+        }
+        Node::Array(items) => {
+            out.push(format!("{indent}{prefix}["));
+            for (i, item) in items.iter().enumerate() {
+                let comma = if i + 1 < items.len() { "," } else { "" };
+                render(item, level + 1, depth, "", comma, out);
+            }
+            out.push(format!("{indent}]{suffix}"));
+        }
+        Node::Object(entries) => {
+            out.push(format!("{indent}{prefix}{{"));
+            for (i, (key, value)) in entries.iter().enumerate() {
+                let comma = if i + 1 < entries.len() { "," } else { "" };
+                let key = serde_json::Value::from(key.as_str());
+                render(value, level + 1, depth, &format!("{key}: "), comma, out);
+            }
+            out.push(format!("{indent}}}{suffix}"));
+        }
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Fold YAML by indentation: lines nested deeper than `depth` levels are
+/// dropped and the line that owned them gets a preview.
+fn fold_yaml(lines: &[String], depth: usize) -> Vec<String> {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let is_content = |line: &str| {
+        let trimmed = line.trim();
+        !trimmed.is_empty() && !trimmed.starts_with('#') && trimmed != "---" && trimmed != "..."
+    };
+
+    let mut out = Vec::new();
+    // Indents of the enclosing levels; the top level is the first entry
+    let mut stack: Vec<usize> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        if !is_content(line) {
+            if stack.len() <= depth + 1 {
+                out.push(line.clone());
+            }
+            i += 1;
+            continue;
+        }
+        let indent = indent_of(line);
+        while stack.last().is_some_and(|&top| top > indent) {
+            stack.pop();
+        }
+        if stack.last() != Some(&indent) {
+            stack.push(indent);
+        }
+        let level = stack.len() - 1;
+
+        // Children are the following lines indented deeper than this one
+        // (list items may sit at the same indent as their parent key)
+        let is_key = line.trim_end().ends_with(':');
+        let children_end = lines[i + 1..]
+            .iter()
+            .position(|next| {
+                is_content(next)
+                    && (indent_of(next) < indent
+                        || (indent_of(next) == indent
+                            && !(is_key && next.trim_start().starts_with("- "))))
+            })
+            .map_or(lines.len(), |p| i + 1 + p);
+        let children = &lines[i + 1..children_end];
+
+        if level >= depth && children.iter().any(|c| is_content(c)) {
+            let child_indent = children
+                .iter()
+                .filter(|c| is_content(c))
+                .map(|c| indent_of(c))
+                .min()
+                .unwrap_or(indent);
+            let direct: Vec<&String> = children
+                .iter()
+                .filter(|c| is_content(c) && indent_of(c) == child_indent)
+                .collect();
+            let preview = if direct.iter().all(|c| c.trim_start().starts_with("- ")) {
+                format!("[... {}]", plural(direct.len(), "item"))
+            } else {
+                format!("{{... {}}}", plural(direct.len(), "key"))
+            };
+            out.push(format!("{} {preview}", line.trim_end()));
+            i = children_end;
+            continue;
+        }
+        out.push(line.clone());
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_fold_json_keeps_order_and_previews() {
+        let json =
+            r#"{"zeta": {"a": 1, "b": {"c": 2}}, "alpha": [1, 2, 3], "empty": {}, "n": null}"#;
+        let folded = fold_lines(&lines(json), Some("JSON"), 1).unwrap();
+        assert_eq!(
+            folded,
+            [
+                "{",
+                r#"  "zeta": {... 2 keys},"#,
+                r#"  "alpha": [... 3 items],"#,
+                r#"  "empty": {},"#,
+                r#"  "n": null"#,
+                "}",
+            ]
+        );
+
+        let folded = fold_lines(&lines(json), Some("JSON"), 2).unwrap();
+        assert!(folded.contains(&r#"    "b": {... 1 key}"#.to_string()));
+        assert!(fold_lines(&lines("{ truncated"), Some("JSON"), 1).is_none());
+        assert!(fold_lines(&lines("fn main() {}"), Some("Rust"), 1).is_none());
+    }
+
+    #[test]
+    fn test_fold_yaml_by_indentation() {
+        let yaml = "# config\nname: app\nservices:\n  web:\n    image: nginx\n    ports:\n      - 80\n  db:\n    image: pg\nsteps:\n- one\n- two\n";
+        let folded = fold_lines(&lines(yaml), Some("YAML"), 1).unwrap();
+        assert_eq!(
+            folded,
+            [
+                "# config",
+                "name: app",
+                "services:",
+                "  web: {... 2 keys}",
+                "  db: {... 1 key}",
+                "steps:",
+                "- one",
+                "- two",
+            ]
+        );
+
+        let folded = fold_lines(&lines(yaml), Some("YAML"), 0).unwrap();
+        assert_eq!(
+            folded,
+            [
+                "# config",
+                "name: app",
+                "services: {... 2 keys}",
+                "steps: [... 2 items]"
+            ]
+        );
+    }
+}
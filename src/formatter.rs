@@ -6,6 +6,8 @@
 use crate::config::BatlessConfig;
 use crate::error::{BatlessError, BatlessResult};
 use crate::file_info::FileInfo;
+use crate::formatters::jsonl_formatter::JsonlFormatter;
+use crate::hex::HexDump;
 use serde_json::json;
 
 /// Output formatter for different display modes
@@ -18,19 +20,72 @@ impl OutputFormatter {
         file_path: &str,
         config: &BatlessConfig,
         output_mode: OutputMode,
+    ) -> BatlessResult<String> {
+        if let Some(template) = config
+            .template
+            .as_deref()
+            .filter(|_| matches!(output_mode, OutputMode::Plain | OutputMode::Summary))
+        {
+            return Ok(crate::template::render(template, file_path, file_info));
+        }
+        let output = Self::format_mode(file_info, file_path, config, output_mode)?;
+        if config.canonical_json && output_mode.is_json() {
+            return Ok(crate::canonical_json::canonicalize_output(&output));
+        }
+        Ok(output)
+    }
+
+    fn format_mode(
+        file_info: &FileInfo,
+        file_path: &str,
+        config: &BatlessConfig,
+        output_mode: OutputMode,
     ) -> BatlessResult<String> {
         use crate::formatters::Formatter;
         use crate::formatters::{
-            ast_formatter::AstFormatter, index_formatter::IndexFormatter,
-            json_formatter::JsonFormatter, plain_formatter::PlainFormatter,
+            ast_formatter::AstFormatter,
+            imports_formatter::ImportsFormatter,
+            index_formatter::IndexFormatter,
+            json_formatter::JsonFormatter,
+            log_formatter::{LogFormatter, LogJsonFormatter},
+            plain_formatter::PlainFormatter,
+            scip_formatter::ScipFormatter,
             summary_formatter::SummaryFormatter,
+            tags_formatter::{CtagsFormatter, EtagsFormatter},
         };
         match output_mode {
             OutputMode::Plain => PlainFormatter.format(file_info, file_path, config),
             OutputMode::Json => JsonFormatter.format(file_info, file_path, config),
+            OutputMode::Jsonl => JsonlFormatter.format(file_info, file_path, config),
             OutputMode::Summary => SummaryFormatter.format(file_info, file_path, config),
             OutputMode::Index => IndexFormatter.format(file_info, file_path, config),
             OutputMode::Ast => AstFormatter.format(file_info, file_path, config),
+            OutputMode::Imports => ImportsFormatter.format(file_info, file_path, config),
+            OutputMode::Log => LogFormatter.format(file_info, file_path, config),
+            OutputMode::LogJson => LogJsonFormatter.format(file_info, file_path, config),
+            OutputMode::Ctags => CtagsFormatter.format(file_info, file_path, config),
+            OutputMode::Etags => EtagsFormatter.format(file_info, file_path, config),
+            OutputMode::Scip => ScipFormatter.format(file_info, file_path, config),
+            OutputMode::Metadata => {
+                let metadata = Self::metadata_json(file_info, file_path);
+                if config.pretty_json {
+                    Ok(serde_json::to_string_pretty(&metadata)?)
+                } else {
+                    Ok(metadata.to_string())
+                }
+            }
+            // Without the raw file, dump the decoded text
+            OutputMode::Hex | OutputMode::HexJson => {
+                let content = file_info.lines.join("\n");
+                let dump = HexDump::from_bytes(content.as_bytes(), config.max_bytes);
+                if output_mode == OutputMode::Hex {
+                    Ok(dump.render_text())
+                } else if config.pretty_json {
+                    Ok(serde_json::to_string_pretty(&dump.to_json(file_path))?)
+                } else {
+                    Ok(dump.to_json(file_path).to_string())
+                }
+            }
         }
     }
 
@@ -51,9 +106,16 @@ impl OutputFormatter {
                 });
                 serde_json::to_string(&json_line).map_err(BatlessError::from)
             }
+            OutputMode::Jsonl => serde_json::to_string(&JsonlFormatter::row(line_number, line))
+                .map_err(BatlessError::from),
             OutputMode::Summary => Ok(line.to_string()), // Summary mode doesn't stream
             OutputMode::Index => Ok(line.to_string()),   // Index mode doesn't stream
             OutputMode::Ast => Ok(line.to_string()),     // Ast mode doesn't stream
+            OutputMode::Imports => Ok(line.to_string()), // Imports mode doesn't stream
+            OutputMode::Log | OutputMode::LogJson => Ok(line.to_string()), // Records span lines
+            OutputMode::Hex | OutputMode::HexJson => Ok(line.to_string()), // Hex dumps raw bytes
+            OutputMode::Ctags | OutputMode::Etags | OutputMode::Scip => Ok(line.to_string()), // Need the outline
+            OutputMode::Metadata => Ok(line.to_string()), // Describes the file, not lines
         }
     }
 
@@ -94,11 +156,15 @@ impl OutputFormatter {
             BatlessError::FileNotFound { .. } => "file_not_found",
             BatlessError::FileReadError { .. } => "file_read_error",
             BatlessError::PermissionDenied { .. } => "permission_denied",
+            BatlessError::FileChangedDuringProcessing { .. } => "file_changed_during_processing",
             BatlessError::LanguageNotFound { .. } => "language_not_found",
             BatlessError::LanguageDetectionError { .. } => "language_detection_error",
             BatlessError::EncodingError { .. } => "encoding_error",
             BatlessError::ProcessingError { .. } => "processing_error",
             BatlessError::ConfigurationError { .. } => "configuration_error",
+            BatlessError::MemoryLimitExceeded { .. } => "memory_limit_exceeded",
+            BatlessError::StdinLimitExceeded { .. } => "stdin_limit_exceeded",
+            BatlessError::StdinTimeout { .. } => "stdin_timeout",
             BatlessError::JsonSerializationError(_) => "json_serialization_error",
             BatlessError::OutputError(_) => "output_error",
             BatlessError::IoError(_) => "io_error",
@@ -107,7 +173,13 @@ impl OutputFormatter {
 
     /// Format metadata only (without content)
     pub fn format_metadata_only(file_info: &FileInfo, file_path: &str) -> BatlessResult<String> {
-        let metadata = json!({
+        serde_json::to_string_pretty(&Self::metadata_json(file_info, file_path))
+            .map_err(BatlessError::from)
+    }
+
+    /// The `--mode=metadata` object for a file
+    pub fn metadata_json(file_info: &FileInfo, file_path: &str) -> serde_json::Value {
+        json!({
             "file_path": file_path,
             "total_lines": file_info.total_lines,
             "total_lines_exact": file_info.total_lines_exact,
@@ -122,9 +194,7 @@ impl OutputFormatter {
             "tokens_truncated": file_info.tokens_truncated(),
             "summary_line_count": file_info.summary_line_count(),
             "processing_ratio": file_info.processing_ratio()
-        });
-
-        serde_json::to_string_pretty(&metadata).map_err(BatlessError::from)
+        })
     }
 
     /// Format statistics report
@@ -227,11 +297,31 @@ Processing Ratio: {:.2}%",
 pub enum OutputMode {
     Plain,
     Json,
+    /// One compact JSON object per source line (`{"n": 1, "text": "..."}`)
+    Jsonl,
     Summary,
     /// Machine-readable symbol index (functions, classes, structs with line ranges)
     Index,
     /// Raw tree-sitter parse tree as JSON (Rust/Python/JS/TS; null root for others)
     Ast,
+    /// Import statements and file → module dependency edges
+    Imports,
+    /// Log records with level coloring and `--min-level` filtering
+    Log,
+    /// One JSON object per log record (timestamp, level, message)
+    LogJson,
+    /// Offset, hex bytes and ASCII gutter of the raw input
+    Hex,
+    /// Hex dump as a JSON byte range
+    HexJson,
+    /// Exuberant-ctags tag lines for the file's definitions
+    Ctags,
+    /// Emacs TAGS section for the file's definitions
+    Etags,
+    /// SCIP index (JSON) of the file's definitions
+    Scip,
+    /// Size, line count, language and encoding only; the content is not read
+    Metadata,
 }
 
 impl OutputMode {
@@ -240,9 +330,19 @@ impl OutputMode {
         match s.to_lowercase().as_str() {
             "plain" => Ok(Self::Plain),
             "json" => Ok(Self::Json),
+            "jsonl" => Ok(Self::Jsonl),
             "summary" => Ok(Self::Summary),
             "index" => Ok(Self::Index),
             "ast" => Ok(Self::Ast),
+            "imports" => Ok(Self::Imports),
+            "log" => Ok(Self::Log),
+            "log-json" => Ok(Self::LogJson),
+            "hex" => Ok(Self::Hex),
+            "hex-json" => Ok(Self::HexJson),
+            "ctags" => Ok(Self::Ctags),
+            "etags" => Ok(Self::Etags),
+            "scip" => Ok(Self::Scip),
+            "metadata" => Ok(Self::Metadata),
             _ => Err(format!("Unknown output mode: {s}")),
         }
     }
@@ -252,9 +352,19 @@ impl OutputMode {
         vec![
             Self::Plain,
             Self::Json,
+            Self::Jsonl,
             Self::Summary,
             Self::Index,
             Self::Ast,
+            Self::Imports,
+            Self::Log,
+            Self::LogJson,
+            Self::Hex,
+            Self::HexJson,
+            Self::Ctags,
+            Self::Etags,
+            Self::Scip,
+            Self::Metadata,
         ]
     }
 
@@ -263,11 +373,37 @@ impl OutputMode {
         match self {
             Self::Plain => "plain",
             Self::Json => "json",
+            Self::Jsonl => "jsonl",
             Self::Summary => "summary",
             Self::Index => "index",
             Self::Ast => "ast",
+            Self::Imports => "imports",
+            Self::Log => "log",
+            Self::LogJson => "log-json",
+            Self::Hex => "hex",
+            Self::HexJson => "hex-json",
+            Self::Ctags => "ctags",
+            Self::Etags => "etags",
+            Self::Scip => "scip",
+            Self::Metadata => "metadata",
         }
     }
+
+    /// Whether the mode emits JSON (one document, or one per line)
+    pub const fn is_json(&self) -> bool {
+        matches!(
+            self,
+            Self::Json
+                | Self::Jsonl
+                | Self::Index
+                | Self::Ast
+                | Self::Imports
+                | Self::LogJson
+                | Self::HexJson
+                | Self::Scip
+                | Self::Metadata
+        )
+    }
 }
 
 #[cfg(test)]
@@ -313,6 +449,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_format_jsonl() -> BatlessResult<()> {
+        let file_info = create_test_file_info();
+        let config = BatlessConfig::default();
+        let result =
+            OutputFormatter::format_output(&file_info, "test.rs", &config, OutputMode::Jsonl)?;
+
+        let rows: Vec<Value> = result
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["n"], 1);
+        assert_eq!(rows[0]["text"], "fn main() {");
+        assert_eq!(rows[2]["n"], 3);
+
+        Ok(())
+    }
+
     #[test]
     fn test_format_summary() -> BatlessResult<()> {
         let file_info = create_test_file_info();
@@ -381,7 +536,10 @@ mod tests {
         assert_eq!(OutputMode::Json.as_str(), "json");
         assert_eq!(OutputMode::Summary.as_str(), "summary");
         assert_eq!(OutputMode::Index.as_str(), "index");
+        assert_eq!(OutputMode::Imports.as_str(), "imports");
         assert_eq!(OutputMode::Ast.as_str(), "ast");
+        assert_eq!(OutputMode::Jsonl.as_str(), "jsonl");
+        assert_eq!(OutputMode::LogJson.as_str(), "log-json");
     }
 
     #[test]
@@ -3,12 +3,14 @@
 //! Serializes the full tree-sitter parse tree as JSON for AI agents that
 //! need deep structural analysis beyond what index mode provides.
 
+use crate::ast_summarizer::AstSummarizer;
 use crate::config::BatlessConfig;
 use crate::error::BatlessResult;
 use crate::file_info::FileInfo;
 use crate::formatter::OutputMode;
 use crate::formatters::Formatter;
 use serde_json::{json, Value};
+use std::time::Instant;
 use tree_sitter::{Node, Parser};
 
 /// Maximum node depth to prevent extreme recursion on pathological inputs.
@@ -17,7 +19,13 @@ const MAX_DEPTH: usize = 64;
 pub struct AstFormatter;
 
 impl AstFormatter {
-    fn node_to_json(node: Node, source: &[u8], depth: usize) -> Value {
+    /// Languages with a grammar compiled in
+    pub const LANGUAGES: &'static [&'static str] =
+        &["Rust", "Python", "JavaScript", "JSX", "TypeScript", "TSX"];
+
+    /// JSON for `node` and its subtree; once `deadline` passes, remaining
+    /// children are left out
+    fn node_to_json(node: Node, source: &[u8], depth: usize, deadline: Option<Instant>) -> Value {
         let start = node.start_position();
         let end = node.end_position();
 
@@ -43,10 +51,13 @@ impl AstFormatter {
                 }
             }
         } else if depth < MAX_DEPTH {
-            let children: Vec<Value> = (0..child_count as u32)
-                .filter_map(|i| node.child(i))
-                .map(|child| Self::node_to_json(child, source, depth + 1))
-                .collect();
+            let mut children = Vec::with_capacity(child_count);
+            for child in (0..child_count as u32).filter_map(|i| node.child(i)) {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    break;
+                }
+                children.push(Self::node_to_json(child, source, depth + 1, deadline));
+            }
             obj["children"] = json!(children);
         } else {
             obj["truncated_depth"] = json!(true);
@@ -55,49 +66,38 @@ impl AstFormatter {
         obj
     }
 
-    fn parse_to_tree(
+    pub(crate) fn parse_to_tree(
+        content: &str,
+        language: Option<&str>,
+    ) -> Option<(tree_sitter::Tree, &'static str)> {
+        Self::parse_to_tree_until(content, language, None)
+    }
+
+    /// Like [`Self::parse_to_tree`], but gives up once `deadline` passes
+    pub(crate) fn parse_to_tree_until(
         content: &str,
         language: Option<&str>,
+        deadline: Option<Instant>,
     ) -> Option<(tree_sitter::Tree, &'static str)> {
+        let language = language?;
+        let (grammar, name) = match crate::grammar_loader::bundled(language) {
+            Some(bundled) => bundled,
+            None => (
+                crate::grammar_loader::grammar_for(language)?,
+                "tree-sitter (runtime grammar)",
+            ),
+        };
         let mut parser = Parser::new();
-        match language {
-            Some("Rust") => {
-                parser
-                    .set_language(&tree_sitter_rust::LANGUAGE.into())
-                    .ok()?;
-                let tree = parser.parse(content, None)?;
-                Some((tree, "tree-sitter-rust"))
-            }
-            Some("Python") => {
-                parser
-                    .set_language(&tree_sitter_python::LANGUAGE.into())
-                    .ok()?;
-                let tree = parser.parse(content, None)?;
-                Some((tree, "tree-sitter-python"))
-            }
-            Some("JavaScript" | "JSX") => {
-                parser
-                    .set_language(&tree_sitter_javascript::LANGUAGE.into())
-                    .ok()?;
-                let tree = parser.parse(content, None)?;
-                Some((tree, "tree-sitter-javascript"))
-            }
-            Some("TypeScript") => {
-                parser
-                    .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
-                    .ok()?;
-                let tree = parser.parse(content, None)?;
-                Some((tree, "tree-sitter-typescript"))
-            }
-            Some("TSX") => {
-                parser
-                    .set_language(&tree_sitter_typescript::LANGUAGE_TSX.into())
-                    .ok()?;
-                let tree = parser.parse(content, None)?;
-                Some((tree, "tree-sitter-tsx"))
-            }
-            _ => None,
-        }
+        parser.set_language(&grammar).ok()?;
+        let tree = match deadline {
+            Some(deadline) => AstSummarizer::parse_with_deadline(
+                &mut parser,
+                content,
+                deadline.saturating_duration_since(Instant::now()),
+            )?,
+            None => parser.parse(content, None)?,
+        };
+        Some((tree, name))
     }
 }
 
@@ -118,15 +118,17 @@ impl Formatter for AstFormatter {
             .join("\n");
         let source = content.as_bytes();
 
-        let (root_value, parser_name) = match Self::parse_to_tree(&content, language) {
-            Some((tree, name)) => {
-                let root = tree.root_node();
-                (Self::node_to_json(root, source, 0), name)
-            }
-            None => (Value::Null, "none"),
-        };
+        let deadline = file_info.deadline;
+        let (root_value, parser_name) =
+            match Self::parse_to_tree_until(&content, language, deadline) {
+                Some((tree, name)) => {
+                    let root = tree.root_node();
+                    (Self::node_to_json(root, source, 0, deadline), name)
+                }
+                None => (Value::Null, "none"),
+            };
 
-        let output = json!({
+        let mut output = json!({
             "file": file_path,
             "language": language,
             "mode": "ast",
@@ -135,6 +137,9 @@ impl Formatter for AstFormatter {
             "total_bytes": file_info.total_bytes,
             "root": root_value,
         });
+        if file_info.timed_out || file_info.deadline_passed() {
+            output["timed_out"] = json!(true);
+        }
 
         if config.pretty_json {
             Ok(serde_json::to_string_pretty(&output)?)
@@ -193,4 +198,18 @@ mod tests {
         assert_eq!(json["parser"], "tree-sitter-python");
         assert_eq!(json["root"]["type"], "module");
     }
+
+    #[test]
+    fn test_ast_stops_at_deadline() {
+        let lines = (0..2000).map(|i| format!("fn f{i}() {{}}")).collect();
+        let mut fi = make_file_info(lines, Some("Rust".to_string()));
+        fi.deadline = Some(Instant::now());
+        let out = AstFormatter
+            .format(&fi, "test.rs", &BatlessConfig::default())
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["timed_out"], true);
+        let children = json["root"]["children"].as_array().map_or(0, Vec::len);
+        assert!(children < 2000);
+    }
 }
@@ -31,7 +31,11 @@ impl ErrorFormatter {
         match error {
             BatlessError::FileNotFound { .. } => "file not found",
             BatlessError::PermissionDenied { .. } => "permission denied",
+            BatlessError::FileChangedDuringProcessing { .. } => "file changed",
             BatlessError::ConfigurationError { .. } => "configuration error",
+            BatlessError::MemoryLimitExceeded { .. } => "memory limit exceeded",
+            BatlessError::StdinLimitExceeded { .. } => "stdin limit exceeded",
+            BatlessError::StdinTimeout { .. } => "stdin timeout",
             BatlessError::ProcessingError { .. } => "processing error",
             BatlessError::IoError(_) => "I/O error",
             BatlessError::EncodingError { .. } => "encoding error",
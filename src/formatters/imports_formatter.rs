@@ -0,0 +1,229 @@
+//! Import graph output mode for batless
+//!
+//! Extracts only import/use/include statements and emits a dependency edge
+//! list (file → imported module), giving AI agents a quick architecture view
+//! without reading file bodies.
+
+use crate::ast_summarizer::AstSummarizer;
+use crate::config::BatlessConfig;
+use crate::error::BatlessResult;
+use crate::file_info::FileInfo;
+use crate::formatter::OutputMode;
+use crate::formatters::Formatter;
+use crate::summarizer::SummaryExtractor;
+use crate::summary::SummaryLevel;
+use serde::Serialize;
+use serde_json::json;
+
+/// A single import statement and the modules it references
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportStatement {
+    pub line: usize,
+    pub statement: String,
+    pub modules: Vec<String>,
+}
+
+/// Import graph formatter — emits the file's dependency edges as JSON.
+pub struct ImportsFormatter;
+
+impl ImportsFormatter {
+    /// Collect import statements using the summarizers' import detection
+    pub fn extract_imports(lines: &[String], language: Option<&str>) -> Vec<ImportStatement> {
+        let content = lines.join("\n");
+        let mut items = AstSummarizer::extract_summary(&content, language, SummaryLevel::Detailed);
+        if items.is_empty() {
+            items = SummaryExtractor::extract_summary(lines, language, SummaryLevel::Detailed);
+        }
+
+        let mut imports: Vec<ImportStatement> = items
+            .iter()
+            .filter(|item| matches!(item.kind.as_str(), "use" | "import" | "import_from"))
+            .map(|item| {
+                let statement = match item.end_line {
+                    Some(end) if end > item.line_number => {
+                        lines[item.line_number - 1..end.min(lines.len())].join("\n")
+                    }
+                    _ => item.line.clone(),
+                };
+                ImportStatement {
+                    line: item.line_number,
+                    modules: Self::modules_of(&statement),
+                    statement: statement.trim().to_string(),
+                }
+            })
+            .collect();
+
+        // Preprocessor includes are not summary items; pick them up directly
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("#include") || trimmed.starts_with("#import") {
+                imports.push(ImportStatement {
+                    line: index + 1,
+                    statement: trimmed.to_string(),
+                    modules: Self::modules_of(trimmed),
+                });
+            }
+        }
+        imports.sort_by_key(|i| i.line);
+        imports.retain(|i| !i.modules.is_empty());
+        imports
+    }
+
+    /// Extract module names referenced by an import statement
+    fn modules_of(statement: &str) -> Vec<String> {
+        let s = statement.trim();
+
+        // Quoted or bracketed targets: JS/TS imports, require(), Go, C includes
+        let quoted: Vec<String> = s
+            .split(['"', '\'', '`'])
+            .skip(1)
+            .step_by(2)
+            .filter(|m| !m.is_empty())
+            .map(String::from)
+            .collect();
+        if !quoted.is_empty() {
+            return quoted;
+        }
+        if let Some(start) = s.find('<') {
+            if let Some(len) = s[start + 1..].find('>') {
+                return vec![s[start + 1..start + 1 + len].to_string()];
+            }
+        }
+
+        let s = s.trim_end_matches(';');
+        let s = s.strip_prefix("pub ").unwrap_or(s);
+        let s = s.strip_prefix("pub(crate) ").unwrap_or(s);
+
+        // Rust: `use a::b::{c, d};` -> `a::b`
+        if let Some(path) = s.strip_prefix("use ") {
+            let path = path.split(" as ").next().unwrap_or(path);
+            let path = path
+                .split("::{")
+                .next()
+                .unwrap_or(path)
+                .trim_end_matches("::*")
+                .trim();
+            return vec![path.to_string()];
+        }
+
+        // Python: `from a.b import c` -> `a.b`
+        if let Some(rest) = s.strip_prefix("from ") {
+            return rest
+                .split_whitespace()
+                .next()
+                .map(|m| vec![m.to_string()])
+                .unwrap_or_default();
+        }
+
+        // `import a, b as c` (Python) and `import a.b.C` (Java, Kotlin, Scala, Swift, Haskell)
+        if let Some(rest) = s.strip_prefix("import ") {
+            let rest = rest
+                .trim_start_matches("static ")
+                .trim_start_matches("qualified ");
+            return rest
+                .split(',')
+                .filter_map(|part| part.split_whitespace().next())
+                .map(String::from)
+                .collect();
+        }
+
+        Vec::new()
+    }
+}
+
+impl Formatter for ImportsFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        let lines = file_info
+            .original_lines
+            .as_deref()
+            .unwrap_or(&file_info.lines);
+        let imports = Self::extract_imports(lines, file_info.language.as_deref());
+
+        let mut edges: Vec<&str> = Vec::new();
+        for module in imports.iter().flat_map(|i| &i.modules) {
+            if !edges.contains(&module.as_str()) {
+                edges.push(module);
+            }
+        }
+        let edges: Vec<_> = edges
+            .into_iter()
+            .map(|module| json!({"from": file_path, "to": module}))
+            .collect();
+
+        let output = json!({
+            "file": file_path,
+            "language": file_info.language,
+            "import_count": imports.len(),
+            "imports": imports,
+            "edges": edges,
+            "mode": "imports",
+        });
+
+        if config.pretty_json {
+            Ok(serde_json::to_string_pretty(&output)?)
+        } else {
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::Imports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn modules(text: &str, language: &str) -> Vec<String> {
+        ImportsFormatter::extract_imports(&lines(text), Some(language))
+            .into_iter()
+            .flat_map(|i| i.modules)
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_imports() {
+        let code = "use std::io::{self, Write};\nuse crate::config::BatlessConfig;\nfn main() {}";
+        assert_eq!(
+            modules(code, "Rust"),
+            ["std::io", "crate::config::BatlessConfig"]
+        );
+    }
+
+    #[test]
+    fn test_python_imports() {
+        let code = "import os, sys\nfrom collections import OrderedDict\n\ndef f():\n    pass";
+        assert_eq!(modules(code, "Python"), ["os", "sys", "collections"]);
+    }
+
+    #[test]
+    fn test_js_and_c_imports() {
+        let code = "import React from 'react';\nimport { a } from \"./util\";\nfunction f() {}";
+        assert_eq!(modules(code, "JavaScript"), ["react", "./util"]);
+        let code = "#include <stdio.h>\n#include \"local.h\"\nint main() {}";
+        assert_eq!(modules(code, "C"), ["stdio.h", "local.h"]);
+    }
+
+    #[test]
+    fn test_format_edges() {
+        let info = FileInfo::with_metadata(3, 0, Some("Python".to_string()), "UTF-8".to_string())
+            .with_lines(lines("import os\nimport os.path\nimport os"));
+        let out = ImportsFormatter
+            .format(&info, "a.py", &BatlessConfig::default())
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["import_count"], 3);
+        assert_eq!(json["edges"].as_array().unwrap().len(), 2);
+        assert_eq!(json["edges"][1]["to"], "os.path");
+    }
+}
@@ -10,10 +10,12 @@ use crate::error::BatlessResult;
 use crate::file_info::FileInfo;
 use crate::formatter::OutputMode;
 use crate::formatters::Formatter;
+use crate::permalink::Permalinker;
 use crate::summarizer::SummaryExtractor;
 use crate::summary::SummaryLevel;
 use crate::summary_item::SummaryItem;
 use serde_json::{json, Value};
+use std::time::Instant;
 
 /// Symbol index formatter — emits a compact JSON symbol table.
 pub struct IndexFormatter;
@@ -25,7 +27,7 @@ impl IndexFormatter {
     /// - Rust:  `pub fn foo(`, `struct Foo {`, `impl Foo {`, `trait Foo {`
     /// - Python: `def foo(`, `class Foo:`
     /// - JS/TS:  `function foo(`, `class Foo`, `const foo =`, `export function foo(`
-    fn extract_name(line: &str) -> Option<String> {
+    pub(crate) fn extract_name(line: &str) -> Option<String> {
         let t = line.trim();
         // Strip common visibility / keyword prefixes to get to the identifier
         let t = t
@@ -66,42 +68,50 @@ impl IndexFormatter {
         }
     }
 
-    /// Determine visibility from the signature line.
-    fn extract_visibility(line: &str, language: Option<&str>) -> Option<String> {
-        let t = line.trim();
-        let lang = language.unwrap_or("").to_lowercase();
-        if lang.contains("rust") {
-            if t.starts_with("pub(crate)") {
-                return Some("pub(crate)".to_string());
-            }
-            if t.starts_with("pub(super)") {
-                return Some("pub(super)".to_string());
-            }
-            if t.starts_with("pub ") {
-                return Some("pub".to_string());
-            }
-            return Some("private".to_string());
-        }
-        if lang.contains("javascript")
-            || lang.contains("typescript")
-            || lang.contains("jsx")
-            || lang.contains("tsx")
-        {
-            if t.starts_with("export default ")
-                || t.starts_with("export ")
-                || t.starts_with("module.exports")
-            {
-                return Some("export".to_string());
-            }
-            return Some("local".to_string());
+    /// Symbols of a file at detailed level, falling back to the regex
+    /// summarizer for languages without a grammar
+    pub fn extract_items(lines: &[String], language: Option<&str>) -> Vec<SummaryItem> {
+        Self::extract_items_until(lines, language, None)
+    }
+
+    /// Like [`Self::extract_items`], but stops once `deadline` passes with
+    /// the symbols found so far
+    pub fn extract_items_until(
+        lines: &[String],
+        language: Option<&str>,
+        deadline: Option<Instant>,
+    ) -> Vec<SummaryItem> {
+        let items = AstSummarizer::try_extract_summary_until(
+            &lines.join("\n"),
+            language,
+            SummaryLevel::Detailed,
+            false,
+            deadline,
+        )
+        .unwrap_or_default();
+        let expired = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if items.is_empty() && !expired {
+            SummaryExtractor::extract_summary(lines, language, SummaryLevel::Detailed)
+        } else {
+            items
         }
-        None
     }
 
     /// Convert a `SummaryItem` into a JSON symbol object.
-    fn symbol_to_json(item: &SummaryItem, language: Option<&str>) -> Value {
-        let name = Self::extract_name(&item.line).unwrap_or_else(|| "unknown".to_string());
-        let visibility = Self::extract_visibility(&item.line, language);
+    fn symbol_to_json(
+        item: &SummaryItem,
+        language: Option<&str>,
+        linker: Option<&Permalinker>,
+    ) -> Value {
+        let name = item
+            .name
+            .clone()
+            .or_else(|| Self::extract_name(&item.line))
+            .unwrap_or_else(|| "unknown".to_string());
+        let visibility = item
+            .visibility
+            .clone()
+            .or_else(|| SummaryItem::detect_visibility(&item.line, language));
         let mut obj = json!({
             "kind": item.kind,
             "name": name,
@@ -114,6 +124,18 @@ impl IndexFormatter {
         if let Some(vis) = visibility {
             obj["visibility"] = json!(vis);
         }
+        if let Some(ref container) = item.container {
+            obj["container"] = json!(container);
+        }
+        if let Some(ref qualified) = item.qualified_name {
+            obj["qualified_name"] = json!(qualified);
+        }
+        if let Some(metrics) = item.metrics {
+            obj["metrics"] = json!(metrics);
+        }
+        if let Some(linker) = linker {
+            obj["permalink"] = json!(linker.link(item.line_number, item.end_line));
+        }
         obj
     }
 }
@@ -123,27 +145,15 @@ impl Formatter for IndexFormatter {
         &self,
         file_info: &FileInfo,
         file_path: &str,
-        _config: &BatlessConfig,
+        config: &BatlessConfig,
     ) -> BatlessResult<String> {
         let language = file_info.language.as_deref();
-        let content = file_info.lines.join("\n");
-
-        // Use detailed summary level to capture the most symbols
-        let mut items: Vec<SummaryItem> =
-            AstSummarizer::extract_summary(&content, language, SummaryLevel::Detailed);
-
-        // Fall back to regex-based summarizer for unsupported languages
-        if items.is_empty() {
-            items = SummaryExtractor::extract_summary(
-                &file_info.lines,
-                language,
-                SummaryLevel::Detailed,
-            );
-        }
+        let linker = Permalinker::from_config(config, file_path);
+        let items = Self::extract_items_until(&file_info.lines, language, file_info.deadline);
 
         let symbols: Vec<Value> = items
             .iter()
-            .map(|item| Self::symbol_to_json(item, language))
+            .map(|item| Self::symbol_to_json(item, language, linker.as_ref()))
             .collect();
 
         let mut output = json!({
@@ -156,6 +166,9 @@ impl Formatter for IndexFormatter {
             "mode": "index",
         });
 
+        if file_info.timed_out || file_info.deadline_passed() {
+            output["timed_out"] = json!(true);
+        }
         if let Some(ref hash) = file_info.file_hash {
             output["file_hash"] = json!(hash);
         }
@@ -195,34 +208,28 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_name_js_function() {
-        assert_eq!(
-            IndexFormatter::extract_name("export function handleRequest("),
-            Some("handleRequest".to_string())
-        );
-    }
-
-    #[test]
-    fn test_extract_visibility_rust_pub() {
-        assert_eq!(
-            IndexFormatter::extract_visibility("pub fn foo()", Some("Rust")),
-            Some("pub".to_string())
-        );
-    }
-
-    #[test]
-    fn test_extract_visibility_rust_private() {
-        assert_eq!(
-            IndexFormatter::extract_visibility("fn foo()", Some("Rust")),
-            Some("private".to_string())
-        );
+    fn test_index_stops_at_deadline() {
+        let lines: Vec<String> = (0..2000).map(|i| format!("fn f{i}() {{}}")).collect();
+        let mut fi = FileInfo::new().with_lines(lines);
+        fi.language = Some("Rust".to_string());
+        let config = BatlessConfig::default();
+        let full: Value =
+            serde_json::from_str(&IndexFormatter.format(&fi, "a.rs", &config).unwrap()).unwrap();
+        assert_eq!(full["symbol_count"], 2000);
+        assert!(full.get("timed_out").is_none());
+
+        fi.deadline = Some(Instant::now());
+        let cut: Value =
+            serde_json::from_str(&IndexFormatter.format(&fi, "a.rs", &config).unwrap()).unwrap();
+        assert_eq!(cut["timed_out"], true);
+        assert!(cut["symbol_count"].as_u64().unwrap() < 2000);
     }
 
     #[test]
-    fn test_extract_visibility_js_export() {
+    fn test_extract_name_js_function() {
         assert_eq!(
-            IndexFormatter::extract_visibility("export function foo() {}", Some("JavaScript")),
-            Some("export".to_string())
+            IndexFormatter::extract_name("export function handleRequest("),
+            Some("handleRequest".to_string())
         );
     }
 }
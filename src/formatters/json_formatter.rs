@@ -5,6 +5,8 @@ use crate::error::{BatlessError, BatlessResult};
 use crate::file_info::FileInfo;
 use crate::formatter::OutputMode;
 use crate::formatters::Formatter;
+use crate::highlight_spans;
+use crate::summary_item::SummaryItem;
 use serde_json::json;
 
 pub struct JsonFormatter;
@@ -21,11 +23,18 @@ impl Formatter for JsonFormatter {
             .as_ref()
             .unwrap_or(&file_info.lines);
 
+        let first_line = file_info.first_line_number();
         let lines_value: serde_json::Value = if config.json_line_numbers {
             line_source
                 .iter()
                 .enumerate()
-                .map(|(i, text)| json!({"n": i + 1, "text": text}))
+                .map(|(i, text)| {
+                    let mut line = json!({"n": i + first_line, "text": text});
+                    if config.is_emphasized(i + first_line) {
+                        line["emphasized"] = json!(true);
+                    }
+                    line
+                })
                 .collect()
         } else {
             json!(line_source)
@@ -42,9 +51,18 @@ impl Formatter for JsonFormatter {
             "truncated_by_lines": file_info.truncated_by_lines,
             "truncated_by_bytes": file_info.truncated_by_bytes,
             "truncated_by_context": file_info.truncated_by_context,
+            "timed_out": file_info.timed_out,
+            "line_endings": {
+                "style": file_info.line_endings.style(),
+                "lf": file_info.line_endings.lf,
+                "crlf": file_info.line_endings.crlf,
+                "cr": file_info.line_endings.cr,
+            },
+            "had_bom": file_info.had_bom,
             "language": file_info.language,
             "encoding": file_info.encoding,
             "syntax_errors": file_info.syntax_errors,
+            "warnings": file_info.warnings,
             "mode": "json"
         });
 
@@ -53,9 +71,67 @@ impl Formatter for JsonFormatter {
         }
         json_data["identifier_count"] = json!(file_info.token_count());
         json_data["identifiers_truncated"] = json!(file_info.tokens_truncated());
+        if let Some(ref frequencies) = file_info.token_frequencies {
+            let map: serde_json::Map<String, serde_json::Value> = frequencies
+                .iter()
+                .map(|entry| (entry.token.clone(), json!(entry.count)))
+                .collect();
+            json_data["token_frequencies"] = serde_json::Value::Object(map);
+        }
 
         if let Some(ref summary_lines) = file_info.summary_lines {
             json_data["summary_lines"] = json!(summary_lines);
+            let items: Vec<_> = summary_lines.iter().map(SummaryItem::record).collect();
+            json_data["summary_items"] = json!(items);
+        }
+        if let Some(engine) = file_info.summary_engine {
+            json_data["summary_engine"] = json!(engine.as_str());
+        }
+        if let Some(ref uses) = file_info.uses {
+            json_data["uses"] = json!(uses);
+        }
+        if let Some(ref media) = file_info.media {
+            json_data["media"] = json!(media);
+        }
+        if let Some(ref dataset) = file_info.dataset {
+            json_data["dataset"] = json!(dataset);
+        }
+        if let Some(ref compression) = file_info.compression {
+            json_data["compression"] = json!(compression);
+        }
+        if file_info.folded {
+            json_data["folded"] = json!(true);
+        }
+        if config.has_annotations() {
+            json_data["annotations"] = json!(file_info.annotations);
+        }
+        if config.has_emphasis() {
+            let emphasized: Vec<usize> = (first_line..first_line + line_source.len())
+                .filter(|&n| config.is_emphasized(n))
+                .collect();
+            json_data["emphasized_lines"] = json!(emphasized);
+        }
+        if let Some(window) = file_info.window.as_ref().filter(|w| w.symbol.is_some()) {
+            json_data["symbol"] = json!({
+                "name": window.symbol,
+                "start": window.start,
+                "end": window.end,
+                "definition": window.enclosing.as_ref().map(SummaryItem::record),
+            });
+        } else if let Some(ref window) = file_info.window {
+            json_data["around"] = json!({
+                "line": window.line,
+                "start": window.start,
+                "end": window.end,
+                "enclosing": window.enclosing.as_ref().map(SummaryItem::record),
+            });
+        }
+        if let Some(source) = file_info.language_detection_source {
+            json_data["language_detection_source"] = json!(source.as_str());
+        }
+        if let Some(confidence) = file_info.language_confidence() {
+            json_data["language_confidence"] = json!(confidence);
+            json_data["language_candidates"] = json!(file_info.language_candidates);
         }
         if let Some(ref hash) = file_info.file_hash {
             json_data["file_hash"] = json!(hash);
@@ -69,6 +145,18 @@ impl Formatter for JsonFormatter {
         if let Some(ratio) = file_info.compression_ratio {
             json_data["compression_ratio"] = json!(ratio);
         }
+        if let Some(ref redactions) = file_info.redactions {
+            json_data["redactions"] = json!(redactions);
+            json_data["redaction_count"] = json!(file_info.redaction_count());
+        }
+        if config.highlight_spans {
+            let content = line_source.join("\n");
+            if let Some(spans) =
+                highlight_spans::line_spans(&content, file_info.language.as_deref())
+            {
+                json_data["highlight_spans"] = json!(spans);
+            }
+        }
 
         if config.pretty_json {
             serde_json::to_string_pretty(&json_data).map_err(BatlessError::from)
@@ -0,0 +1,78 @@
+//! JSON Lines output formatter (one compact object per source line)
+
+use crate::config::BatlessConfig;
+use crate::error::BatlessResult;
+use crate::file_info::FileInfo;
+use crate::formatter::OutputMode;
+use crate::formatters::Formatter;
+use crate::highlight_spans;
+use serde_json::{json, Value};
+
+pub struct JsonlFormatter;
+
+impl JsonlFormatter {
+    /// Build the JSON object for a single line
+    pub fn row(line_number: usize, text: &str) -> Value {
+        json!({ "n": line_number, "text": text })
+    }
+}
+
+impl Formatter for JsonlFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        _file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        // Summary items carry their original line numbers; plain lines are
+        // sequential from the start of the --around window (or line 1)
+        let numbered: Vec<(usize, &str)> = match &file_info.summary_lines {
+            Some(items) => items
+                .iter()
+                .map(|item| (item.line_number, item.line.as_str()))
+                .collect(),
+            None => file_info
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(i, line)| (i + file_info.first_line_number(), line.as_str()))
+                .collect(),
+        };
+
+        let spans = if config.highlight_spans {
+            let source = file_info
+                .original_lines
+                .as_deref()
+                .unwrap_or(&file_info.lines)
+                .join("\n");
+            highlight_spans::line_spans(&source, file_info.language.as_deref())
+        } else {
+            None
+        };
+
+        // Spans are computed over the lines we hold, which start at the window
+        let first_line = file_info.first_line_number();
+        let rendered = numbered
+            .into_iter()
+            .map(|(n, line)| {
+                let mut row = Self::row(n, line);
+                if let Some(line_spans) = spans
+                    .as_ref()
+                    .zip(n.checked_sub(first_line))
+                    .and_then(|(s, i)| s.get(i))
+                {
+                    row["highlight_spans"] = json!(line_spans);
+                }
+                if config.is_emphasized(n) {
+                    row["emphasized"] = json!(true);
+                }
+                serde_json::to_string(&row)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rendered.join("\n"))
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::Jsonl
+    }
+}
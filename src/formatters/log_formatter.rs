@@ -0,0 +1,282 @@
+//! Log output modes (`--mode=log`, `--mode=log-json`)
+//!
+//! Splits service and CI logs into records: a line carrying a timestamp or
+//! a level starts a record, indented lines (stack traces, wrapped messages)
+//! continue it. JSON-per-line logs (`{"level": "warn", "msg": ...}`) are
+//! recognised too. Records can be filtered with `--min-level` and are either
+//! colored by level or emitted as one JSON object each.
+
+use crate::config::{BatlessConfig, LogLevel};
+use crate::error::BatlessResult;
+use crate::file_info::FileInfo;
+use crate::formatter::OutputMode;
+use crate::formatters::Formatter;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// Resets the color of a record
+const RESET: &str = "\x1b[0m";
+
+/// A log entry and the source lines it spans
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogRecord {
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<LogLevel>,
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Last source line of the record
+    pub fn last_line(&self) -> usize {
+        self.end_line.unwrap_or(self.line)
+    }
+}
+
+struct LogPatterns {
+    timestamp: Regex,
+    level: Regex,
+}
+
+fn patterns() -> &'static LogPatterns {
+    static PATTERNS: OnceLock<LogPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let compile = |pattern| Regex::new(pattern).expect("log patterns are valid");
+        LogPatterns {
+            // 2024-05-01T12:00:00.123Z, [2024-05-01 12:00:00,123], May  1 12:00:00, 12:00:00.5
+            timestamp: compile(
+                r"^\[?(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?|[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}|\d{2}:\d{2}:\d{2}(?:[.,]\d+)?)\]?",
+            ),
+            // ERROR, [warn], level=info, or a leading `warning:` / `error[E0308]:`
+            level: compile(
+                r"(?x)
+                \b(TRACE|DEBUG|INFO|NOTICE|WARN|WARNING|ERROR|ERR|FATAL|CRITICAL|CRIT|PANIC)\b
+                | (?i:\[(trace|debug|info|notice|warn|warning|error|err|fatal|critical|crit)\])
+                | (?i:\blevel=(\w+))
+                | ^(?i:(warning|error|note|info|debug))(?:\[\w+\])?:",
+            ),
+        }
+    })
+}
+
+/// Compile the record patterns now instead of on first use
+pub(crate) fn warm_up() {
+    patterns();
+}
+
+/// Timestamp and level of a line that starts a record
+fn parse_line(line: &str) -> (Option<String>, Option<LogLevel>, String) {
+    if line.trim_start().starts_with('{') {
+        if let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(line) {
+            let text = |keys: &[&str]| {
+                keys.iter()
+                    .find_map(|key| fields.get(*key))
+                    .map(|v| v.as_str().map_or_else(|| v.to_string(), String::from))
+            };
+            let level = ["level", "lvl", "severity", "levelname"]
+                .iter()
+                .find_map(|key| fields.get(*key))
+                .and_then(|v| match v {
+                    Value::String(name) => LogLevel::parse(name),
+                    // pino / bunyan numeric levels
+                    Value::Number(n) => match n.as_u64()? {
+                        0..=10 => Some(LogLevel::Trace),
+                        11..=20 => Some(LogLevel::Debug),
+                        21..=30 => Some(LogLevel::Info),
+                        31..=40 => Some(LogLevel::Warn),
+                        41..=50 => Some(LogLevel::Error),
+                        _ => Some(LogLevel::Fatal),
+                    },
+                    _ => None,
+                });
+            let timestamp = text(&["timestamp", "time", "ts", "@timestamp"]);
+            let message = text(&["msg", "message"]).unwrap_or_else(|| line.to_string());
+            return (timestamp, level, message);
+        }
+    }
+
+    let patterns = patterns();
+    let (timestamp, rest) = match patterns.timestamp.captures(line) {
+        Some(caps) => (
+            Some(caps[1].to_string()),
+            line[caps[0].len()..].trim_start(),
+        ),
+        None => (None, line),
+    };
+    let level = patterns.level.captures(rest).and_then(|caps| {
+        caps.iter()
+            .skip(1)
+            .flatten()
+            .find_map(|m| LogLevel::parse(m.as_str()))
+    });
+    (timestamp, level, rest.trim_end().to_string())
+}
+
+/// Group `lines` (numbered from `first_line`) into records
+pub fn parse_records(lines: &[String], first_line: usize) -> Vec<LogRecord> {
+    let mut records: Vec<LogRecord> = Vec::new();
+    for (offset, line) in lines.iter().enumerate() {
+        let number = first_line + offset;
+        let (timestamp, level, message) = parse_line(line);
+        let continues = timestamp.is_none()
+            && level.is_none()
+            && (line.trim().is_empty()
+                || line.starts_with([' ', '\t'])
+                || line.starts_with("Caused by"));
+        match records.last_mut() {
+            Some(record) if continues => {
+                record.end_line = Some(number);
+                if !line.trim().is_empty() {
+                    record.message.push('\n');
+                    record.message.push_str(line.trim_end());
+                }
+            }
+            _ => records.push(LogRecord {
+                line: number,
+                end_line: None,
+                timestamp,
+                level,
+                message,
+            }),
+        }
+    }
+    records
+}
+
+/// Records that pass `--min-level`; unleveled records are dropped when it is set
+fn visible_records(file_info: &FileInfo, config: &BatlessConfig) -> Vec<LogRecord> {
+    let mut records = parse_records(&file_info.lines, file_info.first_line_number());
+    if let Some(min) = config.min_log_level {
+        records.retain(|r| r.level.is_some_and(|level| level >= min));
+    }
+    records
+}
+
+/// Log records as text, colored by level
+pub struct LogFormatter;
+
+impl Formatter for LogFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        _file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        let numbered = config.show_line_numbers || config.show_line_numbers_nonblank;
+        let first_line = file_info.first_line_number();
+        let mut result = Vec::new();
+        for record in visible_records(file_info, config) {
+            for number in record.line..=record.last_line() {
+                let line = &file_info.lines[number - first_line];
+                let text = if numbered {
+                    format!("{number:6}\t{line}")
+                } else {
+                    line.clone()
+                };
+                match record.level.filter(|_| config.use_color) {
+                    Some(level) => result.push(format!("{}{text}{RESET}", level.ansi())),
+                    None => result.push(text),
+                }
+            }
+        }
+        Ok(result.join("\n"))
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::Log
+    }
+}
+
+/// One compact JSON object per log record
+pub struct LogJsonFormatter;
+
+impl Formatter for LogJsonFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        _file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        let rendered = visible_records(file_info, config)
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rendered.join("\n"))
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::LogJson
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start line, end line, timestamp and level of a record
+    type RecordShape<'a> = (usize, Option<usize>, Option<&'a str>, Option<LogLevel>);
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_common_formats() {
+        let log = "2024-05-01T12:00:00Z INFO server started\n\
+                   [2024-05-01 12:00:01,5] [warn] slow request\n\
+                   May  1 12:00:02 host app[1]: ERROR boom\n    at handler (app.js:3:9)\n\
+                   warning: unused variable\n\
+                   {\"level\":\"error\",\"time\":\"12:00:03\",\"msg\":\"db down\"}\n\
+                   {\"level\":50,\"msg\":\"pino\"}\n\
+                   plain line\n";
+        let records = parse_records(&lines(log), 1);
+        let summary: Vec<RecordShape> = records
+            .iter()
+            .map(|r| (r.line, r.end_line, r.timestamp.as_deref(), r.level))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, None, Some("2024-05-01T12:00:00Z"), Some(LogLevel::Info)),
+                (2, None, Some("2024-05-01 12:00:01,5"), Some(LogLevel::Warn)),
+                (3, Some(4), Some("May  1 12:00:02"), Some(LogLevel::Error)),
+                (5, None, None, Some(LogLevel::Warn)),
+                (6, None, Some("12:00:03"), Some(LogLevel::Error)),
+                (7, None, None, Some(LogLevel::Error)),
+                (8, None, None, None),
+            ]
+        );
+        assert_eq!(
+            records[2].message,
+            "host app[1]: ERROR boom\n    at handler (app.js:3:9)"
+        );
+        assert_eq!(records[4].message, "db down");
+    }
+
+    #[test]
+    fn test_min_level_filters_and_colors() {
+        let info = FileInfo::with_metadata(4, 0, None, "UTF-8".to_string())
+            .with_lines(lines("DEBUG a\nERROR b\n  detail\nno level"));
+        let config = BatlessConfig::default()
+            .with_min_log_level(Some(LogLevel::Warn))
+            .with_use_color(false);
+        let out = LogFormatter.format(&info, "app.log", &config).unwrap();
+        assert_eq!(out, "ERROR b\n  detail");
+
+        let colored = LogFormatter
+            .format(&info, "app.log", &config.clone().with_use_color(true))
+            .unwrap();
+        assert!(colored.starts_with("\x1b[31mERROR b\x1b[0m"));
+
+        let json = LogJsonFormatter.format(&info, "app.log", &config).unwrap();
+        let record: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(record["level"], "error");
+        assert_eq!(record["end_line"], 3);
+    }
+}
@@ -5,10 +5,15 @@
 
 pub mod ast_formatter;
 pub mod error_formatter;
+pub mod imports_formatter;
 pub mod index_formatter;
 pub mod json_formatter;
+pub mod jsonl_formatter;
+pub mod log_formatter;
 pub mod plain_formatter;
+pub mod scip_formatter;
 pub mod summary_formatter;
+pub mod tags_formatter;
 
 use crate::config::BatlessConfig;
 use crate::error::BatlessResult;
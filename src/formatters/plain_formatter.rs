@@ -1,13 +1,72 @@
 //! Plain text output formatter
 
+use crate::annotations::Annotation;
 use crate::config::BatlessConfig;
 use crate::error::BatlessResult;
 use crate::file_info::FileInfo;
 use crate::formatter::OutputMode;
 use crate::formatters::Formatter;
 
+/// Resets the style of an emphasized line
+const RESET: &str = "\x1b[0m";
+
 pub struct PlainFormatter;
 
+impl PlainFormatter {
+    /// Render `lines`, the first of which is line `first_line` of the file.
+    ///
+    /// `line_number` is the next number to print; it only advances on
+    /// numbered lines, so streamed output can carry it from chunk to chunk.
+    pub fn render_lines(
+        lines: &[String],
+        first_line: usize,
+        line_number: &mut usize,
+        annotations: &[Annotation],
+        config: &BatlessConfig,
+    ) -> Vec<String> {
+        let numbered = config.show_line_numbers || config.show_line_numbers_nonblank;
+        // Without numbers or color, the marker gets a column of its own
+        let marker_column = !numbered && !config.use_color && config.has_emphasis();
+        let mut result = Vec::with_capacity(lines.len());
+
+        for (offset, line) in lines.iter().enumerate() {
+            let target = config.is_emphasized(first_line + offset);
+            let skip_number = config.show_line_numbers_nonblank && line.trim().is_empty();
+            let text = if marker_column {
+                format!("{}{line}", if target { "> " } else { "  " })
+            } else if !numbered || skip_number {
+                line.clone()
+            } else {
+                // Emphasized lines swap the number's leading space for a marker
+                let text = if target {
+                    format!(">{line_number:5}\t{line}")
+                } else {
+                    format!("{line_number:6}\t{line}")
+                };
+                *line_number += 1;
+                text
+            };
+            if target && config.use_color {
+                result.push(format!("{}{text}{RESET}", config.highlight_style.ansi()));
+            } else {
+                result.push(text);
+            }
+            // Annotation notes go under their line, in the gutter when numbered
+            let gutter = if numbered {
+                "      \t"
+            } else if marker_column {
+                "  "
+            } else {
+                ""
+            };
+            for annotation in annotations.iter().filter(|a| a.line == first_line + offset) {
+                result.push(format!("{gutter}{}", annotation.note(config.use_color)));
+            }
+        }
+        result
+    }
+}
+
 impl Formatter for PlainFormatter {
     fn format(
         &self,
@@ -15,27 +74,16 @@ impl Formatter for PlainFormatter {
         _file_path: &str,
         config: &BatlessConfig,
     ) -> BatlessResult<String> {
-        if config.show_line_numbers || config.show_line_numbers_nonblank {
-            let mut result = Vec::new();
-            let mut line_number = 1usize;
-
-            for line in &file_info.lines {
-                if config.show_line_numbers_nonblank {
-                    if line.trim().is_empty() {
-                        result.push(line.clone());
-                    } else {
-                        result.push(format!("{line_number:6}\t{line}"));
-                        line_number += 1;
-                    }
-                } else {
-                    result.push(format!("{line_number:6}\t{line}"));
-                    line_number += 1;
-                }
-            }
-            Ok(result.join("\n"))
-        } else {
-            Ok(file_info.lines.join("\n"))
-        }
+        let first_line = file_info.first_line_number();
+        let mut line_number = first_line;
+        Ok(Self::render_lines(
+            &file_info.lines,
+            first_line,
+            &mut line_number,
+            &file_info.annotations,
+            config,
+        )
+        .join("\n"))
     }
 
     fn output_mode(&self) -> OutputMode {
@@ -0,0 +1,238 @@
+//! SCIP-lite symbol export (`--mode=scip`)
+//!
+//! Emits the definitions from the AST outline in the JSON shape of a SCIP
+//! `Index` (metadata, documents, occurrences and symbol information), so
+//! code-intelligence pipelines can index a repository without running a
+//! language server. Only definitions are exported; there are no references.
+
+use crate::config::BatlessConfig;
+use crate::error::BatlessResult;
+use crate::file_info::FileInfo;
+use crate::formatter::OutputMode;
+use crate::formatters::Formatter;
+use crate::summary_index::definitions;
+use crate::summary_item::SummaryItem;
+use serde::Serialize;
+
+/// `SymbolRole.Definition` in the SCIP protocol
+const DEFINITION_ROLE: u32 = 1;
+
+/// A SCIP index: tool metadata and one document per source file
+#[derive(Debug, Clone, Serialize)]
+pub struct ScipIndex {
+    pub metadata: ScipMetadata,
+    pub documents: Vec<ScipDocument>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScipMetadata {
+    pub version: u32,
+    pub tool_info: ScipToolInfo,
+    pub project_root: String,
+    pub text_document_encoding: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScipToolInfo {
+    pub name: String,
+    pub version: String,
+    pub arguments: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScipDocument {
+    pub relative_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub position_encoding: String,
+    pub occurrences: Vec<ScipOccurrence>,
+    pub symbols: Vec<ScipSymbol>,
+}
+
+/// Where a symbol occurs; ranges are zero-based `[line, start, end]`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScipOccurrence {
+    pub range: Vec<usize>,
+    pub symbol: String,
+    pub symbol_roles: u32,
+    /// `[start_line, 0, end_line, 0]` of the whole definition, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enclosing_range: Option<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScipSymbol {
+    pub symbol: String,
+    pub kind: String,
+    pub display_name: String,
+}
+
+impl ScipIndex {
+    /// An empty index for files under `project_root` (a `file://` URI)
+    pub fn new(project_root: impl Into<String>, arguments: Vec<String>) -> Self {
+        Self {
+            metadata: ScipMetadata {
+                version: 0,
+                tool_info: ScipToolInfo {
+                    name: "batless".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    arguments,
+                },
+                project_root: project_root.into(),
+                text_document_encoding: "UTF8".to_string(),
+            },
+            documents: Vec::new(),
+        }
+    }
+
+    /// Add a document with the definitions found in a processed file
+    pub fn add_file(&mut self, relative_path: &str, file_info: &FileInfo) {
+        let mut document = ScipDocument {
+            relative_path: relative_path.to_string(),
+            language: file_info.language.clone(),
+            position_encoding: "UTF8CodeUnitOffsetFromLineStart".to_string(),
+            occurrences: Vec::new(),
+            symbols: Vec::new(),
+        };
+        for (name, item) in definitions(file_info) {
+            let symbol = symbol_for(relative_path, &name, &item.kind);
+            let line = item.line_number - 1;
+            let start = item.line.find(&name).unwrap_or(0);
+            document.occurrences.push(ScipOccurrence {
+                range: vec![line, start, start + name.len()],
+                symbol: symbol.clone(),
+                symbol_roles: DEFINITION_ROLE,
+                enclosing_range: enclosing_range(&item),
+            });
+            if !document.symbols.iter().any(|s| s.symbol == symbol) {
+                document.symbols.push(ScipSymbol {
+                    symbol,
+                    kind: symbol_kind(&item.kind).to_string(),
+                    display_name: name,
+                });
+            }
+        }
+        self.documents.push(document);
+    }
+}
+
+fn enclosing_range(item: &SummaryItem) -> Option<Vec<usize>> {
+    item.end_line
+        .filter(|&end| end > item.line_number)
+        .map(|end| vec![item.line_number - 1, 0, end - 1, 0])
+}
+
+/// Global SCIP symbol: `batless . . . <path descriptors><name descriptor>`
+pub fn symbol_for(relative_path: &str, name: &str, kind: &str) -> String {
+    let mut symbol = String::from("batless . . . ");
+    for segment in relative_path
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+    {
+        symbol.push_str(&escape(segment));
+        symbol.push('/');
+    }
+    symbol.push_str(&escape(name));
+    symbol.push_str(match kind {
+        "function" | "method" => "().",
+        "class" | "struct" | "enum" | "trait" | "interface" | "type" | "type_alias" => "#",
+        "mod" | "module" | "namespace" => "/",
+        "macro" => "!",
+        _ => ".",
+    });
+    symbol
+}
+
+/// Backtick-quote descriptors that are not simple identifiers
+fn escape(descriptor: &str) -> String {
+    if descriptor
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '+' | '-' | '$'))
+    {
+        descriptor.to_string()
+    } else {
+        format!("`{}`", descriptor.replace('`', "``"))
+    }
+}
+
+/// `SymbolInformation.Kind` name for a summary kind
+fn symbol_kind(kind: &str) -> &'static str {
+    match kind {
+        "function" => "Function",
+        "method" => "Method",
+        "class" => "Class",
+        "struct" => "Struct",
+        "enum" => "Enum",
+        "trait" => "Trait",
+        "interface" => "Interface",
+        "mod" | "module" => "Module",
+        "namespace" => "Namespace",
+        "type" | "type_alias" => "TypeAlias",
+        "const" | "static" => "Constant",
+        "macro" => "Macro",
+        _ => "Variable",
+    }
+}
+
+/// A single-document SCIP index for one file
+pub struct ScipFormatter;
+
+impl Formatter for ScipFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        let mut index = ScipIndex::new("", Vec::new());
+        index.add_file(file_path.trim_start_matches("./"), file_info);
+        if config.pretty_json {
+            Ok(serde_json::to_string_pretty(&index)?)
+        } else {
+            Ok(serde_json::to_string(&index)?)
+        }
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::Scip
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_definitions_as_scip_documents() {
+        let info = FileInfo::with_metadata(0, 0, Some("Rust".to_string()), "UTF-8".to_string())
+            .with_lines(
+                "use std::fs;\n\npub struct Parser;\n\npub fn parse() {\n    todo!()\n}\n"
+                    .lines()
+                    .map(String::from)
+                    .collect(),
+            );
+        let mut index = ScipIndex::new("file:///repo", vec![]);
+        index.add_file("src/lib.rs", &info);
+        let json = serde_json::to_value(&index).unwrap();
+        let document = &json["documents"][0];
+        assert_eq!(document["relative_path"], "src/lib.rs");
+
+        let occurrences = document["occurrences"].as_array().unwrap();
+        assert_eq!(occurrences.len(), 2);
+        assert_eq!(
+            occurrences[0]["symbol"],
+            "batless . . . src/`lib.rs`/Parser#"
+        );
+        assert_eq!(occurrences[0]["range"], serde_json::json!([2, 11, 17]));
+        assert_eq!(
+            occurrences[1]["symbol"],
+            "batless . . . src/`lib.rs`/parse()."
+        );
+        assert_eq!(occurrences[1]["symbol_roles"], 1);
+        assert_eq!(
+            occurrences[1]["enclosing_range"],
+            serde_json::json!([4, 0, 6, 0])
+        );
+        assert_eq!(document["symbols"][1]["kind"], "Function");
+    }
+}
@@ -44,7 +44,27 @@ impl Formatter for SummaryFormatter {
         if let Some(ref summary_lines) = file_info.summary_lines {
             output.push("=== Code Structure ===".to_string());
             for item in summary_lines {
-                output.push(format!("line {}: {}", item.line_number, item.line));
+                // Nested items lead with their container (`UserRepo.save: def save(self):`)
+                let mut line = match item.qualified_name {
+                    Some(ref qualified) => format!("{qualified}: {}", item.line.trim_start()),
+                    None => item.line.clone(),
+                };
+                if let Some(metrics) = item.metrics {
+                    line = format!(
+                        "{line}  ({} lines, {} branches, depth {})",
+                        metrics.lines, metrics.branches, metrics.depth
+                    );
+                }
+                match item.permalink {
+                    Some(ref link) => {
+                        output.push(format!("line {}: {line}  <{link}>", item.line_number));
+                    }
+                    None => output.push(format!("line {}: {line}", item.line_number)),
+                }
+                // `--summary-docs`: the doc's first sentence, indented under its item
+                if let Some(ref doc) = item.doc {
+                    output.push(format!("    {doc}"));
+                }
             }
         } else {
             output.push("=== Content ===".to_string());
@@ -0,0 +1,199 @@
+//! Tags file output (`--mode=ctags`, `--mode=etags`)
+//!
+//! Turns the AST outline into tag files editors already understand, so
+//! batless can stand in as a fast tags generator. Several inputs (and
+//! walked directories) produce a single tags file.
+
+use crate::config::BatlessConfig;
+use crate::error::BatlessResult;
+use crate::file_info::FileInfo;
+use crate::formatter::OutputMode;
+use crate::formatters::Formatter;
+use crate::summary_index::definitions;
+use std::fmt::Write as _;
+
+/// A definition as it appears in a tags file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagEntry {
+    pub name: String,
+    pub file: String,
+    pub line: usize,
+    pub kind: String,
+    /// Source text of the definition line
+    pub text: String,
+    /// Byte offset of the definition line in the file
+    pub offset: usize,
+}
+
+impl TagEntry {
+    /// Tags for every named definition in `file_info`
+    pub fn collect(file_path: &str, file_info: &FileInfo) -> Vec<Self> {
+        let mut offsets = Vec::with_capacity(file_info.lines.len());
+        let mut offset = 0;
+        for line in &file_info.lines {
+            offsets.push(offset);
+            offset += line.len() + 1;
+        }
+        definitions(file_info)
+            .into_iter()
+            .map(|(name, item)| Self {
+                name,
+                file: file_path.to_string(),
+                line: item.line_number,
+                kind: item.kind,
+                text: file_info
+                    .lines
+                    .get(item.line_number - 1)
+                    .cloned()
+                    .unwrap_or(item.line),
+                offset: offsets.get(item.line_number - 1).copied().unwrap_or(0),
+            })
+            .collect()
+    }
+
+    /// Single-letter kind used by Exuberant/Universal Ctags
+    pub fn kind_letter(&self) -> char {
+        match self.kind.as_str() {
+            "function" => 'f',
+            "method" => 'm',
+            "class" => 'c',
+            "struct" => 's',
+            "enum" => 'g',
+            "trait" | "interface" => 'i',
+            "mod" | "module" | "namespace" => 'n',
+            "type" | "type_alias" => 't',
+            "const" | "static" | "assignment" | "variable" => 'v',
+            "macro" => 'M',
+            other => other.chars().next().unwrap_or('x'),
+        }
+    }
+}
+
+/// Exuberant-ctags file with a header, sorted by name then file and line
+pub fn render_ctags(entries: &mut [TagEntry]) -> String {
+    entries.sort_by(|a, b| (&a.name, &a.file, a.line).cmp(&(&b.name, &b.file, b.line)));
+    let mut out = String::from(
+        "!_TAG_FILE_FORMAT\t2\t/extended format; --format=1 will not append ;\" to lines/\n\
+         !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+         !_TAG_PROGRAM_NAME\tbatless\t//\n",
+    );
+    let _ = writeln!(
+        out,
+        "!_TAG_PROGRAM_VERSION\t{}\t//",
+        env!("CARGO_PKG_VERSION")
+    );
+    for entry in entries.iter() {
+        let _ = writeln!(
+            out,
+            "{}\t{}\t{};\"\t{}\tline:{}",
+            entry.name,
+            entry.file,
+            entry.line,
+            entry.kind_letter(),
+            entry.line
+        );
+    }
+    out
+}
+
+/// Emacs TAGS file: one section per file, entries in line order
+pub fn render_etags(entries: &[TagEntry]) -> String {
+    let mut files: Vec<&str> = Vec::new();
+    for entry in entries {
+        if !files.contains(&entry.file.as_str()) {
+            files.push(&entry.file);
+        }
+    }
+    let mut out = String::new();
+    for file in files {
+        let mut section = String::new();
+        for entry in entries.iter().filter(|e| e.file == file) {
+            // The search pattern is the line up to the end of the name
+            let pattern = entry
+                .text
+                .find(&entry.name)
+                .map_or(entry.text.as_str(), |at| {
+                    &entry.text[..at + entry.name.len()]
+                });
+            let _ = writeln!(
+                section,
+                "{pattern}\x7f{}\x01{},{}",
+                entry.name, entry.line, entry.offset
+            );
+        }
+        let _ = write!(out, "\x0c\n{file},{}\n{section}", section.len());
+    }
+    out
+}
+
+/// Tags for a single file in ctags format
+pub struct CtagsFormatter;
+
+impl Formatter for CtagsFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        file_path: &str,
+        _config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        let mut entries = TagEntry::collect(file_path, file_info);
+        Ok(render_ctags(&mut entries).trim_end().to_string())
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::Ctags
+    }
+}
+
+/// Tags for a single file in etags format
+pub struct EtagsFormatter;
+
+impl Formatter for EtagsFormatter {
+    fn format(
+        &self,
+        file_info: &FileInfo,
+        file_path: &str,
+        _config: &BatlessConfig,
+    ) -> BatlessResult<String> {
+        let entries = TagEntry::collect(file_path, file_info);
+        Ok(render_etags(&entries).trim_end().to_string())
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        OutputMode::Etags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rust_file(code: &str) -> FileInfo {
+        FileInfo::with_metadata(0, 0, Some("Rust".to_string()), "UTF-8".to_string())
+            .with_lines(code.lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn test_ctags_sorted_with_header() {
+        let info = rust_file("struct Zed;\n\npub fn alpha() {}\n");
+        let mut entries = TagEntry::collect("src/lib.rs", &info);
+        let tags = render_ctags(&mut entries);
+        let lines: Vec<&str> = tags.lines().collect();
+        assert!(lines[0].starts_with("!_TAG_FILE_FORMAT\t2\t"));
+        // Byte order, as `!_TAG_FILE_SORTED 1` promises
+        assert_eq!(lines[4], "Zed\tsrc/lib.rs\t1;\"\ts\tline:1");
+        assert_eq!(lines[5], "alpha\tsrc/lib.rs\t3;\"\tf\tline:3");
+    }
+
+    #[test]
+    fn test_etags_sections_and_offsets() {
+        let info = rust_file("struct Zed;\n\npub fn alpha() {}\n");
+        let entries = TagEntry::collect("src/lib.rs", &info);
+        let tags = render_etags(&entries);
+        let section = "struct Zed\x7fZed\x011,0\npub fn alpha\x7falpha\x013,13\n";
+        assert_eq!(
+            tags,
+            format!("\x0c\nsrc/lib.rs,{}\n{section}", section.len())
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Tree-sitter grammars loaded at runtime (`[grammars]`)
+//!
+//! Languages without a grammar compiled into batless can still be parsed by
+//! loading a grammar library built with the tree-sitter CLI
+//! (`tree-sitter build`). Each `[grammars]` entry maps a language name to a
+//! grammar name; the library in the grammar directory must export
+//! `tree_sitter_<name>`. Summaries for these languages use the query files
+//! in `~/.config/batless/queries/<language>/`.
+//!
+//! Loading native libraries needs the `dynamic-grammars` feature on a Unix
+//! platform. WebAssembly grammars need tree-sitter's wasm runtime, which
+//! batless does not bundle, so `.wasm` files are reported as unsupported.
+
+use crate::config::BatlessConfig;
+use crate::error::{BatlessError, BatlessResult};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, PoisonError, RwLock};
+use tree_sitter::Language;
+
+/// Library extensions tried for each file name, native first
+const EXTENSIONS: &[&str] = &["so", "dylib", "dll", "wasm"];
+
+/// Default grammar directory: `~/.config/batless/grammars`
+pub fn default_dir() -> Option<PathBuf> {
+    crate::sys::home_dir().map(|home| home.join(".config/batless/grammars"))
+}
+
+/// Files tried for `grammar` in `dir`, in order: `go.so`, `libgo.so`,
+/// `tree-sitter-go.so`, `libtree-sitter-go.so`, then the other extensions
+pub fn candidate_files(dir: &Path, grammar: &str) -> Vec<PathBuf> {
+    let stems = [
+        grammar.to_string(),
+        format!("lib{grammar}"),
+        format!("tree-sitter-{grammar}"),
+        format!("libtree-sitter-{grammar}"),
+    ];
+    EXTENSIONS
+        .iter()
+        .flat_map(|ext| {
+            stems
+                .iter()
+                .map(move |stem| dir.join(format!("{stem}.{ext}")))
+        })
+        .collect()
+}
+
+/// Symbol a grammar library exports (`tree_sitter_c_sharp` for `c-sharp`)
+pub fn symbol_name(grammar: &str) -> String {
+    format!("tree_sitter_{}", grammar.replace('-', "_"))
+}
+
+fn registry() -> &'static RwLock<HashMap<String, Language>> {
+    static GRAMMARS: OnceLock<RwLock<HashMap<String, Language>>> = OnceLock::new();
+    GRAMMARS.get_or_init(RwLock::default)
+}
+
+/// Load every `[grammars]` entry of `config`; returns how many were loaded
+pub fn load_configured(config: &BatlessConfig) -> BatlessResult<usize> {
+    if config.grammars.is_empty() {
+        return Ok(0);
+    }
+    let dir = config
+        .grammar_dir
+        .clone()
+        .or_else(default_dir)
+        .ok_or_else(|| {
+            BatlessError::config_error_with_help(
+                "Cannot locate the grammar directory".to_string(),
+                Some("Set grammar_dir in the configuration file".to_string()),
+            )
+        })?;
+    for (language, grammar) in &config.grammars {
+        let path = candidate_files(&dir, grammar)
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                BatlessError::config_error_with_help(
+                    format!(
+                        "No library for grammar '{grammar}' (language {language}) in {}",
+                        dir.display()
+                    ),
+                    Some(format!(
+                        "Build it with `tree-sitter build` and copy it there as {grammar}.so or {grammar}.dylib"
+                    )),
+                )
+            })?;
+        let loaded = load_library(&path, &symbol_name(grammar))?;
+        registry()
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(language.to_lowercase(), loaded);
+    }
+    Ok(config.grammars.len())
+}
+
+/// Grammar compiled into batless for `language`, with its crate name;
+/// WebAssembly builds compile none in, since the grammars are C sources
+/// that need a C library
+#[cfg(not(target_arch = "wasm32"))]
+pub fn bundled(language: &str) -> Option<(Language, &'static str)> {
+    Some(match language {
+        "Rust" => (tree_sitter_rust::LANGUAGE.into(), "tree-sitter-rust"),
+        "Python" => (tree_sitter_python::LANGUAGE.into(), "tree-sitter-python"),
+        "JavaScript" | "JSX" => (
+            tree_sitter_javascript::LANGUAGE.into(),
+            "tree-sitter-javascript",
+        ),
+        "TypeScript" => (
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            "tree-sitter-typescript",
+        ),
+        "TSX" => (
+            tree_sitter_typescript::LANGUAGE_TSX.into(),
+            "tree-sitter-tsx",
+        ),
+        _ => return None,
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn bundled(_language: &str) -> Option<(Language, &'static str)> {
+    None
+}
+
+/// Grammar loaded for `language`, compared case-insensitively
+pub fn grammar_for(language: &str) -> Option<Language> {
+    registry()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&language.to_lowercase())
+        .cloned()
+}
+
+fn load_library(path: &Path, symbol: &str) -> BatlessResult<Language> {
+    if path.extension().is_some_and(|ext| ext == "wasm") {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "{} is a WebAssembly grammar, which needs tree-sitter's wasm runtime",
+                path.display()
+            ),
+            Some("Use the native library from `tree-sitter build` (.so or .dylib)".to_string()),
+        ));
+    }
+    let language = native::load(path, symbol)?;
+    let abi = language.abi_version();
+    if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+        .contains(&abi)
+    {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "Grammar {} uses ABI version {abi}; batless supports {} to {}",
+                path.display(),
+                tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+                tree_sitter::LANGUAGE_VERSION
+            ),
+            Some("Rebuild the grammar with a matching tree-sitter CLI".to_string()),
+        ));
+    }
+    Ok(language)
+}
+
+#[cfg(all(unix, feature = "dynamic-grammars"))]
+mod native {
+    use crate::error::{BatlessError, BatlessResult};
+    use std::ffi::{c_void, CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use tree_sitter::Language;
+    use tree_sitter_language::LanguageFn;
+
+    fn load_error(path: &Path, reason: &str) -> BatlessError {
+        BatlessError::config_error_with_help(
+            format!("Cannot load grammar {}: {reason}", path.display()),
+            Some(
+                "Check that the file is a tree-sitter grammar built for this platform".to_string(),
+            ),
+        )
+    }
+
+    /// Text of the last `dlopen`/`dlsym` failure
+    fn last_error() -> String {
+        // SAFETY: dlerror returns null or a NUL-terminated string owned by libc
+        let message = unsafe { libc::dlerror() };
+        if message.is_null() {
+            return "unknown error".to_string();
+        }
+        // SAFETY: checked non-null above; copied before any other dl* call
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn load(path: &Path, symbol: &str) -> BatlessResult<Language> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| load_error(path, "path contains a NUL byte"))?;
+        let c_symbol =
+            CString::new(symbol).map_err(|_| load_error(path, "invalid grammar name"))?;
+        // SAFETY: both strings are NUL-terminated; the library is never
+        // closed, so the grammar stays mapped for the life of the process
+        let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(load_error(path, &last_error()));
+        }
+        // SAFETY: `handle` is a live library handle
+        let function: *mut c_void = unsafe { libc::dlsym(handle, c_symbol.as_ptr()) };
+        if function.is_null() {
+            return Err(load_error(path, &format!("{symbol} is not exported")));
+        }
+        // SAFETY: grammars generated by the tree-sitter CLI export
+        // `const TSLanguage *tree_sitter_<name>(void)`
+        let language_fn = unsafe {
+            LanguageFn::from_raw(std::mem::transmute::<
+                *mut c_void,
+                unsafe extern "C" fn() -> *const (),
+            >(function))
+        };
+        Ok(Language::new(language_fn))
+    }
+}
+
+#[cfg(not(all(unix, feature = "dynamic-grammars")))]
+mod native {
+    use crate::error::{BatlessError, BatlessResult};
+    use std::path::Path;
+    use tree_sitter::Language;
+
+    pub fn load(path: &Path, _symbol: &str) -> BatlessResult<Language> {
+        Err(BatlessError::config_error_with_help(
+            format!(
+                "Cannot load grammar {}: this build has no runtime grammar support",
+                path.display()
+            ),
+            Some("Rebuild with --features dynamic-grammars on Linux or macOS".to_string()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_files_and_symbol() {
+        let files = candidate_files(Path::new("/g"), "c-sharp");
+        assert_eq!(files[0], Path::new("/g/c-sharp.so"));
+        assert_eq!(files[3], Path::new("/g/libtree-sitter-c-sharp.so"));
+        assert_eq!(
+            files.last().unwrap(),
+            Path::new("/g/libtree-sitter-c-sharp.wasm")
+        );
+        assert_eq!(symbol_name("c-sharp"), "tree_sitter_c_sharp");
+    }
+
+    #[test]
+    fn test_load_errors_name_the_problem() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut grammars = std::collections::BTreeMap::new();
+        grammars.insert("Gleam".to_string(), "gleam".to_string());
+        let config = BatlessConfig::default()
+            .with_grammars(grammars)
+            .with_grammar_dir(Some(dir.path().to_path_buf()));
+        let missing = load_configured(&config).unwrap_err().to_string();
+        assert!(
+            missing.contains("No library for grammar 'gleam'"),
+            "{missing}"
+        );
+
+        std::fs::write(dir.path().join("gleam.wasm"), b"\0asm").unwrap();
+        let wasm = load_configured(&config).unwrap_err().to_string();
+        assert!(wasm.contains("WebAssembly"), "{wasm}");
+
+        std::fs::write(dir.path().join("gleam.so"), b"not a library").unwrap();
+        let invalid = load_configured(&config).unwrap_err().to_string();
+        assert!(invalid.contains("Cannot load grammar"), "{invalid}");
+        assert!(grammar_for("gleam").is_none());
+    }
+}
@@ -0,0 +1,185 @@
+//! Hex viewer (`--mode=hex`, `--mode=hex-json`)
+//!
+//! Renders raw bytes as offset, hex pairs and an ASCII gutter, like `xxd`,
+//! so binaries and files with broken encodings can be inspected without
+//! switching tools. Only the first `--max-bytes` bytes are read.
+
+use crate::error::{BatlessError, BatlessResult};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Read;
+
+/// Bytes shown per row
+pub const BYTES_PER_ROW: usize = 16;
+
+/// A byte range read from the start of an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexDump {
+    pub bytes: Vec<u8>,
+    /// Size of the whole input, when known
+    pub total_bytes: Option<u64>,
+    /// Whether `--max-bytes` cut the input short
+    pub truncated: bool,
+}
+
+impl HexDump {
+    /// Dump up to `max_bytes` of an in-memory buffer
+    pub fn from_bytes(bytes: &[u8], max_bytes: Option<usize>) -> Self {
+        let limit = max_bytes.unwrap_or(usize::MAX).min(bytes.len());
+        Self {
+            bytes: bytes[..limit].to_vec(),
+            total_bytes: Some(bytes.len() as u64),
+            truncated: limit < bytes.len(),
+        }
+    }
+
+    /// Read up to `max_bytes` from `path` (`-` for stdin)
+    pub fn read(path: &str, max_bytes: Option<usize>) -> BatlessResult<Self> {
+        let read_error = |e| BatlessError::FileReadError {
+            path: path.to_string(),
+            source: e,
+        };
+        let (reader, total_bytes): (Box<dyn Read>, Option<u64>) = if path == "-" {
+            (Box::new(std::io::stdin()), None)
+        } else {
+            let file = File::open(path).map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    BatlessError::file_not_found_with_suggestions(path.to_string())
+                } else {
+                    read_error(e)
+                }
+            })?;
+            let size = file.metadata().map_err(read_error)?.len();
+            (Box::new(file), Some(size))
+        };
+
+        // One byte past the limit tells piped input apart from an exact fit
+        let limit = max_bytes.map_or(u64::MAX, |max| max as u64 + 1);
+        let mut bytes = Vec::new();
+        reader
+            .take(limit)
+            .read_to_end(&mut bytes)
+            .map_err(read_error)?;
+        let truncated = max_bytes.is_some_and(|max| bytes.len() > max);
+        if let Some(max) = max_bytes {
+            bytes.truncate(max);
+        }
+        Ok(Self {
+            total_bytes: total_bytes.or(if truncated {
+                None
+            } else {
+                Some(bytes.len() as u64)
+            }),
+            bytes,
+            truncated,
+        })
+    }
+
+    /// `xxd`-style rows: `00000010: 4865 6c6c 6f0a ...  Hello.`
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for (row, chunk) in self.bytes.chunks(BYTES_PER_ROW).enumerate() {
+            if row > 0 {
+                out.push('\n');
+            }
+            let _ = write!(out, "{:08x}: ", row * BYTES_PER_ROW);
+            for i in 0..BYTES_PER_ROW {
+                match chunk.get(i) {
+                    Some(byte) => {
+                        let _ = write!(out, "{byte:02x}");
+                    }
+                    None => out.push_str("  "),
+                }
+                if i % 2 == 1 {
+                    out.push(' ');
+                }
+            }
+            out.push(' ');
+            out.push_str(&ascii(chunk));
+        }
+        out
+    }
+
+    /// Byte range with one `{offset, hex, ascii}` object per row
+    pub fn to_json(&self, file_path: &str) -> Value {
+        let rows: Vec<Value> = self
+            .bytes
+            .chunks(BYTES_PER_ROW)
+            .enumerate()
+            .map(|(row, chunk)| {
+                let hex = chunk
+                    .iter()
+                    .fold(String::with_capacity(chunk.len() * 2), |mut s, b| {
+                        let _ = write!(s, "{b:02x}");
+                        s
+                    });
+                json!({
+                    "offset": row * BYTES_PER_ROW,
+                    "hex": hex,
+                    "ascii": ascii(chunk),
+                })
+            })
+            .collect();
+        json!({
+            "file": file_path,
+            "offset": 0,
+            "length": self.bytes.len(),
+            "total_bytes": self.total_bytes,
+            "truncated": self.truncated,
+            "bytes_per_row": BYTES_PER_ROW,
+            "rows": rows,
+            "mode": "hex",
+        })
+    }
+}
+
+/// Printable ASCII as-is, everything else as `.`
+fn ascii(chunk: &[u8]) -> String {
+    chunk
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_render_rows() {
+        let dump = HexDump::from_bytes(b"Hello, world!\n\x00\xffXYZ", None);
+        assert_eq!(
+            dump.render_text(),
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 00ff  Hello, world!...\n\
+             00000010: 5859 5a                                  XYZ"
+        );
+        let json = dump.to_json("a.bin");
+        assert_eq!(json["rows"][1]["offset"], 16);
+        assert_eq!(json["rows"][1]["hex"], "58595a");
+        assert_eq!(json["length"], 19);
+    }
+
+    #[test]
+    fn test_read_respects_max_bytes() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&[0u8; 40]).unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let dump = HexDump::read(path, Some(20)).unwrap();
+        assert_eq!(dump.bytes.len(), 20);
+        assert!(dump.truncated);
+        assert_eq!(dump.total_bytes, Some(40));
+
+        let dump = HexDump::read(path, Some(40)).unwrap();
+        assert!(!dump.truncated);
+        assert!(HexDump::read("/no/such/file.bin", None).is_err());
+    }
+}
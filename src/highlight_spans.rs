@@ -0,0 +1,232 @@
+//! Semantic highlight span export
+//!
+//! With `--color=json-spans` batless emits per-line span data (byte ranges and
+//! a coarse scope name) instead of ANSI escapes, so web UIs and editors can
+//! re-render with their own theme. Spans come from the same tree-sitter parse
+//! used by AST and index modes; languages without a grammar produce no spans.
+//! Streaming chunks use [`ChunkHighlighter`], which carries the unfinished
+//! tail of one chunk into the next parse.
+
+use crate::formatters::ast_formatter::AstFormatter;
+use serde::Serialize;
+use tree_sitter::Node;
+
+/// A styled region of a single line; `start`/`end` are byte offsets into the line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HighlightSpan {
+    pub start: usize,
+    pub end: usize,
+    pub scope: &'static str,
+}
+
+/// Compute spans for each line of `content`, or `None` when the language has
+/// no tree-sitter grammar.
+pub fn line_spans(content: &str, language: Option<&str>) -> Option<Vec<Vec<HighlightSpan>>> {
+    let (tree, _) = AstFormatter::parse_to_tree(content, language)?;
+    Some(spans_for_tree(&tree, content))
+}
+
+fn spans_for_tree(tree: &tree_sitter::Tree, content: &str) -> Vec<Vec<HighlightSpan>> {
+    let line_lengths: Vec<usize> = content.split('\n').map(str::len).collect();
+    let mut spans = vec![Vec::new(); line_lengths.len()];
+    collect(tree.root_node(), &line_lengths, &mut spans);
+    spans
+}
+
+/// Spans for consecutive chunks of one file
+///
+/// Each chunk is parsed together with the lines of the last top-level
+/// construct of the previous chunks, so a string or comment left open at a
+/// chunk boundary is still styled correctly in the next chunk.
+pub struct ChunkHighlighter {
+    language: Option<String>,
+    carry: Vec<String>,
+}
+
+impl ChunkHighlighter {
+    /// Longest tail carried between chunks; longer constructs start afresh
+    const MAX_CARRY_LINES: usize = 2000;
+
+    pub fn new(language: Option<&str>) -> Self {
+        Self {
+            language: language.map(String::from),
+            carry: Vec::new(),
+        }
+    }
+
+    /// Spans for the next chunk's lines, or `None` without a grammar
+    pub fn next_chunk(&mut self, lines: &[String]) -> Option<Vec<Vec<HighlightSpan>>> {
+        if lines.is_empty() {
+            return Some(Vec::new());
+        }
+        let mut combined = std::mem::take(&mut self.carry);
+        let offset = combined.len();
+        combined.extend_from_slice(lines);
+        let content = combined.join("\n");
+        let (tree, _) = AstFormatter::parse_to_tree(&content, self.language.as_deref())?;
+        let mut spans = spans_for_tree(&tree, &content);
+
+        // Carry from where the last top-level node starts; it may continue
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let tail_start = root
+            .named_children(&mut cursor)
+            .last()
+            .map_or(combined.len(), |node| node.start_position().row);
+        if combined.len() - tail_start <= Self::MAX_CARRY_LINES {
+            self.carry = combined.split_off(tail_start);
+        }
+        Some(spans.split_off(offset))
+    }
+}
+
+fn collect(node: Node, line_lengths: &[usize], spans: &mut [Vec<HighlightSpan>]) {
+    if let Some(scope) = scope_for(node) {
+        push_span(node, scope, line_lengths, spans);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(child, line_lengths, spans);
+    }
+}
+
+/// Map a node to a scope. Comments and strings are styled as a whole; other
+/// scopes apply to leaves only.
+fn scope_for(node: Node) -> Option<&'static str> {
+    let kind = node.kind();
+    if kind.contains("comment") {
+        return Some("comment");
+    }
+    if kind.contains("string") || kind == "char_literal" || kind == "template_string" {
+        return Some("string");
+    }
+    if node.child_count() > 0 {
+        return None;
+    }
+    if !node.is_named() {
+        return Some(
+            if kind.chars().all(|c| c.is_ascii_alphabetic() || c == '_') {
+                "keyword"
+            } else {
+                "punctuation"
+            },
+        );
+    }
+    let scope = match kind {
+        "integer_literal" | "float_literal" | "integer" | "float" | "number" => "number",
+        "true" | "false" | "boolean_literal" | "none" | "null" | "undefined" => "constant",
+        "type_identifier" | "primitive_type" | "predefined_type" => "type",
+        "field_identifier" | "property_identifier" | "shorthand_property_identifier" => "property",
+        "identifier" => match node.parent().map(|p| p.kind()) {
+            Some(
+                "function_item"
+                | "function_definition"
+                | "function_declaration"
+                | "method_definition"
+                | "call_expression"
+                | "call",
+            ) => "function",
+            _ => "variable",
+        },
+        "self" | "this" | "super" => "keyword",
+        _ => return None,
+    };
+    Some(scope)
+}
+
+fn push_span(
+    node: Node,
+    scope: &'static str,
+    line_lengths: &[usize],
+    spans: &mut [Vec<HighlightSpan>],
+) {
+    let start = node.start_position();
+    let end = node.end_position();
+    for row in start.row..=end.row.min(line_lengths.len().saturating_sub(1)) {
+        let from = if row == start.row { start.column } else { 0 };
+        let to = if row == end.row {
+            end.column
+        } else {
+            line_lengths[row]
+        };
+        if to > from {
+            spans[row].push(HighlightSpan {
+                start: from,
+                end: to,
+                scope,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(line: &[HighlightSpan]) -> Vec<&'static str> {
+        line.iter().map(|s| s.scope).collect()
+    }
+
+    #[test]
+    fn test_rust_spans() {
+        let spans = line_spans("fn add(a: i32) -> i32 { 1 }\n// done", Some("Rust")).unwrap();
+        assert_eq!(spans.len(), 2);
+        let first = &spans[0];
+        assert_eq!(
+            first[0],
+            HighlightSpan {
+                start: 0,
+                end: 2,
+                scope: "keyword"
+            }
+        );
+        assert_eq!(first[1].scope, "function");
+        assert!(scopes(first).contains(&"type"));
+        assert!(scopes(first).contains(&"number"));
+        assert_eq!(scopes(&spans[1]), ["comment"]);
+    }
+
+    #[test]
+    fn test_multiline_string_spans_each_line() {
+        let spans = line_spans("x = \"\"\"a\nbc\"\"\"\n", Some("Python")).unwrap();
+        assert!(spans[0].iter().any(|s| s.scope == "string" && s.start == 4));
+        assert_eq!(
+            spans[1][0],
+            HighlightSpan {
+                start: 0,
+                end: 5,
+                scope: "string"
+            }
+        );
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(line_spans("key: value", Some("YAML")).is_none());
+        assert!(line_spans("anything", None).is_none());
+    }
+
+    #[test]
+    fn test_chunks_keep_open_strings() {
+        let lines: Vec<String> = [
+            "x = 1",
+            "y = \"\"\"start",
+            "still text",
+            "end\"\"\"",
+            "z = 2",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        let mut highlighter = ChunkHighlighter::new(Some("Python"));
+        let first = highlighter.next_chunk(&lines[..2]).unwrap();
+        assert_eq!(first.len(), 2);
+        let second = highlighter.next_chunk(&lines[2..]).unwrap();
+        assert_eq!(second.len(), 3);
+        assert_eq!(scopes(&second[0]), ["string"]);
+        assert_eq!(second[1][0].scope, "string");
+        assert_eq!(second[2][0].scope, "variable");
+        assert!(ChunkHighlighter::new(None).next_chunk(&lines).is_none());
+    }
+}
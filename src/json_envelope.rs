@@ -0,0 +1,165 @@
+//! Multi-document JSON container (`--json-envelope`)
+//!
+//! Without it, several files in JSON mode are written as one document per
+//! file. The envelope wraps them in a single document that carries the
+//! schema and tool versions and totals over every file, so consumers get
+//! versioning and aggregates in one parse.
+
+use crate::config::BatlessConfig;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// One document holding the JSON output of every processed file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonEnvelope {
+    /// Schema version of each entry in `files`
+    pub schema_version: String,
+    /// RFC 3339 UTC time the document was written
+    pub generated_at: String,
+    pub tool_version: &'static str,
+    /// Per-file JSON output, in input order
+    pub files: Vec<Value>,
+    pub aggregate: Aggregate,
+}
+
+/// Totals over every file of an envelope
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Aggregate {
+    pub file_count: usize,
+    pub total_lines: u64,
+    pub processed_lines: u64,
+    pub total_bytes: u64,
+    /// Files cut short by a line, byte or context limit
+    pub truncated_files: usize,
+    /// Sum over the files that have an estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_llm_tokens: Option<u64>,
+    /// Files per detected language; files without one are not counted
+    pub languages: BTreeMap<String, usize>,
+    pub warnings: usize,
+}
+
+impl Aggregate {
+    /// Totals of the per-file `files`
+    pub fn of(files: &[Value]) -> Self {
+        let number = |file: &Value, key: &str| file.get(key).and_then(Value::as_u64);
+        let mut aggregate = Self {
+            file_count: files.len(),
+            ..Self::default()
+        };
+        for file in files {
+            aggregate.total_lines += number(file, "total_lines").unwrap_or(0);
+            aggregate.processed_lines += number(file, "processed_lines").unwrap_or(0);
+            aggregate.total_bytes += number(file, "total_bytes").unwrap_or(0);
+            if file.get("truncated").and_then(Value::as_bool) == Some(true) {
+                aggregate.truncated_files += 1;
+            }
+            if let Some(tokens) = number(file, "estimated_llm_tokens") {
+                *aggregate.estimated_llm_tokens.get_or_insert(0) += tokens;
+            }
+            if let Some(language) = file.get("language").and_then(Value::as_str) {
+                *aggregate.languages.entry(language.to_string()).or_default() += 1;
+            }
+            aggregate.warnings += file
+                .get("warnings")
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len);
+        }
+        aggregate
+    }
+}
+
+impl JsonEnvelope {
+    /// Wrap `files`, stamped with the current time (the epoch with
+    /// `--deterministic`)
+    pub fn new(files: Vec<Value>, config: &BatlessConfig) -> Self {
+        Self {
+            schema_version: config.schema_version.clone(),
+            generated_at: config
+                .now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            tool_version: env!("CARGO_PKG_VERSION"),
+            aggregate: Aggregate::of(&files),
+            files,
+        }
+    }
+
+    /// Draft-07 schema of the envelope, with `file_schema` for each entry
+    pub fn schema(file_schema: &Value) -> Value {
+        let count = json!({"type": "integer", "minimum": 0});
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Batless JSON Envelope",
+            "description": "Output of several files wrapped by --json-envelope",
+            "type": "object",
+            "required": ["schema_version", "generated_at", "tool_version", "files", "aggregate"],
+            "properties": {
+                "schema_version": {"type": "string"},
+                "generated_at": {"type": "string", "format": "date-time"},
+                "tool_version": {"type": "string"},
+                "files": {"type": "array", "items": file_schema},
+                "aggregate": {
+                    "type": "object",
+                    "required": [
+                        "file_count", "total_lines", "processed_lines", "total_bytes",
+                        "truncated_files", "languages", "warnings"
+                    ],
+                    "properties": {
+                        "file_count": count,
+                        "total_lines": count,
+                        "processed_lines": count,
+                        "total_bytes": count,
+                        "truncated_files": count,
+                        "estimated_llm_tokens": count,
+                        "languages": {"type": "object", "additionalProperties": count},
+                        "warnings": count
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_sums_files() {
+        let files = vec![
+            json!({"total_lines": 10, "processed_lines": 5, "total_bytes": 100,
+                   "truncated": true, "language": "Rust", "warnings": [{"code": "W202"}]}),
+            json!({"total_lines": 3, "processed_lines": 3, "total_bytes": 30,
+                   "truncated": false, "language": "Rust", "estimated_llm_tokens": 7}),
+            json!({"total_lines": 1, "processed_lines": 1, "total_bytes": 2, "truncated": false}),
+        ];
+        let aggregate = Aggregate::of(&files);
+        assert_eq!(aggregate.file_count, 3);
+        assert_eq!(aggregate.total_lines, 14);
+        assert_eq!(aggregate.processed_lines, 9);
+        assert_eq!(aggregate.total_bytes, 132);
+        assert_eq!(aggregate.truncated_files, 1);
+        assert_eq!(aggregate.estimated_llm_tokens, Some(7));
+        assert_eq!(aggregate.languages["Rust"], 2);
+        assert_eq!(aggregate.warnings, 1);
+    }
+
+    #[test]
+    fn test_envelope_matches_its_schema() {
+        let config = BatlessConfig::default();
+        let envelope = JsonEnvelope::new(vec![json!({"file": "a.rs"})], &config);
+        assert_eq!(envelope.tool_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(envelope.schema_version, config.schema_version);
+        assert!(envelope.generated_at.ends_with('Z'));
+        let config = config.with_deterministic(true);
+        assert_eq!(
+            JsonEnvelope::new(Vec::new(), &config).generated_at,
+            "1970-01-01T00:00:00Z"
+        );
+        let schema = JsonEnvelope::schema(&json!({"type": "object", "required": ["file"]}));
+        let value = serde_json::to_value(&envelope).unwrap();
+        assert!(jsonschema::is_valid(&schema, &value));
+        assert!(!jsonschema::is_valid(&schema, &json!({"files": []})));
+    }
+}
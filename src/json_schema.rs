@@ -1,11 +1,19 @@
 //! JSON schema validation for batless output
 //!
 //! This module provides JSON schema validation to ensure AI compatibility
-//! and consistent output format across different modes.
+//! and consistent output format across different modes. Schemas are draft-07
+//! and checked with the `jsonschema` crate, so nested types, enums, patterns
+//! and every missing required field are reported, not just the first.
 
 use crate::error::{BatlessError, BatlessResult};
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::ValidationError;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Schema violations listed in one validation error
+const MAX_REPORTED_ERRORS: usize = 10;
 
 /// JSON schema validator for batless output
 pub struct JsonSchemaValidator {
@@ -38,6 +46,10 @@ impl JsonSchemaValidator {
             "streaming_chunk".to_string(),
             crate::streaming::StreamingProcessor::get_streaming_schema(),
         );
+        self.schemas.insert(
+            "json_envelope".to_string(),
+            crate::json_envelope::JsonEnvelope::schema(&self.json_output_schema()),
+        );
     }
 
     /// Validate JSON against a specific schema
@@ -46,19 +58,39 @@ impl JsonSchemaValidator {
             BatlessError::config_error_with_help(
                 format!("Unknown schema: {schema_name}"),
                 Some(
-                    "Available schemas: file_info, json_output, token_count, processing_stats, streaming_chunk"
+                    "Available schemas: file_info, json_output, token_count, processing_stats, streaming_chunk, json_envelope"
                         .to_string(),
                 ),
             )
         })?;
 
-        self.validate_against_schema(json_value, schema)
-            .map_err(|e| {
-                BatlessError::config_error_with_help(
-                    format!("JSON validation failed for schema '{schema_name}': {e}"),
-                    Some("Check the JSON output format matches the expected schema".to_string()),
-                )
-            })
+        let validator = jsonschema::validator_for(schema).map_err(|e| {
+            BatlessError::processing_error(format!("Schema '{schema_name}' does not compile: {e}"))
+        })?;
+        let problems: Vec<String> = validator
+            .iter_errors(json_value)
+            .map(|error| Self::describe(&error))
+            .collect();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let mut message = problems
+            .iter()
+            .take(MAX_REPORTED_ERRORS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n");
+        if problems.len() > MAX_REPORTED_ERRORS {
+            let _ = write!(
+                message,
+                "\n{} more problems not listed",
+                problems.len() - MAX_REPORTED_ERRORS
+            );
+        }
+        Err(BatlessError::config_error_with_help(
+            format!("JSON validation failed for schema '{schema_name}': {message}"),
+            Some("Check the JSON output format matches the expected schema".to_string()),
+        ))
     }
 
     /// Validate JSON string against a schema
@@ -83,135 +115,48 @@ impl JsonSchemaValidator {
         self.schemas.get(name)
     }
 
-    /// Basic JSON schema validation with enhanced error messages
-    fn validate_against_schema(&self, value: &Value, schema: &Value) -> Result<(), String> {
-        self.validate_against_schema_with_path(value, schema, "")
-    }
-
-    /// Validate with path tracking for better error messages
-    fn validate_against_schema_with_path(
-        &self,
-        value: &Value,
-        schema: &Value,
-        path: &str,
-    ) -> Result<(), String> {
-        match (value, schema) {
-            (_, Value::Object(schema_obj)) => {
-                if let Some(schema_type) = schema_obj.get("type") {
-                    self.validate_type_with_path(value, schema_type, path)?;
-                }
-
-                if let Some(properties) = schema_obj.get("properties") {
-                    if let (Value::Object(value_obj), Value::Object(props)) = (value, properties) {
-                        for (key, prop_schema) in props {
-                            let prop_path = if path.is_empty() {
-                                key.clone()
-                            } else {
-                                format!("{path}.{key}")
-                            };
-
-                            if let Some(prop_value) = value_obj.get(key) {
-                                self.validate_against_schema_with_path(
-                                    prop_value,
-                                    prop_schema,
-                                    &prop_path,
-                                )?;
-                            }
-                        }
-                    }
-                }
-
-                if let Some(required) = schema_obj.get("required") {
-                    if let (Value::Object(value_obj), Value::Array(req_fields)) = (value, required)
-                    {
-                        for field in req_fields {
-                            if let Value::String(field_name) = field {
-                                if !value_obj.contains_key(field_name) {
-                                    let field_path = if path.is_empty() {
-                                        field_name.clone()
-                                    } else {
-                                        format!("{path}.{field_name}")
-                                    };
-
-                                    return Err(format!(
-                                        "Missing required field: '{field_path}'\n  Expected: This field is required for AI compatibility\n  Suggestion: Add the missing field to your JSON output"
-                                    ));
-                                }
-                            }
-                        }
+    /// One schema violation, with the field as a dotted path
+    fn describe(error: &ValidationError) -> String {
+        let pointer = error.instance_path().as_str();
+        let path = pointer.trim_start_matches('/').replace('/', ".");
+        let field_info = if path.is_empty() {
+            "root".to_string()
+        } else {
+            format!("'{path}'")
+        };
+        match error.kind() {
+            ValidationErrorKind::Required { property } => {
+                let name = property
+                    .as_str()
+                    .map_or_else(|| property.to_string(), String::from);
+                let field_path = if path.is_empty() {
+                    name
+                } else {
+                    format!("{path}.{name}")
+                };
+                format!(
+                    "Missing required field: '{field_path}'\n  Expected: This field is required for AI compatibility\n  Suggestion: Add the missing field to your JSON output"
+                )
+            }
+            ValidationErrorKind::Type { kind } => {
+                let expected = match kind {
+                    TypeKind::Single(json_type) => json_type.to_string(),
+                    TypeKind::Multiple(types) => {
+                        let names: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+                        format!("one of {names:?}")
                     }
-                }
-                Ok(())
+                };
+                format!(
+                    "Type mismatch at {field_info}: expected {expected}, got {}\n  Expected: Correct data type for AI compatibility\n  Suggestion: Convert the value to the expected type",
+                    Self::get_value_type(error.instance())
+                )
             }
-            _ => Ok(()),
+            _ => format!("Invalid value at {field_info}: {error}"),
         }
     }
 
-    /// Validate value type against schema type with enhanced error messages
-    fn validate_type_with_path(
-        &self,
-        value: &Value,
-        schema_type: &Value,
-        path: &str,
-    ) -> Result<(), String> {
-        // Handle array of types (e.g., ["string", "null"])
-        if let Value::Array(types) = schema_type {
-            for type_option in types {
-                if self
-                    .validate_type_with_path(value, type_option, path)
-                    .is_ok()
-                {
-                    return Ok(());
-                }
-            }
-            let type_names: Vec<String> = types
-                .iter()
-                .filter_map(|v| v.as_str())
-                .map(ToString::to_string)
-                .collect();
-
-            let field_info = if path.is_empty() {
-                "root".to_string()
-            } else {
-                format!("'{path}'")
-            };
-
-            return Err(format!(
-                "Type mismatch at {field_info}: expected one of {type_names:?}, got {}\n  Expected: One of the allowed types for AI compatibility\n  Suggestion: Convert the value to match the expected type",
-                self.get_value_type(value)
-            ));
-        }
-
-        let expected_type = schema_type.as_str().unwrap_or("unknown");
-
-        let matches = match (value, expected_type) {
-            (Value::String(_), "string") => true,
-            (Value::Number(_), "number") => true,
-            (Value::Number(_), "integer") => value.as_i64().is_some(),
-            (Value::Bool(_), "boolean") => true,
-            (Value::Array(_), "array") => true,
-            (Value::Object(_), "object") => true,
-            (Value::Null, "null") => true,
-            _ => false,
-        };
-
-        if !matches {
-            let field_info = if path.is_empty() {
-                "root".to_string()
-            } else {
-                format!("'{path}'")
-            };
-            return Err(format!(
-                "Type mismatch at {field_info}: expected {expected_type}, got {}\n  Expected: Correct data type for AI compatibility\n  Suggestion: Convert the value to the expected type",
-                self.get_value_type(value)
-            ));
-        }
-
-        Ok(())
-    }
-
     /// Get the type name of a JSON value
-    const fn get_value_type(&self, value: &Value) -> &'static str {
+    const fn get_value_type(value: &Value) -> &'static str {
         match value {
             Value::String(_) => "string",
             Value::Number(_) => "number",
@@ -252,7 +197,10 @@ impl JsonSchemaValidator {
                 },
                 "summary_lines": {
                     "type": ["array", "null"],
-                    "items": { "type": "string" }
+                    "items": {
+                        "type": "object",
+                        "required": ["line", "line_number", "kind"]
+                    }
                 }
             },
             "required": [
@@ -264,42 +212,199 @@ impl JsonSchemaValidator {
 
     /// JSON output schema for batless (matches --mode=json output)
     fn json_output_schema(&self) -> Value {
-        json!({
-            "$schema": "http://json-schema.org/draft-07/schema#",
+        // Built separately to stay within `json!`'s recursion limit
+        let metrics = json!({
             "type": "object",
             "properties": {
-                "file": { "type": "string" },
-                "lines": {
-                    "type": "array",
-                    "items": { "type": "string" }
-                },
-                "processed_lines": { "type": "integer" },
-                "total_lines": { "type": "integer" },
-                "total_bytes": { "type": "integer" },
-                "total_lines_exact": { "type": "boolean" },
-                "truncated": { "type": "boolean" },
-                "truncated_by_lines": { "type": "boolean" },
-                "truncated_by_bytes": { "type": "boolean" },
-                "language": {
-                    "type": ["string", "null"]
-                },
-                "encoding": { "type": "string" },
-                "syntax_errors": {
-                    "type": "array",
-                    "items": { "type": "string" }
+                "lines": { "type": "integer" },
+                "branches": { "type": "integer" },
+                "depth": { "type": "integer" }
+            },
+            "required": ["lines", "branches", "depth"]
+        });
+        let symbol_use = json!({
+            "type": "object",
+            "properties": {
+                "line": { "type": "integer" },
+                "column": { "type": "integer" },
+                "enclosing": { "type": ["string", "null"] },
+                "definition": { "type": "boolean" },
+                "text": { "type": "string" }
+            },
+            "required": ["line", "column", "definition", "text"]
+        });
+        let summary_line = json!({
+            "type": "object",
+            "properties": {
+                "line": { "type": "string" },
+                "line_number": { "type": "integer" },
+                "end_line": { "type": ["integer", "null"] },
+                "kind": { "type": "string" },
+                "name": { "type": "string" },
+                "visibility": { "type": "string" },
+                "permalink": { "type": "string" },
+                "doc": { "type": "string" },
+                "container": { "type": "string" },
+                "qualified_name": { "type": "string" },
+                "metrics": metrics
+            },
+            "required": ["line", "line_number", "kind"]
+        });
+        let properties = json!({
+            "file": { "type": "string" },
+            "lines": {
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "processed_lines": { "type": "integer" },
+            "total_lines": { "type": "integer" },
+            "total_bytes": { "type": "integer" },
+            "total_lines_exact": { "type": "boolean" },
+            "truncated": { "type": "boolean" },
+            "truncated_by_lines": { "type": "boolean" },
+            "truncated_by_bytes": { "type": "boolean" },
+            "language": {
+                "type": ["string", "null"]
+            },
+            "encoding": { "type": "string" },
+            "syntax_errors": {
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "identifiers": {
+                "type": ["array", "null"],
+                "items": { "type": "string" }
+            },
+            "identifier_count": { "type": "integer" },
+            "identifiers_truncated": { "type": "boolean" },
+            "token_frequencies": {
+                "type": "object",
+                "additionalProperties": { "type": "integer" }
+            },
+            "line_endings": {
+                "type": "object",
+                "properties": {
+                    "style": { "enum": ["lf", "crlf", "cr", "mixed", "none"] },
+                    "lf": { "type": "integer" },
+                    "crlf": { "type": "integer" },
+                    "cr": { "type": "integer" }
+                }
+            },
+            "had_bom": { "type": "boolean" },
+            "language_confidence": { "type": "number" },
+            "language_candidates": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "score": { "type": "number" }
+                    },
+                    "required": ["name", "score"]
+                }
+            },
+            "summary_engine": { "enum": ["ast", "regex"] },
+            "warnings": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "code": { "type": "string", "pattern": "^W[0-9]{3}$" },
+                        "message": { "type": "string" }
+                    },
+                    "required": ["code", "message"]
+                }
+            },
+            "summary_lines": {
+                "type": ["array", "null"],
+                "items": summary_line
+            },
+            "summary_items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "kind": { "type": "string" },
+                        "name": { "type": "string" },
+                        "line": { "type": "integer" },
+                        "end_line": { "type": "integer" },
+                        "visibility": { "type": "string" },
+                        "doc": { "type": "string" },
+                        "container": { "type": "string" },
+                        "qualified_name": { "type": "string" },
+                        "metrics": metrics
+                    },
+                    "required": ["kind", "line"]
+                }
+            },
+            "annotations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "file": { "type": "string" },
+                        "line": { "type": "integer" },
+                        "end_line": { "type": "integer" },
+                        "message": { "type": "string" },
+                        "severity": { "enum": ["error", "warning", "info", "note"] },
+                        "source": { "type": "string" }
+                    },
+                    "required": ["line", "message", "severity"]
+                }
+            },
+            "emphasized_lines": {
+                "type": "array",
+                "items": { "type": "integer" }
+            },
+            "folded": { "type": "boolean" },
+            "media": {
+                "type": "object",
+                "properties": {
+                    "format": { "type": "string" },
+                    "mime_type": { "type": "string" },
+                    "width": { "type": "integer" },
+                    "height": { "type": "integer" },
+                    "version": { "type": "string" },
+                    "has_exif": { "type": "boolean" },
+                    "size_bytes": { "type": "integer" }
                 },
-                "identifiers": {
-                    "type": ["array", "null"],
-                    "items": { "type": "string" }
+                "required": ["format", "mime_type", "has_exif", "size_bytes"]
+            },
+            "dataset": dataset_schema(),
+            "compression": {
+                "type": "string",
+                "enum": ["gzip", "bzip2", "xz", "zstd"]
+            },
+            "around": {
+                "type": "object",
+                "properties": {
+                    "line": { "type": "integer" },
+                    "start": { "type": "integer" },
+                    "end": { "type": "integer" },
+                    "enclosing": { "type": ["object", "null"] }
                 },
-                "identifier_count": { "type": "integer" },
-                "identifiers_truncated": { "type": "boolean" },
-                "summary_lines": {
-                    "type": ["array", "null"],
-                    "items": { "type": "string" }
+                "required": ["line", "start", "end"]
+            },
+            "uses": {
+                "type": "array",
+                "items": symbol_use
+            },
+            "symbol": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "start": { "type": "integer" },
+                    "end": { "type": "integer" },
+                    "definition": { "type": "object" }
                 },
-                "mode": { "type": "string" }
+                "required": ["name", "start", "end", "definition"]
             },
+            "mode": { "type": "string" }
+        });
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": properties,
             "required": [
                 "file",
                 "lines",
@@ -308,7 +413,6 @@ impl JsonSchemaValidator {
                 "total_bytes",
                 "total_lines_exact",
                 "truncated",
-                "total_lines_exact",
                 "truncated_by_lines",
                 "truncated_by_bytes",
                 "encoding",
@@ -398,6 +502,44 @@ pub fn get_json_schema(schema_name: &str) -> BatlessResult<Value> {
     })
 }
 
+/// Schema of the `dataset` object (SQLite/Parquet summaries)
+fn dataset_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "format": { "type": "string", "enum": ["sqlite", "parquet"] },
+            "size_bytes": { "type": "integer" },
+            "schema_available": { "type": "boolean" },
+            "tables": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "kind": { "type": "string" },
+                        "row_count": { "type": "integer" },
+                        "columns": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "type": "string" },
+                                    "data_type": { "type": "string" },
+                                    "nullable": { "type": "boolean" },
+                                    "primary_key": { "type": "boolean" }
+                                },
+                                "required": ["name", "data_type", "nullable"]
+                            }
+                        }
+                    },
+                    "required": ["name", "kind", "columns"]
+                }
+            }
+        },
+        "required": ["format", "size_bytes", "schema_available", "tables"]
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -536,6 +678,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_nested_and_multiple_violations() {
+        let validator = JsonSchemaValidator::new();
+        let mut output: Value = serde_json::from_str(
+            r#"{
+            "file": "test.rs", "lines": [], "processed_lines": 0, "total_lines": 0,
+            "total_lines_exact": true, "total_bytes": 0, "truncated": false,
+            "truncated_by_lines": false, "truncated_by_bytes": false, "encoding": "UTF-8",
+            "syntax_errors": [], "identifier_count": 0, "identifiers_truncated": false,
+            "mode": "json"
+        }"#,
+        )
+        .unwrap();
+        assert!(validator.validate("json_output", &output).is_ok());
+
+        output["summary_items"] = json!([{ "kind": "function", "name": "f", "line": "3" }]);
+        output["summary_engine"] = json!("guess");
+        output.as_object_mut().unwrap().remove("mode");
+        let error = validator
+            .validate("json_output", &output)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("Type mismatch at 'summary_items.0.line'"),
+            "{error}"
+        );
+        assert!(
+            error.contains("Invalid value at 'summary_engine'"),
+            "{error}"
+        );
+        assert!(error.contains("Missing required field: 'mode'"), "{error}");
+    }
+
     #[test]
     fn test_enhanced_error_messages() {
         let validator = JsonSchemaValidator::new();
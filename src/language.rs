@@ -3,11 +3,160 @@
 //! Extension-based language detection. The syntect dependency has been removed
 //! in v0.6.0 as part of the AI-native pivot; language names are derived from
 //! a static extension map.
+//!
+//! When the path alone is not enough, `detect_language_with_fallback` also
+//! consults Vim/Emacs modelines inside the file and `language` hints from
+//! `.editorconfig` files in the parent directories.
+//!
+//! Extensions shared by several languages (`.h`, `.m`, `.pl`) are scored
+//! against content markers by `language_candidates`, so JSON consumers can see
+//! how confident the mapping is and which alternatives were considered.
 
 use crate::error::{BatlessError, BatlessResult};
 use crate::traits::LanguageDetection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
+/// How [`LanguageDetector::resolve_language`] matched a language name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LanguageMatch {
+    /// A known language name, in any case; used as given
+    Exact,
+    /// A common alias or extension such as `js` or `golang`
+    Alias(String),
+    /// A close misspelling such as `pyton`
+    Corrected(String),
+}
+
+/// Number of bytes scanned at the start and end of a file for modelines
+const MODELINE_SCAN_BYTES: u64 = 4096;
+
+/// Number of lines at the start and end of a file that may carry a modeline
+/// (matches Vim's default `modelines` setting)
+const MODELINE_SCAN_LINES: usize = 5;
+
+/// Number of leading lines scanned for content markers of ambiguous extensions
+const CANDIDATE_SCAN_LINES: usize = 200;
+
+/// A candidate language and the content markers that count as evidence for it
+type CandidateMarkers = (&'static str, &'static [&'static str]);
+
+/// Extensions mapped to more than one language. The first entry is the
+/// language `extension_to_language` reports; each candidate lists content
+/// markers that count as evidence for it.
+const AMBIGUOUS_EXTENSIONS: &[(&str, &[CandidateMarkers])] = &[
+    (
+        "h",
+        &[
+            (
+                "C",
+                &[
+                    "typedef struct",
+                    "#include <stdio.h>",
+                    "#include <stdlib.h>",
+                    "extern \"C\"",
+                ],
+            ),
+            (
+                "C++",
+                &[
+                    "class ",
+                    "namespace ",
+                    "template<",
+                    "template <",
+                    "std::",
+                    "public:",
+                ],
+            ),
+            (
+                "Objective-C",
+                &[
+                    "@interface",
+                    "@protocol",
+                    "#import",
+                    "@property",
+                    "NS_ASSUME_NONNULL",
+                ],
+            ),
+        ],
+    ),
+    (
+        "m",
+        &[
+            (
+                "Objective-C",
+                &["@implementation", "@interface", "#import", "@end", "[self "],
+            ),
+            (
+                "MATLAB",
+                &["function ", "end;", "disp(", "zeros(", "fprintf("],
+            ),
+        ],
+    ),
+    (
+        "pl",
+        &[
+            (
+                "Perl",
+                &[
+                    "use strict",
+                    "use warnings",
+                    "my $",
+                    "sub ",
+                    "#!/usr/bin/perl",
+                    "#!/usr/bin/env perl",
+                ],
+            ),
+            ("Prolog", &[":- ", "?- ", ":- module", ":- use_module"]),
+        ],
+    ),
+];
+
+/// A possible language for a file with a normalized score in `0.0..=1.0`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageCandidate {
+    pub name: String,
+    pub score: f64,
+}
+
+/// How the language of a file was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageSource {
+    /// Explicitly set via `--language` or configuration
+    Override,
+    /// Matched from the file extension
+    Extension,
+    /// Matched from a well-known extensionless filename (e.g. `Dockerfile`)
+    Filename,
+    /// Read from a Vim or Emacs modeline inside the file
+    Modeline,
+    /// The interpreter named by a `#!` line (e.g. `#!/usr/bin/env python3`)
+    Shebang,
+    /// Read from a `language` property in an `.editorconfig` file
+    EditorConfig,
+    /// Matched a `[language_map]` entry in the configuration
+    LanguageMap,
+}
+
+impl LanguageSource {
+    /// Get string representation
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Override => "override",
+            Self::Extension => "extension",
+            Self::Filename => "filename",
+            Self::Modeline => "modeline",
+            Self::Shebang => "shebang",
+            Self::EditorConfig => "editorconfig",
+            Self::LanguageMap => "language_map",
+        }
+    }
+}
+
 /// Language detection utilities
 pub struct LanguageDetector;
 
@@ -32,6 +181,68 @@ impl LanguageDetector {
             .and_then(Self::filename_to_language)
     }
 
+    /// Rank the languages `file_path` could be, best first.
+    ///
+    /// Unambiguous detections yield a single candidate with score 1.0. For
+    /// ambiguous extensions each candidate starts from a prior (the default
+    /// mapping is favoured) and gains a point per content marker found in the
+    /// first lines of `lines`; scores are normalized to sum to 1.
+    pub fn language_candidates(
+        file_path: &str,
+        detected: Option<(&str, LanguageSource)>,
+        lines: &[String],
+    ) -> Vec<LanguageCandidate> {
+        let Some((language, source)) = detected else {
+            return Vec::new();
+        };
+        let certain = || {
+            vec![LanguageCandidate {
+                name: language.to_string(),
+                score: 1.0,
+            }]
+        };
+        if source != LanguageSource::Extension {
+            return certain();
+        }
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let Some((_, candidates)) = AMBIGUOUS_EXTENSIONS
+            .iter()
+            .find(|(ext, _)| Some(*ext) == extension.as_deref())
+        else {
+            return certain();
+        };
+
+        let sample = &lines[..lines.len().min(CANDIDATE_SCAN_LINES)];
+        let raw: Vec<(&str, f64)> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, (name, markers))| {
+                let prior = if i == 0 { 1.0 } else { 0.5 };
+                let evidence = markers
+                    .iter()
+                    .filter(|marker| sample.iter().any(|line| line.contains(*marker)))
+                    .count();
+                #[allow(clippy::cast_precision_loss)] // marker lists are tiny
+                let score = prior + evidence as f64;
+                (*name, score)
+            })
+            .collect();
+        let total: f64 = raw.iter().map(|(_, score)| score).sum();
+        let mut ranked: Vec<LanguageCandidate> = raw
+            .into_iter()
+            .map(|(name, score)| LanguageCandidate {
+                name: name.to_string(),
+                score: (score / total * 1000.0).round() / 1000.0,
+            })
+            .collect();
+        // Stable sort keeps the default mapping first on ties
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+
     /// Map well-known extensionless filenames to language names.
     fn filename_to_language(filename: &str) -> Option<String> {
         let language_name = match filename.to_lowercase().as_str() {
@@ -44,9 +255,379 @@ impl LanguageDetector {
         Some(language_name.to_string())
     }
 
-    /// Detect language with fallback (alias for detect_language post-syntect removal)
+    /// Detect language with fallback to in-file and project-level hints.
+    ///
+    /// See [`Self::detect_language_with_source`] for the order of the chain.
     pub fn detect_language_with_fallback(file_path: &str) -> Option<String> {
-        Self::detect_language(file_path)
+        Self::detect_language_with_source(file_path).map(|(language, _)| language)
+    }
+
+    /// Detect language and report which step of the fallback chain matched.
+    ///
+    /// The chain is: file extension, well-known filename, Vim/Emacs modeline
+    /// in the first or last few lines, a `#!` interpreter line, then the
+    /// nearest `.editorconfig` with a `language` property for a section
+    /// matching the file.
+    pub fn detect_language_with_source(file_path: &str) -> Option<(String, LanguageSource)> {
+        let path = Path::new(file_path);
+        if let Some(detected) = Self::detect_from_name(path) {
+            return Some(detected);
+        }
+        if let Some(detected) = Self::read_modeline_sample(path)
+            .as_deref()
+            .and_then(Self::detect_from_content_hints)
+        {
+            return Some(detected);
+        }
+        Self::detect_from_editorconfig(path).map(|lang| (lang, LanguageSource::EditorConfig))
+    }
+
+    /// [`Self::detect_language_with_source`], after the `[language_map]`
+    /// entries from the configuration
+    pub fn detect_language_with_map(
+        file_path: &str,
+        language_map: &BTreeMap<String, String>,
+    ) -> Option<(String, LanguageSource)> {
+        Self::detect_from_map(file_path, language_map)
+            .or_else(|| Self::detect_language_with_source(file_path))
+    }
+
+    /// Language of the `language_map` entry matching `file_path`
+    ///
+    /// Patterns without a `/` match the file name, others the end of the
+    /// path. Literal names beat globs; otherwise the longest pattern wins.
+    pub fn detect_from_map(
+        file_path: &str,
+        language_map: &BTreeMap<String, String>,
+    ) -> Option<(String, LanguageSource)> {
+        if language_map.is_empty() {
+            return None;
+        }
+        let path = file_path.trim_start_matches("./");
+        let file_name = Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(path);
+        let (_, language) = language_map
+            .iter()
+            .filter(|(pattern, _)| {
+                if pattern.contains('/') {
+                    let pattern = pattern.trim_start_matches('/');
+                    Self::glob_match(pattern, path)
+                        || Self::glob_match(&format!("**/{pattern}"), path)
+                } else {
+                    Self::glob_match(pattern, file_name)
+                }
+            })
+            .max_by_key(|(pattern, _)| (!pattern.contains(['*', '?', '{']), pattern.len()))?;
+        let language = Self::find_language(language)
+            .or_else(|| Self::resolve_language_hint(language))
+            .unwrap_or_else(|| language.clone());
+        Some((language, LanguageSource::LanguageMap))
+    }
+
+    /// Extension, then well-known filename
+    fn detect_from_name(path: &Path) -> Option<(String, LanguageSource)> {
+        if let Some(lang) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::extension_to_language)
+        {
+            return Some((lang, LanguageSource::Extension));
+        }
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .and_then(Self::filename_to_language)
+            .map(|lang| (lang, LanguageSource::Filename))
+    }
+
+    /// Detect language for content that is not on disk: extension, then
+    /// well-known filename, then a modeline in the first or last lines, then
+    /// a `#!` line.
+    pub fn detect_language_in_memory(
+        file_path: &str,
+        lines: &[String],
+    ) -> Option<(String, LanguageSource)> {
+        if let Some(detected) = Self::detect_from_name(Path::new(file_path)) {
+            return Some(detected);
+        }
+        let head = lines.iter().take(MODELINE_SCAN_LINES);
+        let tail = lines.iter().skip(
+            lines
+                .len()
+                .saturating_sub(MODELINE_SCAN_LINES)
+                .max(MODELINE_SCAN_LINES),
+        );
+        let sample: Vec<&str> = head.chain(tail).map(String::as_str).collect();
+        Self::detect_from_content_hints(&sample.join("\n"))
+    }
+
+    /// Modeline, then shebang, in a sample starting at the first line
+    fn detect_from_content_hints(sample: &str) -> Option<(String, LanguageSource)> {
+        if let Some(lang) = Self::detect_from_modeline(sample) {
+            return Some((lang, LanguageSource::Modeline));
+        }
+        Self::detect_from_shebang(sample).map(|lang| (lang, LanguageSource::Shebang))
+    }
+
+    /// Language of the interpreter named on a `#!` first line.
+    ///
+    /// Handles `/usr/bin/env` (including `env -S` and `VAR=value`
+    /// arguments) and versioned names such as `python3.12`.
+    pub fn detect_from_shebang(content: &str) -> Option<String> {
+        let line = content.trim_start_matches('\u{feff}').lines().next()?;
+        let mut words = line.strip_prefix("#!")?.split_whitespace();
+        let mut interpreter = words.next()?.rsplit('/').next()?;
+        if interpreter == "env" {
+            // `env -S` splits its argument into words, which split_whitespace already did
+            interpreter = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+        }
+        Self::interpreter_to_language(interpreter)
+    }
+
+    fn interpreter_to_language(interpreter: &str) -> Option<String> {
+        let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let extension = match name {
+            "sh" | "bash" | "zsh" | "ksh" | "dash" | "ash" | "mksh" => "sh",
+            "python" | "pypy" => "py",
+            "node" | "nodejs" | "deno" | "bun" => "js",
+            "ts-node" | "tsx" => "ts",
+            "ruby" | "jruby" => "rb",
+            "perl" => "pl",
+            "php" => "php",
+            "lua" | "luajit" => "lua",
+            "Rscript" => "r",
+            "pwsh" | "powershell" => "ps1",
+            "swift" => "swift",
+            "scala" => "scala",
+            "elixir" => "ex",
+            "escript" => "erl",
+            "dart" => "dart",
+            "runghc" | "runhaskell" => "hs",
+            "kotlin" => "kt",
+            "rust-script" => "rs",
+            _ => return None,
+        };
+        Self::extension_to_language(extension)
+    }
+
+    /// Resolve a language hint (modeline filetype, editorconfig value, etc.)
+    /// to a known language name.
+    ///
+    /// Accepts canonical names (`python`), file extensions (`py`), and the
+    /// common Vim/Emacs aliases that differ from both (`sh`, `make`, `js`).
+    pub fn resolve_language_hint(hint: &str) -> Option<String> {
+        let hint = hint.trim().trim_end_matches("-mode").to_lowercase();
+        if hint.is_empty() {
+            return None;
+        }
+        let aliased = match hint.as_str() {
+            "make" => "makefile",
+            "golang" => "go",
+            "shell" | "shell-script" | "zsh" | "sh" => "sh",
+            "javascript" | "js2" => "js",
+            "typescript" => "ts",
+            "python3" => "py",
+            "c++" | "cpp" => "cpp",
+            "objc" | "objective-c" => "m",
+            "docker" => "dockerfile",
+            other => other,
+        };
+        Self::find_language(&hint).or_else(|| Self::extension_to_language(aliased))
+    }
+
+    /// Extract a language from a Vim or Emacs modeline.
+    ///
+    /// Only the first and last few lines are considered, mirroring how the
+    /// editors themselves look for modelines.
+    pub fn detect_from_modeline(content: &str) -> Option<String> {
+        let lines: Vec<&str> = content.lines().collect();
+        let head = lines.iter().take(MODELINE_SCAN_LINES);
+        let tail = lines.iter().skip(
+            lines
+                .len()
+                .saturating_sub(MODELINE_SCAN_LINES)
+                .max(MODELINE_SCAN_LINES),
+        );
+        head.chain(tail).find_map(|line| {
+            Self::parse_emacs_modeline(line)
+                .or_else(|| Self::parse_vim_modeline(line))
+                .and_then(|hint| Self::resolve_language_hint(&hint))
+        })
+    }
+
+    /// Parse `-*- mode: python -*-` or the short form `-*- python -*-`
+    fn parse_emacs_modeline(line: &str) -> Option<String> {
+        let start = line.find("-*-")? + 3;
+        let rest = &line[start..];
+        let end = rest.find("-*-")?;
+        let body = rest[..end].trim();
+        if !body.contains(':') {
+            return Some(body.to_string()).filter(|b| !b.is_empty());
+        }
+        body.split(';').find_map(|entry| {
+            let (key, value) = entry.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case("mode")
+                .then(|| value.trim().to_string())
+        })
+    }
+
+    /// Parse `vim: ft=python`, `vim: set filetype=python :` and the `vi:`/`ex:` variants
+    fn parse_vim_modeline(line: &str) -> Option<String> {
+        let marker = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+            line.match_indices(marker)
+                .find(|(idx, _)| {
+                    *idx == 0
+                        || line[..*idx]
+                            .chars()
+                            .next_back()
+                            .is_some_and(char::is_whitespace)
+                })
+                .map(|(idx, m)| idx + m.len())
+        })?;
+        line[marker..]
+            .split(|c: char| c.is_whitespace() || c == ':')
+            .find_map(|option| {
+                let (key, value) = option.split_once('=')?;
+                matches!(key, "ft" | "filetype" | "syn" | "syntax")
+                    .then(|| value.to_string())
+                    .filter(|v| !v.is_empty())
+            })
+    }
+
+    /// Read the head and tail of a file for modeline scanning.
+    fn read_modeline_sample(path: &Path) -> Option<String> {
+        let mut file = File::open(path).ok()?;
+        let len = file.metadata().ok()?.len();
+        if len <= MODELINE_SCAN_BYTES * 2 {
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf).ok()?;
+            return Some(String::from_utf8_lossy(&buf).into_owned());
+        }
+        let mut head = vec![0; MODELINE_SCAN_BYTES as usize];
+        file.read_exact(&mut head).ok()?;
+        file.seek(SeekFrom::End(-i64::try_from(MODELINE_SCAN_BYTES).ok()?))
+            .ok()?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail).ok()?;
+        // Keep head and tail as separate blocks so the line window logic in
+        // `detect_from_modeline` still sees both ends of the file.
+        let head = String::from_utf8_lossy(&head);
+        let tail = String::from_utf8_lossy(&tail);
+        let head_lines: Vec<&str> = head.lines().take(MODELINE_SCAN_LINES).collect();
+        let tail_lines: Vec<&str> = tail.lines().collect();
+        let tail_start = tail_lines.len().saturating_sub(MODELINE_SCAN_LINES);
+        Some(
+            head_lines
+                .into_iter()
+                .chain(tail_lines[tail_start..].iter().copied())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    /// Look up a `language` hint for `path` in the nearest `.editorconfig` files.
+    ///
+    /// Walks up from the file's directory; closer files take precedence and the
+    /// walk stops at a file declaring `root = true`. Within one file the last
+    /// matching section wins, as in the EditorConfig specification.
+    pub fn detect_from_editorconfig(path: &Path) -> Option<String> {
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir().ok()?.join(path)
+        };
+        let mut dir = absolute.parent();
+        while let Some(current) = dir {
+            let config_path = current.join(".editorconfig");
+            if let Ok(contents) = std::fs::read_to_string(&config_path) {
+                let relative = absolute.strip_prefix(current).unwrap_or(&absolute);
+                let (hint, is_root) = Self::editorconfig_language(&contents, relative);
+                if let Some(lang) = hint.as_deref().and_then(Self::resolve_language_hint) {
+                    return Some(lang);
+                }
+                if is_root {
+                    break;
+                }
+            }
+            dir = current.parent();
+        }
+        None
+    }
+
+    /// Parse one `.editorconfig` file, returning the `language` value of the
+    /// last section matching `relative` and whether the file is marked root.
+    fn editorconfig_language(contents: &str, relative: &Path) -> (Option<String>, bool) {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let file_name = relative.rsplit('/').next().unwrap_or(&relative).to_string();
+        let mut is_root = false;
+        let mut in_preamble = true;
+        let mut section_matches = false;
+        let mut language = None;
+
+        for raw in contents.lines() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(pattern) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_preamble = false;
+                section_matches = if pattern.contains('/') {
+                    Self::glob_match(pattern.trim_start_matches('/'), &relative)
+                } else {
+                    Self::glob_match(pattern, &file_name)
+                };
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            if in_preamble {
+                if key == "root" {
+                    is_root = value.eq_ignore_ascii_case("true");
+                }
+            } else if section_matches && key == "language" {
+                language = Some(value.to_string());
+            }
+        }
+
+        (language, is_root)
+    }
+
+    /// Minimal EditorConfig glob matcher supporting `*`, `**`, `?` and `{a,b}`.
+    pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+        if let (Some(open), Some(close)) = (pattern.find('{'), pattern.find('}')) {
+            if open < close {
+                let (prefix, rest) = pattern.split_at(open);
+                let alternatives = &rest[1..close - open];
+                let suffix = &rest[close - open + 1..];
+                return alternatives
+                    .split(',')
+                    .any(|alt| Self::glob_match(&format!("{prefix}{alt}{suffix}"), text));
+            }
+        }
+        Self::wildcard_match(pattern.as_bytes(), text.as_bytes())
+    }
+
+    fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern.first() {
+            None => text.is_empty(),
+            Some(b'*') if pattern.get(1) == Some(&b'*') => {
+                (0..=text.len()).any(|skip| Self::wildcard_match(&pattern[2..], &text[skip..]))
+            }
+            Some(b'*') => (0..=text.len())
+                .take_while(|&skip| skip == 0 || text[skip - 1] != b'/')
+                .any(|skip| Self::wildcard_match(&pattern[1..], &text[skip..])),
+            Some(b'?') => {
+                !text.is_empty()
+                    && text[0] != b'/'
+                    && Self::wildcard_match(&pattern[1..], &text[1..])
+            }
+            Some(&c) => text.first() == Some(&c) && Self::wildcard_match(&pattern[1..], &text[1..]),
+        }
     }
 
     /// Map file extensions to language names
@@ -110,6 +691,47 @@ impl LanguageDetector {
         }
     }
 
+    /// Resolve a `--language` value: known names in any case, then aliases
+    /// (`js`, `py`, `golang`, `shell`), then a misspelling whose closest
+    /// known name is unambiguous and at most two edits away
+    pub fn resolve_language(name: &str) -> BatlessResult<LanguageMatch> {
+        if Self::find_language(name).is_some() {
+            return Ok(LanguageMatch::Exact);
+        }
+        if let Some(language) = Self::resolve_language_hint(name) {
+            return Ok(LanguageMatch::Alias(language));
+        }
+        let languages = Self::list_languages();
+        let lowered = name.to_lowercase();
+        // Short names are too close to each other to correct safely (`rst`, `cs`)
+        let max_distance = match lowered.chars().count() {
+            0..=3 => 0,
+            length => 2.min(length / 3),
+        };
+        let mut nearest: Vec<(usize, &String)> = languages
+            .iter()
+            .map(|language| {
+                (
+                    BatlessError::levenshtein_distance(&lowered, &language.to_lowercase()),
+                    language,
+                )
+            })
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        nearest.sort_by_key(|(distance, _)| *distance);
+        match nearest.as_slice() {
+            [(_, language)] => return Ok(LanguageMatch::Corrected((*language).clone())),
+            [(best, language), (next, _), ..] if best < next => {
+                return Ok(LanguageMatch::Corrected((*language).clone()))
+            }
+            _ => {}
+        }
+        Err(BatlessError::language_not_found_with_suggestions(
+            name.to_string(),
+            &languages,
+        ))
+    }
+
     /// Get sorted list of all known languages
     pub fn list_languages() -> Vec<String> {
         // Derive unique sorted list from all extension mappings
@@ -226,6 +848,192 @@ mod tests {
         assert_eq!(language, None);
     }
 
+    #[test]
+    fn test_language_candidates() {
+        let lines = |text: &str| text.lines().map(String::from).collect::<Vec<_>>();
+        let cpp = lines("#pragma once\nnamespace app {\nclass Widget {\npublic:\n};\n}");
+        let ranked = LanguageDetector::language_candidates(
+            "widget.h",
+            Some(("C", LanguageSource::Extension)),
+            &cpp,
+        );
+        let names: Vec<&str> = ranked.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["C++", "C", "Objective-C"]);
+        assert!(ranked[0].score > 0.5);
+        let total: f64 = ranked.iter().map(|c| c.score).sum();
+        assert!((total - 1.0).abs() < 0.01);
+
+        // Without evidence the default mapping stays on top
+        let ranked = LanguageDetector::language_candidates(
+            "x.pl",
+            Some(("Perl", LanguageSource::Extension)),
+            &[],
+        );
+        assert_eq!(ranked[0].name, "Perl");
+        assert_eq!(ranked.len(), 2);
+
+        let certain = LanguageDetector::language_candidates(
+            "main.rs",
+            Some(("Rust", LanguageSource::Extension)),
+            &[],
+        );
+        assert_eq!(
+            certain,
+            [LanguageCandidate {
+                name: "Rust".to_string(),
+                score: 1.0
+            }]
+        );
+        assert!(LanguageDetector::language_candidates("x.xyz", None, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_modeline_vim() {
+        assert_eq!(
+            LanguageDetector::detect_from_modeline("#!/bin/env foo\n# vim: ft=python\n"),
+            Some("Python".to_string())
+        );
+        assert_eq!(
+            LanguageDetector::detect_from_modeline("// vim: set filetype=rust :"),
+            Some("Rust".to_string())
+        );
+        // `vim:` must start a word, so identifiers like `novim:` are ignored
+        assert_eq!(
+            LanguageDetector::detect_from_modeline("novim: ft=python"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_modeline_emacs() {
+        assert_eq!(
+            LanguageDetector::detect_from_modeline("# -*- mode: sh; coding: utf-8 -*-"),
+            Some("Bash".to_string())
+        );
+        assert_eq!(
+            LanguageDetector::detect_from_modeline(";; -*- ruby -*-"),
+            Some("Ruby".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shebang_interpreters() {
+        let detect = |line| LanguageDetector::detect_from_shebang(line);
+        for (line, language) in [
+            ("#!/bin/bash\necho hi", "Bash"),
+            ("#!/bin/sh -e", "Bash"),
+            ("#! /usr/bin/python3.12", "Python"),
+            ("#!/usr/bin/env python3", "Python"),
+            ("#!/usr/bin/env -S node --no-warnings", "JavaScript"),
+            ("#!/usr/bin/env -S deno run --allow-net", "JavaScript"),
+            ("#!/usr/bin/env -i PATH=/bin ruby", "Ruby"),
+            ("\u{feff}#!/usr/bin/perl -w", "Perl"),
+            ("#!/usr/bin/env pwsh", "PowerShell"),
+        ] {
+            assert_eq!(detect(line).as_deref(), Some(language), "{line}");
+        }
+        for line in [
+            "#!/usr/bin/env",
+            "#!/usr/bin/awk -f",
+            "echo #!/bin/bash",
+            "",
+        ] {
+            assert_eq!(detect(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_shebang_after_modeline_in_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("deploy");
+        std::fs::write(&script, "#!/usr/bin/env bash\nset -e\n").unwrap();
+        assert_eq!(
+            LanguageDetector::detect_language_with_source(script.to_str().unwrap()),
+            Some(("Bash".to_string(), LanguageSource::Shebang))
+        );
+        std::fs::write(&script, "#!/bin/sh\n# vim: ft=python\n").unwrap();
+        assert_eq!(
+            LanguageDetector::detect_language_with_source(script.to_str().unwrap()),
+            Some(("Python".to_string(), LanguageSource::Modeline))
+        );
+    }
+
+    #[test]
+    fn test_modeline_only_scans_head_and_tail() {
+        let mut content = "x\n".repeat(20);
+        content.insert_str(20, "# vim: ft=python\n");
+        assert_eq!(LanguageDetector::detect_from_modeline(&content), None);
+
+        content.push_str("# vim: ft=python\n");
+        assert_eq!(
+            LanguageDetector::detect_from_modeline(&content),
+            Some("Python".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fallback_chain_reports_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("build-script");
+        std::fs::write(&script, "#!/bin/sh\n# vim: ft=sh\necho hi\n").unwrap();
+        assert_eq!(
+            LanguageDetector::detect_language_with_source(script.to_str().unwrap()),
+            Some(("Bash".to_string(), LanguageSource::Modeline))
+        );
+
+        assert_eq!(
+            LanguageDetector::detect_language_with_source("main.rs"),
+            Some(("Rust".to_string(), LanguageSource::Extension))
+        );
+        assert_eq!(
+            LanguageDetector::detect_language_with_source("Dockerfile"),
+            Some(("Dockerfile".to_string(), LanguageSource::Filename))
+        );
+    }
+
+    #[test]
+    fn test_editorconfig_hint() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".editorconfig"),
+            "root = true\n\n[*]\nindent_style = space\n\n[*.{tpl,tmpl}]\nlanguage = html\n",
+        )
+        .unwrap();
+        let nested = dir.path().join("templates");
+        std::fs::create_dir(&nested).unwrap();
+        let template = nested.join("page.tpl");
+        std::fs::write(&template, "<p>{{ title }}</p>\n").unwrap();
+
+        assert_eq!(
+            LanguageDetector::detect_language_with_source(template.to_str().unwrap()),
+            Some(("HTML".to_string(), LanguageSource::EditorConfig))
+        );
+
+        let other = nested.join("notes.unknownext");
+        std::fs::write(&other, "plain\n").unwrap();
+        assert_eq!(
+            LanguageDetector::detect_language_with_fallback(other.to_str().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_language_hint() {
+        assert_eq!(
+            LanguageDetector::resolve_language_hint("python"),
+            Some("Python".to_string())
+        );
+        assert_eq!(
+            LanguageDetector::resolve_language_hint("js"),
+            Some("JavaScript".to_string())
+        );
+        assert_eq!(
+            LanguageDetector::resolve_language_hint("make"),
+            Some("Makefile".to_string())
+        );
+        assert_eq!(LanguageDetector::resolve_language_hint("nonsense"), None);
+    }
+
     #[test]
     fn test_extension_to_language() {
         assert_eq!(
@@ -264,6 +1072,68 @@ mod tests {
         assert_eq!(LanguageDetector::find_language("nonexistent"), None);
     }
 
+    #[test]
+    fn test_language_map_precedence() {
+        let map: BTreeMap<String, String> = [
+            ("*.star", "Python"),
+            ("Justfile", "Makefile"),
+            ("BUILD*", "Python"),
+            ("BUILD.bazel", "rb"),
+            ("gen/*.rs", "TypeScript"),
+        ]
+        .into_iter()
+        .map(|(pattern, language)| (pattern.to_string(), language.to_string()))
+        .collect();
+        let detect = |path| {
+            LanguageDetector::detect_language_with_map(path, &map)
+                .map(|(language, source)| (language, source.as_str()))
+        };
+        let mapped = |language: &str| Some((language.to_string(), "language_map"));
+        assert_eq!(detect("tools/rules.star"), mapped("Python"));
+        assert_eq!(detect("./Justfile"), mapped("Makefile"));
+        // Literal names beat globs, and aliases resolve
+        assert_eq!(detect("BUILD.bazel"), mapped("Ruby"));
+        assert_eq!(detect("pkg/BUILD"), mapped("Python"));
+        assert_eq!(detect("/src/gen/api.rs"), mapped("TypeScript"));
+        assert_eq!(
+            detect("src/api.rs"),
+            Some(("Rust".to_string(), "extension"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_language() {
+        let resolve = |name| LanguageDetector::resolve_language(name).unwrap();
+        assert_eq!(resolve("python"), LanguageMatch::Exact);
+        for (alias, language) in [
+            ("js", "JavaScript"),
+            ("ts", "TypeScript"),
+            ("py", "Python"),
+            ("rb", "Ruby"),
+            ("golang", "Go"),
+        ] {
+            assert_eq!(resolve(alias), LanguageMatch::Alias(language.to_string()));
+        }
+        assert!(matches!(resolve("shell"), LanguageMatch::Alias(_)));
+        assert_eq!(
+            resolve("pyton"),
+            LanguageMatch::Corrected("Python".to_string())
+        );
+        assert_eq!(
+            resolve("Javscript"),
+            LanguageMatch::Corrected("JavaScript".to_string())
+        );
+        // Too short to correct, and nothing close: suggestions instead
+        for name in ["rst", "klingon"] {
+            match LanguageDetector::resolve_language(name) {
+                Err(BatlessError::LanguageNotFound { suggestions, .. }) => {
+                    assert_eq!(suggestions.len(), 3);
+                }
+                other => panic!("{name}: {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn test_validate_language() {
         assert!(LanguageDetector::validate_language("Rust").is_ok());
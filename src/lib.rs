@@ -2,6 +2,11 @@
 //!
 //! This library provides the core logic for syntax highlighting and file processing
 //! that can be used both by the CLI and in tests.
+//!
+//! Every function may be called from several threads at once. Shared state
+//! (compiled tree-sitter queries and regex sets) is built on first use behind
+//! `OnceLock`s, so concurrent first calls never race; servers can call
+//! [`warm_up`] at startup to pay that cost before the first request.
 
 // Enable pedantic lints but allow specific noisy ones
 #![warn(clippy::pedantic)]
@@ -23,25 +28,68 @@
 #![allow(clippy::unused_self)] // Some methods need self for trait consistency
 #![allow(clippy::match_same_arms)] // Sometimes clearer to have explicit arms
 
+pub mod annotations;
+pub mod around;
 pub mod ast_summarizer;
+pub mod banner;
+pub mod bench;
+pub mod canonical_json;
+pub mod capabilities;
 pub mod chunker;
+pub mod cli_docs;
+pub mod clipboard;
+pub mod compat;
+#[cfg(feature = "compression")]
+pub mod compression;
 pub mod config;
+pub mod config_check;
 pub mod config_manager;
 pub mod config_validation;
+pub mod dataset;
+pub mod env_policy;
+pub mod envelope;
 pub mod error;
+pub mod export;
 pub mod file_info;
+pub mod fold;
 pub mod formatter;
 pub mod formatters;
+pub mod grammar_loader;
+pub mod hex;
+pub mod highlight_spans;
+pub mod json_envelope;
 pub mod json_schema;
 pub mod language;
+pub mod media;
+pub mod metrics;
+pub mod otel;
+pub mod output_policy;
+pub mod permalink;
 pub mod processor;
 pub mod profile;
+pub mod redaction;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod signatures;
+pub mod stdin_guard;
 pub mod streaming;
 pub mod summarizer;
 pub mod summary;
+pub mod summary_index;
 pub mod summary_item;
+pub mod symbol;
+pub mod syntax_check;
+pub mod sys;
+pub mod template;
+pub mod test_code;
+pub mod timing;
 pub mod tokens;
+pub mod trace;
 pub mod traits;
+pub mod uses;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod workspace;
 
 // Re-export for fuzzing and external use
 pub use tokens::TokenExtractor;
@@ -54,11 +102,19 @@ pub use formatter::{OutputFormatter, OutputMode};
 pub use json_schema::{get_json_schema, validate_batless_output, JsonSchemaValidator};
 pub use language::LanguageDetector;
 pub use processor::FileProcessor;
-pub use profile::CustomProfile;
+pub use profile::{CustomProfile, ImportOutcome};
 pub use streaming::{StreamingCheckpoint, StreamingChunk, StreamingProcessor};
 pub use summary::SummaryLevel;
 pub use tokens::{AiModel, TokenCount, TokenCounter};
 
+/// Build the lazily initialized shared state (summary queries, log and
+/// trace patterns) now; safe to call from any thread, any number of times
+pub fn warm_up() {
+    ast_summarizer::AstSummarizer::warm_up();
+    formatters::log_formatter::warm_up();
+    trace::warm_up();
+}
+
 /// Main entry point for processing a file with batless
 pub fn process_file(file_path: &str, config: &BatlessConfig) -> BatlessResult<FileInfo> {
     FileProcessor::process_file(file_path, config)
@@ -110,6 +166,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_concurrent_first_use_agrees() {
+        let file = create_test_file("use std::io;\n\npub fn main() {}\n\nstruct Point;\n");
+        let path = file.path().with_extension("rs");
+        std::fs::copy(file.path(), &path).unwrap();
+        let config = BatlessConfig::default().with_summary_level(SummaryLevel::Standard);
+        let summaries: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| process_file(path.to_str().unwrap(), &config).unwrap()))
+                .collect();
+            warm_up();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap().summary_lines)
+                .collect()
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert!(summaries[0].as_ref().is_some_and(|items| items.len() == 3));
+        assert!(summaries.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
     #[test]
     fn test_process_file_max_lines() -> BatlessResult<()> {
         let file = create_test_file("line1\nline2\nline3\nline4\nline5");
@@ -1,16 +1,37 @@
+use batless::ast_summarizer::AstSummarizer;
+use batless::canonical_json::to_json_string;
+use batless::capabilities::Capabilities;
+use batless::env_policy::StatusStyle;
+use batless::error::{Warning, WarningCode};
+use batless::output_policy::{ErrorFormat, OutputPolicy};
+use batless::timing::StageTimer;
 use batless::{
-    config_manager::ConfigManager, AiModel, BatlessError, BatlessResult, JsonSchemaValidator,
-    OutputMode, TokenCounter,
+    config_manager::ConfigManager, error::exit_codes, AiModel, BatlessConfig, BatlessError,
+    BatlessResult, CustomProfile, FileProcessor, ImportOutcome, JsonSchemaValidator, OutputMode,
+    TokenCounter,
 };
 use clap::CommandFactory;
 use clap_complete::generate;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Condvar, Mutex, PoisonError};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
-use batless::config_manager::{Args, CliAiModel, Shell};
+use batless::config_manager::{Args, Shell};
 
-fn print_error(error: &BatlessError) {
-    let mut stderr = StandardStream::stderr(ColorChoice::Auto);
+/// Stderr with color only when the environment allows it
+fn stderr_stream(style: StatusStyle) -> StandardStream {
+    StandardStream::stderr(if style.color {
+        ColorChoice::Auto
+    } else {
+        ColorChoice::Never
+    })
+}
+
+fn print_error(error: &BatlessError, style: StatusStyle) {
+    let mut stderr = stderr_stream(style);
     let error_string = error.to_string();
     let mut parts = error_string.splitn(2, '\n');
     let first_line = parts.next().unwrap_or("");
@@ -40,16 +61,17 @@ fn print_error(error: &BatlessError) {
 fn main() {
     // Check for common unsupported features before parsing
     let args: Vec<String> = std::env::args().collect();
+    let style = StatusStyle::detect(args.iter().any(|a| a == "--no-emoji"));
 
     // Check for --pattern / -p (but not -p from existing flags like --plain)
     if args.iter().any(|a| a == "--pattern" || a == "-p") {
-        print_pattern_not_supported();
+        print_pattern_not_supported(style);
         std::process::exit(1);
     }
 
     // Check for --list / -l
     if args.iter().any(|a| a == "--list" || a == "-l") {
-        print_list_not_supported();
+        print_list_not_supported(style);
         std::process::exit(1);
     }
 
@@ -60,42 +82,413 @@ fn main() {
             || a.starts_with("-r=")
             || (a.starts_with("-r") && a.len() > 2 && !a.starts_with("--"))
     }) {
-        print_range_not_supported();
+        print_range_not_supported(style);
         std::process::exit(1);
     }
 
-    if let Err(e) = run() {
-        print_error(&e);
-        std::process::exit(e.error_code() as i32);
+    let mut result = run();
+    if let Some(recorder) = batless::metrics::recorder() {
+        let written = match &result {
+            Ok(code) => recorder.write(*code, None),
+            Err(e) => recorder.write(e.error_code() as i32, Some(e)),
+        };
+        if let (Ok(_), Err(e)) = (&result, written) {
+            result = Err(e);
+        }
+    }
+    // Spans are only telemetry; failing to send them never fails the run
+    if let Err(e) = batless::otel::shutdown() {
+        eprintln!("batless: warning: {e}");
+    }
+
+    match result {
+        Ok(exit_codes::SUCCESS) => {}
+        Ok(code) => {
+            let _ = io::stdout().flush();
+            std::process::exit(code);
+        }
+        Err(e) => {
+            if ErrorFormat::from_raw_args(&args) == ErrorFormat::Json {
+                eprintln!("{}", ErrorFormat::json_error_line(&e));
+            } else {
+                print_error(&e, style);
+            }
+            std::process::exit(e.error_code() as i32);
+        }
     }
 }
 
-fn run() -> BatlessResult<()> {
+fn run() -> BatlessResult<i32> {
     let config_manager = ConfigManager::new()?;
-    let args = config_manager.args();
-    let config = config_manager.config();
-    let output_mode = config_manager.output_mode();
+    if let Some(path) = &config_manager.args().metrics_out {
+        batless::metrics::enable(path);
+    }
+    if config_manager.args().otel {
+        batless::otel::init()?;
+    }
+    // Before any --jobs worker starts, so the workers inherit it
+    if let Some(niceness) = config_manager.args().nice {
+        batless::sys::set_niceness(niceness)?;
+    }
+    if config_manager.args().io_low_priority {
+        batless::sys::set_io_low_priority()?;
+    }
+    for warning in config_manager.warnings() {
+        config_manager
+            .output_policy()
+            .warn(&warning.to_warning(), None);
+    }
+    for notice in config_manager.notices() {
+        config_manager
+            .output_policy()
+            .info(format_args!("batless: {notice}"));
+    }
+
+    if let Some(path) = &config_manager.args().check_config {
+        return handle_check_config(path.as_deref(), &config_manager);
+    }
+    batless::grammar_loader::load_configured(config_manager.config())?;
+    AstSummarizer::check_custom_queries()?;
+    if config_manager.args().check {
+        return handle_check(&config_manager);
+    }
+
+    if config_manager.args().capabilities {
+        let capabilities = Capabilities::detect(config_manager.config());
+        println!("{}", serde_json::to_string_pretty(&capabilities)?);
+        return Ok(exit_codes::SUCCESS);
+    }
+
+    if let Some(dir) = &config_manager.args().emit_goldens {
+        let count = batless::compat::emit(std::path::Path::new(dir))?;
+        config_manager.output_policy().info(format_args!(
+            "batless: wrote {count} golden file(s) to {dir}"
+        ));
+        return Ok(exit_codes::SUCCESS);
+    }
+    if let Some(dir) = &config_manager.args().verify_goldens {
+        return handle_verify_goldens(dir, &config_manager);
+    }
+
+    if let Some(addr) = &config_manager.args().serve {
+        return handle_serve(addr, &config_manager);
+    }
+
+    if let Some(trace) = &config_manager.args().from_trace {
+        return handle_from_trace(trace, &config_manager);
+    }
 
     // Handle commands that don't require file processing
-    if handle_special_commands(args)? {
-        return Ok(());
+    if handle_special_commands(config_manager.args(), config_manager.config())? {
+        return Ok(exit_codes::SUCCESS);
     }
 
-    let file_path = config_manager.file_path()?;
+    let file_paths = config_manager.file_paths()?;
+    if let Some(dir) = &config_manager.args().export {
+        return handle_export(&file_paths, dir, &config_manager);
+    }
+    if config_manager.args().copy || config_manager.args().copy_only {
+        check_copy_supported(&file_paths, &config_manager)?;
+    }
+    if config_manager.args().summary_index {
+        return handle_summary_index(&file_paths, &config_manager);
+    }
+    if matches!(
+        config_manager.output_mode(),
+        OutputMode::Ctags | OutputMode::Etags
+    ) {
+        return handle_tags(&file_paths, &config_manager);
+    }
+    if config_manager.output_mode() == OutputMode::Scip {
+        return handle_scip(&file_paths, &config_manager);
+    }
+    if config_manager.output_mode() == OutputMode::Metadata {
+        return handle_metadata(&file_paths, &config_manager);
+    }
+    let multi_file = file_paths.len() > 1;
+    let json_envelope = config_manager.args().json_envelope;
+    if json_envelope {
+        check_json_envelope_supported(&config_manager)?;
+    }
+
+    // Process every file; the first non-success exit code wins
+    let mut out = if json_envelope {
+        ContentSink::capture()
+    } else {
+        ContentSink::new(config_manager.args())
+    };
+    let jobs = jobs(&config_manager);
+    let exit_code = if multi_file && jobs > 1 {
+        process_parallel(&file_paths, &config_manager, jobs, &mut out)?
+    } else {
+        let mut exit_code = exit_codes::SUCCESS;
+        for (index, file_path) in file_paths.iter().enumerate() {
+            let code = process_path(
+                file_path,
+                &*config_manager.for_input(file_path)?,
+                multi_file.then_some(index),
+                &mut out,
+            )?;
+            if exit_code == exit_codes::SUCCESS {
+                exit_code = code;
+            }
+        }
+        exit_code
+    };
+    if json_envelope {
+        out = write_json_envelope(&out.into_text(), &config_manager)?;
+    }
+    out.finish(config_manager.output_policy())?;
+    Ok(exit_code)
+}
 
-    // Directory input with index mode: walk and emit NDJSON
-    if output_mode == OutputMode::Index && std::path::Path::new(&file_path).is_dir() {
-        return handle_directory_index(&file_path, &config_manager);
+/// Worker threads for `--jobs`, where 0 means one per CPU
+fn jobs(manager: &ConfigManager) -> usize {
+    match manager.args().jobs {
+        0 => std::thread::available_parallelism().map_or(1, usize::from),
+        jobs => jobs,
     }
+}
 
-    if config.streaming_json && output_mode == OutputMode::Json {
-        return handle_streaming_json(&file_path, &config_manager);
+/// `--json-envelope` collects whole JSON documents, so every file must be
+/// rendered as one
+fn check_json_envelope_supported(manager: &ConfigManager) -> BatlessResult<()> {
+    if manager.output_mode() != OutputMode::Json {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "--json-envelope needs --mode=json, not --mode={}",
+                manager.output_mode().as_str()
+            ),
+            Some("Add --mode=json".to_string()),
+        ));
     }
+    if manager.config().streaming_json {
+        return Err(BatlessError::config_error_with_help(
+            "--json-envelope cannot wrap streaming JSON chunks".to_string(),
+            Some("Drop --streaming-json (or streaming_json in the config)".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Wrap the per-file documents in `captured` into one envelope and write it
+fn write_json_envelope(captured: &str, manager: &ConfigManager) -> BatlessResult<ContentSink> {
+    let files = serde_json::Deserializer::from_str(captured)
+        .into_iter::<serde_json::Value>()
+        .collect::<Result<Vec<_>, _>>()?;
+    let config = manager.config();
+    let envelope = batless::json_envelope::JsonEnvelope::new(files, config);
+    let mut out = ContentSink::new(manager.args());
+    out.line(to_json_string(
+        &envelope,
+        config.pretty_json,
+        config.canonical_json,
+    )?);
+    Ok(out)
+}
+
+/// What a `--jobs` worker hands back for one input
+enum Rendered {
+    /// Captured output and exit code
+    Done(String, i32),
+    /// Writes straight to stdout, so the main thread processes it in turn
+    Deferred,
+    Failed(BatlessError),
+}
+
+/// Work handed out to `--jobs` workers
+struct Schedule {
+    /// Next input to start
+    next: usize,
+    /// Inputs written so far, in input order
+    written: usize,
+    stopped: bool,
+}
+
+/// Render inputs on `jobs` threads and write each one's output in input
+/// order, or as soon as it is done with `--unordered`. At most `2 * jobs`
+/// inputs run ahead of the one being written, which bounds buffered output.
+fn process_parallel(
+    file_paths: &[String],
+    manager: &ConfigManager,
+    jobs: usize,
+    out: &mut ContentSink,
+) -> BatlessResult<i32> {
+    // Completion order varies between runs
+    let unordered = manager.args().unordered && !manager.config().deterministic;
+    let copying = out.buffer.is_some();
+    let window = jobs * 2;
+    let schedule = Mutex::new(Schedule {
+        next: 0,
+        written: 0,
+        stopped: false,
+    });
+    let advanced = Condvar::new();
+    let lock = || schedule.lock().unwrap_or_else(PoisonError::into_inner);
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (lock, advanced) = (&lock, &advanced);
+            scope.spawn(move || loop {
+                let index = {
+                    let mut state = lock();
+                    while !state.stopped
+                        && !unordered
+                        && state.next < file_paths.len()
+                        && state.next >= state.written + window
+                    {
+                        state = advanced.wait(state).unwrap_or_else(PoisonError::into_inner);
+                    }
+                    if state.stopped || state.next >= file_paths.len() {
+                        break;
+                    }
+                    state.next += 1;
+                    state.next - 1
+                };
+                let path = &file_paths[index];
+                let rendered = match manager.for_input(path) {
+                    Ok(manager) if renders_whole(path, &manager, copying) => {
+                        let mut sink = ContentSink::capture();
+                        match handle_normal_processing(path, &manager, Some(index), &mut sink) {
+                            Ok(code) => Rendered::Done(sink.into_text(), code),
+                            Err(e) => Rendered::Failed(e),
+                        }
+                    }
+                    Ok(_) => Rendered::Deferred,
+                    Err(e) => Rendered::Failed(e),
+                };
+                if sender.send((index, rendered)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut codes = vec![exit_codes::SUCCESS; file_paths.len()];
+        let mut emit = |index: usize, rendered: Rendered| -> BatlessResult<()> {
+            codes[index] = match rendered {
+                Rendered::Done(text, code) => {
+                    out.write_captured(&text);
+                    code
+                }
+                Rendered::Deferred => {
+                    let path = &file_paths[index];
+                    process_path(path, &*manager.for_input(path)?, Some(index), out)?
+                }
+                Rendered::Failed(e) => return Err(e),
+            };
+            Ok(())
+        };
+        let mut pending = BTreeMap::new();
+        let result = receiver.iter().try_for_each(|(index, rendered)| {
+            if unordered {
+                return emit(index, rendered);
+            }
+            pending.insert(index, rendered);
+            let mut written = lock().written;
+            while let Some(rendered) = pending.remove(&written) {
+                emit(written, rendered)?;
+                written += 1;
+                lock().written = written;
+                advanced.notify_all();
+            }
+            Ok(())
+        });
+
+        lock().stopped = true;
+        advanced.notify_all();
+        result?;
+        Ok(codes
+            .into_iter()
+            .find(|&code| code != exit_codes::SUCCESS)
+            .unwrap_or(exit_codes::SUCCESS))
+    })
+}
 
-    handle_normal_processing(&file_path, &config_manager)
+/// Whether `file_path` is rendered whole by [`handle_normal_processing`],
+/// so a `--jobs` worker can capture its output
+fn renders_whole(file_path: &str, manager: &ConfigManager, copying: bool) -> bool {
+    let args = manager.args();
+    let output_mode = manager.output_mode();
+    !(file_path == "-"
+        || args.bench
+        || args.token_stats
+        || args.tokenize.is_some()
+        || args.chunk_for_rag
+        || args.envelope.is_some()
+        // Printed straight to stdout
+        || args.count_tokens
+        || matches!(output_mode, OutputMode::Hex | OutputMode::HexJson)
+        || (matches!(output_mode, OutputMode::Index | OutputMode::Imports)
+            && Path::new(file_path).is_dir())
+        || (manager.config().streaming_json && output_mode == OutputMode::Json)
+        || auto_streams(file_path, manager, copying))
 }
 
-fn handle_special_commands(args: &Args) -> BatlessResult<bool> {
+/// Whether a huge plain-text file is printed chunk by chunk
+fn auto_streams(file_path: &str, manager: &ConfigManager, copying: bool) -> bool {
+    manager.output_mode() == OutputMode::Plain
+        && !copying
+        && !manager.args().fit_context
+        && !manager.args().count_tokens
+        && batless::StreamingProcessor::should_auto_stream(file_path, manager.config())
+}
+
+/// Process a single input; `file_index` is set when several files were given
+fn process_path(
+    file_path: &str,
+    manager: &ConfigManager,
+    file_index: Option<usize>,
+    out: &mut ContentSink,
+) -> BatlessResult<i32> {
+    let output_mode = manager.output_mode();
+
+    if manager.args().bench {
+        return handle_bench(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    if manager.args().token_stats {
+        return handle_token_stats(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    if manager.args().tokenize.is_some() {
+        return handle_tokenize(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    if manager.args().chunk_for_rag {
+        return handle_rag_chunks(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    if manager.args().envelope.is_some() {
+        return handle_file_envelope(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    if matches!(output_mode, OutputMode::Hex | OutputMode::HexJson) {
+        return handle_hex(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    // Directory input with index/imports mode: walk and emit NDJSON
+    if matches!(output_mode, OutputMode::Index | OutputMode::Imports)
+        && std::path::Path::new(file_path).is_dir()
+    {
+        return handle_directory_index(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    if manager.config().streaming_json && output_mode == OutputMode::Json {
+        return handle_streaming_json(file_path, manager).map(|()| exit_codes::SUCCESS);
+    }
+
+    // Huge files are printed chunk by chunk instead of being read whole
+    if auto_streams(file_path, manager, out.buffer.is_some()) {
+        return handle_auto_stream(file_path, manager, file_index);
+    }
+
+    handle_normal_processing(file_path, manager, file_index, out)
+}
+
+fn handle_special_commands(args: &Args, config: &BatlessConfig) -> BatlessResult<bool> {
     if args.version_json {
         // Collect build-time metadata populated by build script (if any)
         // Fallbacks ensure robustness in absence of environment variables.
@@ -115,7 +508,7 @@ fn handle_special_commands(args: &Args) -> BatlessResult<bool> {
         return Ok(true);
     }
     if let Some(shell) = args.generate_completions {
-        let mut cmd = Args::command();
+        let mut cmd = batless::cli_docs::with_value_hints(Args::command(), config);
         let name = cmd.get_name().to_string();
         let mut buffer: Vec<u8> = Vec::new();
         match shell {
@@ -133,13 +526,23 @@ fn handle_special_commands(args: &Args) -> BatlessResult<bool> {
         return Ok(true);
     }
 
+    if args.generate_man {
+        print!("{}", batless::cli_docs::man_page(&Args::command()));
+        return Ok(true);
+    }
+
+    if args.help_markdown {
+        print!("{}", batless::cli_docs::markdown(&Args::command()));
+        return Ok(true);
+    }
+
     if let Some(format) = &args.get_schema {
         let validator = JsonSchemaValidator::new();
         let schema = validator.get_schema(format).ok_or_else(|| {
             BatlessError::config_error_with_help(
                 format!("Unknown schema format '{format}'"),
                 Some(
-                    "Available schemas: file_info, json_output, token_count, processing_stats, streaming_chunk"
+                    "Available schemas: file_info, json_output, token_count, processing_stats, streaming_chunk, json_envelope"
                         .to_string(),
                 ),
             )
@@ -149,6 +552,37 @@ fn handle_special_commands(args: &Args) -> BatlessResult<bool> {
         return Ok(true);
     }
 
+    if args.configure {
+        handle_configure(args)?;
+        return Ok(true);
+    }
+
+    if let Some(name) = &args.export_profile {
+        handle_export_profile(name, config.deterministic)?;
+        return Ok(true);
+    }
+
+    if let Some(source) = &args.import_profile {
+        handle_import_profile(args, source)?;
+        return Ok(true);
+    }
+
+    if args.list_profiles {
+        for path in CustomProfile::discover_profiles() {
+            match CustomProfile::load_from_file(&path) {
+                Ok(profile) => println!("{}\t{}", profile.name, path.display()),
+                Err(e) => {
+                    let warning =
+                        Warning::new(WarningCode::ProfileSkipped, format!("skipping: {e}"));
+                    let format = ErrorFormat::from(args.error_format);
+                    let file = path.display().to_string();
+                    eprintln!("{}", format.warning_line(&warning, Some(&file)));
+                }
+            }
+        }
+        return Ok(true);
+    }
+
     if args.list_languages {
         for language in batless::LanguageDetector::list_languages() {
             println!("{language}");
@@ -159,6 +593,92 @@ fn handle_special_commands(args: &Args) -> BatlessResult<bool> {
     Ok(false)
 }
 
+/// Create or update a profile from `--from-json`/`--set` without prompting
+fn handle_configure(args: &Args) -> BatlessResult<()> {
+    if !args.non_interactive {
+        return Err(BatlessError::config_error_with_help(
+            "The interactive configuration wizard was removed in 0.6.0".to_string(),
+            Some("Use --configure --non-interactive --set KEY=VALUE ... instead".to_string()),
+        ));
+    }
+
+    let mut profile = match (&args.from_json, &args.profile_out) {
+        (Some(source), _) => CustomProfile::load_from_file(Path::new(source))?,
+        (None, Some(out)) if Path::new(out).exists() => {
+            CustomProfile::load_from_file(Path::new(out))?
+        }
+        _ => CustomProfile::new(String::new(), None),
+    };
+
+    for pair in &args.set {
+        let (key, value) = pair.split_once('=').ok_or_else(|| {
+            BatlessError::config_error_with_help(
+                format!("Invalid --set argument '{pair}'"),
+                Some("Use the form --set KEY=VALUE".to_string()),
+            )
+        })?;
+        profile.set_field(key, value)?;
+    }
+
+    let path = match &args.profile_out {
+        Some(out) => PathBuf::from(out),
+        None => CustomProfile::default_path(&profile.name).ok_or_else(|| {
+            BatlessError::config_error_with_help(
+                "Could not determine the user config directory".to_string(),
+                Some("Pass --profile-out FILE to choose a location".to_string()),
+            )
+        })?,
+    };
+
+    profile.save_to_file(&path)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Print a profile, found by name like `--custom-profile`, as JSON; its
+/// timestamps are dropped with `--deterministic`
+fn handle_export_profile(name: &str, deterministic: bool) -> BatlessResult<()> {
+    let project_dir = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| BatlessConfig::find_project_dir(&cwd));
+    let path = CustomProfile::resolve_path(name, project_dir.as_deref());
+    if !path.is_file() {
+        return Err(BatlessError::config_error_with_help(
+            format!("Profile '{name}' not found"),
+            Some("List installed profiles with --list-profiles".to_string()),
+        ));
+    }
+    let mut profile = CustomProfile::load_from_file(&path)?;
+    if deterministic {
+        profile.created_at = None;
+        profile.updated_at = None;
+    }
+    println!("{}", serde_json::to_string_pretty(&profile)?);
+    Ok(())
+}
+
+/// Install a shared profile into the user profile directory
+fn handle_import_profile(args: &Args, source: &str) -> BatlessResult<()> {
+    let mut profile = CustomProfile::read_source(source)?;
+    if let Some(name) = &args.import_as {
+        profile.name.clone_from(name);
+    }
+    let dir = CustomProfile::user_dir().ok_or_else(|| {
+        BatlessError::config_error_with_help(
+            "Could not determine the user config directory".to_string(),
+            Some("Use --custom-profile FILE with the downloaded profile instead".to_string()),
+        )
+    })?;
+    let outcome = profile.install(&dir, args.replace_profile)?;
+    let status = match outcome {
+        ImportOutcome::Installed(_) => "installed",
+        ImportOutcome::Replaced(_) => "replaced",
+        ImportOutcome::Unchanged(_) => "unchanged",
+    };
+    println!("{status} {} {}", profile.name, outcome.path().display());
+    Ok(())
+}
+
 fn handle_streaming_json(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
     use batless::StreamingProcessor;
 
@@ -182,15 +702,21 @@ fn handle_streaming_json(file_path: &str, manager: &ConfigManager) -> BatlessRes
         None
     };
 
-    let chunks = StreamingProcessor::process_streaming(file_path, config, checkpoint)?;
+    let chunks = StreamingProcessor::process_streaming_with_counter(
+        file_path,
+        config,
+        checkpoint,
+        config.token_counter(manager.ai_model()),
+    )?;
 
     for chunk_result in chunks {
         let chunk = chunk_result?;
         // NDJSON: one compact JSON object per line, no separator needed
-        let json_output = serde_json::to_string(&chunk)?;
+        let json_output = to_json_string(&chunk, false, config.canonical_json)?;
         println!("{json_output}");
 
-        if config.enable_resume && !chunk.is_final {
+        // A budget- or --max-chunks-stopped stream can be resumed where it left off
+        if config.enable_resume && (!chunk.is_final || chunk.budget_exhausted || chunk.capped) {
             if let Some(checkpoint_path) = &args.checkpoint {
                 StreamingProcessor::save_checkpoint(
                     &chunk.checkpoint,
@@ -254,7 +780,7 @@ fn handle_directory_index(dir_path: &str, manager: &ConfigManager) -> BatlessRes
         let path_str = file.to_string_lossy();
         let compact = match batless::process_file(&path_str, config) {
             Ok(file_info) => {
-                match batless::format_output(&file_info, &path_str, config, OutputMode::Index) {
+                match batless::format_output(&file_info, &path_str, config, manager.output_mode()) {
                     Ok(pretty) => {
                         // Compact the pretty JSON to a single line for NDJSON
                         serde_json::from_str::<serde_json::Value>(&pretty)
@@ -264,14 +790,14 @@ fn handle_directory_index(dir_path: &str, manager: &ConfigManager) -> BatlessRes
                     Err(e) => {
                         let err_obj =
                             serde_json::json!({"file": path_str.as_ref(), "error": e.to_string()});
-                        serde_json::to_string(&err_obj).unwrap_or_default()
+                        to_json_string(&err_obj, false, config.canonical_json).unwrap_or_default()
                     }
                 }
             }
             Err(e) => {
                 let err_obj =
                     serde_json::json!({"file": path_str.as_ref(), "error": e.to_string()});
-                serde_json::to_string(&err_obj).unwrap_or_default()
+                to_json_string(&err_obj, false, config.canonical_json).unwrap_or_default()
             }
         };
         writeln!(out, "{compact}")?;
@@ -279,35 +805,649 @@ fn handle_directory_index(dir_path: &str, manager: &ConfigManager) -> BatlessRes
     Ok(())
 }
 
-fn handle_normal_processing(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+/// Input files with directories walked, sorted and without duplicates
+fn expand_inputs(file_paths: &[String]) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    for path in file_paths {
+        let path = std::path::Path::new(path);
+        if path.is_dir() {
+            collect_files_recursive(path, &mut files);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn handle_summary_index(file_paths: &[String], manager: &ConfigManager) -> BatlessResult<i32> {
+    let mut index = batless::summary_index::SummaryIndex::new();
+    for file in &expand_inputs(file_paths) {
+        let path = file.to_string_lossy();
+        match manager
+            .for_input(&path)
+            .and_then(|manager| batless::process_file(&path, manager.config()))
+        {
+            Ok(file_info) => index.add_file(&path, &file_info),
+            Err(e) => index.add_error(&path, e.to_string()),
+        }
+    }
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&index, true, manager.config().canonical_json)?
+        );
+    } else {
+        print!("{}", index.render_text());
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+fn handle_export(file_paths: &[String], dir: &str, manager: &ConfigManager) -> BatlessResult<i32> {
+    use batless::export::{Exporter, MANIFEST_FILE};
+
+    let sources = batless::export::sources(file_paths, Path::new(dir));
+    if sources.is_empty() {
+        return Err(BatlessError::config_error_with_help(
+            format!("No files match {}", file_paths.join(" ")),
+            Some("Quote glob patterns such as 'src/**/*.rs' so batless expands them".to_string()),
+        ));
+    }
+    let mut exporter = Exporter::new(dir, manager.config().clone());
+    for source in &sources {
+        let path = source.to_string_lossy();
+        if let Cow::Owned(input) = manager.for_input(&path)? {
+            exporter = exporter.with_source_config(source, input.config().clone());
+        }
+    }
+    let report = exporter
+        .with_format(
+            manager
+                .args()
+                .export_format
+                .map(Into::into)
+                .unwrap_or_default(),
+        )
+        .with_jobs(jobs(manager))
+        .run(&sources)?;
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&report, true, manager.config().canonical_json)?
+        );
+    } else {
+        manager.output_policy().info(format_args!(
+            "batless: exported {} file(s) to {dir}, {} unchanged, {} failed",
+            report.exported, report.unchanged, report.failed
+        ));
+    }
+    if report.failed > 0 {
+        return Err(BatlessError::processing_error_with_help(
+            None,
+            format!("{} file(s) could not be exported", report.failed),
+            format!("See the errors in {dir}/{MANIFEST_FILE}"),
+        ));
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+fn handle_tags(file_paths: &[String], manager: &ConfigManager) -> BatlessResult<i32> {
+    use batless::formatters::tags_formatter::{render_ctags, render_etags, TagEntry};
+
+    let mut entries = Vec::new();
+    for file in &expand_inputs(file_paths) {
+        let path = file.to_string_lossy();
+        match manager
+            .for_input(&path)
+            .and_then(|manager| batless::process_file(&path, manager.config()))
+        {
+            Ok(file_info) => entries.extend(TagEntry::collect(&path, &file_info)),
+            // A tags file has no room for diagnostics
+            Err(e) => eprintln!("batless: skipping {path}: {e}"),
+        }
+    }
+    if manager.output_mode() == OutputMode::Ctags {
+        print!("{}", render_ctags(&mut entries));
+    } else {
+        print!("{}", render_etags(&entries));
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+fn handle_scip(file_paths: &[String], manager: &ConfigManager) -> BatlessResult<i32> {
+    use batless::formatters::scip_formatter::ScipIndex;
+
+    let root = std::env::current_dir().unwrap_or_default();
+    let mut index = ScipIndex::new(
+        format!("file://{}", root.display()),
+        std::env::args().collect(),
+    );
+    for file in &expand_inputs(file_paths) {
+        let path = file.to_string_lossy();
+        // Documents are keyed by their path relative to the project root
+        let relative = file.strip_prefix(&root).unwrap_or(file);
+        let relative = relative.to_string_lossy();
+        match manager
+            .for_input(&path)
+            .and_then(|manager| batless::process_file(&path, manager.config()))
+        {
+            Ok(file_info) => index.add_file(relative.trim_start_matches("./"), &file_info),
+            Err(e) => eprintln!("batless: skipping {path}: {e}"),
+        }
+    }
+    let config = manager.config();
+    println!(
+        "{}",
+        to_json_string(&index, config.pretty_json, config.canonical_json)?
+    );
+    Ok(exit_codes::SUCCESS)
+}
+
+/// One metadata object per file, without reading any content
+fn handle_metadata(file_paths: &[String], manager: &ConfigManager) -> BatlessResult<i32> {
     let config = manager.config();
+    let files = if file_paths == ["-"] {
+        vec![std::path::PathBuf::from("-")]
+    } else {
+        expand_inputs(file_paths)
+    };
+    for file in &files {
+        let path = file.to_string_lossy();
+        let value = match FileProcessor::metadata_only(&path, config) {
+            Ok(file_info) => batless::OutputFormatter::metadata_json(&file_info, &path),
+            // Keep going so one unreadable file doesn't hide the rest
+            Err(e) => serde_json::json!({"file_path": path, "error": e.to_string()}),
+        };
+        println!(
+            "{}",
+            to_json_string(&value, config.pretty_json, config.canonical_json)?
+        );
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+/// Pre-flight for `--check`. Options, config files and profiles were
+/// validated while loading; this adds the inputs, which are opened but not read.
+fn handle_check(manager: &ConfigManager) -> BatlessResult<i32> {
     let args = manager.args();
-    let output_mode = manager.output_mode();
+    if args.serve.is_none() && args.from_trace.is_none() {
+        let file_paths = manager.file_paths()?;
+        let directories_allowed = matches!(
+            manager.output_mode(),
+            OutputMode::Index | OutputMode::Imports
+        );
+        for path in &file_paths {
+            if path == "-" || (directories_allowed && Path::new(path).is_dir()) {
+                continue;
+            }
+            FileProcessor::validate_file_access(path)?;
+        }
+        if args.copy || args.copy_only {
+            check_copy_supported(&file_paths, manager)?;
+        }
+    }
+    manager
+        .output_policy()
+        .info(format_args!("batless: check passed"));
+    Ok(exit_codes::SUCCESS)
+}
+
+fn handle_check_config(path: Option<&str>, manager: &ConfigManager) -> BatlessResult<i32> {
+    let paths = match path {
+        Some(path) => vec![std::path::PathBuf::from(path)],
+        None => batless::config_check::discover_files(&std::env::current_dir()?),
+    };
+    let report = batless::config_check::check_files(&paths);
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&report, true, manager.config().canonical_json)?
+        );
+    } else {
+        print!("{}", report.render_text());
+    }
+    Ok(if report.is_ok() {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::CONFIG_INVALID
+    })
+}
+
+fn handle_verify_goldens(dir: &str, manager: &ConfigManager) -> BatlessResult<i32> {
+    let report = batless::compat::verify(std::path::Path::new(dir))?;
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&report, true, manager.config().canonical_json)?
+        );
+    } else {
+        print!("{}", report.render_text());
+    }
+    Ok(if report.is_compatible() {
+        exit_codes::SUCCESS
+    } else {
+        exit_codes::GOLDENS_CHANGED
+    })
+}
+
+#[cfg(feature = "serve")]
+fn handle_serve(addr: &str, manager: &ConfigManager) -> BatlessResult<i32> {
+    batless::serve::serve(addr, manager.config())?;
+    Ok(exit_codes::SUCCESS)
+}
+
+#[cfg(not(feature = "serve"))]
+fn handle_serve(_addr: &str, _manager: &ConfigManager) -> BatlessResult<i32> {
+    Err(BatlessError::config_error_with_help(
+        "--serve is not available in this build".to_string(),
+        Some("Reinstall with: cargo install batless --features serve".to_string()),
+    ))
+}
+
+fn handle_from_trace(trace: &str, manager: &ConfigManager) -> BatlessResult<i32> {
+    let text = if trace == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(trace).map_err(|e| BatlessError::FileReadError {
+            path: trace.to_string(),
+            source: e,
+        })?
+    };
+    let frames = batless::trace::parse_trace(&text);
+    if frames.is_empty() {
+        return Err(BatlessError::config_error_with_help(
+            format!("No stack frames found in {trace}"),
+            Some("Supported formats: Rust panics/backtraces, Python tracebacks, Node and Java stack traces".to_string()),
+        ));
+    }
+    let pack = batless::trace::build_context_pack(
+        trace,
+        frames,
+        &std::env::current_dir()?,
+        manager.config(),
+        manager.args().trace_context,
+    );
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&pack, true, manager.config().canonical_json)?
+        );
+    } else {
+        print!("{}", pack.render_text());
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+fn handle_bench(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+    let report = batless::bench::run_benchmark(
+        file_path,
+        manager.config(),
+        manager.args().bench_iterations,
+    )?;
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&report, true, manager.config().canonical_json)?
+        );
+    } else {
+        print!("{}", report.render_text());
+    }
+    Ok(())
+}
+
+fn handle_hex(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+    let config = manager.config();
+    let dump = batless::hex::HexDump::read(file_path, config.max_bytes)?;
+    if manager.output_mode() == OutputMode::HexJson {
+        let json = dump.to_json(config.display_path(file_path));
+        println!(
+            "{}",
+            to_json_string(&json, config.pretty_json, config.canonical_json)?
+        );
+    } else {
+        println!("{}", dump.render_text());
+        if let Some(max_bytes) = config
+            .max_bytes
+            .filter(|_| dump.truncated && manager.output_policy().notices())
+        {
+            println!("// Output truncated after {max_bytes} bytes");
+        }
+    }
+    Ok(())
+}
+
+fn mark_stage(timer: Option<&mut StageTimer>, stage: &'static str) {
+    if let Some(timer) = timer {
+        timer.mark(stage);
+    }
+}
+
+/// Stage timer for `--time` and `--otel`
+fn stage_timer(display_path: &str, manager: &ConfigManager) -> Option<StageTimer> {
+    (manager.args().time || batless::otel::enabled()).then(|| StageTimer::start(display_path))
+}
 
+/// `--time` report on stderr, printed even with `--quiet` since it was asked
+/// for, and `--otel` spans
+fn report_time(timer: Option<StageTimer>, manager: &ConfigManager) {
+    if let Some(timer) = timer {
+        if manager.args().time {
+            eprintln!("{}", timer.render(manager.args().time_format.into()));
+        }
+        batless::otel::export(&timer);
+    }
+}
+
+/// Count a processed file towards `--metrics-out`
+fn record_metrics(display_path: &str, file_info: &batless::FileInfo, started: std::time::Instant) {
+    if let Some(recorder) = batless::metrics::recorder() {
+        recorder.record(batless::metrics::FileMetrics::new(
+            display_path,
+            file_info,
+            started.elapsed(),
+        ));
+    }
+}
+
+/// `-v` per-file processing report
+fn report_file(policy: OutputPolicy, display_path: &str, file_info: &batless::FileInfo) {
+    policy.verbose(format_args!(
+        "{display_path}: {}, {}, {} lines, {} bytes{}{}",
+        file_info.language.as_deref().unwrap_or("plain text"),
+        file_info.encoding,
+        file_info.total_lines,
+        file_info.total_bytes,
+        if file_info.truncated {
+            ", truncated"
+        } else {
+            ""
+        },
+        if file_info.streamed { ", streamed" } else { "" }
+    ));
+}
+
+/// Plain output of a file over the auto-stream threshold, printed chunk by
+/// chunk so memory stays bounded by the chunk size
+fn handle_auto_stream(
+    file_path: &str,
+    manager: &ConfigManager,
+    file_index: Option<usize>,
+) -> BatlessResult<i32> {
+    use batless::formatters::plain_formatter::PlainFormatter;
+
+    let config = manager.config();
+    let args = manager.args();
+    let policy = manager.output_policy();
+    let display_path = config.display_path(file_path);
     let start_time = std::time::Instant::now();
-    if config.debug {
-        eprintln!("🔍 DEBUG: Starting file processing for {file_path}");
+    let mut timer = stage_timer(display_path, manager);
+
+    let banner_index = file_index.filter(|_| !config.preview_window);
+    if let Some(index) = banner_index {
+        let header = config
+            .file_header
+            .as_deref()
+            .unwrap_or(batless::banner::DEFAULT_FILE_HEADER);
+        if !header.is_empty() {
+            if index > 0 {
+                println!();
+            }
+            // Counting lines is a cheap memchr pass, unlike reading the file
+            let info = FileProcessor::metadata_only(file_path, config)?;
+            println!("{}", batless::banner::render(header, display_path, &info));
+        }
     }
 
-    let file_info = batless::process_file(file_path, config)?;
+    let mut out = io::stdout().lock();
+    let mut metadata = None;
+    let (mut emitted, mut bytes, mut line_number) = (0, 0, 1);
+    let (mut truncated_by_lines, mut truncated_by_bytes) = (false, false);
+    for chunk in batless::StreamingProcessor::process_auto_stream(file_path, config)? {
+        let mut chunk = chunk?;
+        // The same --max-lines / --max-bytes limits as a whole-file read
+        let mut keep = 0;
+        for line in &chunk.lines {
+            if emitted + keep >= config.max_lines {
+                truncated_by_lines = true;
+                break;
+            }
+            if config
+                .max_bytes
+                .is_some_and(|max| bytes + line.len() + 1 > max)
+            {
+                truncated_by_bytes = true;
+                break;
+            }
+            bytes += line.len() + 1;
+            keep += 1;
+        }
+        chunk.lines.truncate(keep);
+        for line in
+            PlainFormatter::render_lines(&chunk.lines, emitted + 1, &mut line_number, &[], config)
+        {
+            writeln!(out, "{line}")?;
+        }
+        emitted += keep;
+        metadata = Some(chunk.metadata);
+        if truncated_by_lines || truncated_by_bytes {
+            break;
+        }
+    }
+    out.flush()?;
+    drop(out);
+    mark_stage(timer.as_mut(), "stream");
 
-    if config.debug {
-        eprintln!(
-            "🔍 DEBUG: Processing completed in {:?}",
-            start_time.elapsed()
+    let Some(metadata) = metadata else {
+        report_time(timer, manager);
+        return Ok(exit_codes::SUCCESS);
+    };
+    let truncated = truncated_by_lines || truncated_by_bytes;
+    // Like a whole-file read, a truncated stream only knows the lines it kept
+    let file_info = batless::FileInfo::with_metadata(
+        if truncated {
+            emitted
+        } else {
+            metadata.total_file_lines
+        },
+        usize::try_from(metadata.total_file_bytes).unwrap_or(usize::MAX),
+        config.language.clone().or(metadata.language),
+        metadata.encoding,
+    )
+    .with_total_lines_exact(!truncated)
+    .with_truncation(truncated, truncated_by_lines, truncated_by_bytes)
+    .with_bom(metadata.had_bom)
+    .with_streamed(true);
+    report_file(policy, display_path, &file_info);
+    record_metrics(display_path, &file_info, start_time);
+
+    if policy.notices() && !config.preview_window {
+        if truncated_by_lines {
+            let max_lines = config.max_lines; // local to allow inline capture
+            println!("// Output truncated after {max_lines} lines");
+        }
+        if truncated_by_bytes {
+            if let Some(max_bytes) = config.max_bytes {
+                println!("// Output truncated after {max_bytes} bytes");
+            }
+        }
+    }
+    if let Some(footer) = config
+        .file_footer
+        .as_deref()
+        .filter(|f| banner_index.is_some() && !f.is_empty())
+    {
+        println!(
+            "{}",
+            batless::banner::render(footer, display_path, &file_info)
         );
     }
+    mark_stage(timer.as_mut(), "write");
+    report_time(timer, manager);
 
-    if args.count_tokens {
-        print_token_analysis(&file_info, args.ai_model.into());
+    if args.fail_on_truncation && truncated {
+        return Ok(exit_codes::TRUNCATED);
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+/// `--copy` collects per-file formatted output; reports, streams and
+/// aggregated indexes are refused up front rather than silently not copied
+fn check_copy_supported(file_paths: &[String], manager: &ConfigManager) -> BatlessResult<()> {
+    batless::clipboard::ensure_available()?;
+    let args = manager.args();
+    let mode = manager.output_mode();
+    let directory_index = matches!(mode, OutputMode::Index | OutputMode::Imports)
+        && file_paths.iter().any(|path| Path::new(path).is_dir());
+    let unsupported = args.bench
+        || args.token_stats
+        || args.tokenize.is_some()
+        || args.chunk_for_rag
+        || args.envelope.is_some()
+        || args.summary_index
+        || manager.config().streaming_json
+        || matches!(
+            mode,
+            OutputMode::Hex
+                | OutputMode::HexJson
+                | OutputMode::Ctags
+                | OutputMode::Etags
+                | OutputMode::Scip
+                | OutputMode::Metadata
+        )
+        || directory_index;
+    if unsupported {
+        return Err(BatlessError::config_error_with_help(
+            "--copy only applies to the formatted output of files".to_string(),
+            Some(
+                "Pipe stdout into pbcopy, xclip or clip.exe for reports, streams, hex dumps and indexes"
+                    .to_string(),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Where formatted content goes: stdout, the clipboard (`--copy`), or both
+struct ContentSink {
+    stdout: bool,
+    /// Output collected for `--copy`, or by a `--jobs` worker
+    buffer: Option<String>,
+}
+
+impl ContentSink {
+    fn new(args: &Args) -> Self {
+        Self {
+            stdout: !args.copy_only,
+            buffer: (args.copy || args.copy_only).then(String::new),
+        }
+    }
+
+    /// Sink that only collects, for a `--jobs` worker
+    fn capture() -> Self {
+        Self {
+            stdout: false,
+            buffer: Some(String::new()),
+        }
+    }
+
+    fn into_text(self) -> String {
+        self.buffer.unwrap_or_default()
+    }
+
+    fn line(&mut self, text: impl std::fmt::Display) {
+        if self.stdout {
+            println!("{text}");
+        }
+        if let Some(buffer) = self.buffer.as_mut() {
+            buffer.push_str(&text.to_string());
+            buffer.push('\n');
+        }
+    }
+
+    /// Write lines a `--jobs` worker collected with [`ContentSink::capture`]
+    fn write_captured(&mut self, text: &str) {
+        if self.stdout {
+            print!("{text}");
+        }
+        if let Some(buffer) = self.buffer.as_mut() {
+            buffer.push_str(text);
+        }
+    }
+
+    /// Hand everything collected for `--copy` to the clipboard
+    fn finish(self, policy: OutputPolicy) -> BatlessResult<()> {
+        if let Some(buffer) = self.buffer {
+            let text = buffer.trim_end_matches('\n');
+            batless::clipboard::copy(text)?;
+            policy.info(format_args!(
+                "batless: copied {} bytes to the clipboard",
+                text.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn handle_normal_processing(
+    file_path: &str,
+    manager: &ConfigManager,
+    file_index: Option<usize>,
+    out: &mut ContentSink,
+) -> BatlessResult<i32> {
+    let config = manager.config();
+    let args = manager.args();
+    let output_mode = manager.output_mode();
+    let policy = manager.output_policy();
+
+    if args.fail_on_binary && file_path != "-" && FileProcessor::is_likely_binary(file_path)? {
+        eprintln!("batless: {file_path} appears to be a binary file");
+        return Ok(exit_codes::BINARY);
+    }
+
+    let start_time = std::time::Instant::now();
+    policy.debug(format_args!("Starting file processing for {file_path}"));
+    // Piped input is reported under its --stdin-filename when given
+    let display_path = config.display_path(file_path);
+    let mut timer = stage_timer(display_path, manager);
+
+    let file_info = batless::process_file(file_path, config)?;
+    mark_stage(timer.as_mut(), "read");
+    for warning in &file_info.warnings {
+        policy.warn(warning, Some(display_path));
+    }
+    let syntax_failed = args.fail_on_syntax_errors && !file_info.syntax_errors.is_empty();
+    if syntax_failed {
+        for error in &file_info.syntax_errors {
+            eprintln!("batless: {display_path}: {error}");
+        }
+    }
+
+    policy.debug(format_args!(
+        "Processing completed in {:.3}ms",
+        // Milliseconds, as Duration's Debug output uses `µs`
+        start_time.elapsed().as_secs_f64() * 1000.0
+    ));
+    report_file(policy, display_path, &file_info);
+    record_metrics(display_path, &file_info, start_time);
+
+    if args.count_tokens && policy.notices() {
+        print_token_analysis(
+            &file_info,
+            &config.token_counter(manager.ai_model()),
+            policy.style,
+        );
     }
 
     let file_info = if args.fit_context {
-        let counter = TokenCounter::new(args.ai_model.into());
+        let counter = config.token_counter(manager.ai_model());
         let (truncated_content, was_truncated) =
             counter.truncate_to_fit(&file_info.lines.join("\n"), args.prompt_tokens);
         if was_truncated {
-            eprintln!("📐 Context Fitting Applied");
+            policy.info(format_args!(
+                "{}Context Fitting Applied",
+                policy.style.glyph("📐 ", "")
+            ));
             file_info
                 .with_lines(truncated_content.lines().map(String::from).collect())
                 .with_context_truncation(true)
@@ -321,55 +1461,256 @@ fn handle_normal_processing(file_path: &str, manager: &ConfigManager) -> Batless
     // Attach estimated LLM token count when a profile or explicit model is active
     let effective_model: Option<AiModel> = args.profile.map_or_else(
         || {
-            if args.ai_model == CliAiModel::Generic {
-                None
-            } else {
-                Some(args.ai_model.into())
-            }
+            let model = manager.ai_model();
+            (model != AiModel::Generic).then_some(model)
         },
         |profile| Some(profile.get_ai_model()),
     );
     let final_file_info = if let Some(model) = effective_model {
-        let counter = TokenCounter::new(model);
+        let counter = config.token_counter(model);
         let token_count = counter.count_tokens(&file_info.lines.join("\n"));
-        let model_name = format!("{model:?}");
+        let model_name = match model {
+            AiModel::Custom(custom) => custom.name.to_string(),
+            _ => format!("{model:?}"),
+        };
         file_info.with_estimated_llm_tokens(Some(token_count.tokens as u64), Some(model_name))
     } else {
         file_info
     };
 
+    mark_stage(timer.as_mut(), "tokens");
+
     if output_mode == OutputMode::Summary && final_file_info.summary_line_count() == 0 {
-        eprintln!("// No summary-worthy code structures found");
-        return Ok(());
+        policy.info("// No summary-worthy code structures found");
+        report_time(timer, manager);
+        if syntax_failed {
+            return Ok(exit_codes::SYNTAX_ERRORS);
+        }
+        return Ok(exit_codes::SUCCESS);
     }
 
     let formatted_output =
-        batless::format_output(&final_file_info, file_path, config, output_mode)?;
+        batless::format_output(&final_file_info, display_path, config, output_mode)?;
+
+    let json_valid =
+        if (args.validate_json || args.fail_on_validation_error || args.strict_validation)
+            && output_mode == OutputMode::Json
+        {
+            validate_json_output(&formatted_output, policy, args.strict_validation)?
+        } else {
+            true
+        };
+    mark_stage(timer.as_mut(), "format");
 
-    if args.validate_json && output_mode == OutputMode::Json {
-        validate_json_output(&formatted_output)?;
+    // Banners only separate text output; structured modes stay machine-readable,
+    // preview panes show nothing but code and templates shape the output themselves
+    let banner_index = file_index.filter(|_| {
+        matches!(output_mode, OutputMode::Plain | OutputMode::Summary)
+            && !config.preview_window
+            && config.template.is_none()
+    });
+    if let Some(index) = banner_index {
+        let header = config
+            .file_header
+            .as_deref()
+            .unwrap_or(batless::banner::DEFAULT_FILE_HEADER);
+        if !header.is_empty() {
+            if index > 0 {
+                out.line("");
+            }
+            out.line(batless::banner::render(
+                header,
+                display_path,
+                &final_file_info,
+            ));
+        }
     }
 
-    println!("{formatted_output}");
+    if let Some(window) = final_file_info
+        .window
+        .as_ref()
+        .filter(|_| output_mode == OutputMode::Plain && !config.preview_window)
+    {
+        out.line(window.header());
+    }
+    out.line(&formatted_output);
 
-    if output_mode != OutputMode::Json {
+    // Truncation comments would break machine-readable JSON / JSON Lines output
+    // and templated text
+    if policy.notices()
+        && !config.preview_window
+        && config.template.is_none()
+        && !matches!(
+            output_mode,
+            OutputMode::Json | OutputMode::Jsonl | OutputMode::Imports | OutputMode::LogJson
+        )
+    {
         if final_file_info.truncated_by_lines {
             let max_lines = config.max_lines; // local to allow inline capture
-            println!("// Output truncated after {max_lines} lines");
+            out.line(format_args!("// Output truncated after {max_lines} lines"));
         }
         if final_file_info.truncated_by_bytes {
             if let Some(max_bytes) = config.max_bytes {
-                println!("// Output truncated after {max_bytes} bytes");
+                out.line(format_args!("// Output truncated after {max_bytes} bytes"));
             }
         }
     }
 
+    if let Some(footer) = config
+        .file_footer
+        .as_deref()
+        .filter(|f| banner_index.is_some() && !f.is_empty())
+    {
+        out.line(batless::banner::render(
+            footer,
+            display_path,
+            &final_file_info,
+        ));
+    }
+    io::stdout().flush()?;
+    mark_stage(timer.as_mut(), "write");
+    report_time(timer, manager);
+
+    if args.fail_on_validation_error && !json_valid {
+        return Ok(exit_codes::VALIDATION_FAILED);
+    }
+    if syntax_failed {
+        return Ok(exit_codes::SYNTAX_ERRORS);
+    }
+    if args.fail_on_truncation && final_file_info.truncated {
+        return Ok(exit_codes::TRUNCATED);
+    }
+    Ok(exit_codes::SUCCESS)
+}
+
+fn handle_token_stats(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+    let file_info = batless::process_file(file_path, manager.config())?;
+    let report = batless::TokenExtractor::token_stats_report(
+        &file_info.lines.join("\n"),
+        file_path,
+        manager.args().top_tokens,
+    );
+    if manager.output_mode() == OutputMode::Json {
+        println!(
+            "{}",
+            to_json_string(&report, true, manager.config().canonical_json)?
+        );
+    } else {
+        print!("{}", report.render_text());
+    }
+    Ok(())
+}
+
+/// `--tokenize=model`: one JSON line per chunk of exact model tokens
+fn handle_tokenize(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+    batless::tokens::subword::ensure_available()?;
+    let file_info = batless::process_file(file_path, manager.config())?;
+    let chunks = batless::tokens::subword::tokenize_chunks(
+        &file_info.lines.join("\n"),
+        file_path,
+        manager.ai_model(),
+        manager.args().chunk_tokens,
+    )?;
+    let mut stdout = io::stdout().lock();
+    for chunk in &chunks {
+        writeln!(
+            stdout,
+            "{}",
+            to_json_string(chunk, false, manager.config().canonical_json)?
+        )?;
+    }
     Ok(())
 }
 
-fn print_token_analysis(file_info: &batless::FileInfo, model: AiModel) {
+/// `--chunk-for-rag`: one JSON line per retrieval chunk
+fn handle_rag_chunks(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+    let args = manager.args();
+    if args.chunk_tokens == 0 || args.chunk_overlap >= args.chunk_tokens {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "--chunk-overlap ({}) must be smaller than --chunk-tokens ({}), which must be greater than 0",
+                args.chunk_overlap, args.chunk_tokens
+            ),
+            Some("Overlap is usually 10-20% of the chunk size".to_string()),
+        ));
+    }
+    // The display line cap does not apply to chunks unless --max-lines asks for it
+    let mut config = manager.config().clone();
+    if args.max_lines.is_none() {
+        config = config.with_max_lines(batless::config_validation::MAX_LINES_LIMIT);
+    }
+    let file_info = batless::process_file(file_path, &config)?;
+    let display_path = config.display_path(file_path);
+    let policy = manager.output_policy();
+    for warning in &file_info.warnings {
+        policy.warn(warning, Some(display_path));
+    }
+    if file_info.truncated {
+        let warning = Warning::new(
+            WarningCode::ChunksTruncated,
+            format!(
+                "input was cut after line {}; chunks do not cover the rest",
+                file_info.lines.len()
+            ),
+        );
+        policy.warn(&warning, Some(display_path));
+    }
+    let chunker = batless::chunker::RagChunker::new(
+        config.token_counter(manager.ai_model()),
+        args.chunk_tokens,
+        args.chunk_overlap,
+    );
+    let chunks = chunker.chunk(
+        &file_info.lines,
+        file_info.language.as_deref(),
+        display_path,
+    );
+    let canonical = config.canonical_json;
+    let mut stdout = io::stdout().lock();
+    if args.envelope.is_some() {
+        let source = rag_source(file_path, &file_info, manager);
+        for chunk in chunks {
+            let envelope = source.chunk(chunk);
+            writeln!(stdout, "{}", to_json_string(&envelope, false, canonical)?)?;
+        }
+        return Ok(());
+    }
+    for chunk in &chunks {
+        writeln!(stdout, "{}", to_json_string(chunk, false, canonical)?)?;
+    }
+    Ok(())
+}
+
+/// `--envelope rag` without chunking: one document per file
+fn handle_file_envelope(file_path: &str, manager: &ConfigManager) -> BatlessResult<()> {
+    let file_info = batless::process_file(file_path, manager.config())?;
+    let envelope = rag_source(file_path, &file_info, manager).file(&file_info.lines);
+    println!(
+        "{}",
+        to_json_string(&envelope, false, manager.config().canonical_json)?
+    );
+    Ok(())
+}
+
+fn rag_source(
+    file_path: &str,
+    file_info: &batless::FileInfo,
+    manager: &ConfigManager,
+) -> batless::envelope::RagSource {
+    let mut source = batless::envelope::RagSource::detect(
+        file_path,
+        file_info.language.clone(),
+        manager.config().repo_url.as_deref(),
+    );
+    // Piped input is reported under its --stdin-filename when given
+    if file_path == "-" {
+        source.path = manager.config().display_path(file_path).to_string();
+    }
+    source
+}
+
+fn print_token_analysis(file_info: &batless::FileInfo, counter: &TokenCounter, style: StatusStyle) {
     let content = file_info.lines.join("\n");
-    let counter = TokenCounter::new(model);
     let token_count = counter.count_tokens(&content);
 
     println!("Token Count Analysis:");
@@ -380,27 +1721,41 @@ fn print_token_analysis(file_info: &batless::FileInfo, model: AiModel) {
     let context_window = token_count.model.context_window();
     println!("  Context window: {context_window}");
     let fits = if token_count.fits_in_context {
-        "✓"
+        style.glyph("✓", "yes")
     } else {
-        "✗"
+        style.glyph("✗", "no")
     };
     println!("  Fits in context: {fits}");
     println!();
 }
 
-fn validate_json_output(json_output: &str) -> BatlessResult<()> {
+/// Validate JSON output against the schema, warning on stderr; returns whether it passed.
+/// With `strict`, a mismatch is an error and nothing is printed.
+fn validate_json_output(
+    json_output: &str,
+    policy: OutputPolicy,
+    strict: bool,
+) -> BatlessResult<bool> {
     let validator = JsonSchemaValidator::new();
     let json_value: serde_json::Value = serde_json::from_str(json_output)?;
     if let Err(e) = validator.validate("json_output", &json_value) {
-        eprintln!("⚠️  JSON validation warning: {e}. Output may not be fully AI-compatible.");
+        if strict {
+            return Err(e);
+        }
+        let warning = Warning::new(
+            WarningCode::SchemaMismatch,
+            format!("JSON output does not match its schema: {e}"),
+        );
+        policy.warn(&warning, None);
+        return Ok(false);
     }
-    Ok(())
+    Ok(true)
 }
 
 // Helpful error messages for unsupported features
 
-fn print_pattern_not_supported() {
-    let mut stderr = StandardStream::stderr(ColorChoice::Auto);
+fn print_pattern_not_supported(style: StatusStyle) {
+    let mut stderr = stderr_stream(style);
     let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true));
     let _ = writeln!(
         &mut stderr,
@@ -409,7 +1764,11 @@ fn print_pattern_not_supported() {
     let _ = stderr.reset();
     let _ = writeln!(&mut stderr);
     let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)));
-    let _ = writeln!(&mut stderr, "💡 Tip: Use dedicated search tools:");
+    let _ = writeln!(
+        &mut stderr,
+        "{}Tip: Use dedicated search tools:",
+        style.glyph("💡 ", "")
+    );
     let _ = stderr.reset();
     let _ = writeln!(&mut stderr, "     grep -rn \"pattern\" src/");
     let _ = writeln!(
@@ -430,14 +1789,18 @@ fn print_pattern_not_supported() {
     );
 }
 
-fn print_list_not_supported() {
-    let mut stderr = StandardStream::stderr(ColorChoice::Auto);
+fn print_list_not_supported(style: StatusStyle) {
+    let mut stderr = stderr_stream(style);
     let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true));
     let _ = writeln!(&mut stderr, "Error: batless doesn't list files");
     let _ = stderr.reset();
     let _ = writeln!(&mut stderr);
     let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)));
-    let _ = writeln!(&mut stderr, "💡 Tip: Use file listing tools:");
+    let _ = writeln!(
+        &mut stderr,
+        "{}Tip: Use file listing tools:",
+        style.glyph("💡 ", "")
+    );
     let _ = stderr.reset();
     let _ = writeln!(&mut stderr, "     ls -la src/");
     let _ = writeln!(&mut stderr, "     find . -name \"*.py\"");
@@ -460,14 +1823,18 @@ fn print_list_not_supported() {
     );
 }
 
-fn print_range_not_supported() {
-    let mut stderr = StandardStream::stderr(ColorChoice::Auto);
+fn print_range_not_supported(style: StatusStyle) {
+    let mut stderr = stderr_stream(style);
     let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true));
     let _ = writeln!(&mut stderr, "Error: batless doesn't support line ranges");
     let _ = stderr.reset();
     let _ = writeln!(&mut stderr);
     let _ = stderr.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)));
-    let _ = writeln!(&mut stderr, "💡 Tip: Use these alternatives:");
+    let _ = writeln!(
+        &mut stderr,
+        "{}Tip: Use these alternatives:",
+        style.glyph("💡 ", "")
+    );
     let _ = stderr.reset();
     let _ = writeln!(
         &mut stderr,
@@ -0,0 +1,241 @@
+//! Metadata stubs for images and PDFs (`media` feature)
+//!
+//! Agents that glob whole directories hit logos, screenshots and PDFs.
+//! Instead of failing on invalid UTF-8 or dumping binary, batless reports
+//! what the file is: format, dimensions, size and whether EXIF data is
+//! present. Headers are parsed by hand, so the feature adds no dependencies.
+
+use crate::error::{BatlessError, BatlessResult};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+
+/// Bytes needed to recognise every supported signature
+const SIGNATURE_BYTES: u64 = 12;
+
+/// Bytes read when probing; JPEG frame headers can follow large EXIF blocks
+const PROBE_BYTES: u64 = 256 * 1024;
+
+/// What is known about a non-text asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaInfo {
+    /// Short format name (`png`, `jpeg`, `gif`, `webp`, `pdf`)
+    pub format: String,
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// PDF version from the `%PDF-x.y` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub has_exif: bool,
+    pub size_bytes: u64,
+}
+
+impl MediaInfo {
+    fn new(format: &str, mime_type: &str, size_bytes: u64) -> Self {
+        Self {
+            format: format.to_string(),
+            mime_type: mime_type.to_string(),
+            width: None,
+            height: None,
+            version: None,
+            has_exif: false,
+            size_bytes,
+        }
+    }
+
+    /// One-line description used as text output, e.g. `[PNG image, 640x480, 1024 bytes]`
+    pub fn describe(&self) -> String {
+        let mut parts = vec![if self.format == "pdf" {
+            match &self.version {
+                Some(version) => format!("PDF document, version {version}"),
+                None => "PDF document".to_string(),
+            }
+        } else {
+            format!("{} image", self.format.to_uppercase())
+        }];
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            parts.push(format!("{width}x{height}"));
+        }
+        parts.push(format!("{} bytes", self.size_bytes));
+        if self.has_exif {
+            parts.push("EXIF".to_string());
+        }
+        format!("[{}]", parts.join(", "))
+    }
+}
+
+/// Identify `path` as a supported image or PDF; `None` for anything else
+pub fn probe(path: &str) -> BatlessResult<Option<MediaInfo>> {
+    let read_error = |e| BatlessError::FileReadError {
+        path: path.to_string(),
+        source: e,
+    };
+    let mut file = File::open(path).map_err(read_error)?;
+    let size = file.metadata().map_err(read_error)?.len();
+    // Check the magic bytes before reading further, as most inputs are text
+    let mut header = Vec::new();
+    (&mut file)
+        .take(SIGNATURE_BYTES)
+        .read_to_end(&mut header)
+        .map_err(read_error)?;
+    if identify(&header, size).is_none() {
+        return Ok(None);
+    }
+    file.take(PROBE_BYTES - SIGNATURE_BYTES)
+        .read_to_end(&mut header)
+        .map_err(read_error)?;
+    Ok(identify(&header, size))
+}
+
+/// Identify an asset from its leading bytes
+pub fn identify(header: &[u8], size: u64) -> Option<MediaInfo> {
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let mut info = MediaInfo::new("png", "image/png", size);
+        if header.get(12..16) == Some(b"IHDR") {
+            info.width = be_u32(header, 16);
+            info.height = be_u32(header, 20);
+        }
+        info.has_exif = contains(header, b"eXIf");
+        Some(info)
+    } else if header.starts_with(b"\xff\xd8\xff") {
+        Some(jpeg(header, size))
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        let mut info = MediaInfo::new("gif", "image/gif", size);
+        info.width = le_u16(header, 6).map(u32::from);
+        info.height = le_u16(header, 8).map(u32::from);
+        Some(info)
+    } else if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+        Some(webp(header, size))
+    } else if header.starts_with(b"%PDF-") {
+        let mut info = MediaInfo::new("pdf", "application/pdf", size);
+        info.version = header
+            .get(5..)
+            .and_then(|rest| rest.split(u8::is_ascii_whitespace).next())
+            .and_then(|v| std::str::from_utf8(v).ok())
+            .filter(|v| !v.is_empty())
+            .map(String::from);
+        Some(info)
+    } else {
+        None
+    }
+}
+
+/// Walk JPEG segments for an EXIF APP1 block and the frame dimensions
+fn jpeg(header: &[u8], size: u64) -> MediaInfo {
+    let mut info = MediaInfo::new("jpeg", "image/jpeg", size);
+    let mut pos = 2;
+    while pos + 4 <= header.len() && header[pos] == 0xff {
+        let marker = header[pos + 1];
+        let Some(length) = be_u16(header, pos + 2).map(usize::from) else {
+            break;
+        };
+        match marker {
+            0xe1 if header.get(pos + 4..pos + 10) == Some(b"Exif\0\0") => info.has_exif = true,
+            // Start-of-frame markers (not DHT, JPG or DAC, which share the range)
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                info.height = be_u16(header, pos + 5).map(u32::from);
+                info.width = be_u16(header, pos + 7).map(u32::from);
+                break;
+            }
+            // Start of scan: entropy-coded data follows
+            0xda => break,
+            _ => {}
+        }
+        pos += 2 + length;
+    }
+    info
+}
+
+/// Dimensions from the first WebP chunk (VP8X, lossy VP8 or lossless VP8L)
+fn webp(header: &[u8], size: u64) -> MediaInfo {
+    let mut info = MediaInfo::new("webp", "image/webp", size);
+    match header.get(12..16) {
+        Some(b"VP8X") => {
+            info.has_exif = header.get(20).is_some_and(|flags| flags & 0x08 != 0);
+            info.width = le_u24(header, 24).map(|w| w + 1);
+            info.height = le_u24(header, 27).map(|h| h + 1);
+        }
+        Some(b"VP8 ") => {
+            info.width = le_u16(header, 26).map(|w| u32::from(w & 0x3fff));
+            info.height = le_u16(header, 28).map(|h| u32::from(h & 0x3fff));
+        }
+        Some(b"VP8L") => {
+            if let Some(bits) = header.get(21..25) {
+                let bits = u32::from_le_bytes([bits[0], bits[1], bits[2], bits[3]]);
+                info.width = Some((bits & 0x3fff) + 1);
+                info.height = Some(((bits >> 14) & 0x3fff) + 1);
+            }
+        }
+        _ => {}
+    }
+    info
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_png_and_gif() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\x02\x80\0\0\x01\xe0\x08\x06\0\0\0";
+        let info = identify(png, 1024).unwrap();
+        assert_eq!((info.width, info.height), (Some(640), Some(480)));
+        assert_eq!(info.describe(), "[PNG image, 640x480, 1024 bytes]");
+
+        let gif = b"GIF89a\x10\0\x20\0";
+        let info = identify(gif, 10).unwrap();
+        assert_eq!((info.width, info.height), (Some(16), Some(32)));
+        assert!(identify(b"plain text", 10).is_none());
+    }
+
+    #[test]
+    fn test_identify_jpeg_with_exif() {
+        let mut jpeg = b"\xff\xd8".to_vec();
+        jpeg.extend_from_slice(b"\xff\xe1\0\x08Exif\0\0");
+        jpeg.extend_from_slice(b"\xff\xc0\0\x11\x08\0\x64\0\xc8\x03");
+        let info = identify(&jpeg, 2048).unwrap();
+        assert!(info.has_exif);
+        assert_eq!((info.width, info.height), (Some(200), Some(100)));
+        assert_eq!(info.mime_type, "image/jpeg");
+    }
+
+    #[test]
+    fn test_identify_pdf_and_webp() {
+        let info = identify(b"%PDF-1.7\n%\xe2\xe3", 5000).unwrap();
+        assert_eq!(info.version.as_deref(), Some("1.7"));
+        assert_eq!(info.describe(), "[PDF document, version 1.7, 5000 bytes]");
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\x08\0\0\0".to_vec();
+        webp.extend_from_slice(&[0x3f, 0x01, 0x00, 0xc7, 0x00, 0x00]);
+        let info = identify(&webp, 300).unwrap();
+        assert!(info.has_exif);
+        assert_eq!((info.width, info.height), (Some(320), Some(200)));
+    }
+}
@@ -0,0 +1,194 @@
+//! Per-invocation metrics file (`--metrics-out`)
+//!
+//! Batch jobs in CI want to publish how much batless read and how long it
+//! took without scraping stderr. When enabled, every file rendered by the
+//! standard pipeline is recorded, and one JSON document with the totals,
+//! per-file figures and the outcome is written when the run ends, whether it
+//! succeeded or not.
+
+use crate::error::{BatlessError, BatlessResult};
+use crate::file_info::FileInfo;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Figures for one processed file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileMetrics {
+    pub file: String,
+    pub bytes: u64,
+    pub lines: u64,
+    pub truncated: bool,
+    pub timed_out: bool,
+    pub warnings: usize,
+    pub duration_ms: f64,
+}
+
+impl FileMetrics {
+    /// Figures of `file_info`, processed in `duration`
+    pub fn new(file: &str, file_info: &FileInfo, duration: Duration) -> Self {
+        Self {
+            file: file.to_string(),
+            bytes: file_info.total_bytes as u64,
+            lines: file_info.total_lines as u64,
+            truncated: file_info.truncated,
+            timed_out: file_info.timed_out,
+            warnings: file_info.warnings.len(),
+            duration_ms: millis(duration),
+        }
+    }
+}
+
+/// The document written to the `--metrics-out` file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunMetrics {
+    pub tool_version: &'static str,
+    /// RFC 3339 UTC time the run started
+    pub started_at: String,
+    pub duration_ms: f64,
+    pub exit_code: i32,
+    pub files_processed: usize,
+    pub bytes_read: u64,
+    pub lines_read: u64,
+    /// Files cut short by a line, byte or context limit
+    pub truncations: usize,
+    pub timeouts: usize,
+    pub warnings: usize,
+    /// Errors that ended the run; batless stops at the first one
+    pub errors: usize,
+    /// Error code (`E101`...) of the error that ended the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    pub files: Vec<FileMetrics>,
+}
+
+/// Collects file figures from any thread and writes them at the end
+#[derive(Debug)]
+pub struct MetricsRecorder {
+    path: PathBuf,
+    started: Instant,
+    started_at: String,
+    files: Mutex<Vec<FileMetrics>>,
+}
+
+impl MetricsRecorder {
+    /// Recorder that will write to `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            started: Instant::now(),
+            started_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            files: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record(&self, file: FileMetrics) {
+        self.files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(file);
+    }
+
+    /// Totals so far, for a run ending with `exit_code` or `error`
+    pub fn report(&self, exit_code: i32, error: Option<&BatlessError>) -> RunMetrics {
+        let files = self
+            .files
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        RunMetrics {
+            tool_version: env!("CARGO_PKG_VERSION"),
+            started_at: self.started_at.clone(),
+            duration_ms: millis(self.started.elapsed()),
+            exit_code,
+            files_processed: files.len(),
+            bytes_read: files.iter().map(|file| file.bytes).sum(),
+            lines_read: files.iter().map(|file| file.lines).sum(),
+            truncations: files.iter().filter(|file| file.truncated).count(),
+            timeouts: files.iter().filter(|file| file.timed_out).count(),
+            warnings: files.iter().map(|file| file.warnings).sum(),
+            errors: usize::from(error.is_some()),
+            error_code: error.map(|e| e.error_code().as_str().to_string()),
+            files,
+        }
+    }
+
+    /// Write the report to the metrics file
+    pub fn write(&self, exit_code: i32, error: Option<&BatlessError>) -> BatlessResult<()> {
+        let report = self.report(exit_code, error);
+        std::fs::write(&self.path, serde_json::to_string_pretty(&report)? + "\n").map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Cannot write metrics to {}: {e}", self.path.display()),
+                Some("Check that the --metrics-out directory exists and is writable".to_string()),
+            )
+        })
+    }
+}
+
+static RECORDER: OnceLock<MetricsRecorder> = OnceLock::new();
+
+/// Start recording for this process; later calls keep the first path
+pub fn enable(path: impl Into<PathBuf>) {
+    let _ = RECORDER.set(MetricsRecorder::new(path));
+}
+
+/// The process-wide recorder, when `--metrics-out` enabled one
+pub fn recorder() -> Option<&'static MetricsRecorder> {
+    RECORDER.get()
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, bytes: u64, truncated: bool) -> FileMetrics {
+        FileMetrics {
+            file: name.to_string(),
+            bytes,
+            lines: 2,
+            truncated,
+            timed_out: false,
+            warnings: 1,
+            duration_ms: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_report_totals_files() {
+        let recorder = MetricsRecorder::new("unused.json");
+        recorder.record(file("a.rs", 10, false));
+        recorder.record(file("b.rs", 5, true));
+        let report = recorder.report(10, None);
+        assert_eq!(report.files_processed, 2);
+        assert_eq!(report.bytes_read, 15);
+        assert_eq!(report.lines_read, 4);
+        assert_eq!(report.truncations, 1);
+        assert_eq!(report.warnings, 2);
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.exit_code, 10);
+
+        let error = BatlessError::processing_error("boom".to_string());
+        let report = recorder.report(1, Some(&error));
+        assert_eq!(report.errors, 1);
+        assert!(report.error_code.is_some());
+    }
+
+    #[test]
+    fn test_write_creates_json_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        MetricsRecorder::new(&path).write(0, None).unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["files_processed"], 0);
+        assert!(value.get("error_code").is_none());
+
+        let missing = dir.path().join("missing/metrics.json");
+        assert!(MetricsRecorder::new(missing).write(0, None).is_err());
+    }
+}
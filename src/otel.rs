@@ -0,0 +1,152 @@
+//! OpenTelemetry trace export for `--otel` (`otel` feature)
+//!
+//! Each processed file becomes a `batless.file` span with one child span per
+//! pipeline stage (`read`, `tokens`, `format`, `write`), timed by the same
+//! [`StageTimer`] that backs `--time`. Spans are batched on a background
+//! thread and sent over OTLP/HTTP to `OTEL_EXPORTER_OTLP_ENDPOINT` (default
+//! `http://localhost:4318`); the other standard `OTEL_*` variables, such as
+//! `OTEL_SERVICE_NAME` and `OTEL_EXPORTER_OTLP_HEADERS`, apply as well.
+
+use crate::error::{BatlessError, BatlessResult};
+use crate::timing::StageTimer;
+
+/// Fail before any work is done when this build has no exporter
+pub fn ensure_available() -> BatlessResult<()> {
+    if cfg!(feature = "otel") {
+        return Ok(());
+    }
+    Err(BatlessError::config_error_with_help(
+        "--otel needs the OpenTelemetry exporter, which this build does not include".to_string(),
+        Some("Rebuild with --features otel".to_string()),
+    ))
+}
+
+#[cfg(feature = "otel")]
+struct Exporter {
+    provider: opentelemetry_sdk::trace::SdkTracerProvider,
+    tracer: opentelemetry_sdk::trace::SdkTracer,
+}
+
+#[cfg(feature = "otel")]
+static EXPORTER: std::sync::OnceLock<Exporter> = std::sync::OnceLock::new();
+
+/// Start the exporter for this process
+#[cfg(feature = "otel")]
+pub fn init() -> BatlessResult<()> {
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry::KeyValue;
+
+    if EXPORTER.get().is_some() {
+        return Ok(());
+    }
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Cannot create the OTLP exporter: {e}"),
+                Some(
+                    "Check OTEL_EXPORTER_OTLP_ENDPOINT and the other OTEL_* variables".to_string(),
+                ),
+            )
+        })?;
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            opentelemetry_sdk::Resource::builder()
+                .with_service_name("batless")
+                .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer("batless");
+    let _ = EXPORTER.set(Exporter { provider, tracer });
+    Ok(())
+}
+
+#[cfg(not(feature = "otel"))]
+pub fn init() -> BatlessResult<()> {
+    ensure_available()
+}
+
+/// Whether [`init`] started an exporter
+#[cfg(feature = "otel")]
+pub fn enabled() -> bool {
+    EXPORTER.get().is_some()
+}
+
+#[cfg(not(feature = "otel"))]
+pub const fn enabled() -> bool {
+    false
+}
+
+/// Queue a `batless.file` span for `timer` with a child span per stage
+#[cfg(feature = "otel")]
+pub fn export(timer: &StageTimer) {
+    use opentelemetry::trace::{Span, TraceContextExt, Tracer};
+    use opentelemetry::{Context, KeyValue};
+
+    let Some(Exporter { tracer, .. }) = EXPORTER.get() else {
+        return;
+    };
+    let file_span = tracer
+        .span_builder("batless.file")
+        .with_start_time(timer.started_at)
+        .with_attributes([KeyValue::new("file.path", timer.file.clone())])
+        .start(tracer);
+    let cx = Context::current_with_span(file_span);
+    let mut at = timer.started_at;
+    for (stage, micros) in &timer.stages {
+        let end = at + std::time::Duration::from_micros(u64::try_from(*micros).unwrap_or(u64::MAX));
+        tracer
+            .span_builder(format!("batless.{stage}"))
+            .with_start_time(at)
+            .start_with_context(tracer, &cx)
+            .end_with_timestamp(end);
+        at = end;
+    }
+    cx.span().end_with_timestamp(at);
+}
+
+#[cfg(not(feature = "otel"))]
+pub fn export(_timer: &StageTimer) {}
+
+/// Send every queued span; call once before the process exits
+#[cfg(feature = "otel")]
+pub fn shutdown() -> BatlessResult<()> {
+    let Some(Exporter { provider, .. }) = EXPORTER.get() else {
+        return Ok(());
+    };
+    provider.shutdown().map_err(|e| {
+        BatlessError::processing_error(format!("Cannot export OpenTelemetry spans: {e}"))
+    })
+}
+
+#[cfg(not(feature = "otel"))]
+pub fn shutdown() -> BatlessResult<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "otel"))]
+    #[test]
+    fn test_requires_feature() {
+        let error = init().unwrap_err();
+        assert!(error.to_string().contains("--otel"), "{error}");
+        assert!(!enabled());
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_export_without_collector_does_not_block() {
+        init().unwrap();
+        assert!(enabled());
+        let mut timer = StageTimer::start("a.rs");
+        timer.mark("read");
+        timer.mark("format");
+        export(&timer);
+    }
+}
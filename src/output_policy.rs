@@ -0,0 +1,236 @@
+//! How much batless says besides the formatted content (`-q`, `-v`, `-vv`)
+//!
+//! Every informational message goes through [`OutputPolicy`], so quiet mode
+//! can drop them all in one place: truncation notices, context fitting
+//! banners and token analysis leave only the formatted content on stdout.
+//! Warnings and errors are always shown, as text or, with
+//! `--error-format json`, as one JSON object per line.
+
+use crate::env_policy::StatusStyle;
+use crate::error::{BatlessError, Warning};
+use serde_json::json;
+use std::fmt::Display;
+
+/// How warnings and errors are written to stderr (`--error-format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// `batless: warning: [W202] ...` and `Error [E302] ...`
+    #[default]
+    Human,
+    /// `{"level": "warning", "code": "W202", "message": ...}` lines
+    Json,
+}
+
+impl ErrorFormat {
+    /// Format requested on the raw command line, for errors raised before
+    /// the arguments are parsed
+    pub fn from_raw_args(args: &[String]) -> Self {
+        let json = args.iter().any(|arg| arg == "--error-format=json")
+            || args
+                .windows(2)
+                .any(|pair| pair[0] == "--error-format" && pair[1] == "json");
+        if json {
+            Self::Json
+        } else {
+            Self::Human
+        }
+    }
+
+    /// `warning` as one stderr line, about `file` when given
+    pub fn warning_line(self, warning: &Warning, file: Option<&str>) -> String {
+        match self {
+            Self::Human => match file {
+                Some(file) => format!(
+                    "batless: warning: [{}] {file}: {}",
+                    warning.code.as_str(),
+                    warning.message
+                ),
+                None => format!("batless: {warning}"),
+            },
+            Self::Json => {
+                let mut line = json!({
+                    "level": "warning",
+                    "code": warning.code,
+                    "message": warning.message,
+                });
+                if let Some(file) = file {
+                    line["file"] = json!(file);
+                }
+                line.to_string()
+            }
+        }
+    }
+
+    /// `error` as one JSON line: its code, first line and any further detail
+    pub fn json_error_line(error: &BatlessError) -> String {
+        let code = error.error_code().as_str();
+        let text = error.to_string();
+        let (first, details) = text.split_once('\n').unwrap_or((&text, ""));
+        let message = first
+            .strip_prefix(&format!("[{code}] "))
+            .unwrap_or(first)
+            .trim();
+        let mut line = json!({ "level": "error", "code": code, "message": message });
+        if !details.trim().is_empty() {
+            line["details"] = json!(details.trim());
+        }
+        line.to_string()
+    }
+}
+
+/// Verbosity level selected on the command line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// `-q`: formatted content, warnings and errors only
+    Quiet,
+    /// Informational notices as well
+    #[default]
+    Normal,
+    /// `-v`: a per-file processing report on stderr
+    Verbose,
+    /// `-vv` or `--debug`: timings and other diagnostics
+    Debug,
+}
+
+impl Verbosity {
+    /// Level for `--quiet`, the number of `-v` flags and `--debug`
+    pub const fn from_flags(quiet: bool, verbose: u8, debug: bool) -> Self {
+        if quiet {
+            Self::Quiet
+        } else if debug || verbose >= 2 {
+            Self::Debug
+        } else if verbose == 1 {
+            Self::Verbose
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Decides which non-content messages are printed and how they look
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputPolicy {
+    pub verbosity: Verbosity,
+    pub style: StatusStyle,
+    pub error_format: ErrorFormat,
+}
+
+impl OutputPolicy {
+    pub const fn new(verbosity: Verbosity, style: StatusStyle) -> Self {
+        Self {
+            verbosity,
+            style,
+            error_format: ErrorFormat::Human,
+        }
+    }
+
+    pub const fn with_error_format(mut self, error_format: ErrorFormat) -> Self {
+        self.error_format = error_format;
+        self
+    }
+
+    /// Warning on stderr, shown even in quiet mode
+    pub fn warn(self, warning: &Warning, file: Option<&str>) {
+        eprintln!("{}", self.error_format.warning_line(warning, file));
+    }
+
+    /// Whether informational notices (including those on stdout) are shown
+    pub fn notices(self) -> bool {
+        self.verbosity > Verbosity::Quiet
+    }
+
+    /// Whether `-v` diagnostics are shown
+    pub fn verbose_enabled(self) -> bool {
+        self.verbosity >= Verbosity::Verbose
+    }
+
+    /// Whether `-vv` / `--debug` diagnostics are shown
+    pub fn debug_enabled(self) -> bool {
+        self.verbosity >= Verbosity::Debug
+    }
+
+    /// Informational notice on stderr, dropped in quiet mode
+    pub fn info(self, message: impl Display) {
+        if self.notices() {
+            eprintln!("{message}");
+        }
+    }
+
+    /// `-v` diagnostic on stderr
+    pub fn verbose(self, message: impl Display) {
+        if self.verbose_enabled() {
+            eprintln!("batless: {message}");
+        }
+    }
+
+    /// `-vv` / `--debug` diagnostic on stderr
+    pub fn debug(self, message: impl Display) {
+        if self.debug_enabled() {
+            eprintln!("{}DEBUG: {message}", self.style.glyph("🔍 ", ""));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0, false), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1, false), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 2, false), Verbosity::Debug);
+        assert_eq!(Verbosity::from_flags(false, 0, true), Verbosity::Debug);
+        assert_eq!(Verbosity::from_flags(true, 0, true), Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_levels_are_cumulative() {
+        let style = StatusStyle {
+            color: false,
+            emoji: false,
+        };
+        let quiet = OutputPolicy::new(Verbosity::Quiet, style);
+        assert!(!quiet.notices() && !quiet.verbose_enabled());
+        let verbose = OutputPolicy::new(Verbosity::Verbose, style);
+        assert!(verbose.notices() && verbose.verbose_enabled() && !verbose.debug_enabled());
+        assert!(OutputPolicy::new(Verbosity::Debug, style).debug_enabled());
+    }
+
+    #[test]
+    fn test_warning_and_error_lines() {
+        use crate::error::WarningCode;
+        let warning = Warning::new(WarningCode::TimedOut, "output is partial");
+        assert_eq!(
+            ErrorFormat::Human.warning_line(&warning, Some("a.rs")),
+            "batless: warning: [W202] a.rs: output is partial"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&ErrorFormat::Json.warning_line(&warning, None)).unwrap();
+        assert_eq!(json["code"], "W202");
+        assert_eq!(json["level"], "warning");
+        assert!(json.get("file").is_none());
+
+        let error = BatlessError::config_error_with_help(
+            "bad value".to_string(),
+            Some("Use a number".to_string()),
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&ErrorFormat::json_error_line(&error)).unwrap();
+        assert_eq!(json["code"], "E302");
+        assert_eq!(json["message"], "Configuration error: bad value");
+        assert_eq!(json["details"], "Help: Use a number");
+
+        let raw = |args: &[&str]| {
+            ErrorFormat::from_raw_args(&args.iter().map(ToString::to_string).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            raw(&["batless", "--error-format", "json"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            raw(&["batless", "--error-format=human"]),
+            ErrorFormat::Human
+        );
+    }
+}
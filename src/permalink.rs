@@ -0,0 +1,178 @@
+//! Forge permalink generation for batless
+//!
+//! Builds line-anchored links (e.g. `https://github.com/org/repo/blob/<sha>/path#L10-L20`)
+//! for summary and index entries so output embedded in review comments points
+//! straight at the source.
+
+use crate::config::{BatlessConfig, LinkFormat};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Builds permalinks for a single file in a repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permalinker {
+    format: LinkFormat,
+    repo_url: String,
+    git_ref: String,
+    path: String,
+}
+
+impl Permalinker {
+    /// Create a permalinker from explicit parts.
+    ///
+    /// `path` is the repository-relative path of the file, using `/` separators.
+    pub fn new(format: LinkFormat, repo_url: &str, git_ref: &str, path: &str) -> Self {
+        Self {
+            format,
+            repo_url: repo_url.trim_end_matches('/').to_string(),
+            git_ref: git_ref.to_string(),
+            path: path
+                .trim_start_matches("./")
+                .trim_start_matches('/')
+                .to_string(),
+        }
+    }
+
+    /// Create a permalinker for `file_path` when `--link-format` and `--repo-url`
+    /// are configured.
+    ///
+    /// The commit SHA and repository-relative path are resolved with `git`.
+    /// Outside a git checkout the ref falls back to `HEAD` and the path is
+    /// used as given.
+    pub fn from_config(config: &BatlessConfig, file_path: &str) -> Option<Self> {
+        let format = config.link_format?;
+        let repo_url = config.repo_url.as_deref()?;
+        if file_path == "-" {
+            return None;
+        }
+
+        let dir = Self::containing_dir(file_path);
+        let git_ref =
+            Self::git_output(dir, &["rev-parse", "HEAD"]).unwrap_or_else(|| "HEAD".into());
+        let relative =
+            Self::repo_relative_path(file_path).unwrap_or_else(|| file_path.replace('\\', "/"));
+
+        Some(Self::new(format, repo_url, &git_ref, &relative))
+    }
+
+    /// Directory git commands for `file_path` run in
+    pub(crate) fn containing_dir(file_path: &str) -> &Path {
+        Path::new(file_path)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+    }
+
+    /// `file_path` relative to the root of its git checkout, with `/`
+    /// separators; `None` outside a checkout
+    pub(crate) fn repo_relative_path(file_path: &str) -> Option<String> {
+        let root = Self::git_output(
+            Self::containing_dir(file_path),
+            &["rev-parse", "--show-toplevel"],
+        )?;
+        let absolute = std::fs::canonicalize(file_path).ok()?;
+        let root = std::fs::canonicalize(PathBuf::from(root)).ok()?;
+        absolute
+            .strip_prefix(root)
+            .ok()
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Build a link to a 1-based line, or an inclusive line range when `end`
+    /// is past `start`.
+    pub fn link(&self, start: usize, end: Option<usize>) -> String {
+        let base = match self.format {
+            LinkFormat::Github => format!("{}/blob/{}/{}", self.repo_url, self.git_ref, self.path),
+            LinkFormat::Gitlab => {
+                format!("{}/-/blob/{}/{}", self.repo_url, self.git_ref, self.path)
+            }
+            LinkFormat::Sourcehut => {
+                format!("{}/tree/{}/item/{}", self.repo_url, self.git_ref, self.path)
+            }
+        };
+        match end.filter(|&end| end > start) {
+            Some(end) => match self.format {
+                LinkFormat::Github => format!("{base}#L{start}-L{end}"),
+                LinkFormat::Gitlab | LinkFormat::Sourcehut => format!("{base}#L{start}-{end}"),
+            },
+            None => format!("{base}#L{start}"),
+        }
+    }
+
+    /// Run a git command in `dir` and return its trimmed stdout on success.
+    pub(crate) fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = String::from_utf8(output.stdout).ok()?;
+        let trimmed = text.trim();
+        (!trimmed.is_empty()).then(|| trimmed.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_links() {
+        let linker = Permalinker::new(
+            LinkFormat::Github,
+            "https://github.com/org/repo/",
+            "abc123",
+            "./src/lib.rs",
+        );
+        assert_eq!(
+            linker.link(10, Some(20)),
+            "https://github.com/org/repo/blob/abc123/src/lib.rs#L10-L20"
+        );
+        assert_eq!(
+            linker.link(5, Some(5)),
+            "https://github.com/org/repo/blob/abc123/src/lib.rs#L5"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_and_sourcehut_links() {
+        let gitlab = Permalinker::new(
+            LinkFormat::Gitlab,
+            "https://gitlab.com/org/repo",
+            "abc123",
+            "src/lib.rs",
+        );
+        assert_eq!(
+            gitlab.link(3, Some(7)),
+            "https://gitlab.com/org/repo/-/blob/abc123/src/lib.rs#L3-7"
+        );
+
+        let srht = Permalinker::new(
+            LinkFormat::Sourcehut,
+            "https://git.sr.ht/~user/repo",
+            "abc123",
+            "src/lib.rs",
+        );
+        assert_eq!(
+            srht.link(3, None),
+            "https://git.sr.ht/~user/repo/tree/abc123/item/src/lib.rs#L3"
+        );
+    }
+
+    #[test]
+    fn test_from_config_requires_format_and_url() {
+        let config = BatlessConfig::default();
+        assert!(Permalinker::from_config(&config, "Cargo.toml").is_none());
+
+        let config = BatlessConfig::default()
+            .with_link_format(Some(LinkFormat::Github))
+            .with_repo_url(Some("https://github.com/org/repo".to_string()));
+        assert!(Permalinker::from_config(&config, "-").is_none());
+        let linker = Permalinker::from_config(&config, "Cargo.toml").unwrap();
+        assert!(linker.link(1, None).ends_with("/Cargo.toml#L1"));
+    }
+}
@@ -5,13 +5,21 @@
 
 use crate::config::BatlessConfig;
 use crate::error::{BatlessError, BatlessResult};
+use crate::formatter::OutputMode;
 use crate::summary::SummaryLevel;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Highest major profile version this build understands
+pub const PROFILE_FORMAT_MAJOR: u64 = 1;
+
+/// Seconds allowed for downloading a profile with `--import-profile URL`
+const FETCH_TIMEOUT_SECS: &str = "30";
 
 /// Custom AI profile for personalized batless configurations
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CustomProfile {
     /// Profile name
     pub name: String,
@@ -59,6 +67,63 @@ fn default_profile_version() -> String {
     "1.0".to_string()
 }
 
+/// Result of installing a profile with [`CustomProfile::install`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// No profile of that name existed
+    Installed(PathBuf),
+    /// A different profile of that name was overwritten
+    Replaced(PathBuf),
+    /// An identical profile was already installed
+    Unchanged(PathBuf),
+}
+
+impl ImportOutcome {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Installed(path) | Self::Replaced(path) | Self::Unchanged(path) => path,
+        }
+    }
+}
+
+/// Keys accepted by [`CustomProfile::set_field`]
+pub const SETTABLE_KEYS: &[&str] = &[
+    "name",
+    "description",
+    "max_lines",
+    "max_bytes",
+    "language",
+    "strip_ansi",
+    "use_color",
+    "include_tokens",
+    "summary_level",
+    "output_mode",
+    "ai_model",
+    "streaming_json",
+    "streaming_chunk_size",
+    "enable_resume",
+    "debug",
+    "tags",
+];
+
+fn parse_setting<T: std::str::FromStr>(key: &str, value: &str) -> BatlessResult<T> {
+    value.parse().map_err(|_| {
+        BatlessError::config_error_with_help(
+            format!("Invalid value '{value}' for profile key '{key}'"),
+            Some("Numbers must be non-negative integers and flags true or false".to_string()),
+        )
+    })
+}
+
+/// Parse an optional setting; an empty value clears it
+fn parse_optional<T: std::str::FromStr>(key: &str, value: &str) -> BatlessResult<Option<T>> {
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        parse_setting(key, value).map(Some)
+    }
+}
+
 impl CustomProfile {
     /// Create a new custom profile with basic information
     pub fn new(name: String, description: Option<String>) -> Self {
@@ -121,6 +186,75 @@ impl CustomProfile {
         self.ai_model.as_deref()
     }
 
+    /// Set a single field from a `key=value` style pair, as used by
+    /// `--configure --non-interactive --set`.
+    ///
+    /// An empty value clears optional fields; `tags` takes a comma-separated list.
+    pub fn set_field(&mut self, key: &str, value: &str) -> BatlessResult<()> {
+        let value = value.trim();
+        let text = || (!value.is_empty()).then(|| value.to_string());
+        match key.trim() {
+            "name" => self.name = value.to_string(),
+            "description" => self.description = text(),
+            "max_lines" => self.max_lines = parse_optional(key, value)?,
+            "max_bytes" => self.max_bytes = parse_optional(key, value)?,
+            "language" => self.language = text(),
+            "strip_ansi" => self.strip_ansi = parse_optional(key, value)?,
+            "use_color" => self.use_color = parse_optional(key, value)?,
+            "include_tokens" => self.include_tokens = parse_optional(key, value)?,
+            "summary_level" => {
+                self.summary_level = if value.is_empty() {
+                    None
+                } else {
+                    Some(SummaryLevel::parse(value).map_err(|e| {
+                        BatlessError::config_error_with_help(
+                            e,
+                            Some("Valid levels are: none, minimal, standard, detailed".to_string()),
+                        )
+                    })?)
+                };
+            }
+            "output_mode" => {
+                if !value.is_empty() {
+                    OutputMode::parse_mode(value).map_err(|e| {
+                        BatlessError::config_error_with_help(
+                            e,
+                            Some(format!(
+                                "Valid modes are: {}",
+                                OutputMode::all()
+                                    .iter()
+                                    .map(OutputMode::as_str)
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )),
+                        )
+                    })?;
+                }
+                self.output_mode = text();
+            }
+            "ai_model" => self.ai_model = text(),
+            "streaming_json" => self.streaming_json = parse_optional(key, value)?,
+            "streaming_chunk_size" => self.streaming_chunk_size = parse_optional(key, value)?,
+            "enable_resume" => self.enable_resume = parse_optional(key, value)?,
+            "debug" => self.debug = parse_optional(key, value)?,
+            "tags" => {
+                self.tags = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect();
+            }
+            other => {
+                return Err(BatlessError::config_error_with_help(
+                    format!("Unknown profile key '{other}'"),
+                    Some(format!("Valid keys are: {}", SETTABLE_KEYS.join(", "))),
+                ))
+            }
+        }
+        Ok(())
+    }
+
     /// Validate the custom profile
     pub fn validate(&self) -> BatlessResult<()> {
         // Validate profile name
@@ -144,6 +278,8 @@ impl CustomProfile {
             ));
         }
 
+        self.check_version()?;
+
         // Validate individual settings by creating a temporary config
         let temp_config = self.apply_to_config(BatlessConfig::default());
         temp_config.validate()?;
@@ -151,6 +287,29 @@ impl CustomProfile {
         Ok(())
     }
 
+    /// Reject versions that are malformed or from a newer profile format
+    fn check_version(&self) -> BatlessResult<()> {
+        let major = self
+            .version
+            .split('.')
+            .next()
+            .and_then(|major| major.trim().parse::<u64>().ok());
+        match major {
+            Some(major) if major <= PROFILE_FORMAT_MAJOR => Ok(()),
+            Some(_) => Err(BatlessError::config_error_with_help(
+                format!(
+                    "Profile '{}' has version {}, but this batless reads versions up to {PROFILE_FORMAT_MAJOR}.x",
+                    self.name, self.version
+                ),
+                Some("Upgrade batless, or ask the profile author for a compatible version".to_string()),
+            )),
+            None => Err(BatlessError::config_error_with_help(
+                format!("Invalid profile version '{}'", self.version),
+                Some(format!("Use a version such as \"{PROFILE_FORMAT_MAJOR}.0\"")),
+            )),
+        }
+    }
+
     /// Load custom profile from file
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> BatlessResult<Self> {
         let path = path.as_ref();
@@ -160,11 +319,16 @@ impl CustomProfile {
                 Some("Check that the file exists and you have read permissions".to_string()),
             )
         })?;
+        let is_toml = path.extension().and_then(|s| s.to_str()) == Some("toml");
+        Self::parse(&content, is_toml, &path.display().to_string())
+    }
 
-        let profile: Self = if path.extension().and_then(|s| s.to_str()) == Some("toml") {
-            toml::from_str(&content).map_err(|e| {
+    /// Parse and validate profile text; `source` names it in errors
+    fn parse(content: &str, is_toml: bool, source: &str) -> BatlessResult<Self> {
+        let profile: Self = if is_toml {
+            toml::from_str(content).map_err(|e| {
                 BatlessError::config_error_with_help(
-                    format!("Failed to parse TOML profile '{}': {}", path.display(), e),
+                    format!("Failed to parse TOML profile '{source}': {e}"),
                     Some(
                         "Check the TOML syntax and ensure all fields are properly formatted"
                             .to_string(),
@@ -172,9 +336,9 @@ impl CustomProfile {
                 )
             })?
         } else {
-            serde_json::from_str(&content).map_err(|e| {
+            serde_json::from_str(content).map_err(|e| {
                 BatlessError::config_error_with_help(
-                    format!("Failed to parse JSON profile '{}': {}", path.display(), e),
+                    format!("Failed to parse JSON profile '{source}': {e}"),
                     Some(
                         "Check the JSON syntax and ensure all fields are properly formatted"
                             .to_string(),
@@ -230,6 +394,35 @@ impl CustomProfile {
         Ok(())
     }
 
+    /// Profile directory in the user config directory, where `--configure`
+    /// and `--import-profile` write
+    pub fn user_dir() -> Option<PathBuf> {
+        crate::sys::config_dir().map(|d| d.join("batless/profiles"))
+    }
+
+    /// Default save location for a named profile in the user config directory
+    pub fn default_path(name: &str) -> Option<PathBuf> {
+        Self::user_dir().map(|dir| dir.join(format!("{name}.toml")))
+    }
+
+    /// Resolve a `--custom-profile` value: an existing path is used as is,
+    /// otherwise `NAME` is looked up as `NAME.json`/`NAME.toml` in the project
+    /// `.batless/` directory and its `profiles/` subdirectory, then in the
+    /// user profile directory.
+    pub fn resolve_path(name_or_path: &str, project_dir: Option<&Path>) -> PathBuf {
+        let path = PathBuf::from(name_or_path);
+        if path.exists() {
+            return path;
+        }
+        project_dir
+            .into_iter()
+            .flat_map(|dir| [dir.to_path_buf(), dir.join("profiles")])
+            .chain(Self::user_dir())
+            .flat_map(|dir| ["json", "toml"].map(|ext| dir.join(format!("{name_or_path}.{ext}"))))
+            .find(|candidate| candidate.is_file())
+            .unwrap_or(path)
+    }
+
     /// Discover custom profiles in standard locations
     pub fn discover_profiles() -> Vec<PathBuf> {
         let mut profiles = Vec::new();
@@ -239,11 +432,11 @@ impl CustomProfile {
             // Current directory
             PathBuf::from(".batless/profiles"),
             // User config directory
-            dirs::config_dir()
+            crate::sys::config_dir()
                 .map(|d| d.join("batless/profiles"))
                 .unwrap_or_default(),
             // User home directory
-            dirs::home_dir()
+            crate::sys::home_dir()
                 .map(|d| d.join(".batless/profiles"))
                 .unwrap_or_default(),
         ];
@@ -267,6 +460,105 @@ impl CustomProfile {
 
         profiles
     }
+
+    /// Read a profile for `--import-profile` from a file or an http(s) URL;
+    /// URLs ending in `.toml` are parsed as TOML, others as JSON
+    pub fn read_source(source: &str) -> BatlessResult<Self> {
+        if source.starts_with("https://") || source.starts_with("http://") {
+            let content = fetch_url(source)?;
+            let is_toml = source.split(['?', '#']).next().is_some_and(|path| {
+                Path::new(path)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+            });
+            Self::parse(&content, is_toml, source)
+        } else {
+            Self::load_from_file(source)
+        }
+    }
+
+    /// Write this profile to `dir` as `<name>.toml`. An identical profile of
+    /// the same name is left alone; a different one is only overwritten
+    /// when `replace` is set.
+    pub fn install(&self, dir: &Path, replace: bool) -> BatlessResult<ImportOutcome> {
+        self.validate()?;
+        if self.name.starts_with('.')
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(BatlessError::config_error_with_help(
+                format!("Profile name '{}' cannot be used as a file name", self.name),
+                Some(
+                    "Rename it with --import-as NAME, using letters, digits, '-' and '_'"
+                        .to_string(),
+                ),
+            ));
+        }
+
+        let path = dir.join(format!("{}.toml", self.name));
+        let existing = ["toml", "json"]
+            .map(|ext| dir.join(format!("{}.{ext}", self.name)))
+            .into_iter()
+            .find(|candidate| candidate.is_file());
+        let Some(existing) = existing else {
+            self.save_to_file(&path)?;
+            return Ok(ImportOutcome::Installed(path));
+        };
+
+        if Self::load_from_file(&existing).is_ok_and(|installed| &installed == self) {
+            return Ok(ImportOutcome::Unchanged(existing));
+        }
+        if !replace {
+            return Err(BatlessError::config_error_with_help(
+                format!(
+                    "A different profile named '{}' is already installed at {}",
+                    self.name,
+                    existing.display()
+                ),
+                Some(
+                    "Pass --replace-profile to overwrite it, or --import-as NAME to keep both"
+                        .to_string(),
+                ),
+            ));
+        }
+        if existing != path {
+            fs::remove_file(&existing)?;
+        }
+        self.save_to_file(&path)?;
+        Ok(ImportOutcome::Replaced(path))
+    }
+}
+
+/// Download `url` with curl, failing on HTTP errors
+fn fetch_url(url: &str) -> BatlessResult<String> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", FETCH_TIMEOUT_SECS])
+        .arg(url)
+        .output()
+        .map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Cannot run curl to fetch '{url}': {e}"),
+                Some("Install curl, or download the profile and import the file".to_string()),
+            )
+        })?;
+    if !output.status.success() {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "Failed to fetch profile '{url}': {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Some("Check the URL and your network connection".to_string()),
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|_| {
+        BatlessError::config_error_with_help(
+            format!("Profile at '{url}' is not UTF-8 text"),
+            Some("Profiles are JSON or TOML files".to_string()),
+        )
+    })
 }
 
 #[cfg(test)]
@@ -274,6 +566,74 @@ mod tests {
     use super::*;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_resolve_path_in_project_dir() {
+        let project = tempfile::tempdir().unwrap();
+        fs::create_dir(project.path().join("profiles")).unwrap();
+        fs::write(project.path().join("review.json"), "{}").unwrap();
+        fs::write(project.path().join("profiles/ci.toml"), "").unwrap();
+
+        let dir = Some(project.path());
+        assert_eq!(
+            CustomProfile::resolve_path("review", dir),
+            project.path().join("review.json")
+        );
+        assert_eq!(
+            CustomProfile::resolve_path("ci", dir),
+            project.path().join("profiles/ci.toml")
+        );
+        assert_eq!(
+            CustomProfile::resolve_path("missing", dir),
+            PathBuf::from("missing")
+        );
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        let mut profile = CustomProfile::new("shared".to_string(), None);
+        profile.version = "1.4".to_string();
+        assert!(profile.validate().is_ok());
+
+        profile.version = "2.0".to_string();
+        let newer = profile.validate().unwrap_err().to_string();
+        assert!(newer.contains("versions up to 1.x"), "{newer}");
+
+        profile.version = "latest".to_string();
+        assert!(profile.validate().is_err());
+    }
+
+    #[test]
+    fn test_install_handles_conflicts() -> BatlessResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let mut profile = CustomProfile::new("review".to_string(), None);
+        profile.max_lines = Some(300);
+        let path = dir.path().join("review.toml");
+
+        assert_eq!(
+            profile.install(dir.path(), false)?,
+            ImportOutcome::Installed(path.clone())
+        );
+        assert_eq!(
+            profile.install(dir.path(), false)?,
+            ImportOutcome::Unchanged(path.clone())
+        );
+
+        profile.max_lines = Some(50);
+        let conflict = profile.install(dir.path(), false).unwrap_err().to_string();
+        assert!(conflict.contains("already installed"), "{conflict}");
+        assert_eq!(CustomProfile::load_from_file(&path)?.max_lines, Some(300));
+
+        assert_eq!(
+            profile.install(dir.path(), true)?,
+            ImportOutcome::Replaced(path.clone())
+        );
+        assert_eq!(CustomProfile::load_from_file(&path)?.max_lines, Some(50));
+
+        profile.name = "../outside".to_string();
+        assert!(profile.install(dir.path(), true).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_custom_profile_creation() {
         let profile = CustomProfile::new(
@@ -297,6 +657,31 @@ mod tests {
         assert!(profile.validate().is_err());
     }
 
+    #[test]
+    fn test_set_field() -> BatlessResult<()> {
+        let mut profile = CustomProfile::new(String::new(), None);
+        profile.set_field("name", "ci")?;
+        profile.set_field("max_lines", "500")?;
+        profile.set_field("summary_level", "detailed")?;
+        profile.set_field("output_mode", "json")?;
+        profile.set_field("include_tokens", "true")?;
+        profile.set_field("tags", "ci, fast")?;
+        assert_eq!(profile.name, "ci");
+        assert_eq!(profile.max_lines, Some(500));
+        assert_eq!(profile.summary_level, Some(SummaryLevel::Detailed));
+        assert_eq!(profile.output_mode.as_deref(), Some("json"));
+        assert_eq!(profile.include_tokens, Some(true));
+        assert_eq!(profile.tags, ["ci", "fast"]);
+
+        profile.set_field("max_lines", "")?;
+        assert_eq!(profile.max_lines, None);
+
+        assert!(profile.set_field("max_lines", "many").is_err());
+        assert!(profile.set_field("output_mode", "highlight").is_err());
+        assert!(profile.set_field("colour", "true").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_custom_profile_serialization() -> BatlessResult<()> {
         let profile = CustomProfile::new(
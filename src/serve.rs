@@ -0,0 +1,325 @@
+//! Local HTTP API (`--serve ADDR`, `serve` feature)
+//!
+//! Editor plugins and agents that call batless many times can keep one
+//! process running instead of spawning the CLI per file. The server speaks
+//! plain HTTP/1.1 on std's `TcpListener`, handles one request per
+//! connection and returns the same JSON the CLI prints with `--mode=json`.
+//!
+//! Endpoints:
+//! - `POST /process` with `{"content": "...", "filename": "main.rs", "config": {...}}`
+//! - `GET /schema` (optionally `?name=file_info`)
+//! - `GET /languages`
+//!
+//! Requests carry content rather than paths, so the server never reads files
+//! on behalf of a client. There is no authentication; bind to loopback.
+
+use crate::config::BatlessConfig;
+use crate::error::{BatlessError, BatlessResult};
+use crate::formatter::{OutputFormatter, OutputMode};
+use crate::json_schema::get_json_schema;
+use crate::language::LanguageDetector;
+use crate::processor::FileProcessor;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// Largest request body accepted by `POST /process`
+pub const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Idle time after which a half-sent request is dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Schema served by `GET /schema` when no `name` is given
+const DEFAULT_SCHEMA: &str = "json_output";
+
+/// Body of a `POST /process` request
+#[derive(Debug, Deserialize)]
+struct ProcessRequest {
+    content: String,
+    #[serde(default)]
+    filename: Option<String>,
+    /// Config keys layered over the server's own configuration
+    #[serde(default)]
+    config: Option<Value>,
+}
+
+/// A parsed HTTP request
+struct Request {
+    method: String,
+    target: String,
+    body: Vec<u8>,
+}
+
+/// Status code and JSON body of a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+
+    fn from_batless_error(e: &BatlessError) -> Self {
+        Self::json(
+            400,
+            &json!({ "error": e.to_string(), "code": e.error_code().as_str() }),
+        )
+    }
+
+    const fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Accept connections on `addr` until the process is stopped
+pub fn serve(addr: &str, config: &BatlessConfig) -> BatlessResult<()> {
+    let listener = TcpListener::bind(addr).map_err(|e| {
+        BatlessError::config_error_with_help(
+            format!("Cannot listen on {addr}: {e}"),
+            Some("Use HOST:PORT, e.g. --serve 127.0.0.1:7878".to_string()),
+        )
+    })?;
+    let local = listener.local_addr()?;
+    if !local.ip().is_loopback() {
+        eprintln!(
+            "batless: warning: serving on non-loopback address {local} without authentication"
+        );
+    }
+    eprintln!("batless: serving on http://{local}");
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let config = config.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = handle_connection(&stream, &config) {
+                        eprintln!("batless: connection error: {e}");
+                    }
+                });
+            }
+            Err(e) => eprintln!("batless: accept failed: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Read one request from `stream`, answer it and close the connection
+pub fn handle_connection(mut stream: &TcpStream, config: &BatlessConfig) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let response = match read_request(&mut reader)? {
+        Ok(request) => handle_request(&request.method, &request.target, &request.body, config),
+        Err(response) => response,
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.body.len()
+    )?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+/// Parse method, target and body; malformed requests become error responses
+fn read_request<R: BufRead>(reader: &mut R) -> std::io::Result<Result<Request, Response>> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(Err(Response::error(400, "malformed request line")));
+    };
+
+    let mut content_length = 0usize;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let Ok(length) = value.trim().parse() else {
+                    return Ok(Err(Response::error(400, "invalid Content-Length")));
+                };
+                content_length = length;
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Ok(Err(Response::error(413, "request body too large")));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok(Request {
+        method: method.to_string(),
+        target: target.to_string(),
+        body,
+    }))
+}
+
+/// Route a request to its endpoint
+pub fn handle_request(method: &str, target: &str, body: &[u8], config: &BatlessConfig) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    match (method, path) {
+        ("POST", "/process") => process(body, config),
+        ("GET", "/schema") => {
+            let name = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("name="))
+                .unwrap_or(DEFAULT_SCHEMA);
+            match get_json_schema(name) {
+                Ok(schema) => Response::json(200, &schema),
+                Err(e) => Response::from_batless_error(&e),
+            }
+        }
+        ("GET", "/languages") => Response::json(200, &json!(LanguageDetector::list_languages())),
+        (_, "/process" | "/schema" | "/languages") => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "unknown endpoint"),
+    }
+}
+
+fn process(body: &[u8], config: &BatlessConfig) -> Response {
+    let request: ProcessRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return Response::error(400, &format!("invalid request body: {e}")),
+    };
+    let filename = request.filename.as_deref().unwrap_or("<stdin>");
+    let result = request_config(config, request.config).and_then(|config| {
+        FileProcessor::process_content(&request.content, filename, &config).and_then(|info| {
+            OutputFormatter::format_output(&info, filename, &config, OutputMode::Json)
+        })
+    });
+    match result {
+        Ok(output) => Response {
+            status: 200,
+            body: output,
+        },
+        Err(e) => Response::from_batless_error(&e),
+    }
+}
+
+/// Layer the request's config keys over the server configuration
+fn request_config(base: &BatlessConfig, overrides: Option<Value>) -> BatlessResult<BatlessConfig> {
+    let Some(overrides) = overrides else {
+        return Ok(base.clone());
+    };
+    BatlessConfig::reject_file_keys(&overrides)?;
+    let overrides: BatlessConfig = serde_json::from_value(overrides).map_err(|e| {
+        BatlessError::config_error_with_help(
+            format!("Invalid config in request: {e}"),
+            Some("Use the same keys as a batless JSON config file".to_string()),
+        )
+    })?;
+    let config = base.clone().merge_with(overrides);
+    config.validate()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn body(value: &Value) -> Vec<u8> {
+        value.to_string().into_bytes()
+    }
+
+    #[test]
+    fn test_process_endpoint() {
+        let config = BatlessConfig::default();
+        let request = json!({
+            "content": "fn main() {}\nfn other() {}\n",
+            "filename": "main.rs",
+            "config": { "max_lines": 1 }
+        });
+        let response = handle_request("POST", "/process", &body(&request), &config);
+        assert_eq!(response.status, 200);
+        let output: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(output["language"], "Rust");
+        assert_eq!(output["file"], "main.rs");
+        assert_eq!(output["lines"], json!(["fn main() {}"]));
+
+        let response = handle_request("POST", "/process", b"{", &config);
+        assert_eq!(response.status, 400);
+        let bad = json!({ "content": "x", "config": { "max_lines": 0 } });
+        let response = handle_request("POST", "/process", &body(&bad), &config);
+        assert!(response.body.contains("E302"));
+    }
+
+    #[test]
+    fn test_process_refuses_file_reading_keys() {
+        let config = BatlessConfig::default();
+        for (key, value) in [
+            ("annotations", json!("/etc/passwd")),
+            ("sarif", json!("/etc/shadow")),
+            ("grammar_dir", json!("/tmp")),
+            ("grammars", json!({ "Gleam": "gleam" })),
+        ] {
+            let request = json!({ "content": "x", "filename": "a.rs", "config": { key: value } });
+            let response = handle_request("POST", "/process", &body(&request), &config);
+            assert_eq!(response.status, 400, "{key}");
+            assert!(response.body.contains(key), "{key}: {}", response.body);
+        }
+    }
+
+    #[test]
+    fn test_routes() {
+        let config = BatlessConfig::default();
+        let schema = handle_request("GET", "/schema?name=file_info", &[], &config);
+        assert_eq!(schema.status, 200);
+        assert_eq!(
+            handle_request("GET", "/schema?name=nope", &[], &config).status,
+            400
+        );
+        let languages = handle_request("GET", "/languages", &[], &config);
+        assert!(languages.body.contains("\"Rust\""));
+        assert_eq!(handle_request("GET", "/process", &[], &config).status, 405);
+        assert_eq!(handle_request("GET", "/", &[], &config).status, 404);
+    }
+
+    #[test]
+    fn test_round_trip_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(&stream, &BatlessConfig::default()).unwrap();
+        });
+
+        let payload = json!({ "content": "print('hi')\n", "filename": "a.py" }).to_string();
+        let mut client = TcpStream::connect(addr).unwrap();
+        write!(
+            client,
+            "POST /process HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{payload}",
+            payload.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let (_, json_body) = response.split_once("\r\n\r\n").unwrap();
+        let output: Value = serde_json::from_str(json_body).unwrap();
+        assert_eq!(output["language"], "Python");
+    }
+}
@@ -0,0 +1,116 @@
+//! Function body elision for `--signatures-only`
+//!
+//! Keeps declarations and signatures but replaces function bodies with
+//! `{ ... }` (or `...` for Python), producing a compact, compileable-looking
+//! skeleton of a file for whole-repo context packing.
+
+use crate::formatters::ast_formatter::AstFormatter;
+use std::ops::Range;
+use tree_sitter::Node;
+
+/// Node kinds whose `body` field is elided
+const FUNCTION_KINDS: &[&str] = &[
+    "function_item",
+    "function_definition",
+    "function_declaration",
+    "function_expression",
+    "function",
+    "generator_function_declaration",
+    "method_definition",
+    "arrow_function",
+];
+
+/// Replace function bodies in `lines`; `None` when the language has no grammar
+pub fn elide_bodies(lines: &[String], language: Option<&str>) -> Option<Vec<String>> {
+    let content = lines.join("\n");
+    let (tree, _) = AstFormatter::parse_to_tree(&content, language)?;
+    let placeholder = if language == Some("Python") {
+        "..."
+    } else {
+        "{ ... }"
+    };
+
+    let mut bodies = Vec::new();
+    collect_bodies(tree.root_node(), &mut bodies);
+
+    let mut out = String::with_capacity(content.len());
+    let mut pos = 0;
+    for body in bodies {
+        out.push_str(&content[pos..body.start]);
+        out.push_str(placeholder);
+        pos = body.end;
+    }
+    out.push_str(&content[pos..]);
+    Some(out.split('\n').map(String::from).collect())
+}
+
+/// Collect body ranges in source order; nested functions are covered by their parent
+fn collect_bodies(node: Node, bodies: &mut Vec<Range<usize>>) {
+    if FUNCTION_KINDS.contains(&node.kind()) {
+        if let Some(body) = node.child_by_field_name("body") {
+            // Expression-bodied arrow functions are already as short as a signature
+            if matches!(body.kind(), "block" | "statement_block") {
+                bodies.push(body.byte_range());
+                return;
+            }
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_bodies(child, bodies);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_rust_bodies_elided() {
+        let code = "pub struct S { x: i32 }\nimpl S {\n    pub fn get(&self) -> i32 {\n        self.x\n    }\n}\nfn main() {\n    let s = 1;\n}";
+        let out = elide_bodies(&lines(code), Some("Rust")).unwrap();
+        assert_eq!(
+            out,
+            [
+                "pub struct S { x: i32 }",
+                "impl S {",
+                "    pub fn get(&self) -> i32 { ... }",
+                "}",
+                "fn main() { ... }"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_bodies_elided() {
+        let code = "class A:\n    def f(self, x):\n        return x * 2\n\ndef g():\n    pass";
+        let out = elide_bodies(&lines(code), Some("Python")).unwrap();
+        assert_eq!(
+            out,
+            [
+                "class A:",
+                "    def f(self, x):",
+                "        ...",
+                "",
+                "def g():",
+                "    ..."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_javascript_keeps_expression_arrows() {
+        let code = "function f(a) {\n  return a;\n}\nconst g = (x) => x + 1;";
+        let out = elide_bodies(&lines(code), Some("JavaScript")).unwrap();
+        assert_eq!(out, ["function f(a) { ... }", "const g = (x) => x + 1;"]);
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(elide_bodies(&lines("key: value"), Some("YAML")).is_none());
+    }
+}
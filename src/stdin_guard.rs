@@ -0,0 +1,245 @@
+//! Size and time limits for stdin (`--stdin-max-bytes`, `--stdin-timeout`)
+//!
+//! Files have a known size, but a runaway producer piping into batless can
+//! write forever or never close the pipe. [`StdinGuard`] wraps the reader and
+//! fails once more than the allowed bytes have arrived or the deadline has
+//! passed, so buffered reads and streaming chunks both stop with a specific
+//! error instead of growing without bound.
+
+use crate::config::BatlessConfig;
+use crate::error::BatlessError;
+use std::fmt;
+use std::io::{self, Read, Stdin};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bytes handed over by the reader thread per message
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Chunks buffered ahead of the consumer, bounding the reader thread's memory
+const CHUNKS_AHEAD: usize = 4;
+
+/// Why a guarded read failed; carried inside the `io::Error`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdinGuardError {
+    TooLarge { limit: usize },
+    TimedOut { timeout_ms: u64 },
+}
+
+impl fmt::Display for StdinGuardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge { limit } => write!(f, "stdin exceeded {limit} bytes"),
+            Self::TimedOut { timeout_ms } => write!(f, "stdin not finished after {timeout_ms}ms"),
+        }
+    }
+}
+
+impl std::error::Error for StdinGuardError {}
+
+enum Source<R> {
+    Direct(R),
+    /// A thread reads ahead so a stalled producer cannot block past the deadline
+    Timed {
+        chunks: Receiver<io::Result<Vec<u8>>>,
+        pending: Vec<u8>,
+        offset: usize,
+        deadline: Instant,
+        timeout_ms: u64,
+    },
+}
+
+/// Reader enforcing an optional byte limit and an optional deadline
+pub struct StdinGuard<R = Stdin> {
+    source: Source<R>,
+    limit: Option<usize>,
+    bytes_read: usize,
+}
+
+impl StdinGuard {
+    /// Guard the process's stdin with the limits from `config`
+    pub fn new(config: &BatlessConfig) -> Self {
+        Self::with_limits(io::stdin(), config.stdin_max_bytes, config.stdin_timeout_ms)
+    }
+}
+
+impl<R: Read + Send + 'static> StdinGuard<R> {
+    /// Guard `reader`; the whole input must arrive within `timeout_ms`
+    ///
+    /// When the deadline passes, the reader thread is left blocked on the
+    /// producer; it ends with the process.
+    pub fn with_limits(reader: R, limit: Option<usize>, timeout_ms: Option<u64>) -> Self {
+        let source = match timeout_ms {
+            None => Source::Direct(reader),
+            Some(timeout_ms) => Source::Timed {
+                chunks: spawn_reader(reader),
+                pending: Vec::new(),
+                offset: 0,
+                deadline: Instant::now() + Duration::from_millis(timeout_ms),
+                timeout_ms,
+            },
+        };
+        Self {
+            source,
+            limit,
+            bytes_read: 0,
+        }
+    }
+}
+
+fn spawn_reader<R: Read + Send + 'static>(mut reader: R) -> Receiver<io::Result<Vec<u8>>> {
+    let (sender, chunks) = mpsc::sync_channel(CHUNKS_AHEAD);
+    thread::spawn(move || loop {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let message = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => {
+                chunk.truncate(read);
+                Ok(chunk)
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => Err(e),
+        };
+        let failed = message.is_err();
+        if sender.send(message).is_err() || failed {
+            break;
+        }
+    });
+    chunks
+}
+
+impl<R: Read> Read for StdinGuard<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = match &mut self.source {
+            Source::Direct(reader) => reader.read(buf)?,
+            Source::Timed {
+                chunks,
+                pending,
+                offset,
+                deadline,
+                timeout_ms,
+            } => {
+                if *offset == pending.len() {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    match chunks.recv_timeout(remaining) {
+                        Ok(chunk) => {
+                            *pending = chunk?;
+                            *offset = 0;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => return Ok(0),
+                        Err(RecvTimeoutError::Timeout) => {
+                            return Err(io::Error::other(StdinGuardError::TimedOut {
+                                timeout_ms: *timeout_ms,
+                            }))
+                        }
+                    }
+                }
+                let read = buf.len().min(pending.len() - *offset);
+                buf[..read].copy_from_slice(&pending[*offset..*offset + read]);
+                *offset += read;
+                read
+            }
+        };
+        self.bytes_read += read;
+        match self.limit {
+            Some(limit) if self.bytes_read > limit => {
+                Err(io::Error::other(StdinGuardError::TooLarge { limit }))
+            }
+            _ => Ok(read),
+        }
+    }
+}
+
+/// Replace a read error caused by a stdin limit with its specific variant
+pub fn specific_error(error: BatlessError) -> BatlessError {
+    let (BatlessError::FileReadError { source, .. } | BatlessError::IoError(source)) = &error
+    else {
+        return error;
+    };
+    match source
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<StdinGuardError>())
+    {
+        Some(StdinGuardError::TooLarge { limit }) => {
+            BatlessError::StdinLimitExceeded { limit: *limit }
+        }
+        Some(StdinGuardError::TimedOut { timeout_ms }) => BatlessError::StdinTimeout {
+            timeout_ms: *timeout_ms,
+        },
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Yields a few bytes, then stalls like a producer that never closes
+    struct Stalled(bool);
+
+    impl Read for Stalled {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if std::mem::replace(&mut self.0, true) {
+                thread::sleep(Duration::from_secs(5));
+                return Ok(0);
+            }
+            buf[..3].copy_from_slice(b"ab\n");
+            Ok(3)
+        }
+    }
+
+    fn guard_error(mut guard: StdinGuard<impl Read>) -> StdinGuardError {
+        let error = io::read_to_string(&mut guard).unwrap_err();
+        *error.get_ref().unwrap().downcast_ref().unwrap()
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let input = || Cursor::new(vec![b'x'; 100]);
+        let mut exact = StdinGuard::with_limits(input(), Some(100), None);
+        assert_eq!(io::read_to_string(&mut exact).unwrap().len(), 100);
+        assert_eq!(
+            guard_error(StdinGuard::with_limits(input(), Some(99), None)),
+            StdinGuardError::TooLarge { limit: 99 }
+        );
+        // The limit also applies behind the reader thread
+        assert_eq!(
+            guard_error(StdinGuard::with_limits(input(), Some(10), Some(5_000))),
+            StdinGuardError::TooLarge { limit: 10 }
+        );
+    }
+
+    #[test]
+    fn test_timeout_interrupts_stalled_producer() {
+        let started = Instant::now();
+        let mut guard = StdinGuard::with_limits(Stalled(false), None, Some(50));
+        let mut buf = [0; 8];
+        assert_eq!(guard.read(&mut buf).unwrap(), 3);
+        assert_eq!(
+            guard_error(guard),
+            StdinGuardError::TimedOut { timeout_ms: 50 }
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_specific_error_variants() {
+        let read_error = |inner| BatlessError::FileReadError {
+            path: "<stdin>".to_string(),
+            source: io::Error::other(inner),
+        };
+        assert!(matches!(
+            specific_error(read_error(StdinGuardError::TooLarge { limit: 4 })),
+            BatlessError::StdinLimitExceeded { limit: 4 }
+        ));
+        assert!(matches!(
+            specific_error(read_error(StdinGuardError::TimedOut { timeout_ms: 9 })),
+            BatlessError::StdinTimeout { timeout_ms: 9 }
+        ));
+        assert!(matches!(
+            specific_error(BatlessError::IoError(io::Error::other("disk"))),
+            BatlessError::IoError(_)
+        ));
+    }
+}
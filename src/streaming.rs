@@ -6,8 +6,15 @@
 use crate::chunker::SemanticBoundaryFinder;
 use crate::config::{BatlessConfig, ChunkStrategy};
 use crate::error::{BatlessError, BatlessResult};
+use crate::highlight_spans::{ChunkHighlighter, HighlightSpan};
+use crate::processor::UTF8_BOM;
+use crate::redaction::Redactor;
+use crate::stdin_guard::{self, StdinGuard};
+use crate::tokens::{AiModel, TokenCounter};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
@@ -31,6 +38,16 @@ pub struct StreamingCheckpoint {
     pub timestamp: String,
     /// Configuration hash for validation
     pub config_hash: String,
+    /// Estimated tokens emitted up to and including this chunk
+    #[serde(default)]
+    pub tokens_processed: usize,
+    /// `cumulative_hash` of the chunk this checkpoint belongs to
+    #[serde(default)]
+    pub cumulative_hash: String,
+    /// `sha256:` digest of the input lines before `line_number`; a resumed
+    /// stream fails when the file no longer matches it
+    #[serde(default)]
+    pub content_hash: String,
 }
 
 impl StreamingCheckpoint {
@@ -49,8 +66,11 @@ impl StreamingCheckpoint {
             chunk_number,
             total_chunks: None,
             schema_version: config.schema_version.clone(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
+            timestamp: config.now().to_rfc3339(),
             config_hash: Self::compute_config_hash(config),
+            tokens_processed: 0,
+            cumulative_hash: String::new(),
+            content_hash: String::new(),
         }
     }
 
@@ -86,10 +106,25 @@ pub struct StreamingChunk {
     pub metadata: ChunkMetadata,
     /// File content lines for this chunk
     pub lines: Vec<String>,
+    /// Per-line highlight spans (`--color=json-spans`, tree-sitter languages)
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub highlight_spans: Option<Vec<Vec<HighlightSpan>>>,
     /// Checkpoint information
     pub checkpoint: StreamingCheckpoint,
     /// Whether this is the final chunk
     pub is_final: bool,
+    /// Set on the last chunk when `--stream-until-tokens` stopped the stream;
+    /// its checkpoint resumes at the first line that was not emitted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub budget_exhausted: bool,
+    /// Set on the last chunk when `--max-chunks` stopped the stream before
+    /// the end of the input; its checkpoint resumes at the next line
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub capped: bool,
+    /// Set on the first chunk after `--restart-on-change` started the stream
+    /// over; every chunk before it is stale
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub restarted: bool,
 }
 
 /// Metadata for a streaming chunk
@@ -103,6 +138,9 @@ pub struct ChunkMetadata {
     pub encoding: String,
     /// Total file size in bytes
     pub total_file_bytes: u64,
+    /// Whether the input started with a UTF-8 byte order mark (stripped from line 1)
+    #[serde(default)]
+    pub had_bom: bool,
     /// Total lines observed so far (exact only on final chunk)
     pub total_file_lines: usize,
     /// Whether total_file_lines represents the complete count
@@ -115,17 +153,49 @@ pub struct ChunkMetadata {
     pub start_line: usize,
     /// Ending line number for this chunk (0-based)
     pub end_line: usize,
+    /// Estimated tokens in this chunk's lines
+    #[serde(default)]
+    pub chunk_tokens: usize,
+    /// Estimated tokens in this and all previous chunks
+    #[serde(default)]
+    pub cumulative_tokens: usize,
+    /// `sha256:` digest of this chunk's lines, each followed by `\n`
+    #[serde(default)]
+    pub chunk_hash: String,
+    /// `sha256:` digest of the previous chunk's `cumulative_hash` (empty for
+    /// the first chunk) followed by this `chunk_hash`, so a dropped or
+    /// reordered chunk breaks the chain
+    #[serde(default)]
+    pub cumulative_hash: String,
 }
 
 /// Streaming JSON processor
 pub struct StreamingProcessor;
 
 impl StreamingProcessor {
-    /// Process a file with streaming JSON output
+    /// Process a file with streaming JSON output, counting tokens for the generic model
     pub fn process_streaming(
         file_path: &str,
         config: &BatlessConfig,
         checkpoint: Option<StreamingCheckpoint>,
+    ) -> BatlessResult<impl Iterator<Item = BatlessResult<StreamingChunk>>> {
+        Self::process_streaming_with_counter(
+            file_path,
+            config,
+            checkpoint,
+            config.token_counter(AiModel::Generic),
+        )
+    }
+
+    /// Process a file with streaming JSON output, counting tokens with `counter`
+    ///
+    /// Each chunk reports `chunk_tokens` and a running `cumulative_tokens`, so
+    /// consumers can stop pulling chunks once a token budget is spent.
+    pub fn process_streaming_with_counter(
+        file_path: &str,
+        config: &BatlessConfig,
+        checkpoint: Option<StreamingCheckpoint>,
+        counter: TokenCounter,
     ) -> BatlessResult<impl Iterator<Item = BatlessResult<StreamingChunk>>> {
         // Check if this is stdin input
         if file_path == "-" {
@@ -140,7 +210,16 @@ impl StreamingProcessor {
             }
 
             let processor = StreamingProcessorIterator::new_from_stdin(config)?;
-            return Ok(processor);
+            return Self::with_redaction(processor, config, counter, None);
+        }
+
+        // Chunks resume by seeking, which a decompressor cannot do
+        #[cfg(feature = "compression")]
+        if let Some(codec) = crate::compression::Codec::from_path(file_path) {
+            return Err(BatlessError::config_error_with_help(
+                format!("Streaming JSON does not support {} input", codec.as_str()),
+                Some("Decompress the file first, or drop --streaming-json.".to_string()),
+            ));
         }
 
         // Validate checkpoint if provided
@@ -160,8 +239,193 @@ impl StreamingProcessor {
             }
         }
 
-        let processor = StreamingProcessorIterator::new(file_path, config, checkpoint)?;
-        Ok(processor)
+        let (processor, resumed) =
+            match StreamingProcessorIterator::new(file_path, config, checkpoint) {
+                Err(BatlessError::FileChangedDuringProcessing { .. })
+                    if config.restart_on_change =>
+                {
+                    let (processor, _) = StreamingProcessorIterator::new(file_path, config, None)?;
+                    (processor.restarted(1), None)
+                }
+                result => result?,
+            };
+        Self::with_redaction(processor, config, counter, resumed)
+    }
+
+    /// Whether plain output of `file_path` should be streamed rather than read
+    /// whole (`auto_stream`)
+    ///
+    /// Only large regular text files qualify, and only when no option needs
+    /// the whole file at once (summaries, stripping, folding, windows,
+    /// annotations, identifiers, hashes or templates).
+    pub fn should_auto_stream(file_path: &str, config: &BatlessConfig) -> bool {
+        if !config.auto_stream || file_path == "-" {
+            return false;
+        }
+        #[cfg(feature = "compression")]
+        if crate::compression::Codec::from_path(file_path).is_some() {
+            return false;
+        }
+        let needs_whole_file = config.effective_summary_level().is_enabled()
+            || config.strip_comments
+            || config.strip_blank_lines
+            || config.signatures_only
+            || config.fold_depth.is_some()
+            || config.around.is_some()
+            || config.symbol.is_some()
+            || config.uses.is_some()
+            || config.has_annotations()
+            || config.include_tokens
+            || config.token_frequencies.is_some()
+            || config.hash
+            || config.check_syntax
+            || config.template.is_some();
+        !needs_whole_file
+            && std::fs::metadata(file_path)
+                .is_ok_and(|meta| meta.is_file() && meta.len() > config.auto_stream_threshold)
+            // Media, datasets and other binaries keep their dedicated handling
+            && !crate::processor::FileProcessor::is_likely_binary(file_path).unwrap_or(true)
+    }
+
+    /// Line chunks for auto-streamed output
+    ///
+    /// The limits that only make sense for `--streaming-json` (chunk caps,
+    /// pacing, token budgets, semantic boundaries) are not applied, and a
+    /// file that changes fails rather than restarting, since printed lines
+    /// cannot be taken back.
+    pub fn process_auto_stream(
+        file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<impl Iterator<Item = BatlessResult<StreamingChunk>>> {
+        let config = BatlessConfig {
+            max_chunks: None,
+            stream_delay_ms: None,
+            max_chunks_per_second: None,
+            stream_token_budget: None,
+            chunk_strategy: ChunkStrategy::Line,
+            highlight_spans: false,
+            restart_on_change: false,
+            ..config.clone()
+        };
+        Self::process_streaming(file_path, &config, None)
+    }
+
+    /// Mask secrets in each chunk when redaction is enabled; the redactor is
+    /// shared across chunks so multi-line secrets spanning a boundary are caught.
+    ///
+    /// Chunks are also capped by `max_chunks`, with the last one marked final
+    /// and `capped`, and paced by `stream_delay_ms` /
+    /// `max_chunks_per_second` so slow consumers are not overwhelmed. Tokens
+    /// are counted after redaction, on the lines actually emitted, and the
+    /// stream ends before `stream_token_budget` would be exceeded. Highlight
+    /// spans, when requested, describe the final lines of each chunk.
+    ///
+    /// Chunk hashes are of the emitted lines too, while checkpoint content
+    /// hashes cover the input lines. A resumed stream continues the token
+    /// total and hash chain of its checkpoint; a restarted one starts them over.
+    fn with_redaction(
+        processor: StreamingProcessorIterator,
+        config: &BatlessConfig,
+        counter: TokenCounter,
+        resumed: Option<Resumed>,
+    ) -> BatlessResult<impl Iterator<Item = BatlessResult<StreamingChunk>>> {
+        let Resumed {
+            mut cumulative_tokens,
+            mut cumulative_hash,
+            mut content,
+        } = resumed.unwrap_or_default();
+        let mut redactor = Redactor::from_config(config)?;
+        let fresh_redactor = redactor.clone();
+        let interval = config.stream_interval();
+        let budget = config.stream_token_budget;
+        let highlight = config.highlight_spans;
+        let mut last_emit: Option<std::time::Instant> = None;
+        let mut exhausted = false;
+        let mut highlighter: Option<ChunkHighlighter> = None;
+        // The budget covers this run, so a resumed stream gets a fresh one
+        let mut emitted = 0;
+        let max_chunks = config.max_chunks;
+        let mut chunks = 0;
+        Ok(processor
+            .take(max_chunks.unwrap_or(usize::MAX))
+            .map_while(move |chunk| {
+                if exhausted {
+                    return None;
+                }
+                let mut chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => return Some(Err(e)),
+                };
+                if chunk.restarted {
+                    cumulative_tokens = 0;
+                    cumulative_hash.clear();
+                    content = Sha256::new();
+                    redactor.clone_from(&fresh_redactor);
+                    emitted = 0;
+                    highlighter = None;
+                }
+                let input = redactor.is_some().then(|| chunk.lines.clone());
+                if let Some(redactor) = redactor.as_mut() {
+                    redactor.redact_in_place(&mut chunk.lines);
+                }
+                let mut chunk_tokens = counter.count_tokens(&chunk.lines.join("\n")).tokens;
+                if let Some(budget) = budget {
+                    let remaining = budget.saturating_sub(emitted);
+                    if chunk_tokens > remaining {
+                        chunk_tokens = trim_to_budget(&mut chunk, &counter, remaining);
+                        exhausted = true;
+                    } else if chunk_tokens == remaining && !chunk.is_final {
+                        exhausted = true;
+                    }
+                }
+                emitted += chunk_tokens;
+                cumulative_tokens += chunk_tokens;
+                chunk.metadata.chunk_tokens = chunk_tokens;
+                chunk.metadata.cumulative_tokens = cumulative_tokens;
+                chunk.checkpoint.tokens_processed = cumulative_tokens;
+                // A budget-trimmed chunk keeps a prefix, so hash after trimming
+                let input = input.as_deref().unwrap_or(&chunk.lines);
+                for line in &input[..chunk.lines.len()] {
+                    hash_line(&mut content, line);
+                }
+                chunk.checkpoint.content_hash = digest_hex(content.clone());
+                let mut lines_hasher = Sha256::new();
+                for line in &chunk.lines {
+                    hash_line(&mut lines_hasher, line);
+                }
+                chunk.metadata.chunk_hash = digest_hex(lines_hasher);
+                cumulative_hash = digest_hex(
+                    Sha256::new()
+                        .chain_update(&cumulative_hash)
+                        .chain_update(&chunk.metadata.chunk_hash),
+                );
+                chunk.metadata.cumulative_hash.clone_from(&cumulative_hash);
+                chunk
+                    .checkpoint
+                    .cumulative_hash
+                    .clone_from(&cumulative_hash);
+                if exhausted {
+                    chunk.is_final = true;
+                    chunk.budget_exhausted = true;
+                }
+                chunks += 1;
+                if Some(chunks) == max_chunks && !chunk.is_final {
+                    chunk.is_final = true;
+                    chunk.capped = true;
+                }
+                if highlight {
+                    chunk.highlight_spans = highlighter
+                        .get_or_insert_with(|| {
+                            ChunkHighlighter::new(chunk.metadata.language.as_deref())
+                        })
+                        .next_chunk(&chunk.lines);
+                }
+                if let (Some(interval), Some(last)) = (interval, last_emit) {
+                    std::thread::sleep(interval.saturating_sub(last.elapsed()));
+                }
+                last_emit = Some(std::time::Instant::now());
+                Some(Ok(chunk))
+            }))
     }
 
     /// Create a checkpoint file for resuming later
@@ -215,12 +479,17 @@ impl StreamingProcessor {
                         "language": { "type": ["string", "null"] },
                         "encoding": { "type": "string" },
                         "total_file_bytes": { "type": "integer", "minimum": 0 },
+                        "had_bom": { "type": "boolean" },
                         "total_file_lines": { "type": "integer", "minimum": 0 },
                         "total_file_lines_exact": { "type": "boolean" },
                         "chunk_lines": { "type": "integer", "minimum": 0 },
                         "chunk_bytes": { "type": "integer", "minimum": 0 },
                         "start_line": { "type": "integer", "minimum": 0 },
-                        "end_line": { "type": "integer", "minimum": 0 }
+                        "end_line": { "type": "integer", "minimum": 0 },
+                        "chunk_tokens": { "type": "integer", "minimum": 0 },
+                        "cumulative_tokens": { "type": "integer", "minimum": 0 },
+                        "chunk_hash": { "type": "string", "pattern": "^sha256:[0-9a-f]{64}$" },
+                        "cumulative_hash": { "type": "string", "pattern": "^sha256:[0-9a-f]{64}$" }
                     }
                 },
                 "lines": {
@@ -228,6 +497,22 @@ impl StreamingProcessor {
                     "items": { "type": "string" },
                     "description": "Content lines for this chunk"
                 },
+                "highlight_spans": {
+                    "type": "array",
+                    "description": "Per-line highlight spans with --color=json-spans",
+                    "items": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["start", "end", "scope"],
+                            "properties": {
+                                "start": { "type": "integer", "minimum": 0 },
+                                "end": { "type": "integer", "minimum": 0 },
+                                "scope": { "type": "string" }
+                            }
+                        }
+                    }
+                },
                 "checkpoint": {
                     "type": "object",
                     "required": ["file_path", "line_number", "bytes_processed", "chunk_number", "schema_version", "timestamp", "config_hash"],
@@ -239,18 +524,159 @@ impl StreamingProcessor {
                         "total_chunks": { "type": ["integer", "null"], "minimum": 1 },
                         "schema_version": { "type": "string" },
                         "timestamp": { "type": "string", "format": "date-time" },
-                        "config_hash": { "type": "string" }
+                        "config_hash": { "type": "string" },
+                        "tokens_processed": { "type": "integer", "minimum": 0 },
+                        "cumulative_hash": { "type": "string" },
+                        "content_hash": { "type": "string" }
                     }
                 },
                 "is_final": {
                     "type": "boolean",
                     "description": "Whether this is the last chunk in the stream"
+                },
+                "budget_exhausted": {
+                    "type": "boolean",
+                    "description": "Present on the last chunk when the token budget stopped the stream"
+                },
+                "capped": {
+                    "type": "boolean",
+                    "description": "Present on the last chunk when --max-chunks stopped the stream before the end of the input"
+                },
+                "restarted": {
+                    "type": "boolean",
+                    "description": "Present on the first chunk after --restart-on-change started the stream over"
                 }
             }
         })
     }
 }
 
+/// Times a stream starts over with `restart_on_change` before failing
+const MAX_RESTARTS: usize = 3;
+
+/// Identity of a file when it was opened, to notice it changing mid-stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileSnapshot {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+    inode: Option<u64>,
+}
+
+impl FileSnapshot {
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            Some(metadata.ino())
+        };
+        #[cfg(not(unix))]
+        let inode = None;
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            inode,
+        }
+    }
+
+    /// Fail when `path` is no longer the file this snapshot was taken of
+    fn check(&self, path: &str) -> BatlessResult<()> {
+        let change = match std::fs::metadata(path) {
+            Err(e) => Some(format!("it can no longer be read ({e})")),
+            Ok(metadata) => {
+                let now = Self::of(&metadata);
+                if now.inode != self.inode {
+                    Some("it was replaced".to_string())
+                } else if now.len != self.len {
+                    Some(format!(
+                        "size changed from {} to {} bytes",
+                        self.len, now.len
+                    ))
+                } else if now.modified != self.modified {
+                    Some("modification time changed".to_string())
+                } else {
+                    None
+                }
+            }
+        };
+        change.map_or(Ok(()), |change| {
+            Err(BatlessError::FileChangedDuringProcessing {
+                path: path.to_string(),
+                change,
+            })
+        })
+    }
+}
+
+/// Running totals a stream resumed from a checkpoint continues
+#[derive(Default)]
+struct Resumed {
+    cumulative_tokens: usize,
+    cumulative_hash: String,
+    /// Content hasher fed with the skipped input lines
+    content: Sha256,
+}
+
+/// Feed one line, terminated by `\n`, to `hasher`
+fn hash_line(hasher: &mut Sha256, line: &str) {
+    hasher.update(line.as_bytes());
+    hasher.update(b"\n");
+}
+
+/// `line` without its `\n` or `\r\n` terminator
+fn strip_line_ending(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map_or(line, |line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// `sha256:`-prefixed hex digest
+fn digest_hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::from("sha256:"), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Consume a leading UTF-8 byte order mark, reporting whether there was one
+fn strip_bom<R: BufRead>(reader: &mut R) -> std::io::Result<bool> {
+    let had_bom = reader.fill_buf()?.starts_with(UTF8_BOM);
+    if had_bom {
+        reader.consume(UTF8_BOM.len());
+    }
+    Ok(had_bom)
+}
+
+/// Keep the longest prefix of the chunk's lines that fits in `remaining`
+/// tokens, adjusting its metadata and checkpoint; returns the kept tokens
+fn trim_to_budget(chunk: &mut StreamingChunk, counter: &TokenCounter, remaining: usize) -> usize {
+    let tokens = |lines: &[String]| counter.count_tokens(&lines.join("\n")).tokens;
+    // Token estimates grow with the prefix, so binary search its length
+    let (mut low, mut high) = (0, chunk.lines.len());
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if tokens(&chunk.lines[..mid]) <= remaining {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    let dropped: usize = chunk.lines[low..].iter().map(|line| line.len() + 1).sum();
+    chunk.lines.truncate(low);
+
+    let metadata = &mut chunk.metadata;
+    metadata.chunk_lines = low;
+    metadata.chunk_bytes = metadata.chunk_bytes.saturating_sub(dropped);
+    metadata.end_line = metadata.start_line + low.saturating_sub(1);
+    metadata.total_file_lines = metadata.start_line + low;
+    metadata.total_file_lines_exact = false;
+    // Resuming from this checkpoint picks up at the first dropped line
+    chunk.checkpoint.line_number = metadata.start_line + low;
+    chunk.checkpoint.bytes_processed = chunk.checkpoint.bytes_processed.saturating_sub(dropped);
+    tokens(&chunk.lines)
+}
+
 /// Iterator for streaming file processing
 enum StreamingProcessorIterator {
     File {
@@ -263,9 +689,15 @@ enum StreamingProcessorIterator {
         finished: bool,
         /// Pre-computed top-level declaration start lines for semantic chunking (may be empty).
         semantic_boundaries: Vec<usize>,
+        /// The file as opened; checked after every chunk
+        snapshot: FileSnapshot,
+        /// Times the stream has started over
+        restarts: usize,
+        /// Whether the next chunk is the first after a restart
+        restarted: bool,
     },
     Stdin {
-        reader: BufReader<std::io::Stdin>,
+        reader: BufReader<StdinGuard>,
         config: BatlessConfig,
         stdin_metadata: FileMetadata,
         current_line: usize,
@@ -282,6 +714,7 @@ struct FileMetadata {
     language: Option<String>,
     encoding: String,
     total_bytes: u64,
+    had_bom: bool,
 }
 
 impl StreamingProcessorIterator {
@@ -289,18 +722,32 @@ impl StreamingProcessorIterator {
         file_path: &str,
         config: &BatlessConfig,
         checkpoint: Option<StreamingCheckpoint>,
-    ) -> BatlessResult<Self> {
+    ) -> BatlessResult<(Self, Option<Resumed>)> {
         let file = File::open(file_path).map_err(|e| BatlessError::FileReadError {
             path: file_path.to_string(),
             source: e,
         })?;
 
-        let file_metadata = Self::gather_file_metadata(file_path)?;
+        let snapshot = file
+            .metadata()
+            .map(|metadata| FileSnapshot::of(&metadata))
+            .map_err(|e| BatlessError::FileReadError {
+                path: file_path.to_string(),
+                source: e,
+            })?;
+        let mut file_metadata = Self::gather_file_metadata(file_path, config)?;
         let mut reader = BufReader::new(file);
+        file_metadata.had_bom =
+            strip_bom(&mut reader).map_err(|e| BatlessError::FileReadError {
+                path: file_path.to_string(),
+                source: e,
+            })?;
 
         // If resuming, skip to checkpoint position
+        let mut resumed = None;
         let (current_line, bytes_processed, chunk_number) = if let Some(cp) = checkpoint {
-            // Skip lines to resume position
+            // Skip lines to resume position, hashing them to detect edits
+            let mut content = Sha256::new();
             for _ in 0..cp.line_number {
                 let mut line = String::new();
                 reader
@@ -309,10 +756,29 @@ impl StreamingProcessorIterator {
                         path: file_path.to_string(),
                         source: e,
                     })?;
+                hash_line(&mut content, strip_line_ending(&line));
+            }
+            // Checkpoints saved before content hashes existed are trusted
+            if !cp.content_hash.is_empty() && digest_hex(content.clone()) != cp.content_hash {
+                return Err(BatlessError::FileChangedDuringProcessing {
+                    path: file_path.to_string(),
+                    change: "its first lines no longer match the checkpoint".to_string(),
+                });
             }
+            resumed = Some(Resumed {
+                cumulative_tokens: cp.tokens_processed,
+                cumulative_hash: cp.cumulative_hash,
+                content,
+            });
             (cp.line_number, cp.bytes_processed, cp.chunk_number)
         } else {
-            (0, 0, 0)
+            // Byte counts are of the input, so they include the BOM
+            let bom_bytes = if file_metadata.had_bom {
+                UTF8_BOM.len()
+            } else {
+                0
+            };
+            (0, bom_bytes, 0)
         };
 
         // Pre-compute semantic boundaries if requested
@@ -323,7 +789,7 @@ impl StreamingProcessorIterator {
             Vec::new()
         };
 
-        Ok(Self::File {
+        let iterator = Self::File {
             reader,
             config: config.clone(),
             file_metadata,
@@ -332,20 +798,55 @@ impl StreamingProcessorIterator {
             chunk_number,
             finished: false,
             semantic_boundaries,
-        })
+            snapshot,
+            restarts: 0,
+            restarted: false,
+        };
+        Ok((iterator, resumed))
+    }
+
+    /// Mark this fresh iterator as the `restarts`th restart of a stream
+    fn restarted(mut self, count: usize) -> Self {
+        if let Self::File {
+            restarts,
+            restarted,
+            ..
+        } = &mut self
+        {
+            *restarts = count;
+            *restarted = true;
+        }
+        self
     }
 
     fn new_from_stdin(config: &BatlessConfig) -> BatlessResult<Self> {
-        use std::io::stdin;
+        use crate::language::LanguageDetector;
 
-        let reader = BufReader::new(stdin());
+        let path = config.stdin_filename.as_deref().unwrap_or("<stdin>");
+        let mut reader = BufReader::new(StdinGuard::new(config));
+        let had_bom = strip_bom(&mut reader).map_err(|e| {
+            stdin_guard::specific_error(BatlessError::FileReadError {
+                path: path.to_string(),
+                source: e,
+            })
+        })?;
 
-        // Create metadata for stdin
+        // Explicit languages win; otherwise detect from the logical stdin filename
+        let language = config
+            .language
+            .clone()
+            .or_else(|| config.stdin_language.clone())
+            .or_else(|| {
+                let name = config.stdin_filename.as_deref()?;
+                LanguageDetector::detect_language_with_map(name, &config.language_map)
+                    .map(|(language, _)| language)
+            });
         let stdin_metadata = FileMetadata {
-            path: "<stdin>".to_string(),
-            language: None, // Cannot detect language without file extension
+            path: path.to_string(),
+            language,
             encoding: "UTF-8".to_string(),
             total_bytes: 0, // Unknown for stdin
+            had_bom,
         };
 
         Ok(Self::Stdin {
@@ -353,13 +854,16 @@ impl StreamingProcessorIterator {
             config: config.clone(),
             stdin_metadata,
             current_line: 0,
-            bytes_processed: 0,
+            bytes_processed: if had_bom { UTF8_BOM.len() } else { 0 },
             chunk_number: 0,
             finished: false,
         })
     }
 
-    fn gather_file_metadata(file_path: &str) -> BatlessResult<FileMetadata> {
+    fn gather_file_metadata(
+        file_path: &str,
+        config: &BatlessConfig,
+    ) -> BatlessResult<FileMetadata> {
         use crate::language::LanguageDetector;
         use crate::processor::FileProcessor;
 
@@ -377,13 +881,15 @@ impl StreamingProcessorIterator {
         let encoding = FileProcessor::detect_encoding(file_path)?;
 
         // Detect language
-        let language = LanguageDetector::detect_language_with_fallback(file_path);
+        let language = LanguageDetector::detect_language_with_map(file_path, &config.language_map)
+            .map(|(language, _)| language);
 
         Ok(FileMetadata {
             path: file_path.to_string(),
             language,
             encoding,
             total_bytes: metadata.len(),
+            had_bom: false,
         })
     }
 }
@@ -392,6 +898,37 @@ impl Iterator for StreamingProcessorIterator {
     type Item = BatlessResult<StreamingChunk>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.read_chunk();
+        if let Self::File {
+            config,
+            file_metadata,
+            restarts,
+            ..
+        } = self
+        {
+            if matches!(
+                chunk,
+                Some(Err(BatlessError::FileChangedDuringProcessing { .. }))
+            ) && config.restart_on_change
+                && *restarts < MAX_RESTARTS
+            {
+                let count = *restarts + 1;
+                return match Self::new(&file_metadata.path, config, None) {
+                    Ok((iterator, _)) => {
+                        *self = iterator.restarted(count);
+                        self.next()
+                    }
+                    Err(e) => Some(Err(e)),
+                };
+            }
+        }
+        chunk
+    }
+}
+
+impl StreamingProcessorIterator {
+    /// The next chunk, or an error once the file changed under the stream
+    fn read_chunk(&mut self) -> Option<BatlessResult<StreamingChunk>> {
         match self {
             Self::File {
                 reader,
@@ -402,6 +939,9 @@ impl Iterator for StreamingProcessorIterator {
                 chunk_number,
                 finished,
                 semantic_boundaries,
+                snapshot,
+                restarted,
+                ..
             } => {
                 if *finished {
                     return None;
@@ -482,6 +1022,12 @@ impl Iterator for StreamingProcessorIterator {
                     }
                 }
 
+                // Lines read after a change may mix old and new content
+                if let Err(e) = snapshot.check(&file_metadata.path) {
+                    *finished = true;
+                    return Some(Err(e));
+                }
+
                 if chunk_lines.is_empty() {
                     *finished = true;
                     return None;
@@ -498,6 +1044,7 @@ impl Iterator for StreamingProcessorIterator {
                     file_path: file_metadata.path.clone(),
                     language: file_metadata.language.clone(),
                     encoding: file_metadata.encoding.clone(),
+                    had_bom: file_metadata.had_bom,
                     total_file_bytes: file_metadata.total_bytes,
                     total_file_lines: *current_line,
                     total_file_lines_exact: is_final,
@@ -505,6 +1052,10 @@ impl Iterator for StreamingProcessorIterator {
                     chunk_bytes,
                     start_line,
                     end_line,
+                    chunk_tokens: 0,
+                    cumulative_tokens: 0,
+                    chunk_hash: String::new(),
+                    cumulative_hash: String::new(),
                 };
 
                 let checkpoint = StreamingCheckpoint::new(
@@ -519,8 +1070,12 @@ impl Iterator for StreamingProcessorIterator {
                     schema_version: config.schema_version.clone(),
                     metadata,
                     lines: chunk_lines,
+                    highlight_spans: None,
                     checkpoint,
                     is_final,
+                    budget_exhausted: false,
+                    capped: false,
+                    restarted: std::mem::take(restarted),
                 };
 
                 *chunk_number += 1;
@@ -564,10 +1119,12 @@ impl Iterator for StreamingProcessorIterator {
                             *current_line += 1;
                         }
                         Err(e) => {
-                            return Some(Err(BatlessError::FileReadError {
-                                path: stdin_metadata.path.clone(),
-                                source: e,
-                            }));
+                            return Some(Err(stdin_guard::specific_error(
+                                BatlessError::FileReadError {
+                                    path: stdin_metadata.path.clone(),
+                                    source: e,
+                                },
+                            )));
                         }
                     }
                 }
@@ -590,6 +1147,7 @@ impl Iterator for StreamingProcessorIterator {
                     file_path: stdin_metadata.path.clone(),
                     language: stdin_metadata.language.clone(),
                     encoding: stdin_metadata.encoding.clone(),
+                    had_bom: stdin_metadata.had_bom,
                     total_file_bytes: *bytes_processed as u64, // Use current bytes as estimate
                     total_file_lines: *current_line,           // Use current line count as estimate
                     total_file_lines_exact: is_final,
@@ -597,6 +1155,10 @@ impl Iterator for StreamingProcessorIterator {
                     chunk_bytes,
                     start_line,
                     end_line,
+                    chunk_tokens: 0,
+                    cumulative_tokens: 0,
+                    chunk_hash: String::new(),
+                    cumulative_hash: String::new(),
                 };
 
                 let checkpoint = StreamingCheckpoint::new(
@@ -611,8 +1173,12 @@ impl Iterator for StreamingProcessorIterator {
                     schema_version: config.schema_version.clone(),
                     metadata,
                     lines: chunk_lines,
+                    highlight_spans: None,
                     checkpoint,
                     is_final,
+                    budget_exhausted: false,
+                    capped: false,
+                    restarted: false,
                 };
 
                 *chunk_number += 1;
@@ -701,4 +1267,199 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_streaming_max_chunks_and_pacing() -> BatlessResult<()> {
+        let file = create_test_file();
+        let config = BatlessConfig::default()
+            .with_streaming_json(true)
+            .with_streaming_chunk_size(1)
+            .with_max_chunks(Some(3))
+            .with_stream_delay_ms(Some(20));
+
+        let start = std::time::Instant::now();
+        let chunks: Vec<_> =
+            StreamingProcessor::process_streaming(file.path().to_str().unwrap(), &config, None)?
+                .collect::<Result<_, _>>()?;
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].lines[0], "line 3");
+        assert!(chunks[..2]
+            .iter()
+            .all(|chunk| !chunk.is_final && !chunk.capped));
+        assert!(chunks[2].is_final && chunks[2].capped);
+        // No delay before the first chunk, then one per subsequent chunk
+        assert!(start.elapsed() >= std::time::Duration::from_millis(40));
+        Ok(())
+    }
+    #[test]
+    fn test_chunks_accumulate_tokens() -> BatlessResult<()> {
+        let file = create_test_file();
+        let path = file.path().to_str().unwrap();
+        let config = BatlessConfig::default()
+            .with_streaming_json(true)
+            .with_streaming_chunk_size(2);
+        let counter = || TokenCounter::new(AiModel::Gpt4);
+
+        let chunks: Vec<_> =
+            StreamingProcessor::process_streaming_with_counter(path, &config, None, counter())?
+                .collect::<Result<_, _>>()?;
+        let mut running = 0;
+        for chunk in &chunks {
+            assert!(chunk.metadata.chunk_tokens > 0);
+            running += chunk.metadata.chunk_tokens;
+            assert_eq!(chunk.metadata.cumulative_tokens, running);
+            assert_eq!(chunk.checkpoint.tokens_processed, running);
+        }
+
+        // Resuming continues the running total
+        let resumed: Vec<_> = StreamingProcessor::process_streaming_with_counter(
+            path,
+            &config,
+            Some(chunks[0].checkpoint.clone()),
+            counter(),
+        )?
+        .collect::<Result<_, _>>()?;
+        assert_eq!(
+            resumed.last().unwrap().metadata.cumulative_tokens,
+            chunks.last().unwrap().metadata.cumulative_tokens
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk_hashes_chain_and_detect_edits() -> BatlessResult<()> {
+        let file = create_test_file();
+        let path = file.path().to_str().unwrap();
+        let config = BatlessConfig::default()
+            .with_streaming_json(true)
+            .with_streaming_chunk_size(2);
+
+        let chunks: Vec<_> = StreamingProcessor::process_streaming(path, &config, None)?
+            .collect::<Result<_, _>>()?;
+        let mut previous = String::new();
+        for chunk in &chunks {
+            let mut hasher = Sha256::new();
+            for line in &chunk.lines {
+                hash_line(&mut hasher, line);
+            }
+            assert_eq!(chunk.metadata.chunk_hash, digest_hex(hasher));
+            let cumulative = digest_hex(
+                Sha256::new()
+                    .chain_update(&previous)
+                    .chain_update(&chunk.metadata.chunk_hash),
+            );
+            assert_eq!(chunk.metadata.cumulative_hash, cumulative);
+            assert_eq!(chunk.checkpoint.cumulative_hash, cumulative);
+            previous = cumulative;
+        }
+
+        // Resuming continues the chain
+        let checkpoint = chunks[0].checkpoint.clone();
+        let resumed: Vec<_> =
+            StreamingProcessor::process_streaming(path, &config, Some(checkpoint.clone()))?
+                .collect::<Result<_, _>>()?;
+        assert_eq!(
+            resumed.last().unwrap().metadata.cumulative_hash,
+            chunks.last().unwrap().metadata.cumulative_hash
+        );
+
+        // Editing lines the checkpoint already covered fails the resume
+        std::fs::write(path, "line 1\nline two\nline 3\n").unwrap();
+        let error = StreamingProcessor::process_streaming(path, &config, Some(checkpoint.clone()))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("changed"), "{error}");
+
+        // ...unless the stream may start over
+        let config = config.with_restart_on_change(true);
+        let restarted = StreamingProcessor::process_streaming(path, &config, Some(checkpoint))?
+            .next()
+            .unwrap()?;
+        assert!(restarted.restarted && restarted.metadata.start_line == 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_change_mid_stream() -> BatlessResult<()> {
+        let file = create_test_file();
+        let path = file.path().to_str().unwrap();
+        let config = BatlessConfig::default()
+            .with_streaming_json(true)
+            .with_streaming_chunk_size(2);
+        let append = || {
+            let mut handle = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+            writeln!(handle, "line 6").unwrap();
+        };
+
+        let mut chunks = StreamingProcessor::process_streaming(path, &config, None)?;
+        assert!(chunks.next().unwrap().is_ok());
+        append();
+        let error = chunks.next().unwrap().unwrap_err();
+        assert_eq!(error.error_code().as_str(), "E105");
+        assert!(chunks.next().is_none());
+
+        let config = config.with_restart_on_change(true);
+        let mut chunks = StreamingProcessor::process_streaming(path, &config, None)?;
+        let first = chunks.next().unwrap()?;
+        assert!(!first.restarted);
+        append();
+        let rest: Vec<_> = chunks.collect::<Result<_, _>>()?;
+        assert!(rest[0].restarted && rest[0].metadata.start_line == 0);
+        assert_eq!(
+            rest[0].metadata.cumulative_hash,
+            first.metadata.cumulative_hash
+        );
+        assert!(rest[1..].iter().all(|chunk| !chunk.restarted));
+        assert_eq!(rest.last().unwrap().lines.last().unwrap(), "line 6");
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_stops_at_token_budget() -> BatlessResult<()> {
+        let file = create_test_file();
+        let path = file.path().to_str().unwrap();
+        let config = BatlessConfig::default()
+            .with_streaming_json(true)
+            .with_streaming_chunk_size(2);
+        let counter = || TokenCounter::new(AiModel::Gpt4);
+        let full: Vec<_> =
+            StreamingProcessor::process_streaming_with_counter(path, &config, None, counter())?
+                .collect::<Result<_, _>>()?;
+        assert!(full.iter().all(|chunk| !chunk.budget_exhausted));
+
+        // Room for the first chunk and part of the second
+        let budget = full[0].metadata.chunk_tokens + 1;
+        let config = config.with_stream_token_budget(Some(budget));
+        let chunks: Vec<_> =
+            StreamingProcessor::process_streaming_with_counter(path, &config, None, counter())?
+                .collect::<Result<_, _>>()?;
+        assert_eq!(chunks.len(), 2);
+        let last = &chunks[1];
+        assert!(last.is_final && last.budget_exhausted);
+        assert!(last.metadata.cumulative_tokens <= budget);
+        assert_eq!(last.lines.len(), last.metadata.chunk_lines);
+        assert_eq!(last.checkpoint.line_number, 2 + last.lines.len());
+        assert!(!chunks[0].budget_exhausted);
+        // The trimmed checkpoint hashes only the lines it covers
+        let resumed =
+            StreamingProcessor::process_streaming(path, &config, Some(last.checkpoint.clone()));
+        assert!(resumed.is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_strips_bom() -> BatlessResult<()> {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "\u{feff}first\nsecond\n").unwrap();
+        let config = BatlessConfig::default().with_streaming_json(true);
+        let chunks: Vec<_> =
+            StreamingProcessor::process_streaming(file.path().to_str().unwrap(), &config, None)?
+                .collect::<Result<_, _>>()?;
+        assert_eq!(chunks[0].lines, ["first", "second"]);
+        assert!(chunks[0].metadata.had_bom);
+        assert_eq!(chunks[0].metadata.chunk_bytes, 13);
+        assert_eq!(chunks[0].checkpoint.bytes_processed, 16);
+        Ok(())
+    }
 }
@@ -25,7 +25,7 @@ impl SummaryExtractor {
         let mut seen_patterns = HashSet::new();
         let max_items = match level {
             SummaryLevel::Minimal => 25,
-            SummaryLevel::Standard => 50,
+            SummaryLevel::Standard | SummaryLevel::Signatures => 50,
             SummaryLevel::Detailed => 100,
             SummaryLevel::None => 0,
         };
@@ -156,7 +156,7 @@ impl SummaryExtractor {
 
         match level {
             SummaryLevel::Minimal => core_match,
-            SummaryLevel::Standard => language_match || core_match,
+            SummaryLevel::Standard | SummaryLevel::Signatures => language_match || core_match,
             SummaryLevel::Detailed => language_match || core_match || detail_match,
             SummaryLevel::None => false,
         }
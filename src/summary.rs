@@ -14,6 +14,8 @@ pub enum SummaryLevel {
     Minimal,
     /// Standard summary: functions, classes, imports (current behavior)
     Standard,
+    /// Standard, with wrapped function signatures shown in full
+    Signatures,
     /// Detailed summary: includes comments, complexity metrics
     Detailed,
 }
@@ -29,9 +31,10 @@ impl SummaryLevel {
             "none" | "false" | "off" => Ok(Self::None),
             "minimal" | "min" => Ok(Self::Minimal),
             "standard" | "std" | "true" | "on" => Ok(Self::Standard),
+            "signatures" | "signature" | "sig" => Ok(Self::Signatures),
             "detailed" | "detail" | "full" => Ok(Self::Detailed),
             _ => Err(format!(
-                "Unknown summary level: {s}. Valid options: none, minimal, standard, detailed"
+                "Unknown summary level: {s}. Valid options: none, minimal, standard, signatures, detailed"
             )),
         }
     }
@@ -43,6 +46,7 @@ impl SummaryLevel {
             Self::None => "none",
             Self::Minimal => "minimal",
             Self::Standard => "standard",
+            Self::Signatures => "signatures",
             Self::Detailed => "detailed",
         }
     }
@@ -54,6 +58,27 @@ impl SummaryLevel {
     }
 }
 
+/// Which extractor produced a summary
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryEngine {
+    /// Tree-sitter queries over the parsed file
+    Ast,
+    /// Line patterns, used when there is no grammar or the AST pass failed
+    Regex,
+}
+
+impl SummaryEngine {
+    /// Get string representation
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ast => "ast",
+            Self::Regex => "regex",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,6 +98,10 @@ mod tests {
             SummaryLevel::parse("detailed").unwrap(),
             SummaryLevel::Detailed
         );
+        assert_eq!(
+            SummaryLevel::parse("sig").unwrap(),
+            SummaryLevel::Signatures
+        );
         assert!(SummaryLevel::parse("invalid").is_err());
     }
 
@@ -81,6 +110,7 @@ mod tests {
         assert_eq!(SummaryLevel::None.as_str(), "none");
         assert_eq!(SummaryLevel::Minimal.as_str(), "minimal");
         assert_eq!(SummaryLevel::Standard.as_str(), "standard");
+        assert_eq!(SummaryLevel::Signatures.as_str(), "signatures");
         assert_eq!(SummaryLevel::Detailed.as_str(), "detailed");
     }
 
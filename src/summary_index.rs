@@ -0,0 +1,167 @@
+//! Repository-level symbol index (`--summary-index`)
+//!
+//! Merges the summaries of many files into one index keyed by symbol name,
+//! so "where is X defined" can be answered from a single invocation.
+//! Names are sorted, and each lists its definitions by file and line.
+
+use crate::file_info::FileInfo;
+use crate::formatters::index_formatter::IndexFormatter;
+use crate::summary_item::SummaryItem;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// Summary kinds that reference rather than define a symbol, or are local bindings
+const NON_DEFINITION_KINDS: &[&str] = &[
+    "use",
+    "import",
+    "import_from",
+    "impl",
+    "comment",
+    "other",
+    "let",
+];
+
+/// Where a symbol is defined
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SymbolLocation {
+    pub file: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    pub kind: String,
+    pub signature: String,
+}
+
+/// A file that could not be indexed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexError {
+    pub file: String,
+    pub error: String,
+}
+
+/// Definitions of every symbol across the indexed files
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SummaryIndex {
+    pub files: usize,
+    pub symbol_count: usize,
+    pub symbols: BTreeMap<String, Vec<SymbolLocation>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<IndexError>,
+}
+
+impl SummaryIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the definitions found in a processed file
+    pub fn add_file(&mut self, file_path: &str, file_info: &FileInfo) {
+        self.files += 1;
+        for (name, item) in definitions(file_info) {
+            self.insert(name, file_path, &item);
+        }
+    }
+
+    /// Record a file that failed to process
+    pub fn add_error(&mut self, file_path: &str, error: String) {
+        self.errors.push(IndexError {
+            file: file_path.to_string(),
+            error,
+        });
+    }
+
+    fn insert(&mut self, name: String, file_path: &str, item: &SummaryItem) {
+        let location = SymbolLocation {
+            file: file_path.to_string(),
+            line: item.line_number,
+            end_line: item.end_line,
+            kind: item.kind.clone(),
+            signature: item.line.trim().to_string(),
+        };
+        let locations = self.symbols.entry(name).or_default();
+        // The same file may be passed twice (e.g. a file and its directory)
+        if let Err(at) = locations.binary_search(&location) {
+            locations.insert(at, location);
+            self.symbol_count += 1;
+        }
+    }
+
+    /// One `name  kind  file:line` row per definition
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for (name, locations) in &self.symbols {
+            for location in locations {
+                let _ = writeln!(
+                    out,
+                    "{name}\t{}\t{}:{}",
+                    location.kind, location.file, location.line
+                );
+            }
+        }
+        for error in &self.errors {
+            let _ = writeln!(out, "// {}: {}", error.file, error.error);
+        }
+        let _ = writeln!(
+            out,
+            "// {} definition(s) of {} symbol(s) in {} file(s)",
+            self.symbol_count,
+            self.symbols.len(),
+            self.files
+        );
+        out
+    }
+}
+
+/// Named definitions in a processed file, in line order
+pub fn definitions(file_info: &FileInfo) -> Vec<(String, SummaryItem)> {
+    let language = file_info.language.as_deref();
+    IndexFormatter::extract_items(&file_info.lines, language)
+        .into_iter()
+        .filter(|item| {
+            // Indented assignments are locals, not module-level definitions
+            let local = item.kind == "assignment" && item.line.starts_with([' ', '\t']);
+            !local && !NON_DEFINITION_KINDS.contains(&item.kind.as_str())
+        })
+        .filter_map(|item| symbol_name(&item).map(|name| (name, item)))
+        .collect()
+}
+
+fn symbol_name(item: &SummaryItem) -> Option<String> {
+    item.name
+        .clone()
+        .or_else(|| IndexFormatter::extract_name(&item.line))
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rust_file(code: &str) -> FileInfo {
+        FileInfo::with_metadata(0, 0, Some("Rust".to_string()), "UTF-8".to_string())
+            .with_lines(code.lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn test_merges_sorts_and_dedupes() {
+        let mut index = SummaryIndex::new();
+        let a = rust_file("use std::fs;\n\npub fn run() {\n    let x = 1;\n}\nstruct Config;\n");
+        let b = rust_file("fn run() {}\n");
+        index.add_file("src/b.rs", &b);
+        index.add_file("src/a.rs", &a);
+        index.add_file("src/a.rs", &a);
+
+        let names: Vec<&str> = index.symbols.keys().map(String::as_str).collect();
+        assert_eq!(names, ["Config", "run"]);
+        let run: Vec<(&str, usize)> = index.symbols["run"]
+            .iter()
+            .map(|l| (l.file.as_str(), l.line))
+            .collect();
+        assert_eq!(run, [("src/a.rs", 3), ("src/b.rs", 1)]);
+        assert_eq!(index.symbols["Config"][0].line, 6);
+        assert_eq!(index.files, 3);
+        assert_eq!(index.symbol_count, 3);
+        assert!(index.render_text().contains("run\tfunction\tsrc/a.rs:3\n"));
+    }
+}
@@ -16,6 +16,40 @@ pub struct SummaryItem {
     pub end_line: Option<usize>,
     /// Kind of structure: "function", "struct", "class", "import", etc.
     pub kind: String,
+    /// Declared name from the AST `@name` capture (None for imports, impls, etc.)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Visibility such as `pub`, `private` or `export` (Rust and JS/TS only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    /// Forge permalink to the line range (only set with `--link-format`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+    /// First sentence of the attached doc comment or docstring (`--summary-docs`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Enclosing classes, impls, traits or modules, outermost first
+    /// (`UserRepo`, `outer::Inner`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    /// Name prefixed with its container (`UserRepo.save`, `Config::load`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    /// Size and control-flow metrics of functions (detailed level only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<SymbolMetrics>,
+}
+
+/// Per-function metrics for spotting risky code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolMetrics {
+    /// Lines spanned by the definition
+    pub lines: usize,
+    /// Decision points: conditionals, loops, match arms or cases, catch
+    /// clauses, ternaries and short-circuit operators
+    pub branches: usize,
+    /// Deepest nesting of conditionals, loops and try blocks in the body
+    pub depth: usize,
 }
 
 impl SummaryItem {
@@ -30,6 +64,200 @@ impl SummaryItem {
             line_number,
             end_line,
             kind: kind.into(),
+            name: None,
+            visibility: None,
+            permalink: None,
+            doc: None,
+            container: None,
+            qualified_name: None,
+            metrics: None,
         }
     }
+
+    /// Attach the declared name
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Attach the visibility
+    pub fn with_visibility(mut self, visibility: Option<String>) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Attach the first sentence of the item's documentation
+    pub fn with_doc(mut self, doc: Option<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    /// Attach the enclosing container, joined to the name with `separator`
+    pub fn with_container(mut self, container: Option<String>, separator: &str) -> Self {
+        self.qualified_name = container
+            .as_ref()
+            .zip(self.name.as_ref())
+            .map(|(container, name)| format!("{container}{separator}{name}"));
+        self.container = container;
+        self
+    }
+
+    /// Attach function metrics
+    pub fn with_metrics(mut self, metrics: Option<SymbolMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Text lines for plain output: the declaration, with its doc sentence
+    /// in the language's own style (a `///` or `/** */` line above it, or a
+    /// docstring below a Python definition)
+    pub fn text_lines(&self, language: Option<&str>) -> Vec<String> {
+        let mut lines: Vec<String> = self.line.lines().map(str::to_string).collect();
+        let Some(doc) = &self.doc else {
+            return lines;
+        };
+        let indent: String = self
+            .line
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        match language {
+            Some("Python") => lines.push(format!("{indent}    \"\"\"{doc}\"\"\"")),
+            Some("Rust") => lines.insert(0, format!("{indent}/// {doc}")),
+            _ => lines.insert(0, format!("{indent}/** {doc} */")),
+        }
+        lines
+    }
+
+    /// Structured record for the JSON `summary_items` array
+    pub fn record(&self) -> SummaryRecord<'_> {
+        SummaryRecord {
+            kind: &self.kind,
+            name: self.name.as_deref(),
+            line: self.line_number,
+            end_line: self.end_line,
+            visibility: self.visibility.as_deref(),
+            doc: self.doc.as_deref(),
+            container: self.container.as_deref(),
+            qualified_name: self.qualified_name.as_deref(),
+            metrics: self.metrics,
+        }
+    }
+
+    /// Determine visibility from a declaration line.
+    ///
+    /// Rust items are `pub`, `pub(crate)`, `pub(super)` or `private`; JS/TS
+    /// items are `export` or `local`. Other languages have no visibility.
+    pub fn detect_visibility(line: &str, language: Option<&str>) -> Option<String> {
+        let t = line.trim();
+        let lang = language.unwrap_or("").to_lowercase();
+        if lang.contains("rust") {
+            if t.starts_with("pub(crate)") {
+                return Some("pub(crate)".to_string());
+            }
+            if t.starts_with("pub(super)") {
+                return Some("pub(super)".to_string());
+            }
+            if t.starts_with("pub ") {
+                return Some("pub".to_string());
+            }
+            return Some("private".to_string());
+        }
+        if lang.contains("javascript")
+            || lang.contains("typescript")
+            || lang.contains("jsx")
+            || lang.contains("tsx")
+        {
+            if t.starts_with("export default ")
+                || t.starts_with("export ")
+                || t.starts_with("module.exports")
+            {
+                return Some("export".to_string());
+            }
+            return Some("local".to_string());
+        }
+        None
+    }
+
+    /// Attach a forge permalink
+    pub fn with_permalink(mut self, permalink: Option<String>) -> Self {
+        self.permalink = permalink;
+        self
+    }
+}
+
+/// Structured summary record: kind, name and location without the source text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SummaryRecord<'a> {
+    pub kind: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<SymbolMetrics>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_visibility_rust() {
+        assert_eq!(
+            SummaryItem::detect_visibility("pub fn foo()", Some("Rust")),
+            Some("pub".to_string())
+        );
+        assert_eq!(
+            SummaryItem::detect_visibility("fn foo()", Some("Rust")),
+            Some("private".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_visibility_js_export() {
+        assert_eq!(
+            SummaryItem::detect_visibility("export function foo() {}", Some("JavaScript")),
+            Some("export".to_string())
+        );
+        assert_eq!(
+            SummaryItem::detect_visibility("def foo():", Some("Python")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_record_serialization() {
+        let item = SummaryItem::new("pub fn main() {", 3, Some(5), "function")
+            .with_name(Some("main".to_string()))
+            .with_visibility(Some("pub".to_string()));
+        assert_eq!(
+            serde_json::to_value(item.record()).unwrap(),
+            serde_json::json!({
+                "kind": "function",
+                "name": "main",
+                "line": 3,
+                "end_line": 5,
+                "visibility": "pub"
+            })
+        );
+    }
+
+    #[test]
+    fn test_record_omits_missing_name() {
+        let item = SummaryItem::new("use std::io;", 1, Some(1), "import");
+        assert_eq!(
+            serde_json::to_value(item.record()).unwrap(),
+            serde_json::json!({ "kind": "import", "line": 1, "end_line": 1 })
+        );
+    }
 }
@@ -0,0 +1,238 @@
+//! Symbol extraction (`--symbol NAME`)
+//!
+//! Finds a function, method or type by name in the tree-sitter summary and
+//! keeps exactly its source range, starting at the doc comment, attributes
+//! or decorators directly above it, so showing one symbol needs no line
+//! arithmetic. Nested items can be named by their qualified name
+//! (`Config::load`, `Api.get`).
+
+use crate::around::LineWindow;
+use crate::ast_summarizer::AstSummarizer;
+use crate::error::{BatlessError, BatlessResult};
+use crate::formatters::index_formatter::IndexFormatter;
+use crate::summary::SummaryLevel;
+use crate::summary_item::SummaryItem;
+
+/// Line prefixes that attach to the definition below them
+const LEADING_PREFIXES: &[&str] = &["///", "#[", "@"];
+
+/// Definition kinds whose name may be read from their line when the query
+/// captured none; bindings, imports and `impl` blocks are never looked up
+const DEFINITION_KINDS: &[&str] = &[
+    "function",
+    "method",
+    "arrow",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+    "mod",
+    "type",
+    "const",
+    "static",
+    "export",
+];
+
+/// Candidates listed in a "did you mean" hint
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Cut the definition of `name` out of `lines`
+pub fn extract_symbol(
+    lines: &[String],
+    language: Option<&str>,
+    name: &str,
+) -> BatlessResult<(Vec<String>, LineWindow)> {
+    let items = AstSummarizer::extract_summary(&lines.join("\n"), language, SummaryLevel::Detailed);
+    if items.is_empty() {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "--symbol needs a parsed outline, and none was found for {}",
+                language.unwrap_or("this input")
+            ),
+            Some(
+                "Symbols are found in Rust, Python, JavaScript and TypeScript, or with a \
+                 runtime grammar; use --around LINE for other files"
+                    .to_string(),
+            ),
+        ));
+    }
+
+    let mut matches: Vec<&SummaryItem> = items
+        .iter()
+        .filter(|item| symbol_names(item).iter().any(|candidate| candidate == name))
+        .collect();
+    // A decorated or exported definition can be captured more than once
+    matches.dedup_by_key(|item| item.line_number);
+    let item = match matches.as_slice() {
+        [item] => *item,
+        [] => return Err(not_found(name, &items)),
+        _ => {
+            let listed: Vec<String> = matches
+                .iter()
+                .map(|item| format!("{} (line {})", display_name(item), item.line_number))
+                .collect();
+            return Err(BatlessError::config_error_with_help(
+                format!(
+                    "Symbol '{name}' matches {} definitions: {}",
+                    matches.len(),
+                    listed.join(", ")
+                ),
+                Some(format!(
+                    "Use the qualified name, e.g. --symbol {}",
+                    display_name(matches[0])
+                )),
+            ));
+        }
+    };
+
+    let end = item.end_line.unwrap_or(item.line_number).min(lines.len());
+    let start = leading_start(lines, item.line_number);
+    let window = LineWindow {
+        line: item.line_number,
+        start,
+        end,
+        enclosing: Some(item.clone()),
+        symbol: Some(name.to_string()),
+    };
+    Ok((lines[start - 1..end].to_vec(), window))
+}
+
+/// Names an item answers to: its own name and its qualified name.
+/// Definitions the query leaves unnamed (`const`, exports) fall back to the
+/// name in their line; `impl` blocks do not, so `struct A` alone answers to `A`.
+fn symbol_names(item: &SummaryItem) -> Vec<String> {
+    let name = item.name.clone().or_else(|| {
+        DEFINITION_KINDS
+            .contains(&item.kind.as_str())
+            .then(|| IndexFormatter::extract_name(&item.line))
+            .flatten()
+    });
+    name.into_iter()
+        .chain(item.qualified_name.clone())
+        .collect()
+}
+
+/// Qualified name, else the plain name, else the item's line
+pub(crate) fn display_name(item: &SummaryItem) -> String {
+    item.qualified_name
+        .clone()
+        .or_else(|| symbol_names(item).into_iter().next())
+        .unwrap_or_else(|| item.line.trim().to_string())
+}
+
+fn not_found(name: &str, items: &[SummaryItem]) -> BatlessError {
+    let needle = name.to_lowercase();
+    let mut similar: Vec<String> = items
+        .iter()
+        .filter(|item| {
+            symbol_names(item)
+                .iter()
+                .any(|candidate| candidate.to_lowercase().contains(&needle))
+        })
+        .map(display_name)
+        .collect();
+    similar.dedup();
+    similar.truncate(MAX_SUGGESTIONS);
+    let help = if similar.is_empty() {
+        "List the symbols in the file with --summary".to_string()
+    } else {
+        format!("Did you mean: {}?", similar.join(", "))
+    };
+    BatlessError::config_error_with_help(format!("Symbol '{name}' not found"), Some(help))
+}
+
+/// First line of the doc comments, attributes and decorators directly above
+/// the 1-based `line`; a block comment counts only as a `/** */` doc comment
+pub(crate) fn leading_start(lines: &[String], line: usize) -> usize {
+    let mut start = line;
+    while start > 1 {
+        let above = lines[start - 2].trim();
+        if above.ends_with("*/") {
+            let open = (1..start)
+                .rev()
+                .find(|&n| lines[n - 1].trim_start().starts_with("/*"));
+            match open {
+                Some(open) if lines[open - 1].trim_start().starts_with("/**") => start = open,
+                _ => break,
+            }
+        } else if LEADING_PREFIXES
+            .iter()
+            .any(|prefix| above.starts_with(prefix))
+        {
+            start -= 1;
+        } else {
+            break;
+        }
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_symbol_range_includes_docs_and_attributes() {
+        let source = lines(
+            "use std::io;\n\n/// Loads the config\n#[inline]\npub fn load() -> u32 {\n    1\n}\n\nstruct A;\n\nimpl A {\n    /** Build one */\n    fn new() -> Self {\n        A\n    }\n}\n",
+        );
+        let (symbol_lines, window) = extract_symbol(&source, Some("Rust"), "load").unwrap();
+        assert_eq!(symbol_lines.first().unwrap(), "/// Loads the config");
+        assert_eq!(symbol_lines.last().unwrap(), "}");
+        assert_eq!((window.start, window.line, window.end), (3, 5, 7));
+        assert_eq!(window.header(), "// function load (lines 3-7)");
+
+        let (symbol_lines, window) = extract_symbol(&source, Some("Rust"), "A::new").unwrap();
+        assert_eq!(symbol_lines[0], "    /** Build one */");
+        assert_eq!((window.start, window.end), (12, 15));
+        // `impl A` does not compete with `struct A`
+        let (_, window) = extract_symbol(&source, Some("Rust"), "A").unwrap();
+        assert_eq!((window.start, window.end), (9, 9));
+    }
+
+    #[test]
+    fn test_python_decorators() {
+        let source = lines(
+            "import os\n\n@dataclass\nclass Point:\n    x: int\n\n    @property\n    def norm(self):\n        return self.x\n",
+        );
+        let (symbol_lines, _) = extract_symbol(&source, Some("Python"), "Point.norm").unwrap();
+        assert_eq!(
+            symbol_lines,
+            [
+                "    @property",
+                "    def norm(self):",
+                "        return self.x"
+            ]
+        );
+        let (_, window) = extract_symbol(&source, Some("Python"), "Point").unwrap();
+        assert_eq!((window.start, window.end), (3, 9));
+    }
+
+    #[test]
+    fn test_missing_and_ambiguous_symbols() {
+        let source = lines(
+            "struct A;\nstruct B;\nimpl A {\n    fn new() {}\n}\nimpl B {\n    fn new() {}\n}\nfn load_all() {\n    let x = 1;\n}\n",
+        );
+        let ambiguous = extract_symbol(&source, Some("Rust"), "new")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            ambiguous.contains("A::new (line 4), B::new (line 7)"),
+            "{ambiguous}"
+        );
+
+        // Local bindings are not symbols
+        assert!(extract_symbol(&source, Some("Rust"), "x").is_err());
+        let missing = extract_symbol(&source, Some("Rust"), "load").unwrap_err();
+        assert!(missing.to_string().contains("Symbol 'load' not found"));
+        assert!(missing.to_string().contains("Did you mean: load_all?"));
+
+        let plain = lines("just text\n");
+        assert!(extract_symbol(&plain, None, "x").is_err());
+    }
+}
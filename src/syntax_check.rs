@@ -0,0 +1,130 @@
+//! Parse errors reported by tree-sitter (`FileInfo.syntax_errors`,
+//! `--fail-on-syntax-errors`)
+//!
+//! Tree-sitter recovers from invalid input by wrapping what it could not
+//! parse in `ERROR` nodes and inserting zero-width `MISSING` nodes for tokens
+//! it expected. Each becomes one entry such as
+//! `line 4:12: unexpected `=> x`` or `line 9:1: missing `}``.
+
+use crate::formatters::ast_formatter::AstFormatter;
+use tree_sitter::Node;
+
+/// Entries kept per file; a final entry counts the rest
+const MAX_SYNTAX_ERRORS: usize = 50;
+
+/// Characters of source quoted for an `ERROR` node
+const MAX_SNIPPET_CHARS: usize = 40;
+
+/// Syntax errors in `lines`, which start at line `first_line`; empty for
+/// valid input and for languages without a grammar
+pub fn find_syntax_errors(
+    lines: &[String],
+    language: Option<&str>,
+    first_line: usize,
+) -> Vec<String> {
+    let content = lines.join("\n");
+    let Some((tree, _)) = AstFormatter::parse_to_tree(&content, language) else {
+        return Vec::new();
+    };
+    let root = tree.root_node();
+    if !root.has_error() {
+        return Vec::new();
+    }
+    let mut nodes = Vec::new();
+    collect_errors(root, &mut nodes);
+
+    let mut errors: Vec<String> = nodes
+        .iter()
+        .take(MAX_SYNTAX_ERRORS)
+        .map(|node| describe(*node, &content, first_line))
+        .collect();
+    if nodes.len() > MAX_SYNTAX_ERRORS {
+        errors.push(format!(
+            "{} more syntax errors not listed",
+            nodes.len() - MAX_SYNTAX_ERRORS
+        ));
+    }
+    errors
+}
+
+/// Outermost `ERROR` nodes and all `MISSING` nodes, in source order
+fn collect_errors<'t>(node: Node<'t>, out: &mut Vec<Node<'t>>) {
+    if node.is_error() || node.is_missing() {
+        out.push(node);
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_errors(child, out);
+    }
+}
+
+fn describe(node: Node, content: &str, first_line: usize) -> String {
+    let position = node.start_position();
+    let location = format!("line {}:{}", position.row + first_line, position.column + 1);
+    if node.is_missing() {
+        let expected = if node.is_named() {
+            node.kind().to_string()
+        } else {
+            format!("`{}`", node.kind())
+        };
+        return format!("{location}: missing {expected}");
+    }
+    let text = node.utf8_text(content.as_bytes()).unwrap_or_default();
+    let first = text.lines().map(str::trim).find(|line| !line.is_empty());
+    match first {
+        Some(line) => {
+            let snippet: String = line.chars().take(MAX_SNIPPET_CHARS).collect();
+            let more = if snippet.len() < line.len() || text.trim().lines().nth(1).is_some() {
+                " ..."
+            } else {
+                ""
+            };
+            format!("{location}: unexpected `{snippet}`{more}")
+        }
+        None => format!("{location}: syntax error"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_valid_source_has_no_errors() {
+        let source = lines("fn main() {\n    let x = 1;\n}\n");
+        assert!(find_syntax_errors(&source, Some("Rust"), 1).is_empty());
+        // No grammar, nothing to report
+        assert!(find_syntax_errors(&lines("fn ((("), None, 1).is_empty());
+    }
+
+    #[test]
+    fn test_errors_have_locations_and_snippets() {
+        let source = lines("def ok():\n    return 1\n\ndef broken(:\n    return 2\n");
+        let errors = find_syntax_errors(&source, Some("Python"), 1);
+        assert!(!errors.is_empty());
+        assert!(errors[0].starts_with("line 4:"), "{errors:?}");
+
+        let source = lines("fn main() {\n    let x = 1\n}\n");
+        let errors = find_syntax_errors(&source, Some("Rust"), 10);
+        assert_eq!(errors, ["line 11:14: missing `;`"]);
+    }
+
+    #[test]
+    fn test_error_count_is_capped() {
+        let source: Vec<String> = (0..80).map(|_| "let = ;".to_string()).collect();
+        let errors = find_syntax_errors(&source, Some("JavaScript"), 1);
+        assert_eq!(errors.len(), MAX_SYNTAX_ERRORS + 1);
+        assert!(errors
+            .last()
+            .unwrap()
+            .ends_with("more syntax errors not listed"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Process priority for background jobs (`--nice`, `--io-low-priority`)
+//! and the user's directories
+//!
+//! Repository indexing jobs can run batless over thousands of files; these
+//! controls keep them from competing with interactive work. Both apply to
+//! the calling thread on Linux, so they are set at startup, before any
+//! `--jobs` worker is spawned, and the workers inherit them.
+//!
+//! WebAssembly builds have no home or config directory, so the directory
+//! lookups return `None` there and user-level files are never read.
+
+use crate::error::{BatlessError, BatlessResult};
+use std::path::PathBuf;
+
+/// The user's home directory
+#[cfg(not(target_arch = "wasm32"))]
+pub fn home_dir() -> Option<PathBuf> {
+    dirs::home_dir()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn home_dir() -> Option<PathBuf> {
+    None
+}
+
+/// The platform config directory (`~/.config` on Linux)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn config_dir() -> Option<PathBuf> {
+    None
+}
+
+/// Set the CPU scheduling niceness, from -20 (highest priority) to 19
+/// (lowest); values below the current one usually need privileges
+#[cfg(unix)]
+pub fn set_niceness(niceness: i32) -> BatlessResult<()> {
+    // SAFETY: setpriority only reads its integer arguments
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) } == 0 {
+        return Ok(());
+    }
+    Err(BatlessError::config_error_with_help(
+        format!(
+            "Cannot set niceness to {niceness}: {}",
+            std::io::Error::last_os_error()
+        ),
+        Some("Raising priority (lower niceness) needs elevated privileges".to_string()),
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn set_niceness(_niceness: i32) -> BatlessResult<()> {
+    Err(unsupported("--nice"))
+}
+
+/// Only use the disk when no other process needs it: the idle I/O class on
+/// Linux, throttled disk I/O on macOS
+#[cfg(target_os = "linux")]
+pub fn set_io_low_priority() -> BatlessResult<()> {
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+    const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+    // SAFETY: ioprio_set only reads its integer arguments
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            0,
+            IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        )
+    };
+    io_priority_result(result == 0)
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_io_low_priority() -> BatlessResult<()> {
+    const IOPOL_TYPE_DISK: libc::c_int = 0;
+    const IOPOL_SCOPE_PROCESS: libc::c_int = 0;
+    const IOPOL_THROTTLE: libc::c_int = 3;
+    extern "C" {
+        fn setiopolicy_np(
+            iotype: libc::c_int,
+            scope: libc::c_int,
+            policy: libc::c_int,
+        ) -> libc::c_int;
+    }
+    // SAFETY: setiopolicy_np only reads its integer arguments
+    let result = unsafe { setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_PROCESS, IOPOL_THROTTLE) };
+    io_priority_result(result == 0)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn set_io_low_priority() -> BatlessResult<()> {
+    Err(unsupported("--io-low-priority"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn io_priority_result(ok: bool) -> BatlessResult<()> {
+    if ok {
+        return Ok(());
+    }
+    Err(BatlessError::config_error_with_help(
+        format!(
+            "Cannot lower the I/O priority: {}",
+            std::io::Error::last_os_error()
+        ),
+        Some("Run batless under ionice or taskpolicy instead".to_string()),
+    ))
+}
+
+#[cfg(not(all(unix, any(target_os = "linux", target_os = "macos"))))]
+fn unsupported(flag: &str) -> BatlessError {
+    BatlessError::config_error_with_help(
+        format!("{flag} is not supported on this platform"),
+        Some("Lower the priority with the operating system's own tools".to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_niceness_can_only_be_lowered() {
+        // SAFETY: getpriority only reads its integer arguments
+        let current = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+        set_niceness(current.max(10)).unwrap();
+        // SAFETY: as above
+        let now = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+        assert_eq!(now, current.max(10));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_io_low_priority() {
+        set_io_low_priority().unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+//! Output templates for text modes (`--template`)
+//!
+//! A template shapes the whole output of a file, such as a Markdown heading
+//! followed by a fenced code block, so results can be dropped into documents
+//! or prompts without post-processing with awk or sed. Templates are expanded
+//! in a single pass: placeholders inside the file's content are never
+//! expanded again.
+
+use crate::file_info::FileInfo;
+
+/// Placeholders a template may use
+pub const PLACEHOLDERS: &[&str] = &[
+    "path", "name", "language", "lines", "bytes", "encoding", "content", "summary",
+];
+
+/// Check that every `{placeholder}` is known and every brace is balanced
+pub fn validate(template: &str) -> Result<(), String> {
+    expand(template, |name| {
+        PLACEHOLDERS
+            .contains(&name)
+            .then(String::new)
+            .ok_or_else(|| format!("unknown placeholder {{{name}}}"))
+    })
+    .map(|_| ())
+}
+
+/// Expand `template` for a processed file
+///
+/// `{content}` is the full processed text (the original lines when a summary
+/// was extracted), `{summary}` the summary lines, and `{language}` is empty
+/// when the language is unknown. `\n`, `\t` and `\\` are unescaped, and
+/// `{{` / `}}` produce literal braces.
+pub fn render(template: &str, path: &str, info: &FileInfo) -> String {
+    expand(template, |name| {
+        Ok(match name {
+            "path" => path.to_string(),
+            "name" => std::path::Path::new(path)
+                .file_name()
+                .map_or_else(|| path.to_string(), |n| n.to_string_lossy().into_owned()),
+            "language" => info.language.clone().unwrap_or_default(),
+            "lines" => info.total_lines.to_string(),
+            "bytes" => info.total_bytes.to_string(),
+            "encoding" => info.encoding.clone(),
+            "content" => info
+                .original_lines
+                .as_ref()
+                .unwrap_or(&info.lines)
+                .join("\n"),
+            "summary" => info
+                .summary_lines
+                .as_ref()
+                .map_or_else(String::new, |items| {
+                    items
+                        .iter()
+                        .map(|item| item.line.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }),
+            _ => format!("{{{name}}}"),
+        })
+    })
+    .unwrap_or_else(|_| template.to_string())
+}
+
+fn expand(
+    template: &str,
+    mut value: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                Some('n') => {
+                    chars.next();
+                    out.push('\n');
+                }
+                Some('t') => {
+                    chars.next();
+                    out.push('\t');
+                }
+                Some('\\') => {
+                    chars.next();
+                    out.push('\\');
+                }
+                _ => out.push('\\'),
+            },
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("unclosed placeholder {{{name}")),
+                    }
+                }
+                out.push_str(&value(&name)?);
+            }
+            '}' => return Err("unmatched '}' (write '}}' for a literal brace)".to_string()),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown_block() {
+        let info = FileInfo::with_metadata(2, 20, Some("Rust".to_string()), "UTF-8".to_string())
+            .with_lines(vec!["fn main() {}".to_string(), "// {path}".to_string()]);
+        assert_eq!(
+            render(
+                r"### {name}\n```{language}\n{content}\n```",
+                "src/main.rs",
+                &info
+            ),
+            "### main.rs\n```Rust\nfn main() {}\n// {path}\n```"
+        );
+        assert_eq!(render("{{{lines}}}", "a", &info), "{2}");
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_placeholders() {
+        assert!(validate(r"{path}\n{content}").is_ok());
+        assert!(validate("{contents}").unwrap_err().contains("{contents}"));
+        assert!(validate("{path").is_err());
+        assert!(validate("a } b").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Test code detection for `--no-tests`
+//!
+//! Finds `#[test]` functions and `#[cfg(test)]` modules in Rust, `test_*`
+//! functions and `Test*` classes in Python, and `describe`/`it`/`test` blocks
+//! in JavaScript and TypeScript, plus whole test files by their path, so
+//! summaries and context packs can carry production code only.
+
+use crate::formatters::ast_formatter::AstFormatter;
+use crate::summary_item::SummaryItem;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use tree_sitter::Node;
+
+/// Calls that open a JavaScript/TypeScript test block or hook
+const JS_TEST_CALLS: &[&str] = &[
+    "describe",
+    "context",
+    "it",
+    "test",
+    "suite",
+    "beforeAll",
+    "beforeEach",
+    "afterAll",
+    "afterEach",
+];
+
+/// Directories holding tests in common project layouts
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec"];
+
+/// Whether `path` is a test file by name (`test_x.py`, `x_test.go`,
+/// `x.spec.ts`) or by sitting in a test directory
+pub fn is_test_path(path: &str) -> bool {
+    let path = Path::new(path);
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .is_some_and(|c| TEST_DIRS.contains(&c))
+        })
+    });
+    in_test_dir
+        || path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .is_some_and(|stem| {
+                stem.starts_with("test_")
+                    || stem.ends_with("_test")
+                    || stem.ends_with("_tests")
+                    || stem == "conftest"
+                    // `button.spec.tsx`, `add.test.js`
+                    || Path::new(stem)
+                        .extension()
+                        .is_some_and(|ext| ext == "test" || ext == "spec")
+            })
+}
+
+/// 1-based line ranges of test code, including attributes and decorators;
+/// empty when the language has no grammar
+pub fn test_line_ranges(content: &str, language: Option<&str>) -> Vec<RangeInclusive<usize>> {
+    let Some((tree, _)) = AstFormatter::parse_to_tree(content, language) else {
+        return Vec::new();
+    };
+    let mut ranges = Vec::new();
+    collect_ranges(tree.root_node(), content, &mut ranges);
+    ranges
+}
+
+/// Drop summary items that belong to test code
+pub fn retain_production(
+    items: &mut Vec<SummaryItem>,
+    path: &str,
+    content: &str,
+    language: Option<&str>,
+) {
+    if is_test_path(path) {
+        items.clear();
+        return;
+    }
+    let ranges = test_line_ranges(content, language);
+    items.retain(|item| !ranges.iter().any(|r| r.contains(&item.line_number)));
+}
+
+/// Whether `line` of the file at `path` is test code
+pub fn is_test_location(path: &str, language: Option<&str>, line: usize) -> bool {
+    is_test_path(path)
+        || std::fs::read_to_string(path).is_ok_and(|content| {
+            test_line_ranges(&content, language)
+                .iter()
+                .any(|r| r.contains(&line))
+        })
+}
+
+fn collect_ranges(node: Node, content: &str, ranges: &mut Vec<RangeInclusive<usize>>) {
+    if let Some(start) = test_start(node, content) {
+        ranges.push(start.start_position().row + 1..=node.end_position().row + 1);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_ranges(child, content, ranges);
+    }
+}
+
+fn text<'a>(node: Node, content: &'a str) -> &'a str {
+    node.utf8_text(content.as_bytes()).unwrap_or_default()
+}
+
+/// When `node` is a test item, the node its range starts at (its first
+/// attribute or decorator, or the item itself)
+fn test_start<'t>(node: Node<'t>, content: &str) -> Option<Node<'t>> {
+    match node.kind() {
+        "function_item" | "mod_item" => {
+            let mut first = node;
+            let mut is_test = false;
+            while let Some(prev) = first.prev_sibling().filter(|prev| {
+                matches!(
+                    prev.kind(),
+                    "attribute_item" | "line_comment" | "block_comment"
+                )
+            }) {
+                is_test |=
+                    prev.kind() == "attribute_item" && is_test_attribute(text(prev, content));
+                first = prev;
+            }
+            is_test.then_some(first)
+        }
+        "function_definition" | "class_definition" => {
+            let name = text(node.child_by_field_name("name")?, content);
+            let is_test = if node.kind() == "class_definition" {
+                name.starts_with("Test")
+            } else {
+                name == "test" || name.starts_with("test_")
+            };
+            is_test.then(|| {
+                node.parent()
+                    .filter(|parent| parent.kind() == "decorated_definition")
+                    .unwrap_or(node)
+            })
+        }
+        "expression_statement" => {
+            let call = node
+                .named_child(0)
+                .filter(|call| call.kind() == "call_expression")?;
+            // `it.only(...)` and `describe.each(table)(...)` name the block too
+            let mut callee = call.child_by_field_name("function")?;
+            loop {
+                callee = match callee.kind() {
+                    "member_expression" => callee.child_by_field_name("object")?,
+                    "call_expression" => callee.child_by_field_name("function")?,
+                    _ => break,
+                };
+            }
+            (callee.kind() == "identifier" && JS_TEST_CALLS.contains(&text(callee, content)))
+                .then_some(node)
+        }
+        _ => None,
+    }
+}
+
+/// `#[test]`, `#[tokio::test]`, `#[rstest]`, `#[test_case(..)]` or a `cfg`
+/// that requires `test`
+fn is_test_attribute(attribute: &str) -> bool {
+    let inner: String = attribute
+        .trim_start_matches("#[")
+        .trim_end_matches(']')
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if let Some(predicate) = inner.strip_prefix("cfg(") {
+        return !predicate.contains("not(")
+            && predicate
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|word| word == "test");
+    }
+    inner == "test"
+        || inner.ends_with("::test")
+        || inner.starts_with("rstest")
+        || inner.starts_with("test_case")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_ranges_cover_attributes() {
+        let code = "fn run() {}\n\n#[cfg(test)]\nmod tests {\n    #[test]\n    fn works() {}\n}\n\n#[cfg(not(test))]\nfn real() {}\n\n/// Async check\n#[tokio::test]\nasync fn later() {}\n";
+        assert_eq!(test_line_ranges(code, Some("Rust")), [3..=7, 12..=14]);
+    }
+
+    #[test]
+    fn test_python_and_javascript_ranges() {
+        let py = "def helper():\n    pass\n\n@pytest.mark.slow\ndef test_helper():\n    assert helper() is None\n\nclass TestApi:\n    pass\n";
+        assert_eq!(test_line_ranges(py, Some("Python")), [4..=6, 8..=9]);
+        let js = "export function add(a, b) { return a + b; }\n\ndescribe('add', () => {\n  it('adds', () => {});\n});\ntest.each([1])('n', () => {});\n";
+        assert_eq!(test_line_ranges(js, Some("JavaScript")), [3..=5, 6..=6]);
+    }
+
+    #[test]
+    fn test_paths() {
+        for path in [
+            "tests/cli.rs",
+            "src/__tests__/a.js",
+            "pkg/test_models.py",
+            "server_test.go",
+            "ui/button.spec.tsx",
+            "conftest.py",
+        ] {
+            assert!(is_test_path(path), "{path}");
+        }
+        for path in ["src/main.rs", "contest.py", "src/testing.rs", "latest.ts"] {
+            assert!(!is_test_path(path), "{path}");
+        }
+    }
+}
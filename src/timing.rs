@@ -0,0 +1,107 @@
+//! Per-stage wall-clock timings for `--time`
+//!
+//! Unlike `--bench`, which re-runs every stage several times, `--time`
+//! measures the one real run and prints a single stable line per file to
+//! stderr, so scripts comparing batless with `bat` or `cat` can collect
+//! numbers without an external profiler.
+
+use std::fmt::Write as _;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How `--time` reports are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// `batless-time file=src/main.rs read_us=812 ... total_us=1630`
+    #[default]
+    KeyValue,
+    /// One JSON object per file
+    Json,
+}
+
+/// Wall-clock time spent in each stage of one run, in microseconds
+#[derive(Debug, Clone)]
+pub struct StageTimer {
+    pub file: String,
+    pub stages: Vec<(&'static str, u128)>,
+    /// Wall-clock start, for exporting the stages as spans
+    pub started_at: SystemTime,
+    start: Instant,
+    last: Instant,
+}
+
+impl StageTimer {
+    /// Start timing a run over `file`
+    pub fn start(file: &str) -> Self {
+        let now = Instant::now();
+        Self {
+            file: file.to_string(),
+            stages: Vec::new(),
+            started_at: SystemTime::now(),
+            start: now,
+            last: now,
+        }
+    }
+
+    /// Close `stage`, which ran since the previous mark (or the start)
+    pub fn mark(&mut self, stage: &'static str) {
+        let now = Instant::now();
+        self.stages
+            .push((stage, now.duration_since(self.last).as_micros()));
+        self.last = now;
+    }
+
+    /// Time from the start to the last mark
+    pub fn total(&self) -> Duration {
+        self.last.duration_since(self.start)
+    }
+
+    /// The report line; stage keys are `<stage>_us`, followed by `total_us`
+    pub fn render(&self, format: TimeFormat) -> String {
+        let total = self.total().as_micros();
+        match format {
+            TimeFormat::KeyValue => {
+                // Paths with spaces would split the record; quote them
+                let file = if self.file.contains(char::is_whitespace) {
+                    format!("{:?}", self.file)
+                } else {
+                    self.file.clone()
+                };
+                let mut line = format!("batless-time file={file}");
+                for (stage, micros) in &self.stages {
+                    let _ = write!(line, " {stage}_us={micros}");
+                }
+                let _ = write!(line, " total_us={total}");
+                line
+            }
+            TimeFormat::Json => {
+                let mut value = serde_json::json!({ "file": self.file });
+                for (stage, micros) in &self.stages {
+                    value[format!("{stage}_us")] = serde_json::json!(micros);
+                }
+                value["total_us"] = serde_json::json!(total);
+                value.to_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_formats() {
+        let mut timer = StageTimer::start("my file.rs");
+        timer.mark("read");
+        timer.mark("format");
+        let line = timer.render(TimeFormat::KeyValue);
+        assert!(line.starts_with("batless-time file=\"my file.rs\" read_us="));
+        assert!(line.contains(" format_us=") && line.contains(" total_us="));
+
+        let value: serde_json::Value =
+            serde_json::from_str(&timer.render(TimeFormat::Json)).unwrap();
+        assert_eq!(value["file"], "my file.rs");
+        let total = value["total_us"].as_u64().unwrap();
+        assert!(value["read_us"].as_u64().unwrap() <= total);
+    }
+}
@@ -7,14 +7,21 @@
 //!
 //! The module is organized into two main sub-modules:
 //! - `counting`: AI model-specific token counting and context window management
-//! - `extraction`: Semantic token extraction from source code for analysis
+//! - `extraction`: Semantic token extraction from source code for analysis,
+//!   through the single extract → filter → sample pipeline of
+//!   [`TokenExtractor::extract`]
+//! - `subword`: exact model tokens in embedding-sized chunks (`--tokenize`)
 
 pub mod counting;
 pub mod extraction;
+pub mod subword;
 
 // Re-export commonly used types for convenience
-pub use counting::{AiModel, TokenCount, TokenCounter};
-pub use extraction::{TokenExtractor, TokenStats};
+pub use counting::{AiModel, CustomModel, CustomModelSpec, TokenCount, TokenCounter};
+pub use extraction::{
+    TokenExtractionResult, TokenExtractor, TokenFrequency, TokenOptions, TokenOrder, TokenStats,
+    TokenStatsReport,
+};
 
 use crate::traits::TokenExtraction;
 
@@ -4,6 +4,39 @@
 //! allowing users to estimate how much content will fit within model context windows.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const fn default_tokens_per_word() -> f64 {
+    1.3
+}
+
+/// A user-defined model entry from a `[models.<name>]` config table
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CustomModelSpec {
+    /// Context window size in tokens
+    pub context_window: usize,
+    /// Approximate tokens per word used for estimation
+    #[serde(default = "default_tokens_per_word")]
+    pub tokens_per_word: f64,
+}
+
+/// A registered custom model, referenced by `AiModel::Custom`
+#[derive(Debug, Serialize)]
+pub struct CustomModel {
+    pub name: &'static str,
+    pub context_window: usize,
+    pub tokens_per_word: f64,
+}
+
+impl PartialEq for CustomModel {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.context_window == other.context_window
+            && self.tokens_per_word.to_bits() == other.tokens_per_word.to_bits()
+    }
+}
+
+impl Eq for CustomModel {}
 
 /// Supported AI models for token counting
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,16 +47,33 @@ pub enum AiModel {
     Gpt4Turbo,
     /// OpenAI GPT-3.5 family
     Gpt35,
+    /// OpenAI GPT-4o (128K context, o200k tokenizer)
+    Gpt4o,
+    /// OpenAI o3 reasoning model (200K context)
+    O3,
+    /// OpenAI o4-mini reasoning model (200K context)
+    O4Mini,
     /// Anthropic Claude family (Claude 4.x series, 200K context)
     Claude,
     /// Anthropic Claude Sonnet (Claude 4.x Sonnet, 200K context)
     ClaudeSonnet,
-    /// Google Gemini 1.5 Pro (1M context)
+    /// Anthropic Claude 3.7 Sonnet (200K context)
+    Claude37Sonnet,
+    /// Google Gemini 1.5 Pro (2M context)
     Gemini,
     /// Google Gemini 2.0 Flash (1M context)
     GeminiFlash,
+    /// Google Gemini 2.5 Pro (1M context)
+    Gemini25Pro,
+    /// Meta Llama 3 8B/70B (8K context)
+    Llama3,
+    /// Meta Llama 3.1+ (3.1/3.2/3.3, 128K context)
+    Llama31,
     /// Generic model using simple word-based estimation
     Generic,
+    /// User-defined model from the `[models]` config table
+    #[serde(skip_deserializing)]
+    Custom(&'static CustomModel),
 }
 
 impl AiModel {
@@ -33,26 +83,71 @@ impl AiModel {
             "gpt4" | "gpt-4" => Ok(Self::Gpt4),
             "gpt4-turbo" | "gpt-4-turbo" | "gpt4turbo" => Ok(Self::Gpt4Turbo),
             "gpt35" | "gpt-3.5" | "gpt-3.5-turbo" => Ok(Self::Gpt35),
+            "gpt4o" | "gpt-4o" => Ok(Self::Gpt4o),
+            "o3" => Ok(Self::O3),
+            "o4-mini" | "o4mini" => Ok(Self::O4Mini),
             "claude" | "claude-4" | "claude-3" => Ok(Self::Claude),
-            "claude-sonnet" | "claude-4-sonnet" | "claude-3.5" | "claude-3.5-sonnet"
-            | "claude35" | "claude35sonnet" => Ok(Self::ClaudeSonnet),
+            "claude-sonnet" | "claude-4-sonnet" | "claude-sonnet-4" | "claude-3.5"
+            | "claude-3.5-sonnet" | "claude35" | "claude35sonnet" => Ok(Self::ClaudeSonnet),
+            "claude37-sonnet" | "claude-3.7" | "claude-3.7-sonnet" | "claude37" => {
+                Ok(Self::Claude37Sonnet)
+            }
             "gemini" | "gemini-1.5" | "gemini-1.5-pro" | "gemini-pro" => Ok(Self::Gemini),
             "gemini-flash" | "gemini-2.0-flash" | "gemini-2" => Ok(Self::GeminiFlash),
+            "gemini25-pro" | "gemini-2.5" | "gemini-2.5-pro" => Ok(Self::Gemini25Pro),
+            "llama3" | "llama-3" => Ok(Self::Llama3),
+            "llama31" | "llama-3.1" | "llama3.1" | "llama-3.3" | "llama3.3" => Ok(Self::Llama31),
             "generic" => Ok(Self::Generic),
             _ => Err(format!("Unknown AI model: {s}")),
         }
     }
 
+    /// Parse a model name, falling back to the user-defined `models` registry.
+    ///
+    /// Built-in names take precedence over custom entries of the same name.
+    pub fn parse_with_registry(
+        s: &str,
+        models: &BTreeMap<String, CustomModelSpec>,
+    ) -> Result<Self, String> {
+        Self::parse(s).or_else(|err| {
+            models
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(s))
+                .map(|(name, spec)| Self::custom(name, *spec))
+                .ok_or(err)
+        })
+    }
+
+    /// Register a custom model.
+    ///
+    /// The definition is leaked so `AiModel` stays `Copy`; models are
+    /// registered once from configuration at startup.
+    pub fn custom(name: &str, spec: CustomModelSpec) -> Self {
+        let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+        Self::Custom(Box::leak(Box::new(CustomModel {
+            name,
+            context_window: spec.context_window,
+            tokens_per_word: spec.tokens_per_word,
+        })))
+    }
+
     /// Get all available AI models
     pub fn all() -> Vec<Self> {
         vec![
             Self::Gpt4,
             Self::Gpt4Turbo,
             Self::Gpt35,
+            Self::Gpt4o,
+            Self::O3,
+            Self::O4Mini,
             Self::Claude,
             Self::ClaudeSonnet,
+            Self::Claude37Sonnet,
             Self::Gemini,
             Self::GeminiFlash,
+            Self::Gemini25Pro,
+            Self::Llama3,
+            Self::Llama31,
             Self::Generic,
         ]
     }
@@ -63,25 +158,41 @@ impl AiModel {
             Self::Gpt4 => "gpt-4",
             Self::Gpt4Turbo => "gpt-4-turbo",
             Self::Gpt35 => "gpt-3.5",
+            Self::Gpt4o => "gpt-4o",
+            Self::O3 => "o3",
+            Self::O4Mini => "o4-mini",
             Self::Claude => "claude",
             Self::ClaudeSonnet => "claude-sonnet",
+            Self::Claude37Sonnet => "claude-3.7-sonnet",
             Self::Gemini => "gemini-1.5-pro",
             Self::GeminiFlash => "gemini-2.0-flash",
+            Self::Gemini25Pro => "gemini-2.5-pro",
+            Self::Llama3 => "llama-3",
+            Self::Llama31 => "llama-3.1",
             Self::Generic => "generic",
+            Self::Custom(model) => model.name,
         }
     }
 
     /// Get approximate context window size for this model
     pub const fn context_window(&self) -> usize {
         match self {
-            Self::Gpt4 => 128_000,          // GPT-4 Turbo
-            Self::Gpt4Turbo => 128_000,     // GPT-4 Turbo (same as GPT-4)
-            Self::Gpt35 => 16_384,          // GPT-3.5 Turbo
-            Self::Claude => 200_000,        // Claude 4.x family
-            Self::ClaudeSonnet => 200_000,  // Claude Sonnet (same context window)
-            Self::Gemini => 1_000_000,      // Gemini 1.5 Pro
-            Self::GeminiFlash => 1_000_000, // Gemini 2.0 Flash
-            Self::Generic => 8_192,         // Conservative default
+            Self::Gpt4 => 128_000,           // GPT-4 Turbo
+            Self::Gpt4Turbo => 128_000,      // GPT-4 Turbo (same as GPT-4)
+            Self::Gpt35 => 16_384,           // GPT-3.5 Turbo
+            Self::Gpt4o => 128_000,          // GPT-4o
+            Self::O3 => 200_000,             // o3
+            Self::O4Mini => 200_000,         // o4-mini
+            Self::Claude => 200_000,         // Claude 4.x family
+            Self::ClaudeSonnet => 200_000,   // Claude Sonnet (same context window)
+            Self::Claude37Sonnet => 200_000, // Claude 3.7 Sonnet
+            Self::Gemini => 2_000_000,       // Gemini 1.5 Pro
+            Self::GeminiFlash => 1_000_000,  // Gemini 2.0 Flash
+            Self::Gemini25Pro => 1_000_000,  // Gemini 2.5 Pro
+            Self::Llama3 => 8_192,           // Llama 3 8B/70B
+            Self::Llama31 => 128_000,        // Llama 3.1 and later
+            Self::Generic => 8_192,          // Conservative default
+            Self::Custom(model) => model.context_window,
         }
     }
 
@@ -89,9 +200,12 @@ impl AiModel {
     const fn tokens_per_word(self) -> f64 {
         match self {
             Self::Gpt4 | Self::Gpt4Turbo | Self::Gpt35 => 1.3, // GPT models: ~1.3 tokens per word
-            Self::Claude | Self::ClaudeSonnet => 1.2,          // Claude: slightly more efficient
-            Self::Gemini | Self::GeminiFlash => 1.3,           // Gemini: SentencePiece tokenizer
-            Self::Generic => 1.5,                              // Conservative estimate
+            Self::Gpt4o | Self::O3 | Self::O4Mini => 1.2,      // o200k tokenizer: larger vocabulary
+            Self::Claude | Self::ClaudeSonnet | Self::Claude37Sonnet => 1.2, // Claude: slightly more efficient
+            Self::Gemini | Self::GeminiFlash | Self::Gemini25Pro => 1.3, // Gemini: SentencePiece tokenizer
+            Self::Llama3 | Self::Llama31 => 1.25, // Llama 3: 128K-vocab tiktoken-style BPE
+            Self::Generic => 1.5,                 // Conservative estimate
+            Self::Custom(model) => model.tokens_per_word,
         }
     }
 }
@@ -116,12 +230,30 @@ pub struct TokenCount {
 /// Token counter for AI models
 pub struct TokenCounter {
     model: AiModel,
+    sample_count: usize,
+    sample_seed: u64,
 }
 
 impl TokenCounter {
+    /// Samples taken from files too large to count in full
+    pub const DEFAULT_SAMPLE_COUNT: usize = 5;
+    /// Bytes per sample
+    const SAMPLE_SIZE: usize = 10_000;
+
     /// Create a new token counter for the specified model
     pub const fn new(model: AiModel) -> Self {
-        Self { model }
+        Self {
+            model,
+            sample_count: Self::DEFAULT_SAMPLE_COUNT,
+            sample_seed: 0,
+        }
+    }
+
+    /// Number of samples and PRNG seed for large-file estimation
+    pub const fn with_sampling(mut self, sample_count: usize, sample_seed: u64) -> Self {
+        self.sample_count = if sample_count == 0 { 1 } else { sample_count };
+        self.sample_seed = sample_seed;
+        self
     }
 
     /// Get the AI model used by this counter
@@ -135,7 +267,7 @@ impl TokenCounter {
 
         // For very large files (>100KB), use sampling for faster estimation
         let (words, tokens) = if characters > 100_000 {
-            self.estimate_large_file_tokens(text, characters)
+            self.estimate_large_file_tokens(text)
         } else {
             let words = self.count_words(text);
             let tokens = self.estimate_tokens(text, words);
@@ -154,61 +286,41 @@ impl TokenCounter {
         }
     }
 
-    /// Optimized token estimation for large files using sampling
-    fn estimate_large_file_tokens(&self, text: &str, total_chars: usize) -> (usize, usize) {
-        // Sample multiple chunks from the file for better accuracy
-        let sample_size = 10_000; // 10KB samples
-        let num_samples = 5;
+    /// Estimate tokens of a large file from stratified random samples
+    ///
+    /// The file is split into `sample_count` equal strata and one window is
+    /// read from a seeded random offset within each, so a doc header and a
+    /// minified blob in the same file are both represented and the same seed
+    /// always gives the same estimate.
+    fn estimate_large_file_tokens(&self, text: &str) -> (usize, usize) {
+        let stratum = text.len() / self.sample_count;
+        if stratum <= Self::SAMPLE_SIZE {
+            let words = self.count_words(text);
+            return (words, self.estimate_tokens(text, words));
+        }
 
+        let mut state = self.sample_seed;
         let mut total_words = 0;
         let mut total_tokens = 0;
-        let mut samples_taken = 0;
-
-        let chunk_size = total_chars / num_samples;
-
-        for i in 0..num_samples {
-            let start = i * chunk_size;
-            let end = if i == num_samples - 1 {
-                total_chars
-            } else {
-                (start + sample_size).min(total_chars)
-            };
-
-            if start >= total_chars {
-                break;
-            }
-
-            // Extract sample preserving word boundaries
-            let sample = text
-                .chars()
-                .skip(start)
-                .take(end - start)
-                .collect::<String>();
-            if !sample.is_empty() {
-                let words = self.count_words(&sample);
-                let tokens = self.estimate_tokens(&sample, words);
-
-                total_words += words;
-                total_tokens += tokens;
-                samples_taken += 1;
-            }
+        let mut sampled_bytes = 0;
+        for i in 0..self.sample_count {
+            let slack = stratum - Self::SAMPLE_SIZE;
+            let offset = (splitmix64(&mut state) % (slack as u64 + 1)) as usize;
+            let start = floor_char_boundary(text, i * stratum + offset);
+            let end = floor_char_boundary(text, start + Self::SAMPLE_SIZE);
+            let sample = &text[start..end];
+            let words = self.count_words(sample);
+            total_words += words;
+            total_tokens += self.estimate_tokens(sample, words);
+            sampled_bytes += sample.len();
         }
 
-        if samples_taken > 0 {
-            // Scale up based on the ratio of sampled content to total content
-            let sample_chars: usize = samples_taken * sample_size;
-            let scale_factor = total_chars as f64 / sample_chars as f64;
-
-            let estimated_words = (total_words as f64 * scale_factor) as usize;
-            let estimated_tokens = (total_tokens as f64 * scale_factor) as usize;
-
-            (estimated_words, estimated_tokens)
-        } else {
-            // Fallback for edge cases
-            let words = self.count_words(text);
-            let tokens = self.estimate_tokens(text, words);
-            (words, tokens)
-        }
+        // Scale up by the fraction of the file that was sampled
+        let scale_factor = text.len() as f64 / sampled_bytes.max(1) as f64;
+        (
+            (total_words as f64 * scale_factor) as usize,
+            (total_tokens as f64 * scale_factor) as usize,
+        )
     }
 
     /// Estimate if content will fit in context window with additional prompt
@@ -322,12 +434,21 @@ impl TokenCounter {
     /// Estimate tokens based on model-specific patterns
     fn estimate_tokens(&self, text: &str, words: usize) -> usize {
         match self.model {
-            AiModel::Gpt4 | AiModel::Gpt4Turbo | AiModel::Gpt35 => {
-                self.estimate_gpt_tokens(text, words)
+            AiModel::Gpt4
+            | AiModel::Gpt4Turbo
+            | AiModel::Gpt35
+            | AiModel::Gpt4o
+            | AiModel::O3
+            | AiModel::O4Mini
+            | AiModel::Llama3
+            | AiModel::Llama31 => self.estimate_gpt_tokens(text, words),
+            AiModel::Claude | AiModel::ClaudeSonnet | AiModel::Claude37Sonnet => {
+                self.estimate_claude_tokens(text, words)
+            }
+            AiModel::Gemini | AiModel::GeminiFlash | AiModel::Gemini25Pro => {
+                self.estimate_gemini_tokens(text, words)
             }
-            AiModel::Claude | AiModel::ClaudeSonnet => self.estimate_claude_tokens(text, words),
-            AiModel::Gemini | AiModel::GeminiFlash => self.estimate_gemini_tokens(text, words),
-            AiModel::Generic => self.estimate_generic_tokens(words),
+            AiModel::Generic | AiModel::Custom(_) => self.estimate_generic_tokens(words),
         }
     }
 
@@ -426,6 +547,24 @@ impl TokenCounter {
     }
 }
 
+/// SplitMix64: small, fast and good enough to place sample windows
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Largest char boundary at or before `index`
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
 /// Get default token counter based on AI profile
 pub fn get_token_counter_for_profile(profile: &str) -> TokenCounter {
     let model = match profile.to_lowercase().as_str() {
@@ -449,10 +588,57 @@ mod tests {
         assert!(AiModel::parse("unknown").is_err());
     }
 
+    #[test]
+    fn test_custom_model_registry() {
+        let mut models = BTreeMap::new();
+        models.insert(
+            "my-llm".to_string(),
+            CustomModelSpec {
+                context_window: 32_000,
+                tokens_per_word: 1.4,
+            },
+        );
+
+        let model = AiModel::parse_with_registry("my-llm", &models).unwrap();
+        assert_eq!(model.as_str(), "my-llm");
+        assert_eq!(model.context_window(), 32_000);
+        assert_eq!(
+            AiModel::parse_with_registry("gpt-4", &models).unwrap(),
+            AiModel::Gpt4
+        );
+        assert!(AiModel::parse_with_registry("other", &models).is_err());
+
+        let counter = TokenCounter::new(model);
+        let count = counter.count_tokens("one two three four five six seven eight nine ten");
+        assert_eq!(count.tokens, 14);
+        assert!(count.fits_in_context);
+    }
+
+    #[test]
+    fn test_current_model_generations() {
+        assert_eq!(AiModel::parse("gpt-4o").unwrap(), AiModel::Gpt4o);
+        assert_eq!(AiModel::parse("o4-mini").unwrap(), AiModel::O4Mini);
+        assert_eq!(
+            AiModel::parse("claude-3.7-sonnet").unwrap(),
+            AiModel::Claude37Sonnet
+        );
+        assert_eq!(
+            AiModel::parse("gemini-2.5-pro").unwrap(),
+            AiModel::Gemini25Pro
+        );
+        assert_eq!(AiModel::parse("llama-3.3").unwrap(), AiModel::Llama31);
+        for model in AiModel::all() {
+            assert_eq!(AiModel::parse(model.as_str()).unwrap(), model);
+        }
+    }
+
     #[test]
     fn test_context_windows() {
         assert_eq!(AiModel::Gpt4.context_window(), 128_000);
         assert_eq!(AiModel::Claude.context_window(), 200_000);
+        assert_eq!(AiModel::O3.context_window(), 200_000);
+        assert_eq!(AiModel::Gemini.context_window(), 2_000_000);
+        assert_eq!(AiModel::Llama3.context_window(), 8_192);
     }
 
     #[test]
@@ -569,6 +755,30 @@ mod tests {
                expected_words, count.words, word_ratio);
     }
 
+    #[test]
+    fn test_seeded_stratified_sampling() {
+        // A prose header followed by a minified blob
+        let text = format!(
+            "{}{}",
+            "Plain words describe the module here. ".repeat(2000),
+            "a=1;b=[2,3];c={d:4};".repeat(4000)
+        );
+        let counter = TokenCounter::new(AiModel::Gpt4).with_sampling(8, 42);
+        let exact_words = counter.count_words(&text);
+        let first = counter.count_tokens(&text);
+        let second = counter.count_tokens(&text);
+        assert_eq!(first.tokens, second.tokens);
+        assert_eq!(first.words, second.words);
+
+        let ratio = first.words as f64 / exact_words as f64;
+        assert!(ratio > 0.7 && ratio < 1.3, "ratio {ratio:.2}");
+        for seed in 0..4 {
+            let other = TokenCounter::new(AiModel::Gpt4).with_sampling(8, seed);
+            let ratio = other.count_tokens(&text).words as f64 / exact_words as f64;
+            assert!(ratio > 0.7 && ratio < 1.3, "seed {seed}: ratio {ratio:.2}");
+        }
+    }
+
     #[test]
     fn test_new_ai_models() {
         // Test GPT-4 Turbo
@@ -586,7 +796,7 @@ mod tests {
         // Test Gemini 1.5 Pro
         let gemini = TokenCounter::new(AiModel::Gemini);
         assert_eq!(gemini.model(), AiModel::Gemini);
-        assert_eq!(gemini.model().context_window(), 1_000_000);
+        assert_eq!(gemini.model().context_window(), 2_000_000);
         assert_eq!(gemini.model().as_str(), "gemini-1.5-pro");
 
         // Test Gemini 2.0 Flash
@@ -693,7 +903,7 @@ mod tests {
         assert!(max_length_gemini > 0);
         assert!(max_length_gemini < 1_000_000 * 10); // Sanity check
 
-        // Gemini should have larger max content length due to 1M context window
+        // Gemini should have larger max content length due to 2M context window
         assert!(max_length_gemini > max_length_claude);
     }
 }
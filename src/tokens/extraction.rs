@@ -1,10 +1,19 @@
 //! Semantic token extraction from source code for AI analysis
 //!
-//! This module provides token extraction from source code for AI processing
-//! and analysis. It supports multiple tokenization strategies and can be
-//! extended for language-specific tokenization.
-
-use std::collections::HashSet;
+//! Every consumer (JSON `identifiers`, `--token-stats`, benchmarks) goes
+//! through one pipeline, so they agree on what a token is:
+//!
+//! 1. **extract**: split the content with the strategy for the file type
+//!    (programming, markup, data or text); comment lines are skipped
+//! 2. **filter**: drop blank tokens; every remaining token counts towards
+//!    the total
+//! 3. **sample**: apply the [`TokenOptions`] post-processing (first
+//!    occurrences only, with `unique`), put the tokens in the requested
+//!    [`TokenOrder`] and keep at most `max_sample` of them
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
 
 /// Token extractor for source code
 pub struct TokenExtractor;
@@ -13,25 +22,84 @@ pub struct TokenExtractor;
 #[derive(Debug, Clone)]
 pub struct TokenExtractionResult {
     pub tokens: Vec<String>,
+    /// Tokens after filtering, including repeats and those beyond the sample
     pub total_count: usize,
+    /// Most frequent identifiers, with `TokenOptions::frequencies`
+    pub frequencies: Option<Vec<TokenFrequency>>,
+}
+
+/// Order of the sampled tokens (`--token-order`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenOrder {
+    /// As they appear in the file; the sample is the first tokens
+    #[default]
+    Source,
+    /// Each distinct token once, most frequent first, ties in source order
+    Frequency,
+    /// Sorted by text; the sample is the alphabetically first tokens
+    Alpha,
+}
+
+impl TokenOrder {
+    /// Get string representation
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Source => "source",
+            Self::Frequency => "frequency",
+            Self::Alpha => "alpha",
+        }
+    }
+}
+
+/// Post-processing of extracted tokens, taken from the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenOptions {
+    /// Tokens kept in the sample
+    pub max_sample: usize,
+    /// Keep only the first occurrence of each token
+    pub unique: bool,
+    pub order: TokenOrder,
+    /// Also rank the N most frequent identifiers
+    pub frequencies: Option<usize>,
+}
+
+impl Default for TokenOptions {
+    fn default() -> Self {
+        Self {
+            max_sample: TokenExtractor::MAX_SAMPLE_SIZE,
+            unique: false,
+            order: TokenOrder::Source,
+            frequencies: None,
+        }
+    }
+}
+
+impl TokenOptions {
+    /// Every token, repeats included, in source order
+    pub const UNLIMITED: Self = Self {
+        max_sample: usize::MAX,
+        unique: false,
+        order: TokenOrder::Source,
+        frequencies: None,
+    };
 }
 
 impl TokenExtractor {
     /// Maximum number of tokens to emit in JSON output
     pub const MAX_SAMPLE_SIZE: usize = 2048;
 
-    /// Extract tokens from content for AI processing
+    /// Number of identifiers listed in token statistics by default
+    pub const DEFAULT_TOP_TOKENS: usize = 10;
+
+    /// Every token of `content`, in source order
     pub fn extract_tokens(content: &str, file_path: &str) -> Vec<String> {
-        Self::extract_tokens_with_limit(content, file_path, usize::MAX).tokens
+        Self::extract(content, file_path, TokenOptions::UNLIMITED).tokens
     }
 
-    /// Extract tokens with a maximum sample size while tracking total count
-    pub fn extract_tokens_with_limit(
-        content: &str,
-        file_path: &str,
-        max_tokens: usize,
-    ) -> TokenExtractionResult {
-        let mut accumulator = TokenAccumulator::new(max_tokens);
+    /// Run the extract → filter → sample pipeline over `content`
+    pub fn extract(content: &str, file_path: &str, options: TokenOptions) -> TokenExtractionResult {
+        let mut accumulator = TokenAccumulator::new(options);
 
         match Self::determine_tokenization_strategy(file_path) {
             TokenizationStrategy::Programming => {
@@ -49,11 +117,15 @@ impl TokenExtractor {
         }
 
         let total_count = accumulator.total_count();
+        let frequencies = options
+            .frequencies
+            .map(|top_n| accumulator.top_identifiers(top_n));
         let tokens = accumulator.finish();
 
         TokenExtractionResult {
             tokens,
             total_count,
+            frequencies,
         }
     }
 
@@ -335,8 +407,59 @@ impl TokenExtractor {
         found_keywords
     }
 
+    /// Name of the tokenization strategy used for `file_path`
+    pub fn strategy_name(file_path: &str) -> &'static str {
+        Self::determine_tokenization_strategy(file_path).as_str()
+    }
+
+    /// Token totals each strategy would produce for `content`
+    pub fn strategy_breakdown(content: &str) -> BTreeMap<&'static str, usize> {
+        let strategies = [
+            TokenizationStrategy::Programming,
+            TokenizationStrategy::Markup,
+            TokenizationStrategy::Data,
+            TokenizationStrategy::Text,
+        ];
+        strategies
+            .into_iter()
+            .map(|strategy| {
+                let mut acc = TokenAccumulator::new(TokenOptions {
+                    max_sample: 0,
+                    ..TokenOptions::UNLIMITED
+                });
+                match strategy {
+                    TokenizationStrategy::Programming => {
+                        Self::collect_programming_tokens(content, &mut acc);
+                    }
+                    TokenizationStrategy::Markup => Self::collect_markup_tokens(content, &mut acc),
+                    TokenizationStrategy::Data => Self::collect_data_tokens(content, &mut acc),
+                    TokenizationStrategy::Text => Self::collect_text_tokens(content, &mut acc),
+                }
+                (strategy.as_str(), acc.total_count())
+            })
+            .collect()
+    }
+
+    /// Full `--token-stats` report for `content`
+    pub fn token_stats_report(content: &str, file_path: &str, top_n: usize) -> TokenStatsReport {
+        let tokens = Self::extract_tokens(content, file_path);
+        TokenStatsReport {
+            file: file_path.to_string(),
+            strategy: Self::strategy_name(file_path),
+            characters: content.chars().count(),
+            words: content.split_whitespace().count(),
+            stats: Self::get_token_stats_with_top(&tokens, top_n),
+            strategies: Self::strategy_breakdown(content),
+        }
+    }
+
     /// Get token statistics
     pub fn get_token_stats(tokens: &[String]) -> TokenStats {
+        Self::get_token_stats_with_top(tokens, Self::DEFAULT_TOP_TOKENS)
+    }
+
+    /// Get token statistics listing the `top_n` most frequent identifiers
+    pub fn get_token_stats_with_top(tokens: &[String], top_n: usize) -> TokenStats {
         let unique_tokens = tokens.iter().collect::<HashSet<_>>().len();
         let avg_token_length = if tokens.is_empty() {
             0.0
@@ -358,22 +481,71 @@ impl TokenExtractor {
                 .map(std::string::String::len)
                 .min()
                 .unwrap_or(0),
+            top_identifiers: Self::top_identifiers(tokens, top_n),
+            length_histogram: tokens.iter().fold(BTreeMap::new(), |mut hist, token| {
+                *hist.entry(token.chars().count()).or_insert(0) += 1;
+                hist
+            }),
+        }
+    }
+
+    /// Most frequent identifier-like tokens, ties broken alphabetically
+    fn top_identifiers(tokens: &[String], top_n: usize) -> Vec<TokenFrequency> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for token in tokens {
+            if is_identifier_like(token) {
+                *counts.entry(token.as_str()).or_insert(0) += 1;
+            }
         }
+        rank(counts, top_n)
     }
 }
 
-/// Accumulates tokens while tracking the total count and limiting output size
+/// Identifiers and words: punctuation, numbers and string literals are
+/// stop symbols for frequency rankings
+fn is_identifier_like(token: &str) -> bool {
+    token
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+}
+
+/// The `top_n` highest counts, ties broken alphabetically
+fn rank(counts: HashMap<&str, usize>, top_n: usize) -> Vec<TokenFrequency> {
+    let mut ranked: Vec<_> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked
+        .into_iter()
+        .take(top_n)
+        .map(|(token, count)| TokenFrequency {
+            token: token.to_string(),
+            count,
+        })
+        .collect()
+}
+
+/// Filter and sample stages: counts every token while keeping the sample
+/// the options ask for, so the full token list is never held in memory.
+/// Source order keeps the first tokens as they come; the other orders
+/// count each distinct token and pick the sample at the end.
 struct TokenAccumulator {
-    max_tokens: usize,
+    options: TokenOptions,
     tokens: Vec<String>,
+    /// Tokens already sampled, with `unique`
+    seen: HashSet<String>,
+    /// Occurrences and first position of each distinct token, for the
+    /// frequency and alphabetical orders
+    counts: HashMap<String, (usize, usize)>,
     total_count: usize,
 }
 
 impl TokenAccumulator {
-    const fn new(max_tokens: usize) -> Self {
+    fn new(options: TokenOptions) -> Self {
         Self {
-            max_tokens,
+            options,
             tokens: Vec::new(),
+            seen: HashSet::new(),
+            counts: HashMap::new(),
             total_count: 0,
         }
     }
@@ -384,13 +556,65 @@ impl TokenAccumulator {
         }
 
         self.total_count += 1;
-        if self.tokens.len() < self.max_tokens {
-            self.tokens.push(token);
+        if self.options.order != TokenOrder::Source {
+            self.count(token);
+            return;
+        }
+        if self.options.frequencies.is_some() {
+            self.count(token.clone());
+        }
+        if self.tokens.len() >= self.options.max_sample {
+            return;
+        }
+        if self.options.unique && !self.seen.insert(token.clone()) {
+            return;
         }
+        self.tokens.push(token);
+    }
+
+    fn count(&mut self, token: String) {
+        let next = self.counts.len();
+        self.counts.entry(token).or_insert((0, next)).0 += 1;
+    }
+
+    /// The `top_n` most frequent identifier-like tokens seen so far
+    fn top_identifiers(&self, top_n: usize) -> Vec<TokenFrequency> {
+        let counts = self
+            .counts
+            .iter()
+            .filter(|(token, _)| is_identifier_like(token))
+            .map(|(token, (count, _))| (token.as_str(), *count))
+            .collect();
+        rank(counts, top_n)
     }
 
     fn finish(self) -> Vec<String> {
-        self.tokens
+        let max_sample = self.options.max_sample;
+        let mut distinct: Vec<(String, (usize, usize))> = self.counts.into_iter().collect();
+        match self.options.order {
+            TokenOrder::Source => self.tokens,
+            TokenOrder::Frequency => {
+                distinct.sort_by(|(_, (a_count, a_first)), (_, (b_count, b_first))| {
+                    b_count.cmp(a_count).then(a_first.cmp(b_first))
+                });
+                distinct
+                    .into_iter()
+                    .take(max_sample)
+                    .map(|(token, _)| token)
+                    .collect()
+            }
+            TokenOrder::Alpha => {
+                distinct.sort_by(|(a, _), (b, _)| a.cmp(b));
+                let unique = self.options.unique;
+                distinct
+                    .into_iter()
+                    .flat_map(|(token, (count, _))| {
+                        std::iter::repeat_n(token, if unique { 1 } else { count })
+                    })
+                    .take(max_sample)
+                    .collect()
+            }
+        }
     }
 
     const fn total_count(&self) -> usize {
@@ -407,14 +631,84 @@ enum TokenizationStrategy {
     Text,
 }
 
+impl TokenizationStrategy {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Programming => "programming",
+            Self::Markup => "markup",
+            Self::Data => "data",
+            Self::Text => "text",
+        }
+    }
+}
+
 /// Statistics about the tokenization process
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TokenStats {
     pub total_tokens: usize,
     pub unique_tokens: usize,
     pub avg_token_length: f64,
     pub longest_token: usize,
     pub shortest_token: usize,
+    /// Most frequent identifiers, highest count first
+    pub top_identifiers: Vec<TokenFrequency>,
+    /// Token length in characters → number of tokens
+    pub length_histogram: BTreeMap<usize, usize>,
+}
+
+/// Occurrence count of a single token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenFrequency {
+    pub token: String,
+    pub count: usize,
+}
+
+/// Output of `--token-stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenStatsReport {
+    pub file: String,
+    /// Strategy used for this file type
+    pub strategy: &'static str,
+    pub characters: usize,
+    /// Whitespace-separated words
+    pub words: usize,
+    #[serde(flatten)]
+    pub stats: TokenStats,
+    /// Token totals under every strategy
+    pub strategies: BTreeMap<&'static str, usize>,
+}
+
+impl TokenStatsReport {
+    /// Render a human-readable report
+    pub fn render_text(&self) -> String {
+        let stats = &self.stats;
+        let mut out = String::new();
+        let _ = writeln!(out, "Token Statistics: {}", self.file);
+        let _ = writeln!(out, "  Strategy: {}", self.strategy);
+        let _ = writeln!(out, "  Characters: {}", self.characters);
+        let _ = writeln!(out, "  Words: {}", self.words);
+        let _ = writeln!(out, "  Tokens: {}", stats.total_tokens);
+        let _ = writeln!(out, "  Unique tokens: {}", stats.unique_tokens);
+        let _ = writeln!(out, "  Average length: {:.2}", stats.avg_token_length);
+        let _ = writeln!(
+            out,
+            "  Length range: {}-{}",
+            stats.shortest_token, stats.longest_token
+        );
+        let _ = writeln!(out, "  Top identifiers:");
+        for entry in &stats.top_identifiers {
+            let _ = writeln!(out, "    {:>6}  {}", entry.count, entry.token);
+        }
+        let _ = writeln!(out, "  Length histogram:");
+        for (length, count) in &stats.length_histogram {
+            let _ = writeln!(out, "    {length:>6}  {count}");
+        }
+        let _ = writeln!(out, "  Per-strategy tokens:");
+        for (strategy, count) in &self.strategies {
+            let _ = writeln!(out, "    {strategy:<12} {count}");
+        }
+        out
+    }
 }
 
 #[cfg(test)]
@@ -424,8 +718,7 @@ mod tests {
     #[test]
     fn test_extract_programming_tokens() {
         let content = "fn main() {\n    println!(\"Hello, world!\");\n}";
-        let tokens =
-            TokenExtractor::extract_tokens_with_limit(content, "example.rs", usize::MAX).tokens;
+        let tokens = TokenExtractor::extract_tokens(content, "example.rs");
 
         assert!(tokens.contains(&"fn".to_string()));
         assert!(tokens.contains(&"main".to_string()));
@@ -437,8 +730,7 @@ mod tests {
     #[test]
     fn test_extract_markup_tokens() {
         let content = "<html><body><h1>Title</h1></body></html>";
-        let tokens =
-            TokenExtractor::extract_tokens_with_limit(content, "index.html", usize::MAX).tokens;
+        let tokens = TokenExtractor::extract_tokens(content, "index.html");
 
         assert!(tokens.contains(&"<html>".to_string()));
         assert!(tokens.contains(&"<body>".to_string()));
@@ -449,8 +741,7 @@ mod tests {
     #[test]
     fn test_extract_data_tokens() {
         let content = "\"name\": \"test\"\n\"value\": 42";
-        let tokens =
-            TokenExtractor::extract_tokens_with_limit(content, "config.json", usize::MAX).tokens;
+        let tokens = TokenExtractor::extract_tokens(content, "config.json");
 
         assert!(tokens.contains(&"name".to_string()));
         assert!(tokens.contains(&"test".to_string()));
@@ -458,6 +749,82 @@ mod tests {
         assert!(tokens.contains(&"42".to_string()));
     }
 
+    #[test]
+    fn test_pipeline_options_sample_and_dedup() {
+        let content = "let a = b;\nlet a = c;";
+        let all = TokenExtractor::extract(content, "x.rs", TokenOptions::UNLIMITED);
+        assert_eq!(all.tokens.len(), 10);
+        assert_eq!(all.total_count, 10);
+
+        let unique = TokenExtractor::extract(
+            content,
+            "x.rs",
+            TokenOptions {
+                max_sample: 5,
+                unique: true,
+                ..TokenOptions::UNLIMITED
+            },
+        );
+        assert_eq!(unique.tokens, ["let", "a", "=", "b", ";"]);
+        assert_eq!(unique.total_count, 10);
+
+        let sampled = TokenExtractor::extract(
+            content,
+            "x.rs",
+            TokenOptions {
+                max_sample: 3,
+                ..TokenOptions::UNLIMITED
+            },
+        );
+        assert_eq!(sampled.tokens, ["let", "a", "="]);
+        assert_eq!(sampled.total_count, 10);
+
+        let options = |order, unique| TokenOptions {
+            max_sample: 4,
+            unique,
+            order,
+            frequencies: None,
+        };
+        let frequency =
+            TokenExtractor::extract(content, "x.rs", options(TokenOrder::Frequency, false));
+        assert_eq!(frequency.tokens, ["let", "a", "=", ";"]);
+        let alpha = TokenExtractor::extract(content, "x.rs", options(TokenOrder::Alpha, false));
+        assert_eq!(alpha.tokens, [";", ";", "=", "="]);
+        let alpha = TokenExtractor::extract(content, "x.rs", options(TokenOrder::Alpha, true));
+        assert_eq!(alpha.tokens, [";", "=", "a", "b"]);
+        assert_eq!(alpha.total_count, 10);
+        // The JSON sample and the unlimited list come from the same pipeline
+        assert_eq!(TokenExtractor::extract_tokens(content, "x.rs"), all.tokens);
+    }
+
+    #[test]
+    fn test_frequencies_skip_stop_symbols() {
+        let content = "let total = total + count;\nlet count = 1;\nprint(\"total\");";
+        let result = TokenExtractor::extract(
+            content,
+            "x.rs",
+            TokenOptions {
+                frequencies: Some(3),
+                ..TokenOptions::default()
+            },
+        );
+        let ranked: Vec<(&str, usize)> = result
+            .frequencies
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|entry| (entry.token.as_str(), entry.count))
+            .collect();
+        assert_eq!(ranked, [("count", 2), ("let", 2), ("total", 2)]);
+        // The sample itself is unaffected
+        assert_eq!(result.tokens[0], "let");
+        assert!(
+            TokenExtractor::extract(content, "x.rs", TokenOptions::default())
+                .frequencies
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_determine_strategy() {
         assert_eq!(
@@ -505,11 +872,44 @@ mod tests {
         assert_eq!(stats.shortest_token, 1); // "a"
     }
 
+    #[test]
+    fn test_token_stats_top_identifiers_and_histogram() {
+        let tokens: Vec<String> = ["x", "y", "x", "(", "(", "(", "longer", "x", "y"]
+            .iter()
+            .map(|t| (*t).to_string())
+            .collect();
+        let stats = TokenExtractor::get_token_stats_with_top(&tokens, 2);
+        assert_eq!(
+            stats.top_identifiers,
+            [
+                TokenFrequency {
+                    token: "x".to_string(),
+                    count: 3
+                },
+                TokenFrequency {
+                    token: "y".to_string(),
+                    count: 2
+                }
+            ]
+        );
+        assert_eq!(stats.length_histogram[&1], 8);
+        assert_eq!(stats.length_histogram[&6], 1);
+    }
+
+    #[test]
+    fn test_token_stats_report() {
+        let report = TokenExtractor::token_stats_report("fn main() { main(); }", "a.rs", 5);
+        assert_eq!(report.strategy, "programming");
+        assert_eq!(report.stats.top_identifiers[0].token, "main");
+        assert_eq!(report.strategies.len(), 4);
+        assert!(report.strategies["programming"] > report.strategies["text"]);
+        assert!(report.render_text().contains("Top identifiers:"));
+    }
+
     #[test]
     fn test_string_handling() {
         let content = r#"let message = "Hello, world!";"#;
-        let tokens =
-            TokenExtractor::extract_tokens_with_limit(content, "example.rs", usize::MAX).tokens;
+        let tokens = TokenExtractor::extract_tokens(content, "example.rs");
 
         assert!(tokens.contains(&"let".to_string()));
         assert!(tokens.contains(&"message".to_string()));
@@ -519,8 +919,7 @@ mod tests {
     #[test]
     fn test_comment_filtering() {
         let content = "// This is a comment\nfn main() {}";
-        let tokens =
-            TokenExtractor::extract_tokens_with_limit(content, "example.rs", usize::MAX).tokens;
+        let tokens = TokenExtractor::extract_tokens(content, "example.rs");
 
         assert!(tokens.contains(&"fn".to_string()));
         assert!(tokens.contains(&"main".to_string()));
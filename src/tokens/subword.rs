@@ -0,0 +1,164 @@
+//! Exact subword tokens for embedding pipelines (`--tokenize=model`)
+//!
+//! Token counts elsewhere are estimates. With the `exact-tokenizer` feature,
+//! OpenAI models are tokenized with their real BPE vocabularies, and the
+//! token strings and ids are cut into fixed-size chunks that can be fed to
+//! an embedding API as they are.
+
+use super::counting::AiModel;
+use crate::error::{BatlessError, BatlessResult};
+use serde::Serialize;
+
+/// Tokens per chunk unless `--chunk-tokens` says otherwise
+pub const DEFAULT_CHUNK_TOKENS: usize = 512;
+
+/// One chunk of the file's token sequence
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubwordChunk {
+    pub file: String,
+    pub model: String,
+    /// BPE vocabulary, e.g. `o200k_base`
+    pub encoding: &'static str,
+    pub chunk_index: usize,
+    /// Position of the chunk's first token in the whole file
+    pub token_start: usize,
+    pub token_count: usize,
+    pub ids: Vec<u32>,
+    /// Each token's text; a token holding part of a multi-byte character
+    /// shows it as U+FFFD
+    pub tokens: Vec<String>,
+    /// The chunk's text, decoded from its tokens
+    pub text: String,
+}
+
+/// BPE vocabulary of `model`, for the models whose tokenizer is public
+pub const fn encoding_name(model: AiModel) -> Option<&'static str> {
+    match model {
+        AiModel::Gpt4 | AiModel::Gpt4Turbo | AiModel::Gpt35 => Some("cl100k_base"),
+        AiModel::Gpt4o | AiModel::O3 | AiModel::O4Mini => Some("o200k_base"),
+        _ => None,
+    }
+}
+
+/// Fail before any work is done when this build has no exact tokenizer
+pub fn ensure_available() -> BatlessResult<()> {
+    if cfg!(feature = "exact-tokenizer") {
+        return Ok(());
+    }
+    Err(BatlessError::config_error_with_help(
+        "--tokenize=model needs the exact tokenizer, which this build does not include".to_string(),
+        Some("Rebuild with --features exact-tokenizer".to_string()),
+    ))
+}
+
+/// `content` tokenized for `model`, in chunks of `chunk_tokens` tokens
+pub fn tokenize_chunks(
+    content: &str,
+    file: &str,
+    model: AiModel,
+    chunk_tokens: usize,
+) -> BatlessResult<Vec<SubwordChunk>> {
+    ensure_available()?;
+    if chunk_tokens == 0 {
+        return Err(BatlessError::config_error_with_help(
+            "--chunk-tokens must be greater than 0".to_string(),
+            Some(format!("The default is {DEFAULT_CHUNK_TOKENS}")),
+        ));
+    }
+    let Some(encoding) = encoding_name(model) else {
+        return Err(BatlessError::config_error_with_help(
+            format!("No exact tokenizer is available for {}", model.as_str()),
+            Some(
+                "Select an OpenAI model, e.g. --ai-model gpt-4o (o200k_base) or \
+                 --ai-model gpt-4 (cl100k_base)"
+                    .to_string(),
+            ),
+        ));
+    };
+
+    let tokens = encode(encoding, content);
+    Ok(tokens
+        .chunks(chunk_tokens)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let bytes: Vec<u8> = chunk.iter().flat_map(|(_, b)| b.iter().copied()).collect();
+            SubwordChunk {
+                file: file.to_string(),
+                model: model.as_str().to_string(),
+                encoding,
+                chunk_index: index,
+                token_start: index * chunk_tokens,
+                token_count: chunk.len(),
+                ids: chunk.iter().map(|(id, _)| *id).collect(),
+                tokens: chunk
+                    .iter()
+                    .map(|(_, b)| String::from_utf8_lossy(b).into_owned())
+                    .collect(),
+                text: String::from_utf8_lossy(&bytes).into_owned(),
+            }
+        })
+        .collect())
+}
+
+/// Token ids of `content` with the bytes each one stands for
+#[cfg(feature = "exact-tokenizer")]
+fn encode(encoding: &str, content: &str) -> Vec<(u32, Vec<u8>)> {
+    let bpe = if encoding == "o200k_base" {
+        tiktoken_rs::o200k_base_singleton()
+    } else {
+        tiktoken_rs::cl100k_base_singleton()
+    };
+    let ids = bpe.encode_ordinary(content);
+    ids.iter()
+        .copied()
+        .zip(bpe._decode_native_and_split(ids.clone()))
+        .collect()
+}
+
+#[cfg(not(feature = "exact-tokenizer"))]
+fn encode(_encoding: &str, _content: &str) -> Vec<(u32, Vec<u8>)> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encodings_by_model() {
+        assert_eq!(encoding_name(AiModel::Gpt4o), Some("o200k_base"));
+        assert_eq!(encoding_name(AiModel::Gpt35), Some("cl100k_base"));
+        assert_eq!(encoding_name(AiModel::Claude), None);
+    }
+
+    #[cfg(not(feature = "exact-tokenizer"))]
+    #[test]
+    fn test_requires_feature() {
+        let error = tokenize_chunks("hi", "a.txt", AiModel::Gpt4o, 8).unwrap_err();
+        assert!(error.to_string().contains("exact tokenizer"), "{error}");
+    }
+
+    #[cfg(feature = "exact-tokenizer")]
+    #[test]
+    fn test_chunks_cover_every_token() {
+        let content = "fn main() {\n    println!(\"héllo\");\n}\n";
+        let chunks = tokenize_chunks(content, "main.rs", AiModel::Gpt4o, 4).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.token_count <= 4));
+        assert_eq!(chunks[1].token_start, 4);
+        let text: String = chunks
+            .iter()
+            .flat_map(|chunk| chunk.tokens.clone())
+            .collect();
+        assert_eq!(text, content);
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.text.as_str())
+                .collect::<String>(),
+            content
+        );
+        assert!(tokenize_chunks(content, "main.rs", AiModel::Claude, 4).is_err());
+        assert!(tokenize_chunks(content, "main.rs", AiModel::Gpt4o, 0).is_err());
+    }
+}
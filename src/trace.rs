@@ -0,0 +1,414 @@
+//! Stack-trace driven context packs (`--from-trace FILE`)
+//!
+//! Parses Rust panics and backtraces, Python tracebacks, Node and Java stack
+//! traces, then extracts an `--around` window for every referenced frame so
+//! a whole failure can be handed to an AI assistant in one go.
+
+use crate::around::AroundSpec;
+use crate::config::BatlessConfig;
+use crate::processor::FileProcessor;
+use crate::test_code;
+use regex::Regex;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Source roots tried for Java/Kotlin frames, which only name the file
+const JVM_SOURCE_ROOTS: &[&str] = &["", "src/main/java", "src/main/kotlin", "src"];
+
+/// A `file:line` location referenced by a stack trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceFrame {
+    pub path: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+struct TracePatterns {
+    python: Regex,
+    jvm: Regex,
+    rust_panic: Regex,
+    rust_symbol: Regex,
+    located: Regex,
+}
+
+fn patterns() -> &'static TracePatterns {
+    static PATTERNS: OnceLock<TracePatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let compile = |pattern| Regex::new(pattern).expect("trace patterns are valid");
+        TracePatterns {
+            // File "app/main.py", line 12, in handler
+            python: compile(r#"^\s*File "([^"]+)", line (\d+)(?:, in (\S+))?"#),
+            // at com.example.Service.run(Service.java:42)
+            jvm: compile(r"^\s*at ([\w$.<>]+)\(([\w$-]+\.(?:java|kt|scala|groovy)):(\d+)\)"),
+            // thread 'main' panicked at src/main.rs:10:5:
+            rust_panic: compile(r"panicked at ([^\s:]+):(\d+):\d+"),
+            //   12: myapp::run
+            rust_symbol: compile(r"^\s*\d+: (\S+)"),
+            // at run (/app/index.js:3:9), at /app/index.js:3:9, at ./src/main.rs:10:5
+            located: compile(r"^\s*at (?:(.+?) \()?(?:file://)?([^\s()]+?):(\d+):\d+\)?\s*$"),
+        }
+    })
+}
+
+/// Compile the frame patterns now instead of on first use
+pub(crate) fn warm_up() {
+    patterns();
+}
+
+/// Extract frames from `text` in order of appearance, without duplicates
+pub fn parse_trace(text: &str) -> Vec<TraceFrame> {
+    let patterns = patterns();
+    let mut frames: Vec<TraceFrame> = Vec::new();
+    // Rust backtraces name the function on the line before its location
+    let mut pending_symbol: Option<String> = None;
+
+    for line in text.lines() {
+        let frame = if let Some(caps) = patterns.python.captures(line) {
+            frame(&caps[1], &caps[2], caps.get(3).map(|m| m.as_str()))
+        } else if let Some(caps) = patterns.jvm.captures(line) {
+            frame(&jvm_path(&caps[1], &caps[2]), &caps[3], Some(&caps[1]))
+        } else if let Some(caps) = patterns.rust_panic.captures(line) {
+            frame(&caps[1], &caps[2], None)
+        } else if let Some(caps) = patterns.located.captures(line) {
+            let function = caps
+                .get(1)
+                .map(|m| m.as_str().to_string())
+                .or_else(|| pending_symbol.take());
+            frame(&caps[2], &caps[3], function.as_deref())
+        } else {
+            pending_symbol = patterns
+                .rust_symbol
+                .captures(line)
+                .map(|caps| caps[1].to_string());
+            continue;
+        };
+        pending_symbol = None;
+        let Some(frame) = frame else { continue };
+        // A panic location is usually repeated by the backtrace, which also
+        // names the function
+        match frames
+            .iter_mut()
+            .find(|f| f.path == frame.path && f.line == frame.line)
+        {
+            Some(existing) => {
+                if existing.function.is_none() {
+                    existing.function = frame.function;
+                }
+            }
+            None => frames.push(frame),
+        }
+    }
+    frames
+}
+
+fn frame(path: &str, line: &str, function: Option<&str>) -> Option<TraceFrame> {
+    let line = line.parse().ok().filter(|&n| n > 0)?;
+    Some(TraceFrame {
+        path: path.strip_prefix("./").unwrap_or(path).to_string(),
+        line,
+        function: function.map(String::from),
+    })
+}
+
+/// Relative source path for a JVM frame: `com.example.Service.run` in
+/// `Service.java` lives at `com/example/Service.java`
+fn jvm_path(qualified: &str, file: &str) -> String {
+    let segments: Vec<&str> = qualified.split('.').collect();
+    // Drop the method and class names; what remains is the package
+    let package = &segments[..segments.len().saturating_sub(2)];
+    package
+        .iter()
+        .copied()
+        .chain(std::iter::once(file))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Locate a frame's file on disk relative to `base`
+pub fn resolve_frame(frame: &TraceFrame, base: &Path) -> Option<PathBuf> {
+    let path = Path::new(&frame.path);
+    if path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let is_jvm = Path::new(&frame.path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e, "java" | "kt" | "scala" | "groovy"));
+    let roots: &[&str] = if is_jvm { JVM_SOURCE_ROOTS } else { &[""] };
+    roots
+        .iter()
+        .map(|root| base.join(root).join(path))
+        .find(|candidate| candidate.is_file())
+}
+
+/// One frame of a context pack with its surrounding code
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedFrame {
+    pub index: usize,
+    #[serde(flatten)]
+    pub frame: TraceFrame,
+    /// File the frame resolved to, if it exists locally
+    pub resolved: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enclosing: Option<serde_json::Value>,
+    pub lines: Vec<String>,
+    /// Why no code could be extracted for this frame
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// All frames of a trace with their extracted code
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextPack {
+    pub trace: String,
+    pub context: usize,
+    pub frames: Vec<PackedFrame>,
+    pub resolved_frames: usize,
+}
+
+impl ContextPack {
+    /// Render frames as numbered code blocks separated by headers
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for (i, packed) in self.frames.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            let frame = &packed.frame;
+            let _ = write!(out, "=== #{} {}:{}", packed.index, frame.path, frame.line);
+            if let Some(function) = &frame.function {
+                let _ = write!(out, " in {function}");
+            }
+            out.push_str(" ===\n");
+            if let Some(error) = &packed.error {
+                let _ = writeln!(out, "// {error}");
+                continue;
+            }
+            if let Some(enclosing) = &packed.enclosing {
+                let kind = enclosing["kind"].as_str().unwrap_or_default();
+                let name = enclosing["name"].as_str().unwrap_or_default();
+                let _ = writeln!(out, "// enclosing {kind} {name}");
+            }
+            let start = packed.start.unwrap_or(1);
+            for (offset, line) in packed.lines.iter().enumerate() {
+                let number = start + offset;
+                let marker = if number == frame.line { '>' } else { ' ' };
+                let _ = writeln!(out, "{marker}{number:5}\t{line}");
+            }
+        }
+        let _ = writeln!(
+            out,
+            "\n// {} of {} frame(s) resolved",
+            self.resolved_frames,
+            self.frames.len()
+        );
+        out
+    }
+}
+
+/// Extract `context` lines around every frame, resolving paths against `base`
+pub fn build_context_pack(
+    trace: &str,
+    frames: Vec<TraceFrame>,
+    base: &Path,
+    config: &BatlessConfig,
+    context: usize,
+) -> ContextPack {
+    let frames: Vec<PackedFrame> = frames
+        .into_iter()
+        .enumerate()
+        .map(|(i, frame)| pack_frame(i + 1, frame, base, config, context))
+        .collect();
+    ContextPack {
+        trace: trace.to_string(),
+        context,
+        resolved_frames: frames.iter().filter(|f| f.error.is_none()).count(),
+        frames,
+    }
+}
+
+fn pack_frame(
+    index: usize,
+    frame: TraceFrame,
+    base: &Path,
+    config: &BatlessConfig,
+    context: usize,
+) -> PackedFrame {
+    let mut packed = PackedFrame {
+        index,
+        resolved: None,
+        language: None,
+        start: None,
+        end: None,
+        enclosing: None,
+        lines: Vec::new(),
+        error: None,
+        frame,
+    };
+    let Some(path) = resolve_frame(&packed.frame, base) else {
+        packed.error = Some("file not found locally".to_string());
+        return packed;
+    };
+    let path = path.display().to_string();
+    let spec = AroundSpec {
+        line: packed.frame.line,
+        context,
+    };
+    let frame_config = config.clone().with_around(Some(spec));
+    match FileProcessor::process_file(&path, &frame_config) {
+        Ok(info)
+            if config.no_tests
+                && test_code::is_test_location(
+                    &path,
+                    info.language.as_deref(),
+                    packed.frame.line,
+                ) =>
+        {
+            packed.error = Some("test code, left out by --no-tests".to_string());
+        }
+        Ok(info) => {
+            if let Some(window) = &info.window {
+                packed.start = Some(window.start);
+                packed.end = Some(window.end);
+                packed.enclosing = window
+                    .enclosing
+                    .as_ref()
+                    .map(|item| serde_json::json!(item.record()));
+            }
+            packed.language = info.language;
+            packed.lines = info.lines;
+        }
+        Err(e) => packed.error = Some(e.to_string()),
+    }
+    packed.resolved = Some(path);
+    packed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_common_formats() {
+        let trace = r#"thread 'main' panicked at src/lib.rs:10:5:
+boom
+stack backtrace:
+   2: myapp::inner
+             at ./src/lib.rs:10:5
+   3: myapp::run
+             at ./src/main.rs:20:9
+Traceback (most recent call last):
+  File "app/main.py", line 12, in handler
+    raise ValueError()
+TypeError: x is not a function
+    at render (/srv/app/view.js:3:9)
+    at /srv/app/index.js:7:1
+Exception in thread "main" java.lang.IllegalStateException
+	at com.example.Service.run(Service.java:42)
+	at com.example.Service.run(Service.java:42)
+"#;
+        let frames = parse_trace(trace);
+        let summary: Vec<(&str, usize, Option<&str>)> = frames
+            .iter()
+            .map(|f| (f.path.as_str(), f.line, f.function.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("src/lib.rs", 10, Some("myapp::inner")),
+                ("src/main.rs", 20, Some("myapp::run")),
+                ("app/main.py", 12, Some("handler")),
+                ("/srv/app/view.js", 3, Some("render")),
+                ("/srv/app/index.js", 7, None),
+                (
+                    "com/example/Service.java",
+                    42,
+                    Some("com.example.Service.run")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pack_resolves_frames_and_reports_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let java = dir.path().join("src/main/java/com/example");
+        fs::create_dir_all(&java).unwrap();
+        fs::write(java.join("Service.java"), "a\nb\nc\nd\ne\n").unwrap();
+
+        let frames = vec![
+            TraceFrame {
+                path: "com/example/Service.java".to_string(),
+                line: 3,
+                function: None,
+            },
+            TraceFrame {
+                path: "missing.py".to_string(),
+                line: 1,
+                function: None,
+            },
+        ];
+        let pack = build_context_pack(
+            "trace.txt",
+            frames,
+            dir.path(),
+            &BatlessConfig::default(),
+            1,
+        );
+        assert_eq!(pack.resolved_frames, 1);
+        assert_eq!(pack.frames[0].lines, ["b", "c", "d"]);
+        assert_eq!(pack.frames[0].start, Some(2));
+        assert!(pack.frames[1].error.is_some());
+        let text = pack.render_text();
+        assert!(text.contains(">    3\tc"));
+        assert!(text.contains("1 of 2 frame(s) resolved"));
+    }
+
+    #[test]
+    fn test_pack_skips_test_frames_with_no_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("lib.rs"),
+            "fn run() {
+    panic!();
+}
+
+#[test]
+fn check() {
+    run();
+}
+",
+        )
+        .unwrap();
+        let frame = |line| TraceFrame {
+            path: "lib.rs".to_string(),
+            line,
+            function: None,
+        };
+        let config = BatlessConfig::default().with_no_tests(true);
+        let pack = build_context_pack(
+            "trace.txt",
+            vec![frame(2), frame(7)],
+            dir.path(),
+            &config,
+            0,
+        );
+        assert_eq!(pack.frames[0].lines, ["    panic!();"]);
+        assert!(pack.frames[1].lines.is_empty());
+        assert!(pack.frames[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("--no-tests"));
+    }
+}
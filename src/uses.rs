@@ -0,0 +1,169 @@
+//! Intra-file references (`--uses NAME`)
+//!
+//! Lists every identifier named NAME in a file with its position and the
+//! function or type it appears in, found through the tree-sitter parse so
+//! strings and comments that merely mention the name are left out.
+
+use crate::around::innermost_enclosing;
+use crate::ast_summarizer::AstSummarizer;
+use crate::error::{BatlessError, BatlessResult};
+use crate::formatters::ast_formatter::AstFormatter;
+use crate::summary::SummaryLevel;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// One occurrence of the identifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolUse {
+    /// 1-based line number in the file
+    pub line: usize,
+    /// 1-based column of the first character
+    pub column: usize,
+    /// Qualified name of the innermost enclosing function or type
+    pub enclosing: Option<String>,
+    /// Whether this occurrence names the item being defined
+    pub definition: bool,
+    /// The source line, trimmed
+    pub text: String,
+}
+
+impl SymbolUse {
+    /// One-line text form: `line 12:9 in Config::load: let raw = read(path)?;`
+    pub fn render(&self) -> String {
+        let location = match &self.enclosing {
+            Some(enclosing) => format!("line {}:{} in {enclosing}", self.line, self.column),
+            None => format!("line {}:{}", self.line, self.column),
+        };
+        let marker = if self.definition { " (definition)" } else { "" };
+        format!("{location}{marker}: {}", self.text)
+    }
+}
+
+/// Whether `name` can be an identifier; qualified paths are not
+pub fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Occurrences of `name` in `lines`, which start at line `first_line`
+pub fn find_uses(
+    lines: &[String],
+    language: Option<&str>,
+    name: &str,
+    first_line: usize,
+) -> BatlessResult<Vec<SymbolUse>> {
+    let content = lines.join("\n");
+    let Some((tree, _)) = AstFormatter::parse_to_tree(&content, language) else {
+        return Err(BatlessError::config_error_with_help(
+            format!(
+                "--uses needs a tree-sitter grammar, and {} has none",
+                language.unwrap_or("this input")
+            ),
+            Some(format!(
+                "Use grep -nw {name} for languages other than Rust, Python, JavaScript and \
+                 TypeScript, or configure a runtime grammar"
+            )),
+        ));
+    };
+    let items = AstSummarizer::extract_summary(&content, language, SummaryLevel::Standard);
+    let mut nodes = Vec::new();
+    collect_identifiers(tree.root_node(), &content, name, &mut nodes);
+
+    Ok(nodes
+        .into_iter()
+        .map(|node| {
+            let row = node.start_position().row;
+            let line = row + 1;
+            let definition = node
+                .parent()
+                .and_then(|parent| parent.child_by_field_name("name"))
+                .is_some_and(|named| named.id() == node.id());
+            // A definition is reported in its container, not in itself
+            let enclosing = innermost_enclosing(
+                items.iter().filter(|item| {
+                    !(definition && item.line_number == line && item.name.as_deref() == Some(name))
+                }),
+                line,
+            )
+            .map(|item| {
+                item.qualified_name
+                    .clone()
+                    .or_else(|| item.name.clone())
+                    .unwrap_or_else(|| item.kind.clone())
+            });
+            SymbolUse {
+                line: line + first_line - 1,
+                column: node.start_position().column + 1,
+                enclosing,
+                definition,
+                text: lines.get(row).map_or("", |l| l.trim()).to_string(),
+            }
+        })
+        .collect())
+}
+
+/// Identifier leaves spelled `name`, in source order; `type_identifier`,
+/// `field_identifier` and the like count too
+fn collect_identifiers<'t>(node: Node<'t>, content: &str, name: &str, out: &mut Vec<Node<'t>>) {
+    if node.kind().ends_with("identifier")
+        && node
+            .utf8_text(content.as_bytes())
+            .is_ok_and(|text| text == name)
+    {
+        out.push(node);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_identifiers(child, content, name, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_uses_skip_strings_and_comments() {
+        let source = lines(
+            "struct Config;\n\nimpl Config {\n    fn load() -> Config {\n        // Config is built here\n        println!(\"Config\");\n        Config\n    }\n}\n",
+        );
+        let uses = find_uses(&source, Some("Rust"), "Config", 1).unwrap();
+        let found: Vec<(usize, usize, bool)> = uses
+            .iter()
+            .map(|u| (u.line, u.column, u.definition))
+            .collect();
+        assert_eq!(
+            found,
+            [(1, 8, true), (3, 6, false), (4, 18, false), (7, 9, false)]
+        );
+        assert_eq!(uses[0].enclosing, None);
+        assert_eq!(uses[2].enclosing.as_deref(), Some("Config::load"));
+        assert_eq!(uses[3].render(), "line 7:9 in Config::load: Config");
+    }
+
+    #[test]
+    fn test_python_definition_and_offset() {
+        let source = lines(
+            "def helper(x):\n    return x\n\nclass Api:\n    def get(self):\n        return helper(1)\n",
+        );
+        let uses = find_uses(&source, Some("Python"), "helper", 10).unwrap();
+        assert_eq!(uses.len(), 2);
+        assert_eq!(uses[0].render(), "line 10:5 (definition): def helper(x):");
+        assert_eq!(uses[1].render(), "line 15:16 in Api.get: return helper(1)");
+        assert!(find_uses(&source, None, "helper", 1).is_err());
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("parse_config") && is_identifier("$el"));
+        assert!(!is_identifier("Config::load") && !is_identifier("1x") && !is_identifier(""));
+    }
+}
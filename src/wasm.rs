@@ -0,0 +1,102 @@
+//! WebAssembly-friendly entry point (`wasm` feature)
+//!
+//! Browser-based viewers and VS Code web extensions have no filesystem, no
+//! stdin and no config discovery, so this API takes everything as strings:
+//! the content, a logical filename for language detection, and the
+//! configuration as JSON. The result is always a JSON string, with failures
+//! reported as `{"error": ..., "code": ...}` instead of a Rust error type.
+//!
+//! The functions are plain Rust; bind them with `wasm-bindgen` (or any other
+//! glue) in the embedding crate when targeting `wasm32-unknown-unknown`.
+//! Build with `--no-default-features --features wasm` for that target: the
+//! tree-sitter grammars and the user-directory lookups are native-only, so
+//! summaries come from the regex fallback there.
+
+use crate::config::BatlessConfig;
+use crate::error::{BatlessError, BatlessResult};
+use crate::formatter::{OutputFormatter, OutputMode};
+use crate::processor::FileProcessor;
+use serde_json::json;
+
+/// Run the batless pipeline over `content` and return JSON output.
+///
+/// `config_json` holds any subset of the config file keys (e.g.
+/// `{"max_lines": 500, "summary_level": "standard"}`); an empty string uses
+/// the defaults.
+pub fn process_content(content: &str, filename: &str, config_json: &str) -> String {
+    match try_process_content(content, filename, config_json) {
+        Ok(output) => output,
+        Err(e) => json!({
+            "error": e.to_string(),
+            "code": e.error_code().as_str(),
+        })
+        .to_string(),
+    }
+}
+
+fn try_process_content(content: &str, filename: &str, config_json: &str) -> BatlessResult<String> {
+    let config = parse_config(config_json)?;
+    let file_info = FileProcessor::process_content(content, filename, &config)?;
+    OutputFormatter::format_output(&file_info, filename, &config, OutputMode::Json)
+}
+
+fn parse_config(config_json: &str) -> BatlessResult<BatlessConfig> {
+    if config_json.trim().is_empty() {
+        return Ok(BatlessConfig::default());
+    }
+    let invalid = |e: serde_json::Error| {
+        BatlessError::config_error_with_help(
+            format!("Invalid config JSON: {e}"),
+            Some("Use the same keys as a batless JSON config file".to_string()),
+        )
+    };
+    let value: serde_json::Value = serde_json::from_str(config_json).map_err(invalid)?;
+    BatlessConfig::reject_file_keys(&value)?;
+    let config: BatlessConfig = serde_json::from_value(value).map_err(invalid)?;
+    config.validate()?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_content_returns_json() {
+        let output = process_content(
+            "fn main() {}\nfn helper() {}\n",
+            "main.rs",
+            r#"{"max_lines": 1}"#,
+        );
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["language"], "Rust");
+        assert_eq!(value["lines"], json!(["fn main() {}"]));
+        assert_eq!(value["truncated_by_lines"], true);
+    }
+
+    #[test]
+    fn test_errors_are_reported_as_json() {
+        let output = process_content("x", "x.txt", r#"{"max_lines": "many"}"#);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["code"], "E302");
+
+        let output = process_content("x", "x.txt", r#"{"max_lines": 0}"#);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(value["error"].as_str().unwrap().contains("max_lines"));
+    }
+
+    #[test]
+    fn test_file_reading_keys_are_refused() {
+        for config in [
+            r#"{"annotations": "/etc/passwd"}"#,
+            r#"{"sarif": "/etc/shadow"}"#,
+            r#"{"grammar_dir": "/tmp"}"#,
+            r#"{"grammars": {"Gleam": "gleam"}}"#,
+        ] {
+            let output = process_content("x", "x.rs", config);
+            let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+            assert_eq!(value["code"], "E302", "{config}");
+            assert!(value["error"].as_str().unwrap().contains("not accepted"));
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Team-shared workspace configuration (`batless.workspace.toml`)
+//!
+//! A workspace file at the repository root holds settings for everyone
+//! working in it, named profiles chosen by file pattern, and settings that
+//! are locked. It is the lowest config layer: personal and project config
+//! files and command-line options apply on top, except for locked settings,
+//! which are put back to the workspace value afterwards.
+//!
+//! ```toml
+//! locked = ["redact"]
+//!
+//! [settings]
+//! redact = true
+//!
+//! [profiles.docs]
+//! summary_level = "Minimal"
+//!
+//! [[rules]]
+//! pattern = "**/*.md"
+//! profile = "docs"
+//! ```
+
+use crate::config::BatlessConfig;
+use crate::config_check::{self, ConfigFileKind};
+use crate::error::{BatlessError, BatlessResult, ConfigurationWarning};
+use crate::language::LanguageDetector;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name looked up in the input's directory and its ancestors
+pub const WORKSPACE_FILE: &str = "batless.workspace.toml";
+
+/// Contents of a workspace file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceFile {
+    /// Config keys that user files and options cannot change
+    #[serde(default)]
+    pub locked: Vec<String>,
+    /// Settings for every file in the workspace
+    #[serde(default)]
+    pub settings: toml::Table,
+    /// Named sets of settings, applied through `rules`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, toml::Table>,
+    /// Profiles applied to matching files, in order
+    #[serde(default)]
+    pub rules: Vec<WorkspaceRule>,
+}
+
+/// `[[rules]]` entry: files matching `pattern` use `profile`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceRule {
+    pub pattern: String,
+    pub profile: String,
+}
+
+/// A loaded workspace file and the directory it applies to
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub path: PathBuf,
+    pub root: PathBuf,
+    pub file: WorkspaceFile,
+}
+
+impl Workspace {
+    /// Nearest workspace file in `start` or its ancestors
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+        start
+            .ancestors()
+            .map(|dir| dir.join(WORKSPACE_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// Load and check a workspace file; unknown setting keys are returned as
+    /// warnings, while unknown profiles and locked keys are errors
+    pub fn load(path: &Path) -> BatlessResult<(Self, Vec<ConfigurationWarning>)> {
+        let error = |message: String, help: &str| {
+            BatlessError::config_error_with_help(
+                format!("Invalid workspace file '{}': {message}", path.display()),
+                Some(help.to_string()),
+            )
+        };
+        let content = fs::read_to_string(path)
+            .map_err(|e| error(e.to_string(), "Check that the file is readable"))?;
+        let file: WorkspaceFile = toml::from_str(&content).map_err(|e| {
+            error(
+                e.to_string(),
+                "Use locked, [settings], [profiles.<name>] and [[rules]] with pattern and profile",
+            )
+        })?;
+
+        let known: Vec<String> = config_check::known_keys(ConfigFileKind::Config)
+            .into_iter()
+            .collect();
+        for key in &file.locked {
+            if !known.contains(key) {
+                let help = BatlessError::suggest_similar_strings(key, &known)
+                    .into_iter()
+                    .next()
+                    .map_or_else(
+                        || "Lock keys of the config file, such as max_lines".to_string(),
+                        |suggestion| format!("Did you mean '{suggestion}'?"),
+                    );
+                return Err(error(format!("cannot lock unknown key '{key}'"), &help));
+            }
+        }
+        for rule in &file.rules {
+            if !file.profiles.contains_key(&rule.profile) {
+                let names: Vec<&str> = file.profiles.keys().map(String::as_str).collect();
+                return Err(error(
+                    format!(
+                        "rule '{}' uses unknown profile '{}'",
+                        rule.pattern, rule.profile
+                    ),
+                    &format!("Defined profiles: {}", names.join(", ")),
+                ));
+            }
+        }
+
+        let mut warnings = Vec::new();
+        for table in std::iter::once(&file.settings).chain(file.profiles.values()) {
+            to_config(table)
+                .map_err(|e| error(e, "Settings take the same keys as batless.toml"))?;
+            let keys: Vec<String> = table.keys().cloned().collect();
+            warnings.extend(config_check::key_warnings(
+                path,
+                &keys,
+                ConfigFileKind::Config,
+            ));
+        }
+
+        let root = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+        let workspace = Self {
+            path: path.to_path_buf(),
+            root,
+            file,
+        };
+        Ok((workspace, warnings))
+    }
+
+    /// Names of the profiles whose rules match `file`, in rule order
+    pub fn profiles_for(&self, file: Option<&Path>) -> Vec<&str> {
+        let Some(relative) = file.and_then(|file| self.relative_path(file)) else {
+            return Vec::new();
+        };
+        self.file
+            .rules
+            .iter()
+            .filter(|rule| rule_matches(&rule.pattern, &relative))
+            .map(|rule| rule.profile.as_str())
+            .collect()
+    }
+
+    /// Workspace settings for `file`: `[settings]`, then each matching
+    /// profile, later ones winning key by key
+    pub fn config_for(&self, file: Option<&Path>) -> BatlessResult<BatlessConfig> {
+        let mut table = self.file.settings.clone();
+        for profile in self.profiles_for(file) {
+            table.extend(self.file.profiles[profile].clone());
+        }
+        to_config(&table).map_err(|e| {
+            BatlessError::config_error_with_help(
+                format!("Invalid workspace file '{}': {e}", self.path.display()),
+                Some("Settings take the same keys as batless.toml".to_string()),
+            )
+        })
+    }
+
+    /// Put locked keys of `config` back to their value in `workspace`;
+    /// returns the config and the keys that had been changed
+    pub fn enforce_locks(
+        &self,
+        config: BatlessConfig,
+        workspace: &BatlessConfig,
+    ) -> BatlessResult<(BatlessConfig, Vec<String>)> {
+        if self.file.locked.is_empty() {
+            return Ok((config, Vec::new()));
+        }
+        let serialize =
+            |config: &BatlessConfig| serde_json::to_value(config).map_err(BatlessError::from);
+        let mut current = serialize(&config)?;
+        let locked_values = serialize(workspace)?;
+        let mut changed = Vec::new();
+        for key in &self.file.locked {
+            if current[key] != locked_values[key] {
+                current[key] = locked_values[key].clone();
+                changed.push(key.clone());
+            }
+        }
+        if changed.is_empty() {
+            return Ok((config, changed));
+        }
+        let config = serde_json::from_value(current).map_err(BatlessError::from)?;
+        Ok((config, changed))
+    }
+
+    /// `file` relative to the workspace root, with `/` separators
+    fn relative_path(&self, file: &Path) -> Option<String> {
+        let absolute = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        let root = fs::canonicalize(&self.root).unwrap_or_else(|_| self.root.clone());
+        let relative = absolute.strip_prefix(&root).ok()?;
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+/// Patterns without a `/` match the file name; others match the path from
+/// the workspace root, where a leading `**/` may also match no directory
+fn rule_matches(pattern: &str, relative: &str) -> bool {
+    let pattern = pattern.trim_start_matches('/');
+    if !pattern.contains('/') {
+        let file_name = relative.rsplit('/').next().unwrap_or(relative);
+        return LanguageDetector::glob_match(pattern, file_name);
+    }
+    LanguageDetector::glob_match(pattern, relative)
+        || pattern
+            .strip_prefix("**/")
+            .is_some_and(|rest| LanguageDetector::glob_match(rest, relative))
+}
+
+/// Settings as a config; checked for types only, since profiles are partial
+/// and the final configuration is validated as a whole
+fn to_config(table: &toml::Table) -> Result<BatlessConfig, String> {
+    toml::Value::Table(table.clone())
+        .try_into()
+        .map_err(|e: toml::de::Error| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACE: &str = r#"
+locked = ["redact", "max_lines"]
+
+[settings]
+redact = true
+max_lines = 4000
+
+[profiles.docs]
+summary_level = "Minimal"
+max_lines = 200
+
+[profiles.generated]
+max_bytes = 65536
+
+[[rules]]
+pattern = "**/*.md"
+profile = "docs"
+
+[[rules]]
+pattern = "gen/**"
+profile = "generated"
+"#;
+
+    fn workspace(content: &str) -> (tempfile::TempDir, BatlessResult<Workspace>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(WORKSPACE_FILE);
+        fs::write(&path, content).unwrap();
+        let loaded = Workspace::load(&path).map(|(workspace, _)| workspace);
+        (dir, loaded)
+    }
+
+    #[test]
+    fn test_rules_select_profiles() {
+        let (dir, loaded) = workspace(WORKSPACE);
+        let workspace = loaded.unwrap();
+        fs::create_dir_all(dir.path().join("gen/docs")).unwrap();
+        let readme = dir.path().join("README.md");
+        let generated = dir.path().join("gen/docs/api.md");
+        assert_eq!(workspace.profiles_for(Some(&readme)), ["docs"]);
+        assert_eq!(
+            workspace.profiles_for(Some(&generated)),
+            ["docs", "generated"]
+        );
+        assert!(workspace
+            .profiles_for(Some(&dir.path().join("src/main.rs")))
+            .is_empty());
+
+        let config = workspace.config_for(Some(&generated)).unwrap();
+        assert!(config.redact);
+        assert_eq!(config.max_lines, 200);
+        assert_eq!(config.max_bytes, Some(65536));
+        assert_eq!(workspace.config_for(None).unwrap().max_lines, 4000);
+    }
+
+    #[test]
+    fn test_locked_settings_are_restored() {
+        let (_dir, loaded) = workspace(WORKSPACE);
+        let workspace = loaded.unwrap();
+        let base = workspace.config_for(None).unwrap();
+        let user = base
+            .clone()
+            .with_redact(false)
+            .with_max_lines(50)
+            .with_include_tokens(true);
+        let (config, changed) = workspace.enforce_locks(user, &base).unwrap();
+        assert_eq!(changed, ["redact", "max_lines"]);
+        assert!(config.redact && config.include_tokens);
+        assert_eq!(config.max_lines, 4000);
+    }
+
+    #[test]
+    fn test_invalid_workspace_files() {
+        let (_dir, loaded) = workspace("locked = [\"max_line\"]\n");
+        let error = loaded.unwrap_err().to_string();
+        assert!(
+            error.contains("max_line") && error.contains("max_lines"),
+            "{error}"
+        );
+
+        let (_dir, loaded) = workspace("[[rules]]\npattern = \"*.md\"\nprofile = \"docs\"\n");
+        assert!(loaded
+            .unwrap_err()
+            .to_string()
+            .contains("unknown profile 'docs'"));
+
+        let (_dir, loaded) = workspace("[settings]\nmax_lines = \"many\"\n");
+        assert!(loaded.is_err());
+    }
+}